@@ -0,0 +1,62 @@
+//! Benchmarks for canonicalization of wide expressions (e.g., long polynomials from generators).
+//! Also compares wide expressions that trigger the chemistry re-parse with ones that don't.
+//! Run with `cargo bench`.
+#![allow(clippy::needless_return)]
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId};
+use libmathcat::interface::*;
+
+/// Build `x^n + x^(n-1) + ... + x + 1` as a single flat mrow
+fn wide_polynomial(n_terms: usize) -> String {
+    let mut terms = Vec::with_capacity(2*n_terms);
+    for i in (1..n_terms).rev() {
+        terms.push(format!("<mn>{}</mn><msup><mi>x</mi><mn>{}</mn></msup>", i+1, i));
+        terms.push("<mo>+</mo>".to_string());
+    }
+    terms.push("<mn>1</mn>".to_string());
+    return format!("<math><mrow>{}</mrow></math>", terms.join(""));
+}
+
+fn canonicalize_wide_mrow(c: &mut Criterion) {
+    let rules_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Rules");
+    set_rules_dir(rules_dir.to_str().unwrap().to_string()).unwrap();
+
+    let mut group = c.benchmark_group("canonicalize_wide_mrow");
+    group.sample_size(10);
+    for n_terms in [100, 500, 1000] {
+        let mathml = wide_polynomial(n_terms);
+        group.bench_with_input(BenchmarkId::from_parameter(n_terms), &mathml, |b, mathml| {
+            b.iter(|| set_mathml(mathml.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Add a term that looks like it might be chemistry ("NaCl/2") to the wide polynomial so the chemistry scan has to undo it
+fn wide_polynomial_with_maybe_chemistry(n_terms: usize) -> String {
+    let mathml = wide_polynomial(n_terms);
+    return mathml.replace("</mrow></math>",
+        "<mo>+</mo><mfrac><mrow><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></mrow><mn>2</mn></mfrac></mrow></math>");
+}
+
+fn canonicalize_chemistry_rescan(c: &mut Criterion) {
+    let rules_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Rules");
+    set_rules_dir(rules_dir.to_str().unwrap().to_string()).unwrap();
+
+    // only the fraction should be re-parsed, so the two should take about the same time
+    let mut group = c.benchmark_group("canonicalize_chemistry_rescan");
+    group.sample_size(10);
+    for n_terms in [100, 500] {
+        let mathml = wide_polynomial(n_terms);
+        group.bench_with_input(BenchmarkId::new("not_chemistry", n_terms), &mathml, |b, mathml| {
+            b.iter(|| set_mathml(mathml.clone()).unwrap())
+        });
+        let mathml = wide_polynomial_with_maybe_chemistry(n_terms);
+        group.bench_with_input(BenchmarkId::new("maybe_chemistry", n_terms), &mathml, |b, mathml| {
+            b.iter(|| set_mathml(mathml.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, canonicalize_wide_mrow, canonicalize_chemistry_rescan);
+criterion_main!(benches);
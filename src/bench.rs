@@ -0,0 +1,181 @@
+//! Corpus-driven timing of MathCAT (only built with the `bench` feature).
+//!
+//! [`bench_directory`] times canonicalization ([`set_mathml`]), speech ([`get_spoken_text`]),
+//! and braille ([`get_braille`]) for each MathML file in a directory and reports percentiles.
+//! This is meant to be used by packagers and CI to track performance on a corpus that is representative
+//! of their users' math (e.g., to catch regressions such as quadratic behavior on wide mrows).
+//! The [`BenchReport`] `Display` output is a small table that can be logged and compared between versions.
+#![allow(clippy::needless_return)]
+
+use crate::errors::*;
+use crate::interface::*;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The percentiles shown in the `Display` output of [`Timings`]
+const REPORTED_PERCENTILES: [f64; 4] = [50.0, 90.0, 99.0, 100.0];
+
+/// The times for one phase (e.g, canonicalization) over all the expressions in a corpus
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    samples: Vec<Duration>,
+}
+
+impl Timings {
+    fn add(&mut self, duration: Duration) {
+        self.samples.push(duration);
+    }
+
+    /// The number of times that were recorded
+    pub fn len(&self) -> usize {
+        return self.samples.len();
+    }
+
+    /// True if no times were recorded
+    pub fn is_empty(&self) -> bool {
+        return self.samples.is_empty();
+    }
+
+    /// The total of all the times
+    pub fn total(&self) -> Duration {
+        return self.samples.iter().sum();
+    }
+
+    /// The time that `percentile` (0-100) of the samples are at or below (nearest rank). Zero if there are no samples.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        return sorted[rank.max(1) - 1];
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "total {:>10.3}ms", self.total().as_secs_f64() * 1000.0)?;
+        for percentile in REPORTED_PERCENTILES {
+            write!(f, "  p{:<3} {:>8.3}ms", percentile, self.percentile(percentile).as_secs_f64() * 1000.0)?;
+        }
+        return Ok( () );
+    }
+}
+
+/// The result of [`bench_directory`]
+#[derive(Debug, Clone, Default)]
+pub struct BenchReport {
+    /// The number of files that were read (including ones that failed)
+    pub n_files: usize,
+    pub canonicalize: Timings,
+    pub speech: Timings,
+    pub braille: Timings,
+    /// The files that couldn't be read or resulted in an error along with the error
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Timings for {} expressions ({} failures)", self.n_files, self.failures.len())?;
+        writeln!(f, "canonicalize: {}", self.canonicalize)?;
+        writeln!(f, "speech:       {}", self.speech)?;
+        writeln!(f, "braille:      {}", self.braille)?;
+        for (path, error) in &self.failures {
+            writeln!(f, "Failed: {}\n  {}", path.display(), error)?;
+        }
+        return Ok( () );
+    }
+}
+
+/// Times canonicalization, speech, and braille for each `.xml`/`.mml` file in `dir` (subdirectories are included).
+/// Each file should contain a single `math` element. Each expression is processed `iterations` times
+///   and each run is recorded as a sample. The current preferences (e.g., `Language`, `BrailleCode`) are used.
+///
+/// Note: this replaces the MathML that was set by [`set_mathml`].
+pub fn bench_directory(dir: &Path, iterations: usize) -> Result<BenchReport> {
+    if !dir.is_dir() {
+        bail!("bench_directory: '{}' is not a directory", dir.display());
+    }
+    let mut files = vec![];
+    find_mathml_files(dir, &mut files)?;
+    files.sort();
+
+    let mut report = BenchReport{ n_files: files.len(), ..Default::default() };
+    for file in files {
+        let mathml = match std::fs::read_to_string(&file) {
+            Ok(mathml) => mathml,
+            Err(e) => {
+                report.failures.push( (file, e.to_string()) );
+                continue;
+            },
+        };
+        if let Err(e) = bench_expression(&mathml, iterations, &mut report) {
+            report.failures.push( (file, errors_to_string(&e)) );
+        }
+    }
+    return Ok(report);
+
+    fn find_mathml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        let entries = std::fs::read_dir(dir).chain_err(|| format!("reading directory '{}'", dir.display()))?;
+        for entry in entries {
+            let path = entry.chain_err(|| format!("reading directory '{}'", dir.display()))?.path();
+            if path.is_dir() {
+                find_mathml_files(&path, files)?;
+            } else if matches!(path.extension().and_then(|ext| ext.to_str()), Some("xml") | Some("mml")) {
+                files.push(path);
+            }
+        }
+        return Ok( () );
+    }
+}
+
+/// Times canonicalization, speech, and braille for `mathml` `iterations` times, adding the times to `report`
+pub fn bench_expression(mathml: &str, iterations: usize, report: &mut BenchReport) -> Result<()> {
+    for _ in 0..iterations {
+        let start = Instant::now();
+        set_mathml(mathml.to_string())?;
+        report.canonicalize.add(start.elapsed());
+
+        let start = Instant::now();
+        get_spoken_text()?;
+        report.speech.add(start.elapsed());
+
+        let start = Instant::now();
+        get_braille("".to_string())?;
+        report.braille.add(start.elapsed());
+    }
+    return Ok( () );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let mut timings = Timings::default();
+        assert_eq!(timings.percentile(50.0), Duration::ZERO);
+        for ms in [5, 1, 4, 2, 3] {
+            timings.add(Duration::from_millis(ms));
+        }
+        assert_eq!(timings.len(), 5);
+        assert_eq!(timings.percentile(50.0), Duration::from_millis(3));
+        assert_eq!(timings.percentile(90.0), Duration::from_millis(5));
+        assert_eq!(timings.percentile(0.0), Duration::from_millis(1));
+        assert_eq!(timings.total(), Duration::from_millis(15));
+    }
+
+    #[test]
+    fn bench_expressions() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let mut report = BenchReport::default();
+        bench_expression("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>", 3, &mut report).unwrap();
+        assert_eq!(report.canonicalize.len(), 3);
+        assert_eq!(report.speech.len(), 3);
+        assert_eq!(report.braille.len(), 3);
+        assert!(bench_expression("<math><mi>x</mi>", 1, &mut report).is_err());
+        assert!(bench_directory(Path::new("not a directory"), 1).is_err());
+    }
+}
@@ -6,41 +6,54 @@ use regex::{Captures, Regex, RegexSet};
 use phf::{phf_map, phf_set};
 use crate::speech::{BRAILLE_RULES, SpeechRulesWithContext};
 use std::ops::Range;
+use std::collections::HashSet;
+use crate::symbol_category::SymbolCategory;
 
 static UEB_PREFIXES: phf::Set<char> = phf_set! {
     '⠼', '⠈', '⠘', '⠸', '⠐', '⠨', '⠰', '⠠',
 };
 
+/// Braille codes that are built out of a shared set of components (rule files and cleanup code).
+/// The key is the value of the 'BrailleCode' pref; the value is the name of the component set.
+/// Codes not listed here are their own component set (e.g., "UEB").
+static BRAILLE_COMPONENTS: phf::Map<&str, &str> = phf_map! {
+    "Finnish" => "Nordic",
+    "Spoken" => "UEB",
+};
+
+/// The 'BrailleCode' for "word braille": the speech for the math is brailled as (contracted) text using the UEB rules
+const SPOKEN_BRAILLE_CODE: &str = "Spoken";
+
+/// Returns the name of the component set used to implement `braille_code`
+fn braille_components(braille_code: &str) -> &str {
+    return BRAILLE_COMPONENTS.get(braille_code).copied().unwrap_or(braille_code);
+}
+
 
 /// braille the MathML
 /// If 'nav_node_id' is not an empty string, then the element with that id will have dots 7 & 8 turned on as per the pref
 pub fn braille_mathml(mathml: Element, nav_node_id: String) -> Result<String> {
     crate::speech::SpeechRules::update();
-    return BRAILLE_RULES.with(|rules| {
-        rules.borrow_mut().read_files()?;
-        let rules = rules.borrow();
-        let new_package = Package::new();
-        let mut rules_with_context = SpeechRulesWithContext::new(&rules, new_package.as_document(), nav_node_id);
-        let braille_string = rules_with_context.match_pattern::<String>(mathml)
-                        .chain_err(|| "Pattern match/replacement failure!")?;
-        let braille_string = braille_string.replace(' ', "");
-        let pref_manager = rules_with_context.get_rules().pref_manager.borrow();
-        let highlight_style = pref_manager.get_user_prefs().to_string("BrailleNavHighlight");
-        let braille_code = pref_manager.get_user_prefs().to_string("BrailleCode");
-        let braille = match braille_code.as_str() {
-            "UEB" => ueb_cleanup(braille_string),
-            "Nemeth" => nemeth_cleanup(braille_string),
-            _ => braille_string,    // probably needs cleanup if someone has another code, but this will have to get added by hand
-        };
-
-        return Ok(
-            if highlight_style != "Off" {
-                highlight_braille_chars(braille, &braille_code, highlight_style == "All")
-            } else {
-             braille
-            }
-        );
-    });
+    // for word braille, the speech is brailled instead of the math -- nothing in the speech corresponds to 'nav_node_id'
+    let spoken_package;
+    let (mathml, nav_node_id) = if is_spoken_braille() {
+        spoken_package = spoken_text_as_mathml(mathml)?;
+        (crate::interface::get_element(&spoken_package), "".to_string())
+    } else {
+        (mathml, nav_node_id)
+    };
+    let braille = render_braille(mathml, nav_node_id, HashSet::new())?;
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    let pref_manager = pref_manager.borrow();
+    let highlight_style = pref_manager.get_user_prefs().to_string("BrailleNavHighlight");
+    let braille_code = pref_manager.get_user_prefs().to_string("BrailleCode");
+    return Ok(
+        if highlight_style != "Off" {
+            highlight_braille_chars(braille, &braille_code, highlight_style == "All")
+        } else {
+         braille
+        }
+    );
 
     // highlight with dots 7 & 8 based on the highlight style
     // both the start and stop points will be extended to deal with indicators such as capitalization
@@ -85,14 +98,7 @@ pub fn braille_mathml(mathml: Element, nav_node_id: String) -> Result<String> {
             // need to highlight (optional) capital/number, language, and style (max 2 chars) also in that (rev) order
             let prefix_ch_index = std::cmp::max(0, start_index as isize - 5*3) as usize;
             let indicators = &braille[prefix_ch_index..start_index];   // chars to be examined
-            let i_byte_start = start_index - 3 * match braille_code {
-                "Nemeth" => i_start_nemeth(indicators, first_ch),
-                "UEB" => i_start_ueb(indicators),
-                _ => {
-                    error!("highlight_first_indicator: Unknown braille code '{}'", braille);
-                    0
-                },
-            };
+            let i_byte_start = start_index - 3 * n_indicator_chars(indicators, first_ch, braille_code);
             if i_byte_start < start_index {
                 // remove old highlight as long as we don't wipe out the end highlight
                 if start_index < end_index {
@@ -111,6 +117,42 @@ pub fn braille_mathml(mathml: Element, nav_node_id: String) -> Result<String> {
         }
 
     }
+}
+
+/// Returns the (cleaned up) braille for `mathml` without the navigation highlight style applied.
+/// The first char of the braille for each node whose id is in 'highlighted_ids' has dots 7 & 8 turned on.
+fn render_braille(mathml: Element, nav_node_id: String, highlighted_ids: HashSet<String>) -> Result<String> {
+    return BRAILLE_RULES.with(|rules| {
+        rules.borrow_mut().read_files()?;
+        let rules = rules.borrow();
+        let new_package = Package::new();
+        let mut rules_with_context = SpeechRulesWithContext::new(&rules, new_package.as_document(), nav_node_id);
+        rules_with_context.set_highlighted_ids(highlighted_ids);
+        let braille_string = rules_with_context.match_pattern::<String>(mathml)
+                        .chain_err(|| "Pattern match/replacement failure!")?;
+        let braille_string = braille_string.replace(' ', "");
+        let braille_code = rules_with_context.get_rules().pref_manager.borrow().get_user_prefs().to_string("BrailleCode");
+        return Ok( match braille_components(&braille_code) {
+            "UEB" => ueb_cleanup(braille_string),
+            "Nemeth" => nemeth_cleanup(braille_string),
+            "Nordic" => nordic_cleanup(braille_string, &braille_code),
+            _ => braille_string,    // probably needs cleanup if someone has another code, but this will have to get added by hand
+        } );
+    });
+}
+
+/// The number of indicator chars (e.g., a capital or number indicator) just before the char 'first_ch' that belong with it.
+/// 'indicators' are the (up to five) chars that precede 'first_ch' in the braille.
+fn n_indicator_chars(indicators: &str, first_ch: char, braille_code: &str) -> usize {
+    return match braille_components(braille_code) {
+        "Nemeth" => i_start_nemeth(indicators, first_ch),
+        "UEB" => i_start_ueb(indicators),
+        "Nordic" => i_start_nordic(indicators, braille_code),
+        _ => {
+            error!("n_indicator_chars: Unknown braille code '{}'", braille_code);
+            0
+        },
+    };
 
     /// Given a position in a Nemeth string, what is the position character that starts it (e.g, the prev char for capital letter)
     fn i_start_nemeth(braille_prefix: &str, first_ch: char) -> usize {
@@ -163,6 +205,19 @@ pub fn braille_mathml(mathml: Element, nav_node_id: String) -> Result<String> {
         return n_chars;
     }
 
+    /// Given a position in a Nordic code string, what is the position character that starts it (e.g, the number sign)
+    fn i_start_nordic(braille_prefix: &str, braille_code: &str) -> usize {
+        let indicators = match NORDIC_INDICATORS.get(braille_code) {
+            None => return 0,
+            Some(indicators) => indicators,
+        };
+        // at most a capital or letter sign and a number sign precede a char
+        return braille_prefix.chars().rev()
+                .take(2)
+                .take_while(|&ch| ch == indicators.number || ch == indicators.letter || ch == indicators.capital)
+                .count();
+    }
+
     fn check_for_typeform(prefix: &mut dyn std::iter::Iterator<Item=char>) -> usize {
         static UEB_TYPEFORM_PREFIXES: phf::Set<char> = phf_set! {
             '⠈', '⠘', '⠸', '⠨',
@@ -183,6 +238,160 @@ pub fn braille_mathml(mathml: Element, nav_node_id: String) -> Result<String> {
     }
 }
 
+/// Operation signs that a line can be broken before (when they are at the base level of the expression)
+static BREAK_BEFORE_OPERATORS: phf::Set<&str> = phf_set! {
+    "+", "-", "−", "±", "∓", "×", "·", "⋅", "÷", "∗",
+};
+
+/// Returns the (char) indexes into the braille for `mathml` where a line can be broken (the start of the new line).
+/// As in the braille codes' line breaking rules, a line can be broken after a comparison sign and before an operation sign.
+/// Only operators at the base level are considered (e.g., not ones in a fraction or script).
+/// The braille is rendered once with the first char of each node that can start a new line highlighted
+///   (the operation sign or the operand after the comparison sign) so that the indexes
+///   take into account whatever indicators the braille code adds.
+pub fn braille_break_points(mathml: Element) -> Result<Vec<usize>> {
+    if is_spoken_braille() {
+        // word braille is text, so lines are broken between words
+        let braille = braille_mathml(mathml, "".to_string())?.chars().collect::<Vec<char>>();
+        return Ok( (1..braille.len()).filter(|&i| braille[i-1] == '⠀' && braille[i] != '⠀').collect() );
+    }
+    let mut line_starts = HashSet::new();
+    find_line_starts(mathml, &mut line_starts);
+    if line_starts.is_empty() {
+        return Ok( vec![] );
+    }
+
+    crate::speech::SpeechRules::update();
+    let braille = render_braille(mathml, "".to_string(), line_starts)?.chars().collect::<Vec<char>>();
+    let braille_code = crate::prefs::PreferenceManager::get().borrow().get_user_prefs().to_string("BrailleCode");
+    let mut break_points = (0..braille.len())
+            .filter(|&i| is_highlighted(braille[i]))
+            .map(|i| {
+                // back up over any indicators (e.g., a number indicator) that go with the first char
+                let indicators = braille[i.saturating_sub(5)..i].iter().map(|&ch| unhighlight(ch)).collect::<String>();
+                i - n_indicator_chars(&indicators, unhighlight(braille[i]), &braille_code)
+            })
+            .filter(|&i| i > 0)
+            .collect::<Vec<usize>>();
+    break_points.dedup();
+    return Ok(break_points);
+
+    /// Adds the ids of the nodes that can start a new line: an operation sign or the operand after a comparison sign
+    fn find_line_starts(mathml: Element, line_starts: &mut HashSet<String>) {
+        for child in mathml.children() {
+            let child = as_element(child);
+            match name(&child) {
+                "mrow" => find_line_starts(child, line_starts),
+                "mo" => {
+                    // an operator at the start or end of an mrow is prefix/postfix -- can't break there
+                    let following = child.following_siblings();
+                    if child.preceding_siblings().is_empty() || following.is_empty() {
+                        continue;
+                    }
+                    let line_start = if SymbolCategory::Relation.contains(child) {
+                        as_element(following[0])
+                    } else if BREAK_BEFORE_OPERATORS.contains(as_text(child)) {
+                        child
+                    } else {
+                        continue;
+                    };
+                    if let Some(id) = line_start.attribute_value("id") {
+                        line_starts.insert(id.to_string());
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+fn is_spoken_braille() -> bool {
+    return crate::prefs::PreferenceManager::get().borrow().get_user_prefs().to_string("BrailleCode") == SPOKEN_BRAILLE_CODE;
+}
+
+/// Returns a package whose `math` element contains the speech for `mathml` as an `mtext`.
+/// The speech is generated without any TTS markup (e.g., SSML) so that only the words are brailled.
+fn spoken_text_as_mathml(mathml: Element) -> Result<Package> {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    let tts = pref_manager.borrow().get_api_prefs().to_string("TTS");
+    pref_manager.borrow_mut().set_api_string_pref("TTS", "None");
+    let intent_package = Package::new();
+    let speech = crate::speech::intent_from_mathml(mathml, intent_package.as_document())
+                    .and_then(crate::speech::speak_intent);
+    pref_manager.borrow_mut().set_api_string_pref("TTS", &tts);
+    let speech = speech?;
+
+    let package = Package::new();
+    let doc = package.as_document();
+    let math = create_mathml_element(&doc, "math");
+    let mtext = create_mathml_element(&doc, "mtext");
+    mtext.set_text(speech.trim());
+    math.append_child(mtext);
+    doc.root().append_child(math);
+    return Ok(package);
+}
+
+/// Braille codes that mark a line that is broken in the middle of an unspaced expression with an indicator at the end of the line
+static RUNOVER_INDICATORS: phf::Map<&str, &str> = phf_map! {
+    "UEB" => "⠐",       // line continuation indicator
+};
+
+/// The number of blank cells at the start of runover lines (the first line starts in cell 1, runovers in cell 3)
+const RUNOVER_INDENT: usize = 2;
+
+/// Returns the braille for `mathml` split into lines of at most `cells_per_line` cells (lines are separated by '\n').
+/// Lines are broken at the places given by [`braille_break_points`]; runover lines are indented and, for codes that use one,
+///   a runover indicator ends a line that was broken in an unspaced part of the expression.
+/// If no break point fits on a line, the line is broken at the last cell that fits.
+pub fn braille_formatted(mathml: Element, cells_per_line: usize) -> Result<String> {
+    let braille_code = crate::prefs::PreferenceManager::get().borrow().get_user_prefs().to_string("BrailleCode");
+    let indicator = RUNOVER_INDICATORS.get(braille_components(&braille_code)).copied().unwrap_or("");
+    if cells_per_line <= RUNOVER_INDENT + indicator.chars().count() + 1 {
+        bail!("braille line length ({}) is too short to split the braille into lines", cells_per_line);
+    }
+    let braille = braille_mathml(mathml, "".to_string())?.chars().collect::<Vec<char>>();
+    if braille.len() <= cells_per_line {
+        return Ok( braille.into_iter().collect() );
+    }
+    let break_points = braille_break_points(mathml)?;
+
+    let mut lines: Vec<String> = vec![];
+    let mut start = 0;
+    loop {
+        let indent = if lines.is_empty() {0} else {RUNOVER_INDENT};
+        let available = cells_per_line - indent;
+        if braille.len() - start <= available {
+            lines.push( "⠀".repeat(indent) + &braille[start..].iter().collect::<String>() );
+            break;
+        }
+        // a break at a space doesn't need an indicator, so there is room for one more cell
+        let fits = |i: usize| i > start && i - start <= available &&
+                              (braille[i-1] == '⠀' || i - start + indicator.chars().count() <= available);
+        let end = break_points.iter().copied().filter(|&i| fits(i)).max()
+                .or_else(|| (start+1..=start+available).filter(|&i| braille[i-1] == '⠀').max())   // not a good break, but better than nothing
+                .unwrap_or_else(|| {
+                    // no place to break -- don't separate a UEB prefix from what it modifies
+                    let mut end = start + available - indicator.chars().count();
+                    while end > start + 1 && indicator == "⠐" && UEB_PREFIXES.contains(&braille[end-1]) {
+                        end -= 1;
+                    }
+                    end
+                });
+        let mut line = braille[start..end].iter().collect::<String>();
+        if braille[end-1] == '⠀' {
+            line = line.trim_end_matches('⠀').to_string();
+        } else {
+            line.push_str(indicator);
+        }
+        lines.push( "⠀".repeat(indent) + &line );
+        start = end;
+        while braille.get(start) == Some(&'⠀') {
+            start += 1;
+        }
+    }
+    return Ok( lines.join("\n") );
+}
+
 fn is_highlighted(ch: char) -> bool {
     let ch_as_u32 = ch as u32;
     return (0x28C0..0x28FF).contains(&ch_as_u32);
@@ -1216,8 +1425,76 @@ fn handle_contractions(chars: &[char], mut result: String) -> String {
 
 
 
+/// The indicators that differ between the codes that use the Nordic components
+struct NordicIndicators {
+    number: char,       // starts a run of digits
+    letter: char,       // needed before a-j when they follow a number
+    capital: char,
+}
+
+static NORDIC_INDICATORS: phf::Map<&str, NordicIndicators> = phf_map! {
+    "Finnish" => NordicIndicators { number: '⠼', letter: '⠰', capital: '⠠' },
+};
+
+/// Cleanup for the codes using the Nordic components (see Rules/Braille/SharedComponents/Nordic)
+/// The flags in the raw braille are:
+///    N -- a digit or a decimal comma follows (a number sign is needed unless we are in a number)
+///    L -- a letter follows (a letter sign is needed if it is a-j and follows a number)
+///    C -- a capital letter follows
+///    W, 𝐖 -- space
+///    # -- end of numeric mode
+fn nordic_cleanup(raw_braille: String, braille_code: &str) -> String {
+    let indicators = match NORDIC_INDICATORS.get(braille_code) {
+        None => {
+            error!("nordic_cleanup: Unknown braille code '{}'", braille_code);
+            return raw_braille;
+        },
+        Some(indicators) => indicators,
+    };
+
+    let mut result = String::with_capacity(raw_braille.len());
+    let mut is_numeric = false;
+    let mut chars = raw_braille.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            'N' => {
+                if !is_numeric {
+                    result.push(indicators.number);
+                    is_numeric = true;
+                }
+                if let Some(digit) = chars.next() {
+                    result.push(digit);
+                }
+            },
+            'L' => {
+                if is_numeric && chars.peek().is_some_and(|&next| LETTER_NUMBERS.contains(&unhighlight(next))) {
+                    result.push(indicators.letter);
+                }
+                is_numeric = false;
+            },
+            'C' => {
+                result.push(indicators.capital);
+                is_numeric = false;
+            },
+            'W' | '𝐖' => {
+                result.push('⠀');
+                is_numeric = false;
+            },
+            '\u{2800}'..='\u{28FF}' => {
+                result.push(ch);
+                is_numeric = false;
+            },
+            _ => is_numeric = false,      // '#' and any unknown flags
+        }
+    }
+
+    let result = COLLAPSE_SPACES.replace_all(&result, "⠀");
+    return result.trim_matches('⠀').to_string();
+}
+
+
 /************** Braille xpath functionality ***************/
-use crate::canonicalize::{name, as_element, as_text};
+use crate::canonicalize::{name, as_element, as_text, create_mathml_element};
 use crate::xpath_functions::{is_leaf, IsBracketed};
 use sxd_document::dom::ParentOfChild;
 use sxd_xpath::{Value, context, nodeset::*};
@@ -1333,9 +1610,10 @@ impl BrailleChars {
     // this string follows the Nemeth rules typefaces and deals with mathvariant
     //  which has partially turned chars to the alphanumeric block
     fn get_braille_chars(node: &Element, code: &str, text_range: Option<Range<usize>>) -> StdResult<String, XPathError> {
-        match code {
+        match braille_components(code) {
             "Nemeth" => return BrailleChars::get_braille_nemeth_chars(node, text_range),
             "UEB" => return BrailleChars:: get_braille_ueb_chars(node, text_range),
+            "Nordic" => return BrailleChars::get_braille_nordic_chars(node, text_range),
             _ => {
                 warn!("get_braille_chars: unknown braille code '{}'", code);
                 return Ok( as_text(*node).to_string() );
@@ -1353,20 +1631,19 @@ impl BrailleChars {
                 Regex::new(r"(?P<face>[SB𝔹TIR]*)(?P<lang>[EDGVHU]?)(?P<cap>C?)(?P<letter>L?)(?P<num>[N]?)(?P<char>.)").unwrap();
         }
     
-        let math_variant = node.attribute_value("mathvariant");
-        // FIX: cover all the options -- use phf::Map
-        let  attr_typeface = match math_variant {
-            None => "R",
-            Some(variant) => match variant {
-                "bold" => "B",
-                "italic" => "I",
-                "double-struck" => "𝔹",
-                "script" => "T",
-                "fraktur" => "D",
-                "sans-serif" => "S",
-                _ => "R",       // normal and unknown
+        // canonicalize normalized mathvariant (e.g., "sans-serif-bold-italic") -- build the typeface in the order used by the char tables
+        let attr_typeface = match node.attribute_value("mathvariant") {
+            None => "R".to_string(),
+            Some(variant) => {
+                let typeface = [("sans-serif", "S"), ("bold", "B"), ("double-struck", "𝔹"), ("script", "T"), ("italic", "I"), ("fraktur", "D")]
+                        .iter()
+                        .filter(|(part, _)| variant.contains(part))
+                        .map(|(_, face)| *face)
+                        .collect::<String>();
+                if typeface.is_empty() {"R".to_string()} else {typeface}      // normal and unknown
             },
         };
+        let attr_typeface = attr_typeface.as_str();
         let text = BrailleChars::substring(as_text(*node), text_range);
         let braille_chars = crate::speech::braille_replace_chars(&text, *node).unwrap_or_else(|_| "".to_string());
         // debug!("Nemeth chars: text='{}', braille_chars='{}'", &text, &braille_chars);
@@ -1382,7 +1659,8 @@ impl BrailleChars {
             // debug!("  face: {:?}, lang: {:?}, num {:?}, cap: {:?}, char: {:?}",
             //        &caps["face"], &caps["lang"], &caps["num"], &caps["cap"], &caps["char"]);
             let mut nemeth_chars = "".to_string();
-            let char_face = if caps["face"].is_empty() {attr_typeface} else {&caps["face"]};
+            // the mathvariant can have more to it than the char (e.g., there are no bold italic digits)
+            let char_face = if caps["face"].chars().all(|face| attr_typeface.contains(face)) {attr_typeface} else {&caps["face"]};
             let typeface_changed =  typeface != char_face;
             if typeface_changed {
                 typeface = char_face.to_string();   // needs to outlast this instance of the loop
@@ -1458,6 +1736,13 @@ impl BrailleChars {
         return Ok(result.to_string())
     }
 
+    fn get_braille_nordic_chars(node: &Element, text_range: Option<Range<usize>>) -> StdResult<String, XPathError> {
+        // The number/letter/capital indicators depend upon the surrounding chars and are added by nordic_cleanup.
+        // FIX: mathvariant is ignored -- the Nordic codes don't have a standard way to mark typefaces
+        let text = BrailleChars::substring(as_text(*node), text_range);
+        return Ok( crate::speech::braille_replace_chars(&text, *node).unwrap_or_else(|_| "".to_string()) );
+    }
+
     fn is_in_enclosed_list(node: Element) -> bool {
         // Nemeth Rule 10 defines an enclosed list:
         // 1: begins and ends with fence
@@ -1485,7 +1770,7 @@ impl BrailleChars {
             let name = name(&node);
             return match name {
                 "mi" | "mn" => true,
-                "mo"  => !crate::canonicalize::is_relational_op(node),
+                "mo"  => !SymbolCategory::Relation.contains(node),
                 "mtext" => false, // FIX -- should be more nuanced,
                 "mrow" => {
                     if IsBracketed::is_bracketed(&node, "", "", false, false) {
@@ -1584,4 +1869,41 @@ mod tests {
         assert_eq!("⠼⠙⣰⣁⠉", braille);
         return Ok( () );
     }
+
+    #[test]
+    fn spoken_braille() -> Result<()> {
+        let mathml_str = "<math><msqrt><mi>x</mi><mo>+</mo><mn>2</mn></msqrt><mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("BrailleCode".to_string(), "Spoken".to_string()).unwrap();
+        // "the square root of x plus 2; is equal to 1 half" (without the SSML)
+        let braille = get_braille("".to_string())?;
+        assert_eq!(braille, "⠮⠀⠎⠟⠥⠜⠑⠀⠗⠕⠕⠞⠀⠷⠀⠰⠭⠀⠏⠇⠥⠎⠀⠼⠃⠆⠀⠊⠎⠀⠑⠟⠥⠁⠇⠀⠞⠕⠀⠼⠁⠀⠓⠁⠇⠋");
+        assert_eq!(get_braille_break_points()?, vec![2, 8, 13, 15, 18, 23, 27, 30, 36, 39, 42]);
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+        return Ok( () );
+    }
+
+    #[test]
+    fn nordic_highlight() -> Result<()> {
+        let mathml_str = "<math id='id-0'>
+                <mrow id='id-1'>
+                <mn id='id-2'>2</mn>
+                <mo id='id-3'>&#x2062;</mo>
+                <mi id='id-4'>a</mi>
+            </mrow>
+        </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("BrailleCode".to_string(), "Finnish".to_string()).unwrap();
+        set_preference("BrailleNavHighlight".to_string(), "All".to_string()).unwrap();
+        let braille = get_braille("id-2".to_string())?;
+        assert_eq!("⣼⣃⠰⠁", braille);
+        let braille = get_braille("id-4".to_string())?;
+        assert_eq!("⠼⠃⣰⣁", braille);
+        return Ok( () );
+    }
 }
\ No newline at end of file
@@ -15,19 +15,366 @@ use std::{ptr::eq as ptr_eq};
 use crate::pretty_print::*;
 use regex::Regex;
 use std::fmt;
+use std::cell::RefCell;
+use std::rc::Rc;
+use unicode_normalization::UnicodeNormalization;
 use crate::chemistry::*;
 
-// FIX: DECIMAL_SEPARATOR should be set by env, or maybe language
+// the decimal separator if the language doesn't define 'DecimalSeparators' (see `is_decimal_separator`)
+// FIX: the other uses of DECIMAL_SEPARATOR should also use the language's decimal separators
 const DECIMAL_SEPARATOR: &str = ".";
 pub const CHANGED_ATTR: &str = "data-changed";
 pub const ADDED_ATTR_VALUE: &str = "added";
 const MFENCED_ATTR_VALUE: &str = "from_mfenced";
 // character to use instead of the text content for priority, etc.
 pub const CHEMICAL_BOND: &str ="data-chemical-bond";
+/// Marks an mrow (or leaf) as a special kind of number (e.g., "repeating-decimal")
+pub const NUMBER_KIND_ATTR: &str = "data-number-kind";
+const REPEATING_DECIMAL: &str = "repeating-decimal";
+/// Marks a repeating decimal written with an ellipsis (e.g., "0,1666…") -- the value is the repeating digits (e.g., "6")
+pub const REPEATING_DIGITS_ATTR: &str = "data-repeating-digits";
+const SCIENTIFIC_NOTATION: &str = "scientific-notation";
+/// Marks an invisible separator that was added between numbers that are a list (e.g., the entries of a row of a table written with spaces)
+pub const NUMBER_LIST_ATTR: &str = "data-number-list";
+// the most digit blocks merged into a number (e.g., "602 214 076 000 000 000 000 000" or 32 bits as "0110 1110 …")
+// longer runs are more likely a row of numbers in a table than a single number
+const MAX_NUMBER_BLOCKS: usize = 8;
+const NUMBER_RANGE: &str = "range";
+/// A roman numeral (e.g., "XLVIII") -- authors can set this on a token to force the interpretation.
+/// The value of the numeral is put in the 'data-number' attribute.
+pub const ROMAN_NUMERAL: &str = "roman";
+/// Marks an mfrac that is drawn without a line ("no-line", e.g., a binomial) or is bevelled ("bevelled")
+pub const FRACTION_KIND_ATTR: &str = "data-fraction-kind";
+const NO_LINE_FRACTION: &str = "no-line";
+const BEVELLED_FRACTION: &str = "bevelled";
+/// Marks an mover that is an identifier with an accent (e.g., "x̂" or "ẋ") so it is treated as a single identifier
+pub const EMBELLISHED_IDENTIFIER_ATTR: &str = "data-embellished-identifier";
+/// Marks an identifier that is a vector by convention: "arrow" for a letter with an arrow over it (also an embellished identifier)
+/// and "bold" for a bold lowercase letter (bold capitals are usually matrices)
+pub const VECTOR_ATTR: &str = "data-vector";
+/// Marks an mover/munder whose base is an arrow with a label over/under it (e.g., "A -f-> B") so it is treated as a single operator
+pub const LABELED_ARROW_ATTR: &str = "data-labeled-arrow";
+/// Marks an msup that was created from a pseudo-script (e.g., "x′" or "30°") so rules can treat it as the original chars
+pub const PSEUDO_SCRIPT_ATTR: &str = "data-pseudo-script";
+/// Marks an mrow that is a chain of two or more order/equality relations (e.g., "a < b ≤ c").
+/// The value is "inequality" if any of the relations is an order relation, otherwise "equality".
+pub const RELATION_CHAIN_ATTR: &str = "data-relation-chain";
+/// Marks an mtext that is a connective word or phrase (e.g., "where", "such that") from the language's `ConnectiveWords` list.
+/// These are parsed as low priority separators so that they split the expression rather than being part of an operand.
+pub const CONNECTIVE_TEXT_ATTR: &str = "data-connective";
+/// Marks an mi or mtext whose content is several words (e.g., "velocity of light") -- a descriptive identifier.
+/// The token is never split or merged and the words are spoken as written.
+pub const DESCRIPTIVE_IDENTIFIER_ATTR: &str = "data-descriptive-identifier";
+/// Marks an mrow of single letter mi's that canonicalization split from a leaf such as "ABC" because they are likely points.
+/// The value is "geometry" if a geometric shape or operator precedes it, otherwise "unconfirmed" (e.g., just a bar over the letters).
+pub const SPLIT_POINTS_ATTR: &str = "data-split-points";
+const SPLIT_POINTS_CONFIRMED: &str = "geometry";
+const SPLIT_POINTS_UNCONFIRMED: &str = "unconfirmed";
+/// Marks an mtd that has no content (e.g., a spacer in an alignment) -- it contains a whitespace mtext so rules don't need special cases.
+/// Speech uses this to skip the cell or to say it is blank (the `EmptyCells` preference).
+pub const EMPTY_CELL_ATTR: &str = "data-empty-cell";
+/// Marks an mtable whose 'columnlines' only draw a line before the last column (e.g., an augmented matrix "coefficients | constants").
+pub const AUGMENTED_MATRIX_ATTR: &str = "data-augmented-matrix";
+/// Marks an msub/msup/msubsup with an empty base for which no base could be found (e.g., "x = _2 = 3").
+/// The element is left as is so rules can speak the scripts literally.
+pub const UNRESOLVED_SCRIPT_ATTR: &str = "data-unresolved-script";
+/// Marks an mrow that is an exercise label such as "(a)", "(2)", or "(iii)" in front of one of several parts of an mrow.
+/// The value is the label (e.g., "a"). The math up to the next label is grouped into an mrow that follows it.
+pub const ENUMERATOR_ATTR: &str = "data-enumerator";
+/// An author hint that says what an ambiguous part of the MathML means (see [`recanonicalize`]).
+/// The value "math" on chemistry says it is not chemistry.
+pub const INTERPRETATION_ATTR: &str = "data-interpretation";
+/// Prefix used for attributes that would otherwise be removed when the 'PreserveAttributes' API pref is true
+const ORIGINAL_ATTR_PREFIX: &str = "data-orig-";
 
 /// Used when mhchem is detected and we should favor postscripts rather than prescripts in constructing an mmultiscripts
 const MHCHEM_MMULTISCRIPTS_HACK: &str = "MHCHEM_SCRIPT_HACK";
 
+/// The name used for the generator when [`detect_generator`] can't tell what produced the MathML
+pub const UNKNOWN_GENERATOR: &str = "Unknown";
+
+/// A known quirk in the MathML output of a generator that canonicalization recognizes and repairs.
+/// The repair is done in 'clean_mathml' where the quirk is checked for (see 'generator_quirk_fires').
+/// Quirks can be turned off per generator with the 'GeneratorQuirks' API pref (a comma separated list of generators, "All", or "None").
+/// If the generator of the MathML was detected (see [`detect_generator`]), only the quirks of that generator are checked.
+struct GeneratorQuirk {
+	name: &'static str,
+	generator: &'static str,
+	produced_by: &'static str,		// name of the generator returned by 'detect_generator' (e.g., mhchem is run by MathJax)
+	is_match: fn(Element) -> bool,
+}
+
+static GENERATOR_QUIRKS: &[GeneratorQuirk] = &[
+	// msub/msup with mrow/mrow/mpadded width=0/mphantom/mi=A as the base (the scripts are really prescripts of what follows)
+	GeneratorQuirk{ name: "mhchem-phantom-script-base", generator: "mhchem", produced_by: "MathJax", is_match: is_mhchem_phantom_script_base },
+	// non-breaking whitespace as a standalone 'mo'
+	GeneratorQuirk{ name: "whitespace-mo", generator: "WIRIS", produced_by: "WIRIS", is_match: is_whitespace_mo },
+	// binary union/intersection used as the (n-ary) large operator in munder/mover/munderover
+	GeneratorQuirk{ name: "binary-large-operator", generator: "MathType", produced_by: "MathType", is_match: is_binary_large_operator },
+];
+
+thread_local!{
+	/// The names of the generator quirks that were repaired in the last call to 'canonicalize'
+	static GENERATOR_QUIRKS_FIRED: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+
+	/// The generator detected in the last call to 'canonicalize'
+	static DETECTED_GENERATOR: RefCell<&'static str> = const { RefCell::new(UNKNOWN_GENERATOR) };
+
+	/// The (original, normalized) text of the tokens that were changed by Unicode normalization in the last call to 'canonicalize'
+	static UNICODE_NORMALIZATIONS: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+
+	/// The invalid MathML that was repaired (with its location) in the last call to 'canonicalize' (see [`ValidationLevel::Lenient`])
+	static VALIDATION_REPAIRS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns the name of the tool that likely produced 'mathml' based on class names, attributes, and annotations.
+/// The names are "MathType", "MathJax", "LaTeXML", "WIRIS", "Word" (OMML converters), and [`UNKNOWN_GENERATOR`].
+pub fn detect_generator(mathml: Element) -> &'static str {
+	// these are checked in order -- e.g., LaTeXML and MathJax both have TeX annotations
+	if let Some(generator) = find_fingerprint(mathml) {
+		return generator;
+	}
+	return UNKNOWN_GENERATOR;
+
+	fn find_fingerprint(mathml: Element) -> Option<&'static str> {
+		if let Some(class) = mathml.attribute_value("class") {
+			if class.split_whitespace().any(|class| class.starts_with("ltx_")) {
+				return Some("LaTeXML");
+			}
+			if class.split_whitespace().any(|class| class.starts_with("wrs_")) {
+				return Some("WIRIS");
+			}
+		}
+		if mathml.attributes().iter().any(|attr| attr.name().local_part().starts_with("data-mjx-")) {
+			return Some("MathJax");
+		}
+		match name(&mathml) {
+			"annotation" | "annotation-xml" => {
+				let encoding = mathml.attribute_value("encoding").unwrap_or_default();
+				if encoding == "MathType-MTEF" {
+					return Some("MathType");
+				} else if encoding.eq_ignore_ascii_case("wiris") {
+					return Some("WIRIS");
+				}
+				return None;		// don't look inside annotations
+			},
+			"mfenced" if mathml.attribute_value("separators") == Some("|") => return Some("Word"),	// OMML2MML.xsl (Word) writes this on its mfenced
+			_ => (),
+		}
+		return mathml.children().iter()
+				.filter_map(|child| child.element())
+				.find_map(find_fingerprint);
+	}
+}
+
+/// Returns the generator detected for the MathML in the last call to 'canonicalize' (see [`detect_generator`])
+pub fn detected_generator() -> &'static str {
+	return DETECTED_GENERATOR.with(|generator| *generator.borrow());
+}
+
+/// Returns true if the quirk named 'quirk_name' is turned on and matches 'mathml'. Quirks that fire are recorded for diagnostics.
+fn generator_quirk_fires(quirk_name: &str, mathml: Element) -> bool {
+	let quirk = GENERATOR_QUIRKS.iter().find(|quirk| quirk.name == quirk_name)
+				.unwrap_or_else(|| panic!("Internal error: unknown generator quirk '{}'", quirk_name));
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	let generators = pref_manager.borrow().get_api_prefs().to_string("GeneratorQuirks");
+	let is_on = match generators.as_str() {
+		"All" => true,
+		"None" => false,
+		_ => generators.split(',').any(|generator| generator.trim() == quirk.generator),
+	};
+	let generator = detected_generator();
+	if !is_on || !(generator == UNKNOWN_GENERATOR || generator == quirk.produced_by) || !(quirk.is_match)(mathml) {
+		return false;
+	}
+	debug!("Generator quirk '{}' ({}) fired", quirk.name, quirk.generator);
+	GENERATOR_QUIRKS_FIRED.with(|fired| fired.borrow_mut().push(quirk.name));
+	return true;
+}
+
+/// Returns the names (with the generator) of the generator quirks that were repaired when the last expression was canonicalized.
+pub fn generator_quirks_fired() -> Vec<String> {
+	return GENERATOR_QUIRKS_FIRED.with(|fired|
+		fired.borrow().iter()
+			.map(|&quirk_name| {
+				let quirk = GENERATOR_QUIRKS.iter().find(|quirk| quirk.name == quirk_name).unwrap();
+				format!("{} ({})", quirk.name, quirk.generator)
+			})
+			.collect()
+	);
+}
+
+/// Returns the (original, normalized) text of the tokens that were changed by Unicode normalization when the last expression was canonicalized.
+pub fn unicode_normalizations() -> Vec<(String, String)> {
+	return UNICODE_NORMALIZATIONS.with(|normalizations| normalizations.borrow().clone());
+}
+
+/// Returns the NFC (composed) form of 'text' (e.g., "=" followed by a combining long solidus becomes "≠").
+pub fn nfc_normalize(text: &str) -> String {
+	if text.is_ascii() {
+		return text.to_string();
+	}
+	return text.nfc().collect();
+}
+
+/// Normalize the text of all the tokens in 'mathml' to NFC so that operator dictionary and rule lookups match
+fn normalize_token_text(mathml: Element) {
+	if is_leaf(mathml) {
+		let text = as_text(mathml);
+		let normalized = nfc_normalize(text);
+		if normalized != text {
+			debug!("Unicode normalization changed '{}' to '{}'", text.escape_unicode(), normalized.escape_unicode());
+			UNICODE_NORMALIZATIONS.with(|normalizations| normalizations.borrow_mut().push( (text.to_string(), normalized.clone()) ));
+			mathml.set_text(&normalized);
+		}
+		return;
+	}
+	for child in mathml.children() {
+		if let Some(child) = child.element() {
+			normalize_token_text(child);
+		}
+	}
+}
+
+/// Content converted from PDFs sometimes lays out a table as lines of text with the columns separated by tabs or runs of spaces.
+/// If an element's children are only 'mtext's and newline 'mspace's (or an 'mtext' contains newlines) and each line
+///   splits into the same number (> 1) of columns, the children are replaced by an 'mtable'.
+/// This is only done when the 'TextTables' API pref is true because the columns might not have been intended as a table.
+fn reconstruct_text_tables(mathml: Element) {
+	lazy_static! {
+		// a tab or figure space (and surrounding whitespace) or two or more whitespace chars
+		static ref COLUMN_GAP: Regex = Regex::new(r"\s*[\t\u{2007}]\s*|\s{2,}").unwrap();
+	}
+
+	if is_leaf(mathml) {
+		return;
+	}
+	let children = mathml.children();
+	if name(&mathml) == "math" || name(&mathml) == "mrow" {
+		if let Some(rows) = text_table_rows(&children) {
+			let doc = mathml.document();
+			let mtable = create_mathml_element(&doc, "mtable");
+			mtable.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			for row in rows {
+				let mtr = create_mathml_element(&doc, "mtr");
+				for column in COLUMN_GAP.split(row.trim()) {
+					let mtext = create_mathml_element(&doc, "mtext");
+					mtext.set_text(column);
+					let mtd = create_mathml_element(&doc, "mtd");
+					mtd.append_child(mtext);
+					mtr.append_child(mtd);
+				}
+				mtable.append_child(mtr);
+			}
+			mathml.replace_children(vec![ChildOfElement::Element(mtable)]);
+			return;
+		}
+	}
+	for child in children {
+		if let Some(child) = child.element() {
+			reconstruct_text_tables(child);
+		}
+	}
+
+	/// Returns the lines of text if 'children' form a table (at least two lines with the same number of columns)
+	fn text_table_rows(children: &[ChildOfElement]) -> Option<Vec<String>> {
+		let mut rows = vec![String::new()];
+		for child in children {
+			let child = child.element()?;
+			match name(&child) {
+				"mtext" => {
+					let mut lines = as_text(child).split('\n');
+					rows.last_mut().unwrap().push_str(lines.next().unwrap());
+					rows.extend(lines.map(|line| line.to_string()));
+				},
+				"mspace" if child.attribute_value("linebreak") == Some("newline") => rows.push(String::new()),
+				_ => return None,
+			}
+		}
+		rows.retain(|row| !row.trim().is_empty());
+		if rows.len() < 2 {
+			return None;
+		}
+		let n_columns = COLUMN_GAP.split(rows[0].trim()).count();
+		if n_columns < 2 || rows.iter().any(|row| COLUMN_GAP.split(row.trim()).count() != n_columns) {
+			return None;
+		}
+		return Some(rows);
+	}
+}
+
+/// Explicit line breaks (an 'mspace' or 'mo' with linebreak="newline") split an expression into lines.
+/// If the children of a 'math', 'mrow', or 'mstyle' form at least two (non-empty) lines, the children are replaced by an 'mtable'
+///   with a row for each line so that speech announces the lines and navigation can move by line (they would otherwise be
+///   dropped as ignorable space). An 'mo' with a line break starts the next line unless linebreakstyle="after".
+fn convert_linebreaks_to_lines(mathml: Element) {
+	if is_leaf(mathml) {
+		return;
+	}
+	let children = mathml.children();
+	for child in &children {
+		if let Some(child) = child.element() {
+			convert_linebreaks_to_lines(child);
+		}
+	}
+	if !matches!(name(&mathml), "math" | "mrow" | "mstyle") {
+		return;
+	}
+
+	let mut lines: Vec<Vec<ChildOfElement>> = vec![vec![]];
+	for child in children {
+		let element = match child.element() {
+			None => continue,
+			Some(element) => element,
+		};
+		let is_newline = matches!(element.attribute_value("linebreak"), Some("newline" | "indentingnewline"));
+		match name(&element) {
+			"mspace" if is_newline => lines.push(vec![]),
+			"mo" if is_newline => {
+				if element.attribute_value("linebreakstyle") == Some("after") {
+					lines.last_mut().unwrap().push(child);
+					lines.push(vec![]);
+				} else {
+					lines.push(vec![child]);
+				}
+			},
+			_ => lines.last_mut().unwrap().push(child),
+		}
+	}
+	lines.retain(|line| !line.is_empty());
+	if lines.len() < 2 {
+		return;
+	}
+	let doc = mathml.document();
+	let mtable = create_mathml_element(&doc, "mtable");
+	mtable.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+	for line in lines {
+		let mtd = create_mathml_element(&doc, "mtd");
+		if line.len() == 1 {
+			mtd.append_child(line[0]);
+		} else {
+			let mrow = create_mathml_element(&doc, "mrow");
+			mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			mrow.append_children(line);
+			mtd.append_child(mrow);
+		}
+		let mtr = create_mathml_element(&doc, "mtr");
+		mtr.append_child(mtd);
+		mtable.append_child(mtr);
+	}
+	mathml.replace_children(vec![ChildOfElement::Element(mtable)]);
+}
+
+fn is_roman_numerals_on() -> bool {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	return pref_manager.borrow().get_api_prefs().to_string("RomanNumerals") == "true";
+}
+
+fn is_text_tables_on() -> bool {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	return pref_manager.borrow().get_api_prefs().to_string("TextTables") == "true";
+}
+
 // (perfect) hash of operators built from MathML's operator dictionary
 static OPERATORS: phf::Map<&str, OperatorInfo> = include!("operator-info.in");
 
@@ -37,6 +384,15 @@ static AMBIGUOUS_OPERATORS: phf::Set<&str> = phf_set! {
 	"|", "∥", "\u{2016}"
 };
 
+// Relations that can be chained together at the same level (e.g., "a < b ≤ c = d") rather than nesting the mrows.
+// Other relational operators (e.g., arrows, "∈") keep their pairwise structure.
+static CHAIN_EQUALITY_RELATIONS: phf::Set<&str> = phf_set! {
+	"=", "≈", "≡", "≅", "≃", "∼",
+};
+static CHAIN_ORDER_RELATIONS: phf::Set<&str> = phf_set! {
+	"<", ">", "≤", "≥", "≦", "≧", "⩽", "⩾", "≪", "≫", "≠", "≲", "≳",
+};
+
 // static vars used when canonicalizing
 lazy_static!{
 	// lowest priority operator so it is never popped off the stack
@@ -69,6 +425,18 @@ lazy_static!{
 	static ref IMPLIED_PLUS_SLASH_HIGH_PRIORITY: OperatorInfo = OperatorInfo{	// (linear) mixed fraction 2 3/4
 		op_type: OperatorTypes::INFIX, priority: 881, next: &None
 	};
+	// IMPLIED_MODULUS_SEPARATOR -- separates "(mod n)" from a congruence; lower priority than relations so it applies to all of "a ≡ b"
+	static ref IMPLIED_MODULUS_SEPARATOR: OperatorInfo = OperatorInfo{
+		op_type: OperatorTypes::INFIX, priority: 45, next: &None
+	};
+	// CONNECTIVE_TEXT_SEPARATOR -- connective text such as "where" or "such that"; lower priority than relations and "(mod n)", higher than ","
+	static ref CONNECTIVE_TEXT_SEPARATOR: OperatorInfo = OperatorInfo{
+		op_type: OperatorTypes::INFIX, priority: 43, next: &None
+	};
+	// IMPLIED_ENUMERATOR_SEPARATOR -- separates an exercise label such as "(a)" from the parts; lower priority than "," so a part can be a list
+	static ref IMPLIED_ENUMERATOR_SEPARATOR: OperatorInfo = OperatorInfo{
+		op_type: OperatorTypes::INFIX, priority: 30, next: &None
+	};
 
 	// Useful static defaults to have available if there is no character match
 	static ref DEFAULT_OPERATOR_INFO_PREFIX: &'static OperatorInfo = &OperatorInfo{
@@ -91,6 +459,8 @@ lazy_static!{
 
 	// useful for detecting whitespace
 	static ref IS_WHITESPACE: Regex = Regex::new(r"^\s+$").unwrap();    // only Unicode whitespace
+	// spacing used for layout (e.g., columns in text converted from a PDF) -- tabs, figure spaces, newlines, or runs of whitespace
+	static ref LAYOUT_SPACING: Regex = Regex::new(r"\s{2,}|[\t\n\r\u{2007}]").unwrap();
 }
 
 // Operators are either PREFIX, INFIX, or POSTFIX, but can also have other properties such as LEFT_FENCE
@@ -196,7 +566,8 @@ impl OperatorInfo {
 	}
 
 	fn is_fence(&self) -> bool {
-		return (self.op_type.bits & (OperatorTypes::LEFT_FENCE.bits | OperatorTypes::RIGHT_FENCE.bits)) != 0;
+		// LEFT_FENCE/RIGHT_FENCE include the PREFIX/POSTFIX bits, so each needs to be checked separately
+		return self.is_left_fence() || self.is_right_fence();
 	}
 
 	fn is_operator_type(&self, op_type: OperatorTypes) -> bool {
@@ -320,6 +691,20 @@ pub fn is_relational_op(mo: Element) -> bool {
 			.find_operator(mo, None, None, None).priority == *EQUAL_PRIORITY;
 }
 
+/// Returns the priority of the operator in the operator dictionary (higher priorities bind more tightly).
+/// The form (prefix, infix, postfix) is determined from the position of 'mo' in the (canonicalized) tree.
+pub fn operator_priority(mo: Element) -> usize {
+	let context = CanonicalizeContext::new();
+	let previous_node = mo.preceding_siblings().last().map(|child| as_element(*child));
+	let next_node = mo.following_siblings().first().map(|child| as_element(*child));
+	let previous_op = match previous_node {
+		None => Some(&*LEFT_FENCEPOST),		// nothing to the left -- acts like the start of an mrow
+		Some(previous) if name(&previous) == "mo" => Some(context.find_operator(previous, None, None, Some(mo))),
+		Some(_) => None,						// an operand
+	};
+	return context.find_operator(mo, previous_op, previous_node, next_node).priority;
+}
+
 pub fn set_mathml_name(element: Element, new_name: &str) {
 	element.set_name(QName::with_namespace_uri(Some("http://www.w3.org/1998/Math/MathML"), new_name));
 }
@@ -354,6 +739,7 @@ pub fn replace_children<'a>(mathml: Element<'a>, replacements: Vec<Element<'a>>)
 }
 
 // returns the presentation element of a "semantics" element
+// If the presentation is just an image (mglyph), a machine-readable annotation is used instead (see 'AnnotationFallback' pref)
 pub fn get_presentation_element(element: Element) -> (usize, Element) {
 	// FIX: implement this
 	assert_eq!(name(&element), "semantics");
@@ -369,11 +755,61 @@ pub fn get_presentation_element(element: Element) -> (usize, Element) {
 		// debug!("get_presentation_element:\n{}", mml_to_string(&presentation_annotation));
 		assert_eq!(presentation_annotation.children().len(), 1);
 		return (i, as_element(presentation_annotation.children()[0]));
+	} else if let Some( (i, fallback) ) = get_annotation_fallback(element) {
+		return (i, fallback);
 	} else {
 		return (0, as_element(children[0]));
 	}
 }
 
+/// If the presentation child of 'semantics' is just an image, return the index and contents of a MathML or TeX annotation to use instead.
+/// TeX can't be converted, so it is returned as an 'mtext'.
+fn get_annotation_fallback(semantics: Element) -> Option<(usize, Element)> {
+	static MATHML_ENCODINGS: phf::Set<&str> = phf_set! {
+		"MathML", "application/mathml-presentation+xml", "application/mathml+xml",
+	};
+	static TEX_ENCODINGS: phf::Set<&str> = phf_set! {
+		"application/x-tex", "application/x-latex", "TeX", "LaTeX",
+	};
+
+	let children = semantics.children();
+	if children.is_empty() || !is_image_only(as_element(children[0])) {
+		return None;
+	}
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	let fallback_pref = pref_manager.borrow().get_api_prefs().to_string("AnnotationFallback");
+	if fallback_pref == "None" {
+		return None;
+	}
+	let annotations = children.iter().enumerate().skip(1)
+			.filter_map(|(i, child)| child.element().map(|child| (i, child)));
+	for (i, annotation) in annotations.clone() {
+		if name(&annotation) == "annotation-xml" && annotation.children().len() == 1 &&
+		   annotation.attribute_value("encoding").is_some_and(|encoding| MATHML_ENCODINGS.contains(encoding)) {
+			return Some( (i, as_element(annotation.children()[0])) );
+		}
+	}
+	if fallback_pref == "MathML" {
+		return None;
+	}
+	for (i, annotation) in annotations {
+		if name(&annotation) == "annotation" && annotation.attribute_value("encoding").is_some_and(|encoding| TEX_ENCODINGS.contains(encoding)) {
+			let tex = create_mathml_element(&semantics.document(), "mtext");
+			tex.set_text(as_text(annotation).trim());
+			return Some( (i, tex) );
+		}
+	}
+	return None;
+
+	fn is_image_only(mathml: Element) -> bool {
+		return match name(&mathml) {
+			"mglyph" => true,
+			"mrow" | "mstyle" | "mpadded" => mathml.children().len() == 1 && is_image_only(as_element(mathml.children()[0])),
+			_ => false,
+		};
+	}
+}
+
 /// Canonicalize does several things:
 /// 1. cleans up the tree so all extra white space is removed (should only have element and text nodes)
 /// 2. normalize the characters
@@ -390,7 +826,138 @@ pub fn get_presentation_element(element: Element) -> (usize, Element) {
 /// An mrow is never deleted unless it is redundant.
 pub fn canonicalize(mathml: Element) -> Result<Element> {
 	let context = CanonicalizeContext::new();
-	return context.canonicalize(mathml);
+	return context.canonicalize(mathml, false);
+}
+
+/// How strictly the MathML is checked before it is canonicalized (the 'Validation' API pref)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValidationLevel {
+	/// Like 'Standard', but deprecated elements (`mfenced`) and empty `mi`, `mn`, and `mo` elements are also errors
+	Strict,
+	/// MathML that can't be processed (unknown elements, the wrong number of children, ...) is an error
+	Standard,
+	/// MathML that can't be processed is repaired (see [`validation_repairs`]) instead of being an error
+	Lenient,
+}
+
+fn validation_level() -> ValidationLevel {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	return match pref_manager.borrow().get_api_prefs().to_string("Validation").as_str() {
+		"Strict" => ValidationLevel::Strict,
+		"Lenient" => ValidationLevel::Lenient,
+		_ => ValidationLevel::Standard,
+	};
+}
+
+/// Returns the repairs (with an XPath-like location) that were made to invalid MathML in the last call to 'canonicalize'.
+/// Repairs are only made when the 'Validation' API pref is "Lenient"; otherwise invalid MathML is an error.
+pub fn validation_repairs() -> Vec<String> {
+	return VALIDATION_REPAIRS.with(|repairs| repairs.borrow().clone());
+}
+
+/// Returns an XPath-like location of 'mathml' (e.g., "/math/mrow[1]/mfrac[2]") -- the index counts siblings with the same name
+pub(crate) fn xpath_location(mathml: Element) -> String {
+	let mut steps = vec![];
+	let mut element = Some(mathml);
+	while let Some(current) = element {
+		let parent = current.parent().and_then(|parent| parent.element());
+		match parent {
+			None => steps.push( name(&current).to_string() ),
+			Some(parent) => {
+				let position = parent.children().iter()
+						.filter_map(|child| child.element())
+						.filter(|child| name(child) == name(&current))
+						.position(|child| child == current)
+						.unwrap_or(0);
+				steps.push( format!("{}[{}]", name(&current), position + 1) );
+			},
+		}
+		element = parent;
+	}
+	steps.reverse();
+	return "/".to_string() + &steps.join("/");
+}
+
+/// A faster, partial version of [`canonicalize`] meant for previews (e.g., speaking math as it is typed in an editor).
+/// The characters are normalized, the tree is cleaned up/repaired, and the mrows are parsed, but
+/// the chemistry scan (which may also reparse all the mrows) is skipped.
+/// The result is good enough for speech, but [`canonicalize`] should be used once the math stops changing.
+pub fn quick_clean(mathml: Element) -> Result<Element> {
+	let context = CanonicalizeContext::new();
+	return context.canonicalize(mathml, true);
+}
+
+/// Same as [`canonicalize`], but the diagnostics ([`detected_generator`], [`generator_quirks_fired`], [`unicode_normalizations`],
+/// and [`validation_repairs`])
+/// from the last call to 'canonicalize' are left unchanged.
+/// This is used for MathML that is only compared (e.g., a search pattern) and not spoken.
+pub fn canonicalize_detached(mathml: Element) -> Result<Element> {
+	let generator = detected_generator();
+	let quirks_fired = GENERATOR_QUIRKS_FIRED.with(|fired| fired.borrow().clone());
+	let normalizations = unicode_normalizations();
+	let repairs = validation_repairs();
+	let result = canonicalize(mathml);
+	VALIDATION_REPAIRS.with(|repairs_made| *repairs_made.borrow_mut() = repairs);
+	DETECTED_GENERATOR.with(|detected| *detected.borrow_mut() = generator);
+	GENERATOR_QUIRKS_FIRED.with(|fired| *fired.borrow_mut() = quirks_fired);
+	UNICODE_NORMALIZATIONS.with(|normalizations_fired| *normalizations_fired.borrow_mut() = normalizations);
+	return result;
+}
+
+/// Canonicalize the part of the (canonical) MathML around 'mathml' again after an author hint was added to 'mathml'.
+/// The hints are an explicit invisible operator (e.g., U+2061 instead of a guessed U+2062), which is kept as is,
+///   and [`INTERPRETATION_ATTR`]="math" on chemistry, which removes the chemistry marks so that it is parsed as math.
+/// The mrows that were added around 'mathml' are removed and the enclosing element is parsed again;
+///   the element that was parsed again (or what replaced it) is returned.
+pub fn recanonicalize(mathml: Element) -> Result<Element> {
+	let context = CanonicalizeContext::new();
+	let mut subtree = mathml.parent().and_then(|parent| parent.element())
+			.ok_or_else(|| format!("recanonicalize: '{}' has no parent", name(&mathml)))?;
+	while is_removable_mrow(subtree) {
+		subtree = subtree.parent().unwrap().element().unwrap();		// an mrow that can be removed has an mrow parent
+	}
+	if mathml.attribute_value(INTERPRETATION_ATTR) == Some("math") {
+		crate::chemistry::unmark_chemistry_subtree(mathml);
+	}
+	remove_added_mrows(subtree);
+	context.assure_nary_tag_has_mrow(subtree);
+	let reparsed = context.canonicalize_mrows(subtree)
+			.chain_err(|| format!("while processing\n{}", mml_to_string(&subtree)))?;
+	replace_reparsed(subtree, reparsed);
+	return Ok(reparsed);
+
+	/// An mrow added by canonicalization that just groups operands of its parent mrow (it is redone by the parse)
+	fn is_removable_mrow(mathml: Element) -> bool {
+		if name(&mathml) != "mrow" || mathml.attribute_value(CHANGED_ATTR) != Some(ADDED_ATTR_VALUE) || mathml.attribute("intent").is_some() {
+			return false;
+		}
+		return mathml.parent().and_then(|parent| parent.element()).is_some_and(|parent| name(&parent) == "mrow");
+	}
+
+	fn remove_added_mrows(mathml: Element) {
+		if crate::xpath_functions::is_leaf(mathml) {
+			return;
+		}
+		for child in mathml.children() {
+			remove_added_mrows(as_element(child));
+		}
+		if is_removable_mrow(mathml) {
+			let children = mathml.children().iter().map(|&child| as_element(child)).collect::<Vec<Element>>();
+			replace_children(mathml, children);
+		}
+	}
+}
+
+/// Put 'reparsed' in the place of 'subtree' (if they differ) -- 'math' is never replaced
+fn replace_reparsed<'a>(subtree: Element<'a>, reparsed: Element<'a>) {
+	if let Some(parent) = subtree.parent().and_then(|parent| parent.element()) {
+		if reparsed != subtree {
+			let children = parent.children().into_iter()
+				.map(|child| if child.element() == Some(subtree) {ChildOfElement::Element(reparsed)} else {child})
+				.collect::<Vec<ChildOfElement>>();
+			parent.replace_children(children);
+		}
+	}
 }
 
 struct CanonicalizeContext {
@@ -440,8 +1007,16 @@ impl CanonicalizeContext {
 		return CanonicalizeContext{}
 	}
 
-	fn canonicalize<'a>(&self, mut mathml: Element<'a>) -> Result<Element<'a>> {
+	fn canonicalize<'a>(&self, mut mathml: Element<'a>, is_quick: bool) -> Result<Element<'a>> {
 		// debug!("MathML before canonicalize:\n{}", mml_to_string(&mathml));
+		GENERATOR_QUIRKS_FIRED.with(|fired| fired.borrow_mut().clear());
+		DETECTED_GENERATOR.with(|generator| *generator.borrow_mut() = detect_generator(mathml));
+		UNICODE_NORMALIZATIONS.with(|normalizations| normalizations.borrow_mut().clear());
+		normalize_token_text(mathml);
+		if is_text_tables_on() {
+			reconstruct_text_tables(mathml);
+		}
+		convert_linebreaks_to_lines(mathml);
 	
 		if name(&mathml) != "math" {
 			// debug!("Didn't start with <math> element -- attempting repair");
@@ -453,17 +1028,27 @@ impl CanonicalizeContext {
 			root.append_child(math_element);
 			mathml = root.children()[0].element().unwrap();
 		}
-		CanonicalizeContext::assure_mathml(mathml)?;
+		VALIDATION_REPAIRS.with(|repairs| repairs.borrow_mut().clear());
+		CanonicalizeContext::assure_mathml(mathml, validation_level())?;
 		let mathml = self.clean_mathml(mathml).unwrap();	// 'math' is never removed
 		self.assure_math_not_empty(mathml);
 		self.assure_nary_tag_has_mrow(mathml);
 		let mut converted_mathml = self.canonicalize_mrows(mathml)
 				.chain_err(|| format!("while processing\n{}", mml_to_string(&mathml)))?;
-		if !crate::chemistry::scan_and_mark_chemistry(converted_mathml) {
-			debug!("Not chemistry -- retry:\n{}", mml_to_string(&converted_mathml));
-			self.assure_nary_tag_has_mrow(converted_mathml);
-			converted_mathml = self.canonicalize_mrows(mathml)
-				.chain_err(|| format!("while processing\n{}", mml_to_string(&mathml)))?;
+		if !is_quick {
+			// only the parts that were parsed as if they might be chemistry need to be parsed again
+			for subtree in crate::chemistry::scan_and_mark_chemistry(converted_mathml) {
+				debug!("Not chemistry -- retry:\n{}", mml_to_string(&subtree));
+				self.assure_nary_tag_has_mrow(subtree);
+				let is_math = name(&subtree) == "math";
+				let reparsed = self.canonicalize_mrows(subtree)
+					.chain_err(|| format!("while processing\n{}", mml_to_string(&subtree)))?;
+				if is_math {
+					converted_mathml = reparsed;
+				} else {
+					replace_reparsed(subtree, reparsed);
+				}
+			}
 		}
 		debug!("\nMathML after canonicalize:\n{}", mml_to_string(&converted_mathml));
 		return Ok(converted_mathml);
@@ -489,67 +1074,132 @@ impl CanonicalizeContext {
 		}
 	}
 
-	/// Return an error is some element is not MathML (only look at first child of <semantics>) or if it has the wrong number of children
-	fn assure_mathml(mathml: Element) -> Result<()> {
-		static ALL_MATHML_ELEMENTS: phf::Set<&str> = phf_set!{
-			"mi", "mo", "mn", "mtext", "ms", "mspace", "mglyph",
-			"mfrac", "mroot", "msub", "msup", "msubsup","munder", "mover", "munderover", "mmultiscripts",
-			"mstack", "mlongdiv", "msgroup", "msrow", "mscarries", "mscarry", "msline",
-			"none", "mprescripts", "malignmark", "maligngroup",
-			"math", "msqrt", "merror", "mpadded", "mphantom", "menclose", "mtd", "mstyle",
-			"mrow", "mfenced", "mtable", "mtr", "mlabeledtr",
-		};
-
-		let n_children = mathml.children().len();
+	/// Return an error is some element is not MathML (only look at first child of <semantics>) or if it has the wrong number of children.
+	/// The error includes the location of the bad element (see [`xpath_location`]) and its source.
+	/// With [`ValidationLevel::Lenient`], the bad elements are repaired instead (see [`validation_repairs`]).
+	fn assure_mathml(mathml: Element, level: ValidationLevel) -> Result<()> {
 		let element_name = name(&mathml);
-		if is_leaf(mathml) {
-			if EMPTY_ELEMENTS.contains(element_name) {
-				if n_children != 0 {
-					bail!("{} should only have one child:\n{}", element_name, mml_to_string(&mathml));
-				}
-			} else if (n_children == 1 && mathml.children()[0].text().is_some()) || n_children == 0 {  // allow empty children such as mtext
-				return Ok( () );
-			} else {
-				bail!("Not a valid MathML leaf element:\n{}", mml_to_string(&mathml));
-			};
-		}
-
-		if ELEMENTS_WITH_FIXED_NUMBER_OF_CHILDREN.contains(element_name) {
-			match element_name {
-				"munderover" | "msubsup" => if n_children != 3 {
-					bail!("{} should have 3 children:\n{}", element_name, mml_to_string(&mathml));
-				},
-				"mmultiscripts" => {
-					let has_prescripts = mathml.children().iter()
-							.any(|&child| name(&as_element(child)) == "mprescripts");
-					if has_prescripts ^ (n_children % 2 == 0) {
-						bail!("{} has the wrong number of children:\n{}", element_name, mml_to_string(&mathml));
-					}
-				},
-				"mlongdiv" => if n_children < 3 {
-					bail!("{} should have at least 3 children:\n{}", element_name, mml_to_string(&mathml));
-				},
-				_ => if n_children != 2 {
-					bail!("{} should have 2 children:\n{}", element_name, mml_to_string(&mathml));
-				},
+		if element_name == "merror" {
+			// the contents are whatever the generator produced when it failed -- if they aren't valid MathML, just keep the text
+			if mathml.children().iter().any(|&child| CanonicalizeContext::assure_mathml(as_element(child), ValidationLevel::Standard).is_err()) {
+				replace_with_text(mathml);
 			}
+			return Ok( () );
 		}
-		let children = mathml.children();
 		if element_name == "semantics" {
-			if children.is_empty() {
+			if mathml.children().is_empty() {
 				return Ok( () );
 			} else {
-				return CanonicalizeContext::assure_mathml(get_presentation_element(mathml).1);
+				return CanonicalizeContext::assure_mathml(get_presentation_element(mathml).1, level);
 			}
 		}
-		if !ALL_MATHML_ELEMENTS.contains(element_name) {
-			bail!("'{}' is not a valid MathML element", element_name);
+
+		if let Some(problem) = mathml_problem(mathml, level) {
+			if level != ValidationLevel::Lenient {
+				bail!("{} at {}:\n{}", problem, xpath_location(mathml), mml_to_string(&mathml));
+			}
+			let repair = format!("{} at {}", problem, xpath_location(mathml));
+			debug!("Repairing invalid MathML: {}", repair);
+			VALIDATION_REPAIRS.with(|repairs| repairs.borrow_mut().push(repair));
+			if is_leaf(mathml) || mathml.children().iter().all(|child| child.element().is_none()) {
+				replace_with_text(mathml);		// tokens with element children or unknown elements with only text
+				return Ok( () );
+			}
+			// the children might make sense on their own (e.g., an mfrac with three children or an unknown element)
+			set_mathml_name(mathml, "mrow");
+			mathml.set_attribute_value(CHANGED_ATTR, "invalid_content");
 		}
-		// valid MathML element and not a leaf -- check the children
-		for child in children {
-			CanonicalizeContext::assure_mathml( as_element(child) )?;
+		if !is_leaf(mathml) {
+			for child in mathml.children().iter().filter_map(|child| child.element()) {
+				CanonicalizeContext::assure_mathml(child, level)?;
+			}
 		}
 		return Ok( () );
+
+		/// Return a description of what is wrong with 'mathml' (not its children)
+		fn mathml_problem(mathml: Element, level: ValidationLevel) -> Option<String> {
+			static ALL_MATHML_ELEMENTS: phf::Set<&str> = phf_set!{
+				"mi", "mo", "mn", "mtext", "ms", "mspace", "mglyph",
+				"mfrac", "mroot", "msub", "msup", "msubsup","munder", "mover", "munderover", "mmultiscripts",
+				"mstack", "mlongdiv", "msgroup", "msrow", "mscarries", "mscarry", "msline",
+				"none", "mprescripts", "malignmark", "maligngroup",
+				"math", "msqrt", "merror", "mpadded", "mphantom", "menclose", "mtd", "mstyle",
+				"mrow", "mfenced", "mtable", "mtr", "mlabeledtr",
+			};
+
+			let n_children = mathml.children().len();
+			let element_name = name(&mathml);
+			if is_leaf(mathml) {
+				if EMPTY_ELEMENTS.contains(element_name) {
+					if n_children != 0 {
+						return Some( format!("{} should not have any children", element_name) );
+					}
+				} else if n_children == 1 && mathml.children()[0].text().is_some() {
+					return None;
+				} else if n_children == 0 {		// allow empty children such as mtext
+					if level == ValidationLevel::Strict && matches!(element_name, "mi" | "mn" | "mo") {
+						return Some( format!("{} is empty", element_name) );
+					}
+				} else {
+					return Some( format!("{} is not a valid MathML leaf element", element_name) );
+				};
+				return None;
+			}
+
+			if ELEMENTS_WITH_FIXED_NUMBER_OF_CHILDREN.contains(element_name) {
+				match element_name {
+					"munderover" | "msubsup" => if n_children != 3 {
+						return Some( format!("{} should have 3 children", element_name) );
+					},
+					"mmultiscripts" => {
+						let has_prescripts = mathml.children().iter()
+								.any(|&child| child.element().is_some_and(|child| name(&child) == "mprescripts"));
+						if has_prescripts ^ (n_children % 2 == 0) {
+							return Some( format!("{} has the wrong number of children", element_name) );
+						}
+					},
+					"mlongdiv" => if n_children < 3 {
+						return Some( format!("{} should have at least 3 children", element_name) );
+					},
+					_ => if n_children != 2 {
+						return Some( format!("{} should have 2 children", element_name) );
+					},
+				}
+			}
+			if !ALL_MATHML_ELEMENTS.contains(element_name) {
+				return Some( format!("'{}' is not a valid MathML element", element_name) );
+			}
+			if level == ValidationLevel::Strict && element_name == "mfenced" {
+				return Some( "'mfenced' is deprecated".to_string() );
+			}
+			return None;
+		}
+
+		/// Replace the children of 'mathml' with an mtext of all the text in it
+		fn replace_with_text(mathml: Element) {
+			let mut text = String::new();
+			gather_text(mathml, &mut text);
+			let mtext = create_mathml_element(&mathml.document(), "mtext");
+			mtext.set_text(text.trim());
+			mathml.replace_children([mtext]);
+			if name(&mathml) != "merror" {
+				set_mathml_name(mathml, "mrow");
+			}
+			mathml.set_attribute_value(CHANGED_ATTR, "invalid_content");
+		}
+
+		fn gather_text(mathml: Element, text: &mut String) {
+			for child in mathml.children() {
+				match child {
+					ChildOfElement::Element(child) => gather_text(child, text),
+					ChildOfElement::Text(t) => {
+						text.push_str(t.text());
+						text.push(' ');
+					},
+					_ => (),
+				}
+			}
+		}
 	}
 
 	fn make_empty_element(mathml: Element) -> Element {
@@ -591,8 +1241,30 @@ impl CanonicalizeContext {
 		// Note: this works bottom-up (clean the children first, then this element)
 		lazy_static! {
 			static ref IS_PRIME: Regex = Regex::new(r"['′″‴⁗]").unwrap(); 
+			// a number in "e" notation (e.g., "3.2e5" or "6.02E-23")
+			static ref IS_E_NOTATION: Regex = Regex::new(r"^([0-9]+(?:[.,][0-9]+)?)([eE])([-+−]?)([0-9]+)$").unwrap();
+			// the parts of "m × 10ⁿ": a number and an integer exponent
+			static ref IS_MANTISSA: Regex = Regex::new(r"^[0-9]+([.,][0-9]+)?$").unwrap();
+			static ref IS_INTEGER: Regex = Regex::new(r"^[0-9]+$").unwrap();
         }
 
+		// chars used over digits to indicate they are the repeating part of a decimal number (bars and dots)
+		static REPEATING_DECIMAL_ACCENTS: phf::Set<&str> = phf_set! {
+			"¯", "‾", "_", "\u{0304}", "\u{0305}", "˙", ".", "\u{0307}"
+		};
+
+		// accents that make an identifier into a "modified variable" (e.g., "x hat") -- both the combining and non-combining chars
+		// breve, check, dot, double-dot, triple-dot, quadruple-dot, grave, hat, tilde, vector, line/bar
+		static IDENTIFIER_ACCENTS: phf::Set<&str> = phf_set! {
+			"\u{0306}", "˘", "\u{030C}", "ˇ", ".", "˙", "\u{0307}", "¨", "\u{0308}", "\u{20DB}", "\u{20DC}", "`", "\u{0300}",
+			"^", "\u{02C6}", "\u{0302}", "~", "\u{02DC}", "\u{0303}", "→", "\u{20D7}", "⇀", "\u{20D1}", "¯", "_", "\u{02C9}", "\u{0304}", "\u{0305}",
+		};
+
+		// arrows that are used as "maps to" or "tends to" when they have a label over or under them
+		static LABELED_ARROWS: phf::Set<&str> = phf_set! {
+			"→", "⟶", "↦", "⟼",
+		};
+
 		static CURRENCY_SYMBOLS: phf::Set<&str> = phf_set! {
 			"$", "¢", "€", "£", "₡", "₤", "₨", "₩", "₪", "₱", "₹", "₺", "₿" // could add more currencies...
 		};
@@ -609,6 +1281,15 @@ impl CanonicalizeContext {
 		let parent_requires_child = ELEMENTS_WITH_FIXED_NUMBER_OF_CHILDREN.contains(&parent_name);
 
 		// handle empty leaves -- leaving it empty causes problems with the speech rules
+		// an empty 'ms' is kept because it is meaningful (the empty string)
+		if element_name == "ms" {
+			normalize_ms_quotes(mathml);
+			return Some(mathml);
+		}
+		// the contents of an 'merror' are the message (and maybe the source) of a generator's error -- they are spoken as is
+		if element_name == "merror" {
+			return Some(mathml);
+		}
 		if is_leaf(mathml) && !EMPTY_ELEMENTS.contains(element_name) && as_text(mathml).is_empty() {
 			if !parent_requires_child {
 				return None;
@@ -642,10 +1323,11 @@ impl CanonicalizeContext {
 				let text = as_text(mathml);
 				let mut chars = text.chars();
 				let first_char = chars.next().unwrap();		// we have already made sure it is non-empty
-				// if !text.trim().is_empty() && is_roman_number_match(text) {
-				// 	// people tend to set them in a non-italic font and software makes that 'mtext'
-				// 	mathml.set_attribute_value("data-roman-numeral", "true");	// mark for easy detection
-				// }
+				if (is_roman_numerals_on() || mathml.attribute_value(NUMBER_KIND_ATTR) == Some(ROMAN_NUMERAL)) &&
+				   is_roman_number_match(text) {
+					// an 'mn' with letters in it is clearly meant to be a number
+					return Some(mark_roman_numeral(mathml));
+				}
 				if first_char == '-' || first_char == '\u{2212}' {
 					let doc = mathml.document();
 					let mo = create_mathml_element(&doc, "mo");
@@ -655,20 +1337,29 @@ impl CanonicalizeContext {
 					set_mathml_name(mathml, "mrow");
 					mathml.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
 					mathml.replace_children([mo,mn]);
+					split_superscript_chars(mn);		// "-10²" is -(10²)
+					split_e_notation(mn);
+				} else if let Some(result) = split_superscript_chars(mathml) {
+					return Some(result);
+				} else if let Some(result) = split_repeating_decimal(mathml) {
+					return Some(result);
+				} else if let Some(result) = split_e_notation(mathml) {
+					return Some(result);
 				}
 				return Some(mathml);
 			},
-			"ms" | "mglyph" => {
+			"mglyph" => {
 				return Some(mathml);
 			},
 			"mi" => {
 				let text = as_text(mathml);
-				// if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) {
-				// 	// people tend to set them in a non-italic font and software makes that 'mtext'
-				// 	set_mathml_name(mathml, "mn");
-				// 	mathml.set_attribute_value("data-roman-numeral", "true");	// mark for easy detection
-				// 	return Some(mathml);
-			 	// }
+				if is_descriptive_identifier(text) {
+					mathml.set_attribute_value(DESCRIPTIVE_IDENTIFIER_ATTR, "true");
+					return Some(mathml);
+				}
+				if let Some(result) = clean_roman_numeral(mathml) {
+					return Some(result);
+				}
 				if let Some(dash) = canonicalize_dash(text) {		// needs to be before OPERATORS.get due to "--"
 					mathml.set_text(dash);
 					return Some(mathml);
@@ -677,6 +1368,12 @@ impl CanonicalizeContext {
 					return Some(mathml);
 				} else if let Some(result) = merge_arc_trig(mathml) {
 						return Some(result);
+				} else if let Some(result) = split_superscript_chars(mathml) {
+					return Some(result);
+				} else if let Some(result) = split_accented_identifier(mathml) {
+					return Some(result);
+				} else if let Some(mrow) = split_plain_text_expression(mathml) {
+					return self.clean_mathml(mrow);		// clean up the new tokens (e.g., primes become scripts)
 				} else if IS_PRIME.is_match(text) {
 					let new_text = merge_prime_text(text);
 					mathml.set_text(&new_text);
@@ -696,18 +1393,20 @@ impl CanonicalizeContext {
 					return Some(result);
 				}
 				
+				if let Some(result) = clean_roman_numeral(mathml) {
+					return Some(result);
+				}
+
 				let text = as_text(mathml);
-				// if !text.trim().is_empty() && is_roman_number_match(text) && is_roman_numeral_number_context(mathml) {
-				// 	// people tend to set them in a non-italic font and software makes that 'mtext'
-				// 	set_mathml_name(mathml, "mn");
-				// 	mathml.set_attribute_value("data-roman-numeral", "true");	// mark for easy detection
-				// 	return Some(mathml);
-				// }
 				// allow non-breaking whitespace to stay -- needed by braille
 				let mathml = mathml;
 				if IS_WHITESPACE.is_match(text) {
 					// normalize to just a single non-breaking space
 					CanonicalizeContext::make_empty_element(mathml);
+				} else if LAYOUT_SPACING.is_match(text) {
+					// the spacing is an artifact of the layout -- it shouldn't show up in speech
+					let collapsed = LAYOUT_SPACING.replace_all(text, " ").to_string();
+					mathml.set_text(&collapsed);
 				} else if let Some(dash) = canonicalize_dash(text) {
 					mathml.set_text(dash);
 				} else if OPERATORS.get(text).is_some() {
@@ -717,11 +1416,12 @@ impl CanonicalizeContext {
 				return if parent_requires_child || !text.is_empty() {Some(mathml)} else {None};
 			},
 			"mo" => {
-				// WIRIS editor puts non-breaking whitespace as standalone in 'mo'
 				let text = as_text(mathml);
-				if !text.is_empty() && IS_WHITESPACE.is_match(text) {
+				if generator_quirk_fires("whitespace-mo", mathml) {
 					// can't throw it out because it is needed by braille -- change to what it really is
 					set_mathml_name(mathml, "mtext");
+				} else if generator_quirk_fires("binary-large-operator", mathml) {
+					mathml.set_text(if text == "∪" {"⋃"} else {"⋂"});
 				}
 				// common bug: trig functions, lim, etc., should be mi
 				// same for ellipsis ("…")
@@ -802,7 +1502,20 @@ impl CanonicalizeContext {
 				// no attempt is made to clean the annotations or verify they are annotations
 				// the cleaned child is made the first child and it's annotation-xml wrapper, if any, is removed
 				let mut children = mathml.children();
-				let (i, presentation) = get_presentation_element(mathml);
+				let (mut i, presentation) = get_presentation_element(mathml);
+				if i != 0 && as_element(children[i]).attribute_value("encoding") != Some("MathML-Presentation") {
+					// the presentation is just an image -- replace it with the annotation (an emptied annotation-xml wrapper is removed)
+					let annotation = as_element(children[i]);
+					warn!("The presentation in 'semantics' is just an image -- using the '{}' annotation in its place",
+							annotation.attribute_value("encoding").unwrap_or_default());
+					children[0] = ChildOfElement::Element(presentation);
+					if name(&annotation) == "annotation-xml" {
+						children.remove(i);
+					}
+					mathml.replace_children(children);
+					children = mathml.children();
+					i = 0;
+				}
 				let new_presentation = if let Some(presentation) = self.clean_mathml(presentation) {
 					presentation
 				} else {
@@ -827,7 +1540,7 @@ impl CanonicalizeContext {
 					if children.is_empty() {
 						return if parent_requires_child {Some(mathml)} else {None};
 					} else if children.len() == 1 {
-						let is_from_mhchem = is_from_mhchem_hack(mathml);
+						let is_from_mhchem = generator_quirk_fires("mhchem-phantom-script-base", mathml);
 						if let Some(new_mathml) = self.clean_mathml(as_element(children[0])) {
 							// "lift" the child up so all the links (e.g., siblings) are correct
 							mathml.replace_children(new_mathml.children());
@@ -855,7 +1568,9 @@ impl CanonicalizeContext {
 					mathml
 				};
 
-				// cleaning children can add or delete subsequent children, so we need to constantly update the children (and mathml)
+				// Cleaning children can add or delete subsequent children, so 'children' is re-read from the tree after each child is cleaned.
+				// Replacing all the children on each iteration is O(n^2) for wide mrows (e.g., 1000 term polynomials),
+				// so they are only replaced when cleaning returned a different element.
 				let mut children = mathml.children();
 				let mut i = 0;
 				while i < children.len() {
@@ -867,19 +1582,21 @@ impl CanonicalizeContext {
 							},
 							Some(new_child) => {
 								let new_child_name = name(&new_child);
-								children = mathml.children();				// clean_mathml(child) may have changed following siblings
-								// debug!("new_child (i={})\n{}", i, mml_to_string(&new_child));
-								children[i] = ChildOfElement::Element(new_child);
-								mathml.replace_children(children);
+								if new_child != child {
+									let mut new_children = mathml.children();		// clean_mathml(child) may have changed following siblings
+									// debug!("new_child (i={})\n{}", i, mml_to_string(&new_child));
+									new_children[i] = ChildOfElement::Element(new_child);
+									mathml.replace_children(new_children);
+								}
 								if new_child_name == "mi" || new_child_name == "mtext" {
 									// can't do this above in 'match' because this changes the tree and
 									// lifting single element mrows messes with structure in a conflicting way
-									clean_chemistry_leaf(as_element(mathml.children()[i]));
-								}			
+									clean_chemistry_leaf(new_child);
+								}
 								i += 1;
 							}
 						}
-						children = mathml.children();						// 'children' moved above, so need need new values
+						children = mathml.children();
 					} else {
 						// bad mathml such as '<annotation-xml> </annotation-xml>' -- don't add to new_children
 						i += 1;
@@ -898,8 +1615,14 @@ impl CanonicalizeContext {
 
 				if element_name == "mrow" || ELEMENTS_WITH_ONE_CHILD.contains(element_name) {
 					merge_number_blocks(mathml, &mut children);
+					merge_repeating_decimals(&mut children);
+					merge_scientific_notation(&mut children);
+					merge_number_ranges(&mut children);
+					mark_connective_text(&children);
+					mark_descriptive_text(&children);
 					merge_whitespace(&mut children);
 					handle_convert_to_mmultiscripts(&mut children);
+					group_enumerated_parts(&mut children);
 
 				} else if element_name == "msub" || element_name == "msup" || 
 						  element_name == "msubsup" || element_name == "mmultiscripts"{
@@ -934,10 +1657,30 @@ impl CanonicalizeContext {
 					} else {
 						return Some(mathml);
 					}
+				} else if element_name == "mfrac" {
+					mark_fraction_kind(mathml);
+				} else if element_name == "mover" && is_embellished_identifier(&children) {
+					mathml.set_attribute_value(EMBELLISHED_IDENTIFIER_ATTR, "true");
+					mark_arrow_vector(mathml);
+				} else if (element_name == "mover" || element_name == "munder") && is_labeled_arrow(&children) {
+					mathml.set_attribute_value(LABELED_ARROW_ATTR, "true");
+				}
+
+				if element_name == "mtd" {
+					// the content might have been cleaned away (e.g., '<mtd><mrow/></mtd>') -- the cell still needs a placeholder
+					if children.is_empty() {
+						children.push( ChildOfElement::Element(CanonicalizeContext::create_empty_element(&mathml.document())) );
+					}
+					if children.len() == 1 && CanonicalizeContext::is_empty_element(as_element(children[0])) {
+						mathml.set_attribute_value(EMPTY_CELL_ATTR, "true");
+					}
 				}
 
 				mathml.replace_children(children);
 				// debug!("clean_mathml: after loop\n{}", mml_to_string(&mathml));
+				if element_name == "mtable" && is_augmented_matrix(mathml) {
+					mathml.set_attribute_value(AUGMENTED_MATRIX_ATTR, "true");
+				}
 
 				if element_name == "mrow" || ELEMENTS_WITH_ONE_CHILD.contains(element_name) {
 					clean_chemistry_mrow(mathml);
@@ -959,36 +1702,56 @@ impl CanonicalizeContext {
 		}
 
 
-		/// Returns true if it detects that this is likely coming from mhchem (msub/msup with mrow/mrow/mpadded width=0/mphantom/mi=A)
-		/// This should be called with 'mrow' being the outer mrow
-		fn is_from_mhchem_hack(mrow: Element) -> bool {
-			assert_eq!(name(&mrow), "mrow");
-			assert_eq!(mrow.children().len(), 1);
-			let parent = mrow.parent().unwrap().element().unwrap();
-			let parent_name = name(&parent);
-			if !(parent_name == "msub" || parent_name == "msup") {
-				return false;
+		/// Makes the quotes of an 'ms' explicit so that speech and braille don't need to know the defaults.
+		/// The default (from the MathML spec) is '"' and TeX-style quotes ("``" and "''") become curly quotes.
+		fn normalize_ms_quotes(ms: Element) {
+			for attr_name in ["lquote", "rquote"] {
+				let quote = match ms.attribute_value(attr_name) {
+					None => "\"",
+					Some("``") => "“",
+					Some("''") => "”",
+					Some(_) => continue,
+				};
+				ms.set_attribute_value(attr_name, quote);
 			}
+		}
 
-			let mrow = as_element(mrow.children()[0]);
-			if !(name(&mrow) == "mrow" && mrow.children().len() == 1) {
-				return false;
-			}
-			let child = as_element(mrow.children()[0]);
-			if !(name(&child) == "mpadded" && child.attribute("width").is_some()) {
-				return false;
+		/// Marks an mfrac that has no line or is bevelled with FRACTION_KIND_ATTR.
+		/// 'linethickness' can have units (e.g., "0px") and 'bevelled' can be "false", so the rules test FRACTION_KIND_ATTR and not the attrs
+		fn mark_fraction_kind(mfrac: Element) {
+			if mfrac.attribute_value("linethickness").is_some_and(is_zero_length) {
+				mfrac.set_attribute_value(FRACTION_KIND_ATTR, NO_LINE_FRACTION);
+			} else if mfrac.attribute_value("bevelled").is_some_and(|bevelled| bevelled.trim() == "true") {
+				mfrac.set_attribute_value(FRACTION_KIND_ATTR, BEVELLED_FRACTION);
 			}
-			if child.attribute_value("width").unwrap() != "0" {
-				return false;
+
+			fn is_zero_length(length: &str) -> bool {
+				let amount = length.trim().trim_end_matches(|ch: char| ch.is_ascii_alphabetic() || ch == '%');
+				return amount.parse::<f64>().is_ok_and(|amount| amount == 0.0);
 			}
+		}
 
-			let child = as_element(child.children()[0]);
-			if !(name(&child) == "mphantom" && child.children().len() == 1) {
+		/// Returns true if 'columnlines' on 'mtable' has a line before the last column and nowhere else.
+		/// 'columnlines' has an entry for each gap between columns, with the last entry repeated if there are too few.
+		fn is_augmented_matrix(mtable: Element) -> bool {
+			let column_lines = match mtable.attribute_value("columnlines") {
+				None => return false,
+				Some(column_lines) => column_lines.split_whitespace().collect::<Vec<&str>>(),
+			};
+			let n_columns = mtable.children().iter()
+					.map(|&row| {
+						let row = as_element(row);
+						let n_cells = row.children().len();
+						if name(&row) == "mlabeledtr" {n_cells.saturating_sub(1)} else {n_cells}
+					})
+					.max()
+					.unwrap_or(0);
+			if n_columns < 2 || column_lines.is_empty() {
 				return false;
 			}
-
-			let child = as_element(child.children()[0]);
-			return name(&child) == "mi" && as_text(child) == "A";
+			let column_line = |i: usize| *column_lines.get(i).unwrap_or(column_lines.last().unwrap());
+			return matches!(column_line(n_columns - 2), "solid" | "dashed") &&
+				   (0..n_columns - 2).all(|i| column_line(i) == "none");
 		}
 
 		/// Returns true if it appears the width is just a spacing tweak rather than really a space.
@@ -1012,6 +1775,7 @@ impl CanonicalizeContext {
 			return false;
 		}
 
+
 		fn clean_chemistry_leaf(mathml: Element) -> Element {
 			if !(is_chemistry_off() || mathml.attribute(MAYBE_CHEMISTRY).is_some()) {
 				assert!(name(&mathml)=="mi" || name(&mathml)=="mtext");
@@ -1187,70 +1951,119 @@ impl CanonicalizeContext {
 		}
 
 		fn is_roman_number_match(text: &str) -> bool {
-			return UPPER_ROMAN_NUMERAL.is_match(text) || LOWER_ROMAN_NUMERAL.is_match(text);
+			return !text.trim().is_empty() && (UPPER_ROMAN_NUMERAL.is_match(text) || LOWER_ROMAN_NUMERAL.is_match(text));
+		}
+
+		/// If 'mathml' (an 'mi' or 'mtext') is a roman numeral, it is changed to an 'mn' (see [`mark_roman_numeral`]).
+		/// An author can force this with NUMBER_KIND_ATTR; otherwise it is only done if the 'RomanNumerals' API pref is true
+		///   and the context makes it likely (see [`is_roman_numeral_number_context`]).
+		fn clean_roman_numeral(mathml: Element) -> Option<Element> {
+			// people tend to set them in a non-italic font and software makes that 'mtext'
+			let text = as_text(mathml);
+			if !is_roman_number_match(text) {
+				return None;
+			}
+			if mathml.attribute_value(NUMBER_KIND_ATTR) == Some(ROMAN_NUMERAL) ||
+			   (is_roman_numerals_on() && is_roman_numeral_number_context(mathml)) {
+				return Some(mark_roman_numeral(mathml));
+			}
+			return None;
+		}
+
+		/// Change 'mathml' to an 'mn' marked as a roman numeral with its value in 'data-number'
+		fn mark_roman_numeral(mathml: Element) -> Element {
+			set_mathml_name(mathml, "mn");
+			mathml.set_attribute_value(NUMBER_KIND_ATTR, ROMAN_NUMERAL);
+			mathml.set_attribute_value("data-number", &roman_numeral_value(as_text(mathml).trim()).to_string());
+			return mathml;
+		}
+
+		/// Returns the value of 'text', which must be a valid roman numeral
+		fn roman_numeral_value(text: &str) -> usize {
+			let digits = text.chars()
+				.map(|ch| match ch.to_ascii_uppercase() {
+					'I' => 1, 'V' => 5, 'X' => 10, 'L' => 50, 'C' => 100, 'D' => 500, 'M' => 1000,
+					_ => panic!("Internal error: '{}' is not a roman numeral", text),
+				})
+				.collect::<Vec<usize>>();
+			// a digit that is smaller than the one after it is subtracted (e.g., the 'I' in "IX")
+			return digits.iter().enumerate()
+				.map(|(i, &digit)| if i+1 < digits.len() && digit < digits[i+1] {-(digit as isize)} else {digit as isize})
+				.sum::<isize>() as usize;
 		}
 
 		/// Return true if 'element' (which is syntactically a roman numeral) is only inside mrows and
-		///  if its length is < 3 chars, then there is another roman numeral near it (separated by an operator).
+		///  if it is short (< 3 chars) or might be something else (a unit, chemical element, or in the `NonRomanNumerals` definition),
+		///  then there are other roman numerals near it (separated by operators) and at least one of them isn't in doubt.
 		/// We want to rule out something like 'm' or 'cm' being a roman numeral.
-		// fn is_roman_numeral_number_context(mathml: Element) -> bool {
-		// 	assert!(name(&mathml)=="mtext" || name(&mathml)=="mi");
-		// 	let mut parent = mathml;
-		// 	loop {
-		// 		parent = parent.parent().unwrap().element().unwrap();
-		// 		let current_name = name(&parent);
-		// 		if current_name == "math" {
-		// 			break;
-		// 		} else if current_name != "mrow" {
-		// 			return false;
-		// 		}
-		// 	}
-		// 	if as_text(mathml).len() > 2 {
-		// 		return true;
-		// 	} else {
-		// 		let is_upper_case = as_text(mathml).as_bytes()[0].is_ascii_uppercase();	// safe since we know it is a 
-		// 		let preceding = mathml.preceding_siblings();
-		// 		if !preceding.is_empty() {
-		// 			if !is_roman_numeral_adjacent(preceding.iter().rev(), is_upper_case) {
-		// 				return false;
-		// 			}
-		// 		}
-		// 		let following = mathml.following_siblings();
-		// 		if following.is_empty() {
-		// 			return false;		// no context and too short to confirm it is a roman numeral
-		// 		}
-		// 		return is_roman_numeral_adjacent(following.iter(), is_upper_case);
-		// 	}
-
-		// 	/// make sure all the non-mo leaf siblings are roman numerals
-		// 	fn is_roman_numeral_adjacent<'a, I>(mut siblings: I, must_be_upper_case: bool) -> bool
-		// 			where I: Iterator<Item = &'a ChildOfElement<'a>> {				
-		// 		let mut found_match = false;		// guard against no siblings
-		// 		while let Some(child) = siblings.next() {
-		// 			let mut maybe_roman_numeral = as_element(*child);
-		// 			if name(&maybe_roman_numeral) == "mo" {
-		// 				let after_mo = siblings.next();
-		// 				if after_mo.is_none() {
-		// 					return false;
-		// 				}
-		// 				maybe_roman_numeral = as_element(*after_mo.unwrap());
-		// 			}
-		// 			if !is_leaf(maybe_roman_numeral) {
-		// 				return false;
-		// 			}
-		// 			let text = as_text(maybe_roman_numeral);
-		// 			if text.trim().is_empty() {
-		// 				return false;
-		// 			}
-		// 			if !(( must_be_upper_case && UPPER_ROMAN_NUMERAL.is_match(text)) ||
-		// 				 (!must_be_upper_case && LOWER_ROMAN_NUMERAL.is_match(text)) ) {
-		// 					return false;
-		// 			};
-		// 			found_match = true;
-		// 		}
-		// 		return found_match;
-		// 	}
-		// }
+		fn is_roman_numeral_number_context(mathml: Element) -> bool {
+			assert!(name(&mathml)=="mtext" || name(&mathml)=="mi");
+			let mut parent = mathml;
+			loop {
+				parent = parent.parent().unwrap().element().unwrap();
+				let current_name = name(&parent);
+				if current_name == "math" {
+					break;
+				} else if current_name != "mrow" {
+					return false;
+				}
+			}
+			if !is_doubtful_roman_numeral(mathml) {
+				return true;
+			}
+			let is_upper_case = as_text(mathml).trim().as_bytes()[0].is_ascii_uppercase();	// safe since we know it is a roman numeral
+			let preceding = mathml.preceding_siblings();
+			let following = mathml.following_siblings();
+			if preceding.is_empty() && following.is_empty() {
+				return false;		// no context and too short to confirm it is a roman numeral
+			}
+			let mut found_confirmed = false;
+			return is_roman_numeral_adjacent(preceding.iter().rev(), is_upper_case, &mut found_confirmed) &&
+				   is_roman_numeral_adjacent(following.iter(), is_upper_case, &mut found_confirmed) &&
+				   found_confirmed;
+
+			/// Returns true if the (leaf) element is a roman numeral that might be something else
+			fn is_doubtful_roman_numeral(mathml: Element) -> bool {
+				let text = as_text(mathml).trim();
+				return text.len() < 3 ||
+					   crate::xpath_functions::IsUnit::is_unit(mathml) ||
+					   is_chemical_element(mathml) ||
+					   crate::definitions::DEFINITIONS.with(|definitions|
+						   definitions.borrow().get_hashset("NonRomanNumerals").unwrap().contains(text)
+					   );
+			}
+
+			/// make sure all the non-mo leaf siblings are roman numerals (of the same case)
+			/// 'found_confirmed' is set if one of them is not doubtful
+			fn is_roman_numeral_adjacent<'a, I>(mut siblings: I, must_be_upper_case: bool, found_confirmed: &mut bool) -> bool
+					where I: Iterator<Item = &'a ChildOfElement<'a>> {
+				while let Some(child) = siblings.next() {
+					let mut maybe_roman_numeral = as_element(*child);
+					if name(&maybe_roman_numeral) == "mo" {
+						let after_mo = siblings.next();
+						if after_mo.is_none() {
+							return false;
+						}
+						maybe_roman_numeral = as_element(*after_mo.unwrap());
+					}
+					if !is_leaf(maybe_roman_numeral) {
+						return false;
+					}
+					let text = as_text(maybe_roman_numeral);
+					if text.trim().is_empty() {
+						return false;
+					}
+					if !(( must_be_upper_case && UPPER_ROMAN_NUMERAL.is_match(text)) ||
+						 (!must_be_upper_case && LOWER_ROMAN_NUMERAL.is_match(text)) ) {
+							return false;
+					};
+					if !is_doubtful_roman_numeral(maybe_roman_numeral) {
+						*found_confirmed = true;
+					}
+				}
+				return true;
+			}
+		}
 
 		fn is_digit_block(mathml: Element) -> DigitBlockType {
 			// returns true if an 'mn' with exactly three digits
@@ -1280,6 +2093,52 @@ impl CanonicalizeContext {
 			return DigitBlockType::None;
 		}
 
+		/// Mark mtext that is a connective word/phrase (e.g., "where", "for all", "such that") with CONNECTIVE_TEXT_ATTR.
+		/// The words come from the language's `ConnectiveWords` definition (surrounding whitespace is ignored).
+		/// 
+		/// Note: this should be called before `merge_whitespace` so that whitespace merged into the mtext doesn't hide the words
+		fn mark_connective_text(children: &[ChildOfElement]) {
+			for child in children {
+				let child = as_element(*child);
+				if name(&child) != "mtext" || child.attribute(CONNECTIVE_TEXT_ATTR).is_some() {
+					continue;
+				}
+				let text = as_text(child).trim_matches(|ch: char| ch.is_whitespace());
+				if text.is_empty() {
+					continue;
+				}
+				let is_connective = crate::definitions::DEFINITIONS.with(|defs| {
+					return match defs.borrow().get_hashset("ConnectiveWords") {
+						Some(words) => words.contains(&text.to_lowercase()),
+						None => false,
+					};
+				});
+				if is_connective {
+					child.set_attribute_value(CONNECTIVE_TEXT_ATTR, "true");
+				}
+			}
+		}
+
+		/// Mark mtext that is several words used as an operand (e.g., "2 total cost" or "area = …") with DESCRIPTIVE_IDENTIFIER_ATTR.
+		/// Because mtext is often prose, the mtext must be next to a number or an operator.
+		///
+		/// Note: this should be called after `mark_connective_text` (connective text is not an identifier)
+		fn mark_descriptive_text(children: &[ChildOfElement]) {
+			for (i, child) in children.iter().enumerate() {
+				let child = as_element(*child);
+				if name(&child) != "mtext" || child.attribute(CONNECTIVE_TEXT_ATTR).is_some() || !is_descriptive_identifier(as_text(child)) {
+					continue;
+				}
+				let is_operand = |neighbor: Option<&ChildOfElement>| neighbor.is_some_and(|&neighbor| {
+					let neighbor = as_element(neighbor);
+					return name(&neighbor) == "mn" || (name(&neighbor) == "mo" && !IS_WHITESPACE.is_match(as_text(neighbor)));
+				});
+				if is_operand(i.checked_sub(1).and_then(|i| children.get(i))) || is_operand(children.get(i+1)) {
+					child.set_attribute_value(DESCRIPTIVE_IDENTIFIER_ATTR, "true");
+				}
+			}
+		}
+
 		/// Merge mtext that is whitespace onto preceding or following mi/mn.
 		/// 
 		/// Note: this should be called *after* the mo/mtext cleanup (i.e., after the MathML child cleanup loop).
@@ -1333,6 +2192,14 @@ impl CanonicalizeContext {
 				static ref SEPARATORS: Regex = Regex::new(r"[],. \u{00A0}]").unwrap(); 
 			}
 			// debug!("parent:\n{}", mml_to_string(&parent_mrow));
+			// wide space between two numbers separates columns (e.g., a row of a table) -- the numbers are a list, not a product
+			for i in 1..children.len().saturating_sub(1) {
+				if is_column_space(as_element(children[i])) &&
+				   name(&as_element(children[i-1])) == "mn" && name(&as_element(children[i+1])) == "mn" {
+					children[i] = ChildOfElement::Element( create_invisible_separator(as_element(children[i])) );
+				}
+			}
+
 			let mut i = 0;
 			while i < children.len() {
 				let child = as_element(children[i]);
@@ -1394,8 +2261,16 @@ impl CanonicalizeContext {
 						}
 						looking_for_separator = !looking_for_separator;
 					}
+					// don't include whitespace at the end (e.g., the space before the next column)
+					while end >= start+3 && is_whitespace_leaf(as_element(children[end-1])) {
+						end -= 1;
+					}
 					// debug!("start={}, end={}", start, end);
-					if is_likely_a_number(parent_mrow, children, start, end) {
+					if children[start..end].iter().filter(|&&child| name(&as_element(child)) == "mn").count() > MAX_NUMBER_BLOCKS {
+						// too long to be a number -- probably a row of table entries
+						separate_blocks(children, start, end);
+						i = end-1;
+					} else if is_likely_a_number(parent_mrow, children, start, end) {
 						merge_block(children, start, end);
 						// note: i..i+end has been collapsed, so just inc 'i' by one
 					} else {
@@ -1406,21 +2281,623 @@ impl CanonicalizeContext {
 			}
 		}
 
+		fn is_whitespace_leaf(mathml: Element) -> bool {
+			return is_leaf(mathml) && as_text(mathml).trim().is_empty();
+		}
+
+		/// Replace the whitespace between the digit blocks in children[start..end] with invisible separators so the blocks
+		/// are a list of numbers (e.g., a row of table entries) rather than being multiplied together
+		fn separate_blocks(children: &mut [ChildOfElement], start: usize, end: usize) {
+			for i in start+1..end {
+				let child = as_element(children[i]);
+				if is_whitespace_leaf(child) && name(&as_element(children[i-1])) == "mn" {
+					children[i] = ChildOfElement::Element( create_invisible_separator(child) );
+				}
+			}
+		}
+
+		/// An invisible separator (U+2063) that replaces the whitespace 'space' (its width is kept)
+		fn create_invisible_separator(space: Element) -> Element {
+			let separator = create_mo(space.document(), "\u{2063}", ADDED_ATTR_VALUE);
+			separator.set_attribute_value(NUMBER_LIST_ATTR, "true");
+			if let Some(width) = space.attribute_value("width") {
+				separator.set_attribute_value("width", width);
+			}
+			return separator;
+		}
+
+		/// Returns true if 'mathml' is space that is too wide to be a digit group separator (e.g., the space between columns of a table).
+		/// The width comes from an `mspace` that was converted to an `mtext` (e.g., `<mspace width='2em'/>`).
+		fn is_column_space(mathml: Element) -> bool {
+			if name(&mathml) != "mtext" {
+				return false;
+			}
+			let width = match mathml.attribute_value("width") {
+				None => return false,
+				Some(width) => width.trim(),
+			};
+			if let Some(i) = width.find(|ch: char| ch.is_ascii_alphabetic()) {
+				let (amount, unit) = width.split_at(i);
+				let amount = amount.parse::<f64>().unwrap_or(0.);
+				return match unit {
+					"em" | "rem" => amount >= 1.0,
+					"ex" => amount >= 2.0,
+					"px" => amount >= 16.0,	// assume 12pt font -- hack (see is_width_ignorable)
+					_ => false,				// named spaces such as "thickmathspace" are all less than 1em
+				};
+			}
+			return false;
+		}
+
+		/// Look for a decimal number followed by digits with a bar or dot over them (e.g., 0.3̄) -- a repeating decimal.
+		/// Also look for a decimal number whose last digits repeat followed by an ellipsis (e.g., "0,1666…").
+		///   The repeating digits are recorded in REPEATING_DIGITS_ATTR. Because "0,333…" could be a list (or a number with
+		///   a thousands separator), this is only done when the separator is one of the language's decimal separators.
+		/// The number might be split at the decimal separator (e.g., "0", ",", "3̄") -- it is merged if the separator is
+		///   one of the language's decimal separators.
+		/// These are grouped into an mrow marked with NUMBER_KIND_ATTR so that no invisible times is added between them.
+		fn merge_repeating_decimals(children: &mut Vec<ChildOfElement>) {
+			let mut i = 1;
+			while i < children.len() {
+				let repeat_marker = as_element(children[i]);
+				let is_overbar = is_repeating_digits(repeat_marker);
+				if is_overbar || is_ellipsis(repeat_marker) {
+					if let Some((number_text, n_number_children)) = decimal_before(children, i) {
+						let repeating_digits = if is_overbar {None} else {ellipsis_repeating_digits(&number_text)};
+						if is_overbar || repeating_digits.is_some() {
+							let i_number = i - n_number_children;
+							let number = as_element(children[i_number]);
+							if n_number_children > 1 {
+								number.set_text(&number_text);
+							}
+							let mrow = create_mathml_element(&number.document(), "mrow");
+							mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+							mrow.set_attribute_value(NUMBER_KIND_ATTR, REPEATING_DECIMAL);
+							if let Some(repeating_digits) = repeating_digits {
+								mrow.set_attribute_value(REPEATING_DIGITS_ATTR, repeating_digits);
+							}
+							mrow.replace_children([number, repeat_marker]);
+							children.splice(i_number..i+1, [ChildOfElement::Element(mrow)]);
+							i = i_number;
+						}
+					}
+				}
+				i += 1;
+			}
+
+			/// Returns the text of the decimal number that ends just before 'children[i]' and the number of children it uses
+			fn decimal_before(children: &[ChildOfElement], i: usize) -> Option<(String, usize)> {
+				let is_digits = |child: ChildOfElement| {
+					let child = as_element(child);
+					return name(&child) == "mn" && !as_text(child).is_empty() && as_text(child).chars().all(|ch| ch.is_ascii_digit());
+				};
+				let number = as_element(children[i-1]);
+				if name(&number) == "mn" && is_decimal_start(as_text(number)) {
+					return Some( (as_text(number).to_string(), 1) );
+				}
+				// split number: "0", "," or "0", ",", "1"
+				let n_children = if is_digits(children[i-1]) {3} else {2};
+				if i < n_children {
+					return None;
+				}
+				let i_start = i - n_children;
+				let separator = as_element(children[i_start+1]);
+				if !is_digits(children[i_start]) || name(&separator) != "mo" || !is_decimal_separator(as_text(separator)) {
+					return None;
+				}
+				let text = children[i_start..i].iter().map(|&child| as_text(as_element(child))).collect::<String>();
+				return Some( (text, n_children) );
+			}
+
+			fn is_ellipsis(element: Element) -> bool {
+				return (name(&element) == "mo" || name(&element) == "mi") && matches!(as_text(element), "…" | "...");
+			}
+
+			/// Returns the shortest digit sequence that repeats (at least twice) at the end of 'number' (e.g., "6" for "0,1666")
+			fn ellipsis_repeating_digits(number: &str) -> Option<&str> {
+				let (_, decimals) = split_at_decimal_separator(number)?;
+				let fraction = decimals.as_bytes();		// all ASCII digits
+				for n_repeating in 1..=fraction.len()/2 {
+					// find the start of the digits that repeat with this period, then round it to a whole number of repetitions
+					let mut i_start = fraction.len() - n_repeating;
+					while i_start > 0 && fraction[i_start-1] == fraction[i_start-1+n_repeating] {
+						i_start -= 1;
+					}
+					i_start += (fraction.len() - i_start) % n_repeating;
+					if fraction.len() - i_start >= 2 * n_repeating {
+						return Some( &decimals[i_start..i_start+n_repeating] );
+					}
+				}
+				return None;
+			}
+
+			fn is_repeating_digits(mover: Element) -> bool {
+				if name(&mover) != "mover" {
+					return false;
+				}
+				let base = as_element(mover.children()[0]);
+				let accent = as_element(mover.children()[1]);
+				return name(&base) == "mn" && as_text(base).chars().all(|ch| ch.is_ascii_digit()) &&
+					   is_leaf(accent) && REPEATING_DECIMAL_ACCENTS.contains(as_text(accent));
+			}
+		}
+
+		/// Look for an en dash between two numbers (e.g., "pages 12–15") -- a range.
+		/// The dash would otherwise bind less tightly than an invisible times with what comes before (e.g., "pages⁢12"),
+		///   so these are grouped into an mrow marked with NUMBER_KIND_ATTR.
+		/// This is only needed if something other than an operator comes before the numbers.
+		/// It isn't done if an operator is next to the numbers (e.g., "3·12–15") because then the dash is likely a minus sign.
+		fn merge_number_ranges(children: &mut Vec<ChildOfElement>) {
+			let mut i = 2;
+			while i < children.len() {
+				let start = as_element(children[i-2]);
+				let dash = as_element(children[i-1]);
+				let end = as_element(children[i]);
+				if name(&start) == "mn" && name(&dash) == "mo" && as_text(dash) == "–" && name(&end) == "mn" &&
+				   i > 2 && name(&as_element(children[i-3])) != "mo" &&
+				   !(i+1 < children.len() && name(&as_element(children[i+1])) == "mo") {
+					let mrow = create_mathml_element(&start.document(), "mrow");
+					mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+					mrow.set_attribute_value(NUMBER_KIND_ATTR, NUMBER_RANGE);
+					mrow.replace_children([start, dash, end]);
+					children.splice(i-2..i+1, [ChildOfElement::Element(mrow)]);
+				}
+				i += 1;
+			}
+		}
+
+		/// Group the parts of an exercise that are labeled "(a)", "(b)", ... (also "(1)", "(2)", ... and "(i)", "(ii)", ...).
+		/// Each label is put into an mrow marked with ENUMERATOR_ATTR and the math up to the next label is put into an mrow
+		///   so that "(a)" isn't parsed as a parenthesized variable times what follows it.
+		/// To avoid false positives, the first label must start the mrow and there must be at least two labels in sequence.
+		fn group_enumerated_parts(children: &mut Vec<ChildOfElement>) {
+			const ROMAN_LABELS: [&str; 12] = ["i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x", "xi", "xii"];
+			let mut label_starts = vec![];
+			let mut i = 0;
+			while i + 3 < children.len() {		// there must be something after the label
+				if let Some(label) = enumerator_label(&children[i..i+3]) {
+					if (label_starts.is_empty() && i == 0) ||
+					   (!label_starts.is_empty() && is_next_label(enumerator_label(&children[*label_starts.last().unwrap()..]).unwrap(), label)) {
+						label_starts.push(i);
+						i += 3;
+						continue;
+					}
+				}
+				if label_starts.is_empty() {
+					return;
+				}
+				i += 1;
+			}
+			if label_starts.len() < 2 {
+				return;
+			}
+
+			let doc = as_element(children[0]).document();
+			let mut new_children = Vec::with_capacity(2 * label_starts.len());
+			for (i_label, &i_start) in label_starts.iter().enumerate() {
+				let label = create_mathml_element(&doc, "mrow");
+				label.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+				label.set_attribute_value(ENUMERATOR_ATTR, enumerator_label(&children[i_start..]).unwrap());
+				label.append_children(children[i_start..i_start+3].to_vec());
+				new_children.push(ChildOfElement::Element(label));
+
+				// the spacing around the part is only there to separate it from the labels (it might have been merged into a leaf)
+				let i_end = if i_label + 1 < label_starts.len() {label_starts[i_label + 1]} else {children.len()};
+				let mut part = &children[i_start+3..i_end];
+				while let Some((first, rest)) = part.split_first() {
+					if !CanonicalizeContext::is_empty_element(as_element(*first)) {
+						let first = as_element(*first);
+						if is_leaf(first) {
+							let text = as_text(first).trim_start_matches('\u{A0}').to_string();
+							first.set_text(&text);
+						}
+						break;
+					}
+					part = rest;
+				}
+				while let Some((last, rest)) = part.split_last() {
+					if !CanonicalizeContext::is_empty_element(as_element(*last)) {
+						let last = as_element(*last);
+						if is_leaf(last) {
+							let text = as_text(last).trim_end_matches('\u{A0}').to_string();
+							last.set_text(&text);
+						}
+						break;
+					}
+					part = rest;
+				}
+				match part.len() {
+					0 => continue,
+					1 => new_children.push(part[0]),
+					_ => {
+						let mrow = create_mathml_element(&doc, "mrow");
+						mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+						mrow.append_children(part.to_vec());
+						new_children.push(ChildOfElement::Element(mrow));
+					},
+				}
+			}
+			*children = new_children;
+
+			/// If 'children' starts with "(", a label, and ")", return the label
+			fn enumerator_label<'a>(children: &[ChildOfElement<'a>]) -> Option<&'a str> {
+				if children.len() < 3 {
+					return None;
+				}
+				let open = as_element(children[0]);
+				let label = as_element(children[1]);
+				let close = as_element(children[2]);
+				if name(&open) != "mo" || as_text(open) != "(" || name(&close) != "mo" || as_text(close) != ")" {
+					return None;
+				}
+				let label_name = name(&label);
+				if label_name != "mi" && label_name != "mn" {
+					return None;
+				}
+				let text = as_text(label).trim();
+				let is_label = if label_name == "mi" {
+					text.chars().all(|ch| ch.is_ascii_lowercase()) && (text.len() == 1 || ROMAN_LABELS.contains(&text))
+				} else {
+					text.len() <= 2 && text.chars().all(|ch| ch.is_ascii_digit())
+				};
+				return if is_label {Some(text)} else {None};
+			}
+
+			/// True if 'label' follows 'previous' (e.g., "b" follows "a", "3" follows "2", "iv" follows "iii")
+			fn is_next_label(previous: &str, label: &str) -> bool {
+				if let (Ok(previous), Ok(label)) = (previous.parse::<usize>(), label.parse::<usize>()) {
+					return label == previous + 1;
+				}
+				if let (Some(i_previous), Some(i_label)) = (ROMAN_LABELS.iter().position(|&roman| roman == previous),
+				                                            ROMAN_LABELS.iter().position(|&roman| roman == label)) {
+					if i_label == i_previous + 1 {
+						return true;
+					}
+				}
+				let mut previous = previous.chars();
+				let mut label = label.chars();
+				return match (previous.next(), previous.next(), label.next(), label.next()) {
+					(Some(previous), None, Some(label), None) => label as u32 == previous as u32 + 1,
+					_ => false,
+				};
+			}
+		}
+
+		/// An 'mn' written in "e" notation (e.g., "3.2e5" or "6.02E-23") is split into the number, the "e", and the exponent.
+		/// The mrow (which 'mn' becomes) is marked as scientific notation.
+		fn split_e_notation(mn: Element) -> Option<Element> {
+			if name(&mn) != "mn" {
+				return None;
+			}
+			let text = as_text(mn).to_string();
+			let parts = IS_E_NOTATION.captures(&text)?;
+			let doc = mn.document();
+			let mantissa = create_mathml_element(&doc, "mn");
+			mantissa.set_text(&parts[1]);
+			let e = create_mathml_element(&doc, "mi");
+			e.set_text(&parts[2]);
+			let mut exponent = create_mathml_element(&doc, "mn");
+			exponent.set_text(&parts[4]);
+			if !parts[3].is_empty() {
+				let sign = create_mo(doc, if &parts[3] == "+" {"+"} else {"-"}, ADDED_ATTR_VALUE);
+				let signed_exponent = create_mathml_element(&doc, "mrow");
+				signed_exponent.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+				signed_exponent.replace_children([sign, exponent]);
+				exponent = signed_exponent;
+			}
+			set_mathml_name(mn, "mrow");
+			mn.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			mn.set_attribute_value(NUMBER_KIND_ATTR, SCIENTIFIC_NOTATION);
+			mn.replace_children([mantissa, e, exponent]);
+			return Some(mn);
+		}
+
+		/// Group "m × 10ⁿ" (also with "·" or "⋅") where 'm' is a number and 'n' is an integer into an mrow marked as scientific notation.
+		fn merge_scientific_notation(children: &mut Vec<ChildOfElement>) {
+			let mut i = 2;
+			while i < children.len() {
+				let mantissa = as_element(children[i-2]);
+				let times = as_element(children[i-1]);
+				let power = as_element(children[i]);
+				if name(&mantissa) == "mn" && IS_MANTISSA.is_match(as_text(mantissa)) &&
+				   name(&times) == "mo" && matches!(as_text(times), "×" | "·" | "⋅") &&
+				   is_power_of_ten(power) {
+					let mrow = create_mathml_element(&mantissa.document(), "mrow");
+					mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+					mrow.set_attribute_value(NUMBER_KIND_ATTR, SCIENTIFIC_NOTATION);
+					mrow.replace_children([mantissa, times, power]);
+					children.splice(i-2..i+1, [ChildOfElement::Element(mrow)]);
+				}
+				i += 1;
+			}
+
+			fn is_power_of_ten(msup: Element) -> bool {
+				if name(&msup) != "msup" {
+					return false;
+				}
+				let base = as_element(msup.children()[0]);
+				if !(name(&base) == "mn" && as_text(base) == "10") {
+					return false;
+				}
+				let exponent = as_element(msup.children()[1]);
+				return match name(&exponent) {
+					"mn" => IS_INTEGER.is_match(as_text(exponent)),
+					"mrow" => {
+						// "-5" (not yet parsed) or "(-5)" isn't allowed
+						let exponent_children = exponent.children();
+						exponent_children.len() == 2 && 
+							name(&as_element(exponent_children[0])) == "mo" &&
+							matches!(as_text(as_element(exponent_children[0])), "-" | "−" | "+") &&
+							name(&as_element(exponent_children[1])) == "mn" &&
+							IS_INTEGER.is_match(as_text(as_element(exponent_children[1])))
+					},
+					_ => false,
+				};
+			}
+		}
+
+		/// An 'mn' such as "0.3̄" uses combining chars to mark the repeating digits.
+		/// This splits it into the same structure that `merge_repeating_decimals` produces.
+		fn split_repeating_decimal(mn: Element) -> Option<Element> {
+			let chars = as_text(mn).chars().collect::<Vec<char>>();
+			let mut accent = None;
+			let mut i = chars.len();
+			while i >= 2 && chars[i-2].is_ascii_digit() && (accent.is_none() || accent == Some(chars[i-1])) {
+				match chars[i-1] {
+					'\u{0304}' | '\u{0305}' | '\u{0307}' => accent = Some(chars[i-1]),
+					_ => break,
+				}
+				i -= 2;
+			}
+			let accent = match accent? {
+				'\u{0307}' => "\u{02D9}",	// dot above
+				_ => "\u{00AF}",			// macron/overline
+			};
+			let non_repeating = chars[..i].iter().collect::<String>();
+			if !is_decimal_start(&non_repeating) {
+				return None;
+			}
+			let repeating = chars[i..].iter().step_by(2).collect::<String>();
+
+			let doc = mn.document();
+			let base = create_mathml_element(&doc, "mn");
+			base.set_text(&repeating);
+			let mo = create_mathml_element(&doc, "mo");
+			mo.set_text(accent);
+			let mover = create_mathml_element(&doc, "mover");
+			mover.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			mover.replace_children([base, mo]);
+			let number = create_mathml_element(&doc, "mn");
+			number.set_text(&non_repeating);
+			set_mathml_name(mn, "mrow");
+			mn.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			mn.set_attribute_value(NUMBER_KIND_ATTR, REPEATING_DECIMAL);
+			mn.replace_children([number, mover]);
+			return Some(mn);
+		}
+
+		/// Splits trailing superscript chars in a token (e.g., "10²", "cm³", or "xⁿ") into an msup.
+		/// Only numbers (possibly signed) and single letters are split off; anything else is left alone.
+		/// The returned (msup) element reuses the arg so tree siblings links remain correct.
+		fn split_superscript_chars(leaf: Element) -> Option<Element> {
+			static SUPERSCRIPT_CHARS: phf::Map<char, char> = phf_map! {
+				'⁰' => '0', '¹' => '1', '²' => '2', '³' => '3', '⁴' => '4',
+				'⁵' => '5', '⁶' => '6', '⁷' => '7', '⁸' => '8', '⁹' => '9',
+				'⁺' => '+', '⁻' => '-', 'ⁿ' => 'n', 'ⁱ' => 'i',
+			};
+			let text = as_text(leaf);
+			let (i_script, _) = text.char_indices().rev()
+					.take_while(|(_, ch)| SUPERSCRIPT_CHARS.contains_key(ch))
+					.last()?;
+			if i_script == 0 {
+				return None;
+			}
+			let script_text = text[i_script..].chars().map(|ch| SUPERSCRIPT_CHARS[&ch]).collect::<String>();
+
+			let doc = leaf.document();
+			let script = if script_text.chars().all(|ch| ch.is_ascii_digit()) {
+				let mn = create_mathml_element(&doc, "mn");
+				mn.set_text(&script_text);
+				mn
+			} else if script_text.len() > 1 && script_text[1..].chars().all(|ch| ch.is_ascii_digit()) &&
+					  (script_text.starts_with('-') || script_text.starts_with('+')) {
+				let mo = create_mathml_element(&doc, "mo");
+				mo.set_text(&script_text[..1]);
+				let mn = create_mathml_element(&doc, "mn");
+				mn.set_text(&script_text[1..]);
+				let mrow = create_mathml_element(&doc, "mrow");
+				mrow.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+				mrow.replace_children([mo, mn]);
+				mrow
+			} else if script_text == "n" || script_text == "i" {
+				let mi = create_mathml_element(&doc, "mi");
+				mi.set_text(&script_text);
+				mi
+			} else {
+				return None;
+			};
+
+			let base = create_mathml_element(&doc, name(&leaf));
+			base.set_text(&text[..i_script]);
+			if let Some(variant) = leaf.attribute_value("mathvariant") {
+				base.set_attribute_value("mathvariant", variant);		// needed for units such as an upright "m²"
+				leaf.remove_attribute("mathvariant");
+			}
+			set_mathml_name(leaf, "msup");
+			leaf.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			leaf.replace_children([base, script]);
+			return Some(leaf);
+		}
+
+		/// Returns true if the mover children are an identifier with an accent over it (e.g., "x" and "^")
+		fn is_embellished_identifier(children: &[ChildOfElement]) -> bool {
+			if children.len() != 2 {
+				return false;		// bad MathML
+			}
+			let base = as_element(children[0]);
+			let accent = as_element(children[1]);
+			return name(&base) == "mi" && name(&accent) == "mo" && IDENTIFIER_ACCENTS.contains(as_text(accent));
+		}
+
+		/// Returns true if the base is an arrow and the script is a label (not a chemistry arrow, which has already been handled)
+		fn is_labeled_arrow(children: &[ChildOfElement]) -> bool {
+			if children.len() != 2 {
+				return false;		// bad MathML
+			}
+			let base = as_element(children[0]);
+			let label = as_element(children[1]);
+			return name(&base) == "mo" && LABELED_ARROWS.contains(as_text(base)) &&
+				   !(is_leaf(label) && as_text(label).trim().is_empty());
+		}
+
+		/// Splits an identifier with an accent in its text into an mover (e.g., "x hat").
+		/// The accent is split off when the author wrote the letter followed by a combining accent (e.g., "x\u{0302}")
+		///   unless the composed char is a letter of the speech language's alphabet ('AccentedLetters' in definitions.yaml).
+		/// A precomposed char is only split for a dot or diaeresis (e.g., "ẋ" and "ẍ" are derivatives), but not when the
+		///   base is a vowel (e.g., Finnish "ä") or the char is in the alphabet. Chars with several accents (e.g., "ǟ") aren't split.
+		/// The returned (mover) element reuses the arg so tree siblings links remain correct.
+		fn split_accented_identifier(leaf: Element) -> Option<Element> {
+			let text = as_text(leaf);
+			let mut chars = text.chars();
+			let first_char = chars.next()?;
+			// the text has been NFC normalized, so a letter + combining char is only two chars if there is no precomposed char
+			let is_precomposed = chars.next().is_none();
+			let decomposed = if is_precomposed {first_char.to_string().nfd().collect::<String>()} else {text.to_string()};
+			let mut chars = decomposed.chars();
+			let (base_char, accent_char) = (chars.next()?, chars.next()?);
+			if chars.next().is_some() || !base_char.is_alphabetic() || base_char.to_string().nfd().nth(1).is_some() ||
+			   !IDENTIFIER_ACCENTS.contains(accent_char.to_string().as_str()) || (is_precomposed && is_alphabet_letter(first_char)) {
+				return None;
+			}
+			if is_precomposed && !was_written_decomposed(text) &&
+			   (!matches!(accent_char, '\u{0307}' | '\u{0308}') ||
+			    (accent_char == '\u{0308}' && "aeiouyAEIOUY".contains(base_char))) {
+				return None;
+			}
+
+			let doc = leaf.document();
+			let base = create_mathml_element(&doc, "mi");
+			base.set_text(&base_char.to_string());
+			if let Some(variant) = leaf.attribute_value("mathvariant") {
+				base.set_attribute_value("mathvariant", variant);
+				leaf.remove_attribute("mathvariant");
+			}
+			let accent = create_mathml_element(&doc, "mo");
+			accent.set_text(&accent_char.to_string());		// canonicalize_mo_text converts it to the non-combining char
+			set_mathml_name(leaf, "mover");
+			leaf.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			leaf.set_attribute_value("accent", "true");
+			leaf.set_attribute_value(EMBELLISHED_IDENTIFIER_ATTR, "true");
+			leaf.replace_children([base, accent]);
+			mark_arrow_vector(leaf);
+			return Some(leaf);
+		}
+
+		/// True if 'ch' is an accented letter in the speech language's alphabet (e.g., Finnish "ä" or Vietnamese "ê")
+		fn is_alphabet_letter(ch: char) -> bool {
+			let ch = ch.to_lowercase().to_string();
+			return crate::definitions::DEFINITIONS.with(|definitions|
+				definitions.borrow().get_hashset("AccentedLetters").is_some_and(|letters| letters.contains(&ch))
+			);
+		}
+
+		/// True if NFC normalization composed 'text' from a letter followed by a combining char (i.e., the author wrote "x\u{0307}")
+		fn was_written_decomposed(text: &str) -> bool {
+			return UNICODE_NORMALIZATIONS.with(|normalizations|
+				normalizations.borrow().iter().any(|(original, normalized)| normalized == text && original.chars().count() == 2)
+			);
+		}
+
+		/// Mark an embellished identifier (mover) that is a single letter with a (right) arrow over it as a vector
+		fn mark_arrow_vector(mover: Element) {
+			let children = mover.children();
+			let base_text = as_text(as_element(children[0]));
+			if matches!(as_text(as_element(children[1])), "→" | "\u{20D7}" | "⇀" | "\u{20D1}") &&
+			   base_text.chars().count() == 1 && base_text.chars().all(char::is_alphabetic) {
+				mover.set_attribute_value(VECTOR_ATTR, "arrow");
+			}
+		}
+
+		/// Bad converters sometimes put copy-pasted text such as "x-1" or "f'(x)" into a single 'mi'.
+		/// That is split into mi/mn/mo tokens (in an mrow) using the operator dictionary so that it is spoken as math.
+		/// A hyphen between words (e.g., "x-ray") and an apostrophe in a word (e.g., "don't") are not operators, so those aren't split;
+		/// with spaces around it (e.g., "x - ray"), a hyphen is a minus. Text with a char that isn't a letter, digit, or operator isn't split
+		/// and neither is styled text (it has a 'mathvariant').
+		fn split_plain_text_expression(leaf: Element) -> Option<Element> {
+			lazy_static! {
+				static ref IS_WORD_PUNCTUATION: Regex = Regex::new(r"\p{L}{2}-\p{L}|\p{L}-\p{L}{2}|['’]\p{L}").unwrap();
+				static ref IS_NUMBER_START: Regex = Regex::new(r"^[0-9]+(?:\.[0-9]+)?").unwrap();
+			}
+			let text = as_text(leaf);
+			if name(&leaf) != "mi" || leaf.attribute("intent").is_some() || leaf.attribute("mathvariant").is_some() || text.chars().count() < 2 ||
+			   !text.chars().any(|ch| !ch.is_alphanumeric() && !ch.is_whitespace()) || IS_WORD_PUNCTUATION.is_match(text) {
+				return None;
+			}
+
+			let mut tokens: Vec<(&str, String)> = Vec::new();		// (element name, text)
+			let mut rest = text.trim_start();
+			while let Some(ch) = rest.chars().next() {
+				let (token_name, len) = if ch.is_ascii_digit() {
+					("mn", IS_NUMBER_START.find(rest).unwrap().end())
+				} else if ch.is_alphabetic() {
+					("mi", rest.find(|ch: char| !ch.is_alphabetic()).unwrap_or(rest.len()))
+				} else if OPERATORS.get(&rest[..ch.len_utf8()]).is_some() {
+					("mo", ch.len_utf8())
+				} else {
+					return None;
+				};
+				let token_text = if &rest[..len] == "'" {"′"} else {&rest[..len]};
+				tokens.push( (token_name, token_text.to_string()) );
+				rest = rest[len..].trim_start();
+			}
+			// need an operator (not just parens as in "(aq)") and an operand; a letter followed by primes is handled elsewhere (e.g., "f'")
+			if !tokens.iter().any(|(token_name, token_text)| *token_name == "mo" && !matches!(token_text.as_str(), "(" | ")" | "[" | "]" | "{" | "}")) ||
+			   tokens.iter().all(|(token_name, _)| *token_name == "mo") ||
+			   tokens.iter().skip(1).all(|(_, token_text)| token_text == "′") {
+				return None;
+			}
+
+			let doc = leaf.document();
+			let children = tokens.iter()
+					.map(|(token_name, token_text)| {
+						let token = create_mathml_element(&doc, token_name);
+						token.set_text(token_text);
+						token
+					})
+					.collect::<Vec<Element>>();
+			set_mathml_name(leaf, "mrow");
+			leaf.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+			leaf.replace_children(children);
+			return Some(leaf);
+		}
+
 		/// If we have something like 'shape' ABC, we split the ABC and add IMPLIED_SEPARATOR_HIGH_PRIORITY between them
 		/// under some specific conditions (trying to be a little cautious).
+		/// Acronyms (the `NonPointAcronyms` definition), long names, and leaves with an author's intent are never split.
+		/// The resulting mrow is marked with SPLIT_POINTS_ATTR so that speech can join the letters if geometry isn't confirmed.
 		/// The returned (mrow) element reuses the arg so tree siblings links remain correct.
 		fn split_points(leaf: Element) -> Option<Element> {
 			lazy_static!{
 				static ref IS_UPPERCASE: Regex = Regex::new(r"^[A-Z]+$").unwrap(); 
 			}
+			// a line segment, ray, or arc has at most three points; a polygon after a shape can have more (but not many)
+			const MAX_POINTS_UNDER_SCRIPT: usize = 3;
+			const MAX_POINTS_AFTER_SHAPE: usize = 8;
 
-			if !IS_UPPERCASE.is_match(as_text(leaf)) {
+			let text = as_text(leaf);
+			if text.len() < 2 || !IS_UPPERCASE.is_match(text) || leaf.attribute("intent").is_some() {
+				return None;
+			}
+			let is_acronym = crate::definitions::DEFINITIONS.with(|definitions|
+				definitions.borrow().get_hashset("NonPointAcronyms").unwrap().contains(text)
+			);
+			if is_acronym {
 				return None;
 			}
 
 			// check to see if there is a bar, arrow, etc over the letters (line-segment, arc, ...)
 			let parent = leaf.parent().unwrap().element().unwrap();
-			if name(&parent) == "mover" {
+			if name(&parent) == "mover" && parent.attribute("intent").is_none() && text.len() <= MAX_POINTS_UNDER_SCRIPT {
 				// look for likely overscripts (basically just rule out some definite 'no's)
 				let over = as_element(parent.children()[1]);
 				if is_leaf(over) {
@@ -1428,14 +2905,14 @@ impl CanonicalizeContext {
 					let first_char = over_chars.next();
 					if first_char.is_some() && over_chars.next().is_none() && !first_char.unwrap().is_alphanumeric(){
 						// only one char and it isn't alphanumeric
-						return Some( split_element(leaf) );
+						return Some( split_element(leaf, SPLIT_POINTS_UNCONFIRMED) );
 					}
 				}
 			}
 	
 			// check to see if it is preceded by a geometric shape (e.g, ∠ABC)
 			let preceding_siblings = leaf.preceding_siblings();
-			if !preceding_siblings.is_empty() {
+			if !preceding_siblings.is_empty() && text.len() <= MAX_POINTS_AFTER_SHAPE {
 				let preceding_sibling = as_element(preceding_siblings[preceding_siblings.len()-1]);
 				let preceding_sibling_name = name(&preceding_sibling);
 				if preceding_sibling_name == "mi" || preceding_sibling_name == "mo" || preceding_sibling_name == "mtext" {
@@ -1446,7 +2923,7 @@ impl CanonicalizeContext {
 						let shapes = defs.get_hashset("GeometryShapes").unwrap();
 						if prefix_ops.contains(preceding_text) || shapes.contains(preceding_text) {
 							// split leaf
-							return Some( split_element(leaf) );	// always treated as function names
+							return Some( split_element(leaf, SPLIT_POINTS_CONFIRMED) );	// always treated as function names
 						} else {
 							return None;
 						}
@@ -1455,7 +2932,7 @@ impl CanonicalizeContext {
 			}
 			return None;
 
-			fn split_element(leaf: Element) -> Element {
+			fn split_element<'a>(leaf: Element<'a>, split_kind: &str) -> Element<'a> {
 				let mut children = Vec::with_capacity(leaf.children().len());
 				for ch in as_text(leaf).chars() {
 					let new_leaf = create_mathml_element(&leaf.document(), "mi");
@@ -1464,6 +2941,7 @@ impl CanonicalizeContext {
 				}
 				set_mathml_name(leaf, "mrow");
 				leaf.replace_children(children);
+				leaf.set_attribute_value(SPLIT_POINTS_ATTR, split_kind);
 				return leaf;
 			}
 		}
@@ -1474,15 +2952,6 @@ impl CanonicalizeContext {
 				return false;
 			}
 
-			// remove/don't include whitespace at the end
-			while end >= start+3 {
-				let child = as_element(children[end-1]);	// end is not inclusive
-				if !is_leaf(child) || !as_text(child).trim().is_empty() {
-					break;
-				}
-				end -= 1;
-			}
-
 			let decimal_at_start = count_decimal_pts(children, start, start+1) == 1;
 			// decimal_at_start => none at end
 			let decimal_at_end = !(decimal_at_start || count_decimal_pts(children, end-1, end) == 0);
@@ -1505,9 +2974,9 @@ impl CanonicalizeContext {
 
 			if name(&as_element(children[start+1])) == "mtext" || 
 			   IS_WHITESPACE.is_match(as_text(as_element(children[start+1]))) {
-			    // make sure all the digit blocks are of the same type
+			    // make sure all the digit blocks are of the same type (only look at this number -- the mrow might be a table row)
 				let mut digit_block = DigitBlockType::None;		// initial "illegal" value (we know it is not NONE)
-				for &child in children {
+				for &child in &children[start..end] {
 					let child = as_element(child);
 					if name(&child) == "mn" {
 						if digit_block == DigitBlockType::None {
@@ -1681,30 +3150,45 @@ impl CanonicalizeContext {
 			return result;
 		}
 
+		/// Convert a trailing pseudo-script (e.g., "°" or "′") into a superscript of the preceding operand (see [`pseudo_script_base_start`]).
+		/// The set of pseudo-scripts comes from the "PseudoScripts" definition (see [`pseudo_scripts_set`]).
+		/// The msup is marked with PSEUDO_SCRIPT_ATTR so that speech rules can choose to undo the conversion.
 		fn handle_pseudo_scripts(mrow: Element) -> Element {
-			// from https://www.w3.org/TR/MathML3/chapter7.html#chars.pseudo-scripts
-			static PSEUDO_SCRIPTS: phf::Set<&str> = phf_set! {
-				"\"", "'", "*", "`", "ª", "°", "²", "³", "´", "¹", "º",
-				"‘", "’", "“", "”", "„", "‟",
-				"′", "″", "‴", "‵", "‶", "‷", "⁗",
-			};
-	
-			// merge consecutive <mo>s containing primes (in various forms)
 			let mut children = mrow.children();
+			if children.len() < 2 {
+				return mrow;
+			}
+			let pseudo_scripts = pseudo_scripts_set();
 			let mut i = 1;
 			let mut found = false;
 			while i < children.len() {
 				let child = as_element(children[i]);
-				if name(&child) == "mo" && PSEUDO_SCRIPTS.contains(as_text(child)) {
-					let msup = create_mathml_element(&child.document(), "msup");
-					msup.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
-					msup.append_child(children[i-1]);
-					msup.append_child(child);
-					children[i-1] = ChildOfElement::Element(msup);
-					children.remove(i);
-					found = true;
-				} else {
+				if name(&child) != "mo" || !pseudo_scripts.contains(as_text(child)) {
 					i += 1;
+					continue;
+				}
+				match pseudo_script_base_start(&children, i) {
+					None => i += 1,
+					Some(start) => {
+						let doc = child.document();
+						let base = if start + 1 == i {
+							children[start]
+						} else {
+							// a parenthesized base (e.g., "(fg)′") -- group it
+							let base = create_mathml_element(&doc, "mrow");
+							base.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+							base.append_children(children[start..i].to_vec());
+							ChildOfElement::Element(base)
+						};
+						let msup = create_mathml_element(&doc, "msup");
+						msup.set_attribute_value(CHANGED_ATTR, ADDED_ATTR_VALUE);
+						msup.set_attribute_value(PSEUDO_SCRIPT_ATTR, "true");
+						msup.append_child(base);
+						msup.append_child(child);
+						children.splice(start..=i, [ChildOfElement::Element(msup)]);
+						i = start + 1;
+						found = true;
+					},
 				}
 			}
 			if found {
@@ -1713,6 +3197,88 @@ impl CanonicalizeContext {
 			return mrow;
 		}
 
+		/// Returns the pseudo-script chars to use -- a "PseudoScripts_<SubjectArea>" definition takes precedence over "PseudoScripts".
+		/// The set is cached because it is needed for every mrow; it is rebuilt when the subject area or definitions files change.
+		fn pseudo_scripts_set() -> Rc<std::collections::HashSet<String>> {
+			type PseudoScriptsCache = Option<(String, crate::prefs::Locations, Rc<std::collections::HashSet<String>>)>;	// (subject area, definitions files, set)
+			thread_local!{
+				static PSEUDO_SCRIPTS: RefCell<PseudoScriptsCache> = const { RefCell::new(None) };
+			}
+			let pref_manager = crate::prefs::PreferenceManager::get();
+			let pref_manager = pref_manager.borrow();
+			let subject_area = pref_manager.get_user_prefs().to_string("SubjectArea");
+			let locations = pref_manager.get_definitions_file();
+			return PSEUDO_SCRIPTS.with(|cache| {
+				let mut cache = cache.borrow_mut();
+				if let Some((cached_subject_area, cached_locations, pseudo_scripts)) = cache.as_ref() {
+					if *cached_subject_area == subject_area && cached_locations == locations {
+						return pseudo_scripts.clone();
+					}
+				}
+				let pseudo_scripts = Rc::new( crate::definitions::DEFINITIONS.with(|definitions| {
+					let defs = definitions.borrow();
+					if let Some(subject_set) = defs.get_hashset(&format!("PseudoScripts_{}", subject_area)) {
+						return subject_set.clone();
+					}
+					return defs.get_hashset("PseudoScripts").unwrap().clone();
+				}) );
+				*cache = Some( (subject_area, locations.clone(), pseudo_scripts.clone()) );
+				return pseudo_scripts;
+			});
+		}
+
+		/// Returns the index of the first child of the base of the pseudo-script at 'i' or None if it isn't a pseudo-script.
+		/// The base is an mi or mn (possibly with scripts already on it, e.g., x_1′), an identifier with an accent (e.g., x̂′),
+		///   or a parenthesized expression (e.g., (fg)′), which might not have been grouped into an mrow yet.
+		/// Because "*" is also used for multiplication (and convolution), it is not a pseudo-script if an operand follows it.
+		fn pseudo_script_base_start(children: &[ChildOfElement], i: usize) -> Option<usize> {
+			if as_text(as_element(children[i])) == "*" {
+				if let Some(following) = children.get(i+1) {
+					if name(&as_element(*following)) != "mo" {
+						return None;
+					}
+				}
+			}
+			let mut base = as_element(children[i-1]);
+			while matches!(name(&base), "msub" | "msup" | "msubsup") {
+				base = as_element(base.children()[0]);
+			}
+			return match name(&base) {
+				"mi" | "mn" => Some(i-1),
+				"mover" if is_embellished_identifier(&base.children()) => Some(i-1),
+				"mrow" if IsBracketed::is_bracketed(&base, "", "", false, true) => Some(i-1),
+				"mo" if base == as_element(children[i-1]) => matching_open_fence(children, i-1),
+				_ => None,
+			};
+
+			/// Returns the index of the open paren/bracket/brace that matches the close one at 'close'
+			fn matching_open_fence(children: &[ChildOfElement], close: usize) -> Option<usize> {
+				let (open_text, close_text) = match as_text(as_element(children[close])) {
+					")" => ("(", ")"),
+					"]" => ("[", "]"),
+					"}" => ("{", "}"),
+					_ => return None,
+				};
+				let mut depth = 0;
+				for j in (0..close).rev() {
+					let child = as_element(children[j]);
+					if name(&child) != "mo" {
+						continue;
+					}
+					let text = as_text(child);
+					if text == close_text {
+						depth += 1;
+					} else if text == open_text {
+						if depth == 0 {
+							return if j + 1 < close {Some(j)} else {None};	// "()" isn't a base
+						}
+						depth -= 1;
+					}
+				}
+				return None;
+			}
+		}
+
 		fn handle_convert_to_mmultiscripts(children: &mut Vec<ChildOfElement>) {
   			let mut i = 0;
 			while i < children.len() {
@@ -1732,6 +3298,7 @@ impl CanonicalizeContext {
 		///   has a closer mi/mtext, it is used.
 		/// mhchem has some ugly output (at least in MathJax) and that's where using the following element makes sense
 		///   because an empty based (mpadded width=0) is used for the scripts. A hacky attribute indicates this case.
+		/// If there is no reasonable base (operators are never a base), the script is marked with UNRESOLVED_SCRIPT_ATTR and left alone.
 		fn convert_to_mmultiscripts(mrow_children: &mut Vec<ChildOfElement>, i: usize) -> usize {
 			// this is a bit messy/confusing because we might scan forwards or backwards and this affects whether
 			// we are scanning for prescripts or postscripts
@@ -1742,7 +3309,13 @@ impl CanonicalizeContext {
 			// let parent = as_element(mrow_children[i]).parent().unwrap().element().unwrap();
 			// debug!("convert_to_mmultiscripts (i={}) -- PARENT:\n{}", i, mml_to_string(&parent));
 
-			let i_base = choose_base_of_mmultiscripts(mrow_children, i);
+			let i_base = match choose_base_of_mmultiscripts(mrow_children, i) {
+				Some(i_base) => i_base,
+				None => {
+					as_element(mrow_children[i]).set_attribute_value(UNRESOLVED_SCRIPT_ATTR, "true");
+					return i + 1;
+				},
+			};
 			let mut base = as_element(mrow_children[i_base]);
 			// debug!("convert_to_mmultiscripts -- base\n{}", mml_to_string(&base));
 			let base_name = name(&base);
@@ -1861,14 +3434,17 @@ impl CanonicalizeContext {
 
 		/// Find the closest likely base to the 'i'th child, preferring the next one over the preceding one, but want the closest.
 		///
+		/// Operators (e.g., "=" or "+") are never a base, so the search never crosses a relation.
+		/// Returns None if there is no reasonable base.
+		///
 		/// Note: because the base might be (...), 'mrow_children might be changed so that they are grouped into an mrow.
-		fn choose_base_of_mmultiscripts(mrow_children: &mut Vec<ChildOfElement>, i: usize) -> usize {
+		fn choose_base_of_mmultiscripts(mrow_children: &mut Vec<ChildOfElement>, i: usize) -> Option<usize> {
 			// We already know there are no empty scripts to the left (because we find first empty base from left to right).
 			// However, there may be some empty bases before we get to real base on the right.
 			let script_element_base = as_element(as_element(mrow_children[i]).children()[0]);
 			let from_mchem = script_element_base.attribute(MHCHEM_MMULTISCRIPTS_HACK).is_some();
 			if mrow_children.len() > i+1 && !(from_mchem && i > 0) && is_child_simple_base(mrow_children[i+1]) {
-				return i+1;
+				return Some(i+1);
 			}
 			if i > 0 {
 				if let Some(i_start) = is_grouped_base(&mrow_children[..i]) {
@@ -1882,17 +3458,17 @@ impl CanonicalizeContext {
 					}
 					mrow_children.drain(i_start+1..i);
 					mrow_children[i_start] = ChildOfElement::Element(new_mrow);
-					return i_start;
+					return Some(i_start);
 				}
 				if is_child_simple_base(mrow_children[i-1]) {
-					return i-1;
+					return Some(i-1);
 				}
 			}
 
 			// base very likely after multiple scripts to the right
 			for i_base in i+1..mrow_children.len() {
 				if is_child_simple_base(mrow_children[i_base]) {
-						return i_base;
+						return Some(i_base);
 				} else {
 					let child = as_element(mrow_children[i_base]);
 					let child_name = name(&child);
@@ -1901,11 +3477,10 @@ impl CanonicalizeContext {
 					}
 				}
 			}
-			// didn't find any good candidates for a base -- pick something valid
-			assert!(mrow_children.len() > i);
-			return i;
-			
-			
+			// didn't find any good candidates for a base -- let the rules deal with it
+			return None;
+
+
 			fn is_child_simple_base(child: ChildOfElement) -> bool {
 				let mut child = as_element(child);
 				let child_name = name(&child);
@@ -1913,7 +3488,8 @@ impl CanonicalizeContext {
 					child = as_element(child.children()[0]);
 				}
 
-				return is_leaf(child) && !CanonicalizeContext::is_empty_element(child);  // a little overly general (but hopefully doesn't matter)
+				// operators separate operands (e.g., the "=" in "x = _2 = 3"), so they are never a base
+				return is_leaf(child) && name(&child) != "mo" && !CanonicalizeContext::is_empty_element(child);  // a little overly general (but hopefully doesn't matter)
 			}
 
 			/// Return the index of the matched open paren/bracket if the last element is a closed paren/bracket
@@ -1943,6 +3519,9 @@ impl CanonicalizeContext {
 		match tag_name {
 			"mi" | "ms" | "mtext" | "mspace"  => {
 				self.canonicalize_plane1(mathml);
+				if tag_name == "mi" && is_bold_lowercase_letter(as_text(mathml)) {
+					mathml.set_attribute_value(VECTOR_ATTR, "bold");
+				}
 				return Ok( mathml ); },
 			"mo" => {
 				self.canonicalize_plane1(mathml);
@@ -1953,9 +3532,12 @@ impl CanonicalizeContext {
 				self.canonicalize_plane1(mathml);
 				return Ok( mathml );
 			},
-			"mrow" => {
+			"mrow" if mathml.attribute(NUMBER_KIND_ATTR).is_none() => {
 				return self.canonicalize_mrows_in_mrow(mathml);
 			},
+			"merror" => {
+				return Ok( mathml );	// see 'clean_mathml' -- the contents are left as is
+			},
 			"semantics" => {
 				let mut children = mathml.children();
 				let (i, presentation) = get_presentation_element(mathml);
@@ -2032,20 +3614,93 @@ impl CanonicalizeContext {
 			"monospace" => [0x1D670, 0x1D7F6, 0],
 		};
 
-		let variant = mi.attribute_value("mathvariant");
-		if variant.is_none() {
+		if name(&mi) == "mi" {
+			normalize_dotless_letter(mi);
+		}
+		let variant = match mi.attribute_value("mathvariant") {
+			Some(variant) => variant.to_string(),
+			None => match inherited_math_variant(mi) {
+				None => return mi,
+				Some(variant) => variant,
+			},
+		};
+
+		// the normalized mathvariant is kept on the token -- braille codes use it for typeform indicators
+		// because the plane 1 chars don't cover everything (e.g., there are no italic digits)
+		let variant = normalize_math_variant(&variant);
+		if variant == "normal" {
 			return mi;
 		}
+		if mi.attribute_value("mathvariant") != Some(variant.as_str()) {
+			mi.set_attribute_value("mathvariant", &variant);
+		}
 
 		let mi_text = as_text(mi);
-		let new_text = match MATH_VARIANTS.get(variant.unwrap()) {
+		let new_text = match MATH_VARIANTS.get(variant.as_str()) {
 			None => mi_text.to_string(),
 			Some(start) => shift_text(mi_text, start),
 		};
-		// mi.remove_attribute("mathvariant");  // leave attr -- for Nemeth, there are italic digits etc that don't have Unicode points
 		mi.set_text(&new_text);
 		return mi;
 
+		/// Dotless i and j (used for unit vectors such as "î") are turned into the base letter so they are spoken and brailled as "i" and "j".
+		/// Only italic versions exist in plane 1 -- that style is kept as 'mathvariant' (if there isn't already one).
+		/// This is the inverse of the plane 1 mapping done below.
+		fn normalize_dotless_letter(mi: Element) {
+			static DOTLESS_LETTERS: phf::Map<&str, (&str, &str)> = phf_map! {
+				"ı" => ("i", ""),			// U+0131
+				"ȷ" => ("j", ""),			// U+0237
+				"𝚤" => ("i", "italic"),		// U+1D6A4
+				"𝚥" => ("j", "italic"),		// U+1D6A5
+			};
+			if let Some((letter, variant)) = DOTLESS_LETTERS.get(as_text(mi)) {
+				mi.set_text(letter);
+				if !variant.is_empty() && mi.attribute("mathvariant").is_none() {
+					mi.set_attribute_value("mathvariant", variant);
+				}
+			}
+		}
+
+		/// 'mathvariant' on an mstyle (which became an mrow) applies to the mi and mn elements inside of it
+		fn inherited_math_variant(mathml: Element) -> Option<String> {
+			let leaf_name = name(&mathml);
+			if leaf_name != "mi" && leaf_name != "mn" {
+				return None;
+			}
+			let mut parent = mathml.parent().and_then(|parent| parent.element());
+			while let Some(element) = parent {
+				if let Some(variant) = element.attribute_value("mathvariant") {
+					return Some(variant.to_string());
+				}
+				if name(&element) == "math" {
+					return None;
+				}
+				parent = element.parent().and_then(|parent| parent.element());
+			}
+			return None;
+		}
+
+		/// Returns the MathML name for the variant (e.g., "Italic Bold" becomes "bold-italic")
+		/// Unknown variants (e.g., Arabic "initial") are returned unchanged
+		fn normalize_math_variant(variant: &str) -> String {
+			let variant = variant.trim().to_ascii_lowercase();
+			let bold = variant.contains("bold");
+			let italic = variant.contains("italic");
+			let face = ["double-struck", "script", "fraktur", "sans-serif", "monospace"].iter().copied()
+						.find(|&face| variant.contains(face));
+			return match (face, bold, italic) {
+				(Some("sans-serif"), true, true) => "sans-serif-bold-italic".to_string(),
+				(Some("sans-serif"), true, false) => "bold-sans-serif".to_string(),
+				(Some("sans-serif"), false, true) => "sans-serif-italic".to_string(),
+				(Some(face), true, _) => format!("bold-{}", face),
+				(Some(face), false, _) => face.to_string(),
+				(None, true, true) => "bold-italic".to_string(),
+				(None, true, false) => "bold".to_string(),
+				(None, false, true) => "italic".to_string(),
+				(None, false, false) => variant,
+			};
+		}
+
 		fn shift_text(old_text: &str, char_mapping: &[u32; 3]) -> String {
 			// if there is no block for something, use 'a', 'A', 0 as that will be a no-op
 			struct Offsets {
@@ -2254,10 +3909,12 @@ impl CanonicalizeContext {
 				"_" | "\u{02C9}"| "\u{0304}"| "\u{0305}"| "\u{2212}" |
 				"\u{2010}" | "\u{2011}" | "\u{2012}" | "\u{2013}" | "\u{2014}" | "\u{2015}" => "\u{00AF}",
 				"\u{02BC}" => "`",
-				"\u{02DC}" => "~",
+				"\u{02DC}" | "\u{0303}" => "~",
 				"\u{02C6}"| "\u{0302}" => "^",
 				"\u{0307}" => "\u{02D9}",	// Nemeth distinguishes this from "." -- \u{02D9} is generated for over dots by most generators
 				"\u{0308}" => "¨",
+				"\u{20D7}" => "→",
+				"\u{20D1}" => "⇀",
 				_ => mo_text,
 			}
 			// FIX: MathType generates the wrong version of union and intersection ops (binary instead of unary)
@@ -2584,6 +4241,12 @@ impl CanonicalizeContext {
 			if shapes.contains(base_name) {
 				return FunctionNameCertainty::True;	// always treated as function names
 			}
+
+			// names that are function names in the subject area (e.g., "O" in "ComputerScience")
+			let subject_area = crate::xpath_functions::subject_area(base_of_name);
+			if defs.get_hashset(&format!("FunctionNames_{}", subject_area)).is_some_and(|names| names.contains(base_name)) {
+				return FunctionNameCertainty::True;
+			}
 	
 			if right_siblings.is_none() {
 				return FunctionNameCertainty::False;	// only accept known names, which is tested above
@@ -2637,7 +4300,7 @@ impl CanonicalizeContext {
 
 			if is_comma_arg(as_text(first_sibling), &right_siblings[1..]) {
 				// debug!("      ...is comma arg");
-				return FunctionNameCertainty::True;	// if there is only a single arg, why else would you use parens?
+				return FunctionNameCertainty::True;	// args separated by ',' or ';' (e.g., f(x; θ)) -- why else would you use parens?
 			};
 	
 			// FIX: should really make sure all the args are marked as MAYBE_CHEMISTRY, but we don't know the matching close paren/bracket
@@ -2681,6 +4344,7 @@ impl CanonicalizeContext {
 	
 		fn is_comma_arg<'a>(open: &str, following_nodes: &[ChildOfElement<'a>]) -> bool {
 			// following_nodes are nodes after "("
+			// ';' also separates args -- it is used to separate parameters (e.g, f(x; θ) and ₀F₁(a, b; c; z))
 			if following_nodes.len() == 1 {
 				return false; 
 			}
@@ -2694,7 +4358,8 @@ impl CanonicalizeContext {
 			for child in following_nodes {
 				let child = as_element(*child);
 				if name(&child) == "mo" {
-					if as_text(child) == "," {
+					let text = as_text(child);
+					if text == "," || text == ";" {
 						return true;
 					}
 					if is_matching_right_paren(open, child) {
@@ -2811,6 +4476,45 @@ impl CanonicalizeContext {
 		}
 	}
 
+	/// Returns true if 'following' is a trailing "(mod n)" (either still a sequence of children or already an mrow)
+	///   and the nearest relation in 'preceding' is a congruence (e.g., "a ≡ b (mod n)", but not "a ≡ b ⇒ c = d (mod n)").
+	/// In that case, the modulus applies to the whole relation and not (via implied times) to the operand before it.
+	fn is_modulus_group(&self, preceding: &[ChildOfElement], following: &[ChildOfElement]) -> bool {
+		let nearest_relation = preceding.iter().rev()
+				.map(|&child| as_element(child))
+				.find(|&child| name(&child) == "mo" && is_relational_op(child));
+		let is_congruence = nearest_relation.is_some_and(|relation| matches!(as_text(relation), "≡" | "≢"));
+		if !is_congruence || following.is_empty() {
+			return false;
+		}
+
+		let group = as_element(following[0]);
+		let group_children = if name(&group) == "mrow" {
+			if following.len() != 1 {
+				return false;
+			}
+			group.children()
+		} else {
+			following.to_vec()
+		};
+		if group_children.len() < 3 {
+			return false;
+		}
+		let open = as_element(group_children[0]);
+		let close = as_element(group_children[group_children.len()-1]);
+		if !(name(&open) == "mo" && as_text(open) == "(" && name(&close) == "mo" && as_text(close) == ")") {
+			return false;
+		}
+		let mut mod_child = as_element(group_children[1]);
+		if name(&mod_child) == "mrow" {
+			match mod_child.children().first() {
+				Some(&first) => mod_child = as_element(first),
+				None => return false,
+			}
+		}
+		return is_leaf(mod_child) && as_text(mod_child).trim() == "mod";
+	}
+
 	// implied comma when two numbers are adjacent and are in a script position
 	fn is_implied_comma<'a>(&self, prev: &'a Element<'a>, current: &'a Element<'a>, mrow: &'a Element<'a>) -> bool {
 		if name(prev) != "mn" || name(current) != "mn" {
@@ -2883,7 +4587,7 @@ impl CanonicalizeContext {
 		// debug!(" shift_stack: shift on '{}'; ops: prev '{}/{}', cur '{}/{}'",
 		// 		element_summary(current_child),show_invisible_op_char(previous_op.ch), previous_op.op.priority,
 		// 		show_invisible_op_char(current_op.ch), current_op.op.priority);
-		if !previous_op.op.is_nary(current_op.op) {
+		if !previous_op.op.is_nary(current_op.op) && !is_relation_chain_link(&previous_op, &current_op) {
 			// grab operand on top of stack (if there is one) and make it part of the new mrow since current op has higher precedence
 			// if operators are the same and are binary, then this push makes them act as left associative
 			let mut top_of_stack = parse_stack.pop().unwrap();
@@ -2962,6 +4666,7 @@ impl CanonicalizeContext {
 			mrow = single_child;
 		}
 
+		mark_relation_chain(mrow);
 		let mut top_of_stack = parse_stack.pop().unwrap();
 		top_of_stack.add_child_to_mrow(mrow, OperatorPair::new());	// mrow on top is "parsed" -- now add it to previous
 		let prev_priority = top_of_stack.priority();
@@ -3082,7 +4787,9 @@ impl CanonicalizeContext {
 
 			let mut current_op = OperatorPair::new();
 			// figure what the current operator is -- it either comes from the 'mo' (if we have an 'mo') or it is implied
-			if name(&base_of_child) == "mo" &&
+			if is_connective_text(base_of_child) {
+				current_op = OperatorPair{ ch: as_text(base_of_child), op: &CONNECTIVE_TEXT_SEPARATOR };
+			} else if name(&base_of_child) == "mo" &&
 			   !( base_of_child.children().is_empty() || IS_WHITESPACE.is_match(as_text(base_of_child)) ) { // shouldn't have empty mo node, but...
 				let previous_op = if top(&parse_stack).is_operand {None} else {Some( top(&parse_stack).op_pair.op )};
 				let next_node = if i_child + 1 < num_children {Some(as_element(children[i_child+1]))} else {None};
@@ -3104,11 +4811,20 @@ impl CanonicalizeContext {
 			} else if top(&parse_stack).last_child_in_mrow().is_some() {
 				let previous_child = top(&parse_stack).last_child_in_mrow().unwrap();
 				let base_of_previous_child = get_possible_embellished_node(previous_child);
-				if name(&base_of_previous_child) != "mo" {
+				if name(&base_of_previous_child) != "mo" && !is_connective_text(base_of_previous_child) {
 					// consecutive operands -- add an invisible operator as appropriate
-					let likely_function_name = self.is_function_name(previous_child, Some(&children[i_child..]));
-					current_op = if likely_function_name == FunctionNameCertainty::True {
+					let is_enumerator_boundary = is_enumerator(previous_child) || is_enumerator(current_child);
+					let likely_function_name = if is_enumerator_boundary {
+							FunctionNameCertainty::False
+						} else {
+							self.is_function_name(previous_child, Some(&children[i_child..]))
+						};
+					current_op = if is_enumerator_boundary {
+								OperatorPair{ ch: "\u{2063}", op: &IMPLIED_ENUMERATOR_SEPARATOR }
+							} else if likely_function_name == FunctionNameCertainty::True {
 								OperatorPair{ ch: "\u{2061}", op: &INVISIBLE_FUNCTION_APPLICATION }
+							} else if self.is_modulus_group(&children[..i_child], &children[i_child..]) {
+								OperatorPair{ ch: "\u{2063}", op: &IMPLIED_MODULUS_SEPARATOR }
 							} else if self.is_mixed_fraction(&previous_child, &children[i_child..])? {
 								OperatorPair{ ch: "\u{2064}", op: &IMPLIED_INVISIBLE_PLUS }
 							} else if self.is_implied_comma(&previous_child, &current_child, &mrow) {
@@ -3154,13 +4870,17 @@ impl CanonicalizeContext {
 						let likely_function_name = self.is_function_name(as_element(children[i_child-1]), Some(&children[i_child..]));
 						let implied_operator = if likely_function_name== FunctionNameCertainty::True {
 								OperatorPair{ ch: "\u{2061}", op: &INVISIBLE_FUNCTION_APPLICATION }
+							} else if self.is_modulus_group(&children[..i_child], &children[i_child..]) {
+								// unlike the other implied operators, this is lower priority than what is on the stack
+								self.reduce_stack(&mut parse_stack, IMPLIED_MODULUS_SEPARATOR.priority);
+								OperatorPair{ ch: "\u{2063}", op: &IMPLIED_MODULUS_SEPARATOR }
 							} else {
 								OperatorPair{ ch: "\u{2062}", op: &IMPLIED_TIMES }
 							};
 						// debug!("  adding implied {}", if ptr_eq(implied_operator.op,*IMPLIED_TIMES) {"times"} else {"function apply"});
 	
 						let implied_mo = create_mo(current_child.document(), implied_operator.ch, ADDED_ATTR_VALUE);
-						if likely_function_name == FunctionNameCertainty::Maybe {
+						if likely_function_name == FunctionNameCertainty::Maybe && implied_operator.ch == "\u{2062}" {
 							implied_mo.set_attribute_value("data-function-guess", "true");
 						}
 						let shift_result = self.shift_stack(&mut parse_stack, implied_mo, implied_operator.clone());
@@ -3209,15 +4929,175 @@ impl CanonicalizeContext {
 		}
 	
 		parsed_mrow.remove_attribute(CHANGED_ATTR);
+		mark_relation_chain(parsed_mrow);
 		return Ok( add_attrs(parsed_mrow, saved_mrow_attrs) );
 	}	
 }
 
+/// Returns true if 'element' is an mtext that was marked as connective text (e.g., "where") during cleanup
+fn is_connective_text(element: Element) -> bool {
+	return name(&element) == "mtext" && element.attribute(CONNECTIVE_TEXT_ATTR).is_some();
+}
+
+/// True if 'text' is several words that name something (e.g., "velocity of light" or "total cost").
+/// Each word must be at least two letters (so "sin x" is not a match) and the first word can't be a function name.
+/// Connective text (e.g., "such that") is not a descriptive identifier.
+fn is_descriptive_identifier(text: &str) -> bool {
+	let words = text.split_whitespace().collect::<Vec<&str>>();
+	if words.len() < 2 || !words.iter().all(|word| word.chars().count() >= 2 && word.chars().all(char::is_alphabetic)) {
+		return false;
+	}
+	return crate::definitions::DEFINITIONS.with(|definitions| {
+		let definitions = definitions.borrow();
+		let is_function_name = definitions.get_hashset("FunctionNames").is_some_and(|names| names.contains(words[0]));
+		let is_connective = definitions.get_hashset("ConnectiveWords").is_some_and(|connectives| connectives.contains(&words.join(" ").to_lowercase()));
+		return !is_function_name && !is_connective;
+	});
+}
+
+/// True if 'text' is a bold (or bold italic) lowercase Latin or Greek letter (e.g., "𝐯" or "𝛂") -- these are conventionally vectors.
+fn is_bold_lowercase_letter(text: &str) -> bool {
+	let mut chars = text.chars();
+	return match (chars.next(), chars.next()) {
+		(Some(ch), None) => matches!(ch, '\u{1D41A}'..='\u{1D433}' | '\u{1D482}'..='\u{1D49B}' |
+		                                 '\u{1D6C2}'..='\u{1D6DA}' | '\u{1D736}'..='\u{1D74E}'),
+		_ => false,
+	};
+}
+
+/// True if 'text' is one of the speech language's decimal separators ('DecimalSeparators' in definitions.yaml).
+/// If the language doesn't define them, "." is the decimal separator.
+fn is_decimal_separator(text: &str) -> bool {
+	return crate::definitions::DEFINITIONS.with(|definitions| {
+		return match definitions.borrow().get_hashset("DecimalSeparators") {
+			Some(separators) => separators.contains(text),
+			None => text == DECIMAL_SEPARATOR,
+		};
+	});
+}
+
+/// Splits 'number' at its (last) decimal separator into the integer and the decimal digits.
+/// The separators are the speech language's decimal separators (see `is_decimal_separator`).
+/// Returns 'None' if 'number' doesn't contain a decimal separator.
+pub fn split_at_decimal_separator(number: &str) -> Option<(&str, &str)> {
+	return crate::definitions::DEFINITIONS.with(|definitions| {
+		let (i, separator_len) = match definitions.borrow().get_hashset("DecimalSeparators") {
+			Some(separators) => separators.iter()
+					.filter(|separator| !separator.is_empty())
+					.filter_map(|separator| number.rfind(separator.as_str()).map(|i| (i, separator.len())))
+					.max()?,
+			None => (number.rfind(DECIMAL_SEPARATOR)?, DECIMAL_SEPARATOR.len()),
+		};
+		return Some( (&number[..i], &number[i+separator_len..]) );
+	});
+}
+
+/// True if 'text' is the part of a repeating decimal before the repeating digits (e.g., "0." or "0.1")
+fn is_decimal_start(text: &str) -> bool {
+	return match split_at_decimal_separator(text) {
+		Some((integer, decimals)) => integer.chars().all(|ch| ch.is_ascii_digit()) && decimals.chars().all(|ch| ch.is_ascii_digit()),
+		None => false,
+	};
+}
+
+/// Returns true if 'element' is an exercise label (e.g., "(a)") that was grouped during cleanup
+fn is_enumerator(element: Element) -> bool {
+	return name(&element) == "mrow" && element.attribute(ENUMERATOR_ATTR).is_some();
+}
+
+/// Returns true if 'previous_op' and 'current_op' are (possibly different) relations that form links of a single chain
+fn is_relation_chain_link(previous_op: &OperatorPair, current_op: &OperatorPair) -> bool {
+	let is_chain_relation = |ch: &str| CHAIN_EQUALITY_RELATIONS.contains(ch) || CHAIN_ORDER_RELATIONS.contains(ch);
+	return previous_op.op.priority == *EQUAL_PRIORITY && current_op.op.priority == *EQUAL_PRIORITY &&
+		   is_chain_relation(previous_op.ch) && is_chain_relation(current_op.ch);
+}
+
+/// If 'mrow' is 'operand relation operand relation operand...' with at least two chainable relations, mark it with RELATION_CHAIN_ATTR
+fn mark_relation_chain(mrow: Element) {
+	if name(&mrow) != "mrow" {
+		return;
+	}
+	let children = mrow.children();
+	if children.len() < 5 || children.len().is_multiple_of(2) {
+		return;
+	}
+	let mut is_inequality = false;
+	for child in children.iter().skip(1).step_by(2) {
+		let child = as_element(*child);
+		if name(&child) != "mo" {
+			return;
+		}
+		let op = as_text(child);
+		if CHAIN_ORDER_RELATIONS.contains(op) {
+			is_inequality = true;
+		} else if !CHAIN_EQUALITY_RELATIONS.contains(op) {
+			return;
+		}
+	}
+	mrow.set_attribute_value(RELATION_CHAIN_ATTR, if is_inequality {"inequality"} else {"equality"});
+}
+
 // ---------------- useful utility functions --------------------
+/// Returns true if it detects that this is likely coming from mhchem (msub/msup with mrow/mrow/mpadded width=0/mphantom/mi=A)
+/// This should be called with 'mrow' being the outer mrow (which has a single child)
+fn is_mhchem_phantom_script_base(mrow: Element) -> bool {
+	assert_eq!(name(&mrow), "mrow");
+	assert_eq!(mrow.children().len(), 1);
+	let parent = mrow.parent().unwrap().element().unwrap();
+	let parent_name = name(&parent);
+	if !(parent_name == "msub" || parent_name == "msup") {
+		return false;
+	}
+
+	let mrow = as_element(mrow.children()[0]);
+	if !(name(&mrow) == "mrow" && mrow.children().len() == 1) {
+		return false;
+	}
+	let child = as_element(mrow.children()[0]);
+	if !(name(&child) == "mpadded" && child.attribute("width").is_some()) {
+		return false;
+	}
+	if child.attribute_value("width").unwrap() != "0" {
+		return false;
+	}
+
+	let child = as_element(child.children()[0]);
+	if !(name(&child) == "mphantom" && child.children().len() == 1) {
+		return false;
+	}
+
+	let child = as_element(child.children()[0]);
+	return name(&child) == "mi" && as_text(child) == "A";
+}
+
+/// Returns true if 'mo' is just (Unicode) whitespace (WIRIS editor puts non-breaking whitespace as standalone in 'mo')
+fn is_whitespace_mo(mo: Element) -> bool {
+	let text = as_text(mo);
+	return !text.is_empty() && IS_WHITESPACE.is_match(text);
+}
+
+/// Returns true if 'mo' is a binary union or intersection that is the base of munder/mover/munderover (MathType does this)
+fn is_binary_large_operator(mo: Element) -> bool {
+	let text = as_text(mo);
+	if !(text == "∪" || text == "∩") || !mo.preceding_siblings().is_empty() {
+		return false;
+	}
+	let parent = match mo.parent().and_then(|parent| parent.element()) {
+		None => return false,
+		Some(parent) => parent,
+	};
+	return matches!(name(&parent), "munder" | "mover" | "munderover");
+}
+
 fn top<'s, 'a:'s, 'op:'a>(vec: &'s[StackInfo<'a, 'op>]) -> &'s StackInfo<'a, 'op> {
 	return &vec[vec.len()-1];
 }
 // Replace the attrs of 'mathml' with 'attrs' and keep the global attrs of 'mathml' (i.e, lift 'attrs' to 'mathml' for replacing children)
+fn is_preserve_attributes_on() -> bool {
+	let pref_manager = crate::prefs::PreferenceManager::get();
+	return pref_manager.borrow().get_api_prefs().to_string("PreserveAttributes") == "true";
+}
+
 fn add_attrs<'a>(mathml: Element<'a>, attrs: Vec<Attribute>) -> Element<'a> {
 	static GLOBAL_ATTRS: phf::Set<&str> = phf_set! {
 		"class", "dir", "displaystyle", "id", "mathbackground", "mathcolor", "mathsize",
@@ -3226,11 +5106,15 @@ fn add_attrs<'a>(mathml: Element<'a>, attrs: Vec<Attribute>) -> Element<'a> {
 	};
 	
 	// debug!(   "Adding back {} attr(s) to {}", attrs.len(), name(&mathml));
-	// remove non-global attrs
+	// remove non-global attrs (if 'PreserveAttributes' is set, they are kept as 'data-orig-*' so callers can restore them)
+	let mut is_preserving = None;		// only look up the pref if there is something to remove
 	for attr in mathml.attributes() {
 		let attr_name = attr.name().local_part();
-		if !( attr_name.starts_with("data-") || GLOBAL_ATTRS.contains(attr_name) ||
+		if !( attr_name.starts_with("data-") || attr_name.starts_with("aria-") || GLOBAL_ATTRS.contains(attr_name) ||
 		      attr_name.starts_with("on") ) {			// allows too much - cheapo way to allow event handlers like "onchange"
+			if *is_preserving.get_or_insert_with(is_preserve_attributes_on) {
+				mathml.set_attribute_value(format!("{}{}", ORIGINAL_ATTR_PREFIX, attr_name).as_str(), attr.value());
+			}
 			mathml.remove_attribute(attr.name());
 		}
 	}
@@ -3297,12 +5181,20 @@ fn is_adorned_node<'a>(node: &'a Element<'a>) -> bool {
 }
 
 /// return 'node' or if it is adorned, return its base (recursive)
+/// Wrappers that don't change the meaning (e.g., a color from 'mstyle' or a box from 'menclose') are also looked through
+///   if they have a single child, as are mrows with a single child (these can be created late in canonicalization).
 pub fn get_possible_embellished_node(node: Element) -> Element {
 	let mut node = node;
-	while is_adorned_node(&node) {
+	while is_adorned_node(&node) || is_transparent_wrapper(node) {
 		node = as_element(node.children()[0]);
 	}
 	return node;
+
+	fn is_transparent_wrapper(node: Element) -> bool {
+		return matches!(name(&node), "mrow" | "mstyle" | "mpadded" | "menclose") &&
+			   node.children().len() == 1 &&
+			   node.children()[0].element().is_some();
+	}
 }		
 
 #[allow(dead_code)] // for debugging with println
@@ -3327,6 +5219,123 @@ mod canonicalize_tests {
     use sxd_document::parser;
 
 
+    #[test]
+    fn relation_chain() {
+        // mixed order/equality relations form a single chain
+        let test_str = "<math><mi>a</mi><mo>&lt;</mo><mi>b</mi><mo>=</mo><mi>c</mi><mo>≤</mo><mi>d</mi></math>";
+        let target_str = "<math>
+			<mrow data-changed='added' data-relation-chain='inequality'>
+				<mi>a</mi>
+				<mo>&lt;</mo>
+				<mi>b</mi>
+				<mo>=</mo>
+				<mi>c</mi>
+				<mo>≤</mo>
+				<mi>d</mi>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // other relations (e.g., "∝") are not part of a chain
+        let test_str = "<math><mi>a</mi><mo>∝</mo><mi>b</mi><mo>=</mo><mi>c</mi><mo>=</mo><mi>d</mi></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mi>a</mi>
+				<mo>∝</mo>
+				<mrow data-changed='added' data-relation-chain='equality'>
+					<mi>b</mi>
+					<mo>=</mo>
+					<mi>c</mi>
+					<mo>=</mo>
+					<mi>d</mi>
+				</mrow>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        let test_str = "<math><mi>x</mi><mo>=</mo><mi>y</mi><mo>=</mo><mn>2</mn></math>";
+        let target_str = "<math>
+			<mrow data-changed='added' data-relation-chain='equality'>
+				<mi>x</mi>
+				<mo>=</mo>
+				<mi>y</mi>
+				<mo>=</mo>
+				<mn>2</mn>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+    }
+
+    #[test]
+    fn connective_text() {
+        // the test rules dir uses the English definitions
+        let test_str = "<math><msup><mi>x</mi><mn>2</mn></msup><mtext>&#xA0;</mtext><mtext>where</mtext><mi>x</mi><mo>&gt;</mo><mn>0</mn></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<msup><mi>x</mi><mn>2</mn></msup>
+				<mtext data-connective='true'>&#xA0;where</mtext>
+				<mrow data-changed='added'>
+					<mi>x</mi>
+					<mo>&gt;</mo>
+					<mn>0</mn>
+				</mrow>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // not a connective -- stays an operand
+        let test_str = "<math><mn>2</mn><mtext>apples</mtext></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mn>2</mn>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mtext>apples</mtext>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+    }
+
+    #[test]
+    fn descriptive_identifier() {
+        let test_str = "<math><mi>E</mi><mo>=</mo><mi>m</mi><msup><mi>velocity of light</mi><mn>2</mn></msup></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mi>E</mi>
+				<mo>=</mo>
+				<mrow data-changed='added'>
+					<mi>m</mi>
+					<mo data-changed='added'>&#x2062;</mo>
+					<msup><mi data-descriptive-identifier='true'>velocity of light</mi><mn>2</mn></msup>
+				</mrow>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        let test_str = "<math><mn>2</mn><mtext>total cost</mtext></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mn>2</mn>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mtext data-descriptive-identifier='true'>total cost</mtext>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+    }
+
+    #[test]
+    fn not_descriptive_identifier() {
+        // single letter words and function names are not descriptive identifiers
+        let test_str = "<math><mi>sin x</mi></math>";
+        let target_str = "<math><mi>sin x</mi></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // prose that isn't next to a number or operator is left alone
+        let test_str = "<math><mtext>for all positive</mtext><mi>x</mi></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mtext>for all positive</mtext>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mi>x</mi>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+    }
+
     #[test]
     fn canonical_same() {
         let target_str = "<math><mrow><mo>-</mo><mi>a</mi></mrow></math>";
@@ -3354,7 +5363,7 @@ mod canonicalize_tests {
 					<mo >,</mo>
 					<mi mathvariant='italic'>bB4</mi>
 					<mo>,</mo>
-					<mi mathvariant='bold'>𝐚</mi>
+					<mi mathvariant='bold' data-vector='bold'>𝐚</mi>
 					<mo>,</mo>
 					<mi mathvariant='bold'>𝐙</mi>
 					<mo>,</mo>
@@ -3401,6 +5410,76 @@ mod canonicalize_tests {
 		assert!(are_strs_canonically_equal(test_str, target_str));
 	}
 	
+	#[test]
+    fn plane1_inherited_and_normalized_variants() {
+        let test_str = "<math>
+				<mstyle mathvariant='bold'><mi>x</mi><mo>+</mo><mn>2</mn></mstyle> <mo>,</mo>	<!-- mi and mn inherit -->
+				<mi mathvariant='Italic Bold'>a</mi> <mo>,</mo>
+				<mi mathvariant='bold-italic-sans-serif'>b</mi>
+			</math>";
+        let target_str = "<math>
+				<mrow data-changed='added'>
+					<mrow mathvariant='bold' data-changed='added'>
+						<mi mathvariant='bold' data-vector='bold'>𝐱</mi>
+						<mo>+</mo>
+						<mn mathvariant='bold'>𝟐</mn>
+					</mrow>
+					<mo>,</mo>
+					<mi mathvariant='bold-italic' data-vector='bold'>𝒂</mi>
+					<mo>,</mo>
+					<mi mathvariant='sans-serif-bold-italic'>𝙗</mi>
+				</mrow>
+			</math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+	
+	#[test]
+    fn plane1_dotless_letters() {
+        let test_str = "<math>
+				<mi>&#x1D6A4;</mi> <mo>,</mo>						<!-- italic dotless i -->
+				<mi mathvariant='normal'>&#x1D6A5;</mi> <mo>,</mo>	<!-- mathvariant isn't changed -->
+				<mi mathvariant='bold'>ı</mi> <mo>,</mo>
+				<mover><mi>ȷ</mi><mo>^</mo></mover>
+			</math>";
+        let target_str = "<math>
+				<mrow data-changed='added'>
+					<mi mathvariant='italic'>i</mi>
+					<mo>,</mo>
+					<mi mathvariant='normal'>j</mi>
+					<mo>,</mo>
+					<mi mathvariant='bold' data-vector='bold'>𝐢</mi>
+					<mo>,</mo>
+					<mover data-embellished-identifier='true'><mi>j</mi><mo>^</mo></mover>
+				</mrow>
+			</math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+	
+	#[test]
+    fn nfc_decomposed_input() {
+        let test_str = "<math><mi>x</mi><mo>=&#x338;</mo><mi>a&#x308;&#x304;</mi><mo>&#x2208;&#x338;</mo><mi>&#x1100;&#x1161;&#x11A8;</mi></math>";
+        let target_str = "<math>
+				<mrow data-changed='added'>
+					<mrow data-changed='added'>
+						<mi>x</mi>
+						<mo>≠</mo>
+						<mi>ǟ</mi>
+					</mrow>
+					<mo>∉</mo>
+					<mi>각</mi>
+				</mrow>
+			</math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+	fn nfc_reordered_marks() {
+		// the marks are put in canonical order before they are composed
+		assert_eq!(nfc_normalize("a\u{302}\u{323}"), "ậ");
+		assert_eq!(nfc_normalize("a\u{323}\u{302}"), "ậ");
+		assert_eq!(nfc_normalize("x\u{307}"), "ẋ");
+	}
+	
 	#[test]
     fn plane1_greek() {
         let test_str = "<math>
@@ -3672,6 +5751,54 @@ mod canonicalize_tests {
         assert!(are_strs_canonically_equal(test_str, target_str));
     }
 
+	#[test]
+	fn embellished_function_names() {
+		let test_str = "<math><mrow>
+			<mover><mi>f</mi><mo>^</mo></mover><mo>(</mo><mi>x</mi><mo>)</mo>
+			<mo>+</mo>
+			<menclose notation='box'><mi>f</mi></menclose><mo>(</mo><mi>x</mi><mo>)</mo>
+			<mo>+</mo>
+			<mstyle mathcolor='red'><mi>f</mi></mstyle><mo>(</mo><mi>x</mi><mo>)</mo>
+		</mrow></math>";
+		let target_str = "<math>
+		<mrow>
+		  <mrow data-changed='added'>
+			<mover data-embellished-identifier='true'><mi>f</mi><mo>^</mo></mover>
+			<mo data-changed='added'>&#x2061;</mo>
+			<mrow data-changed='added'><mo>(</mo><mi>x</mi><mo>)</mo></mrow>
+		  </mrow>
+		  <mo>+</mo>
+		  <mrow data-changed='added'>
+			<menclose notation='box'><mi>f</mi></menclose>
+			<mo data-changed='added'>&#x2061;</mo>
+			<mrow data-changed='added'><mo>(</mo><mi>x</mi><mo>)</mo></mrow>
+		  </mrow>
+		  <mo>+</mo>
+		  <mrow data-changed='added'>
+			<mi mathcolor='red'>f</mi>
+			<mo data-changed='added'>&#x2061;</mo>
+			<mrow data-changed='added'><mo>(</mo><mi>x</mi><mo>)</mo></mrow>
+		  </mrow>
+		</mrow>
+	   </math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+	fn embellished_boxed_function_name() {
+		let test_str = "<math><mrow>
+			<menclose notation='box'><mover><mi>f</mi><mo>^</mo></mover></menclose><mo>(</mo><mi>x</mi><mo>)</mo>
+		</mrow></math>";
+		let target_str = "<math>
+		<mrow>
+			<menclose notation='box'><mover data-embellished-identifier='true'><mi>f</mi><mo>^</mo></mover></menclose>
+			<mo data-changed='added'>&#x2061;</mo>
+			<mrow data-changed='added'><mo>(</mo><mi>x</mi><mo>)</mo></mrow>
+		</mrow>
+	   </math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
 	#[test]
 	fn maybe_function() {
 		let test_str = "<math>
@@ -3702,20 +5829,41 @@ mod canonicalize_tests {
 		assert!(are_strs_canonically_equal(test_str, target_str));
 	}
 
-    #[test]
-    fn function_with_multiple_args() {
-        let test_str = "<math>
-		<mi>sin</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
-			<mo>+</mo>
-		 <mi>f</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
-			<mo>+</mo>
-		 <mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
-			<mo>+</mo>
-		 <mi>w</mi><mo>(</mo><mi>x</mi><mo>,</mo><mi>y</mi><mo>)</mo>
-		</math>";
-        let target_str = " <math>
-		<mrow data-changed='added'>
-		<mrow data-changed='added'>
+	#[test]
+	fn function_with_semicolon_args() {
+		let test_str = "<math><mi>p</mi><mo>(</mo><mi>x</mi><mo>;</mo><mi>θ</mi><mo>)</mo></math>";
+		let target_str = "<math>
+				<mrow data-changed='added'>
+				<mi>p</mi>
+				<mo data-changed='added'>&#x2061;</mo>
+				<mrow data-changed='added'>
+					<mo>(</mo>
+					<mrow data-changed='added'>
+					<mi>x</mi>
+					<mo>;</mo>
+					<mi>θ</mi>
+					</mrow>
+					<mo>)</mo>
+				</mrow>
+				</mrow>
+			</math>";
+		assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+    #[test]
+    fn function_with_multiple_args() {
+        let test_str = "<math>
+		<mi>sin</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
+			<mo>+</mo>
+		 <mi>f</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
+			<mo>+</mo>
+		 <mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mi>y</mi><mo>)</mo>
+			<mo>+</mo>
+		 <mi>w</mi><mo>(</mo><mi>x</mi><mo>,</mo><mi>y</mi><mo>)</mo>
+		</math>";
+        let target_str = " <math>
+		<mrow data-changed='added'>
+		<mrow data-changed='added'>
 		  <mi>sin</mi>
 		  <mo data-changed='added'>&#x2061;</mo>
 		  <mrow data-changed='added'>
@@ -3853,7 +6001,7 @@ mod canonicalize_tests {
 			<mrow>
 				<mn>2</mn>
 				<mo data-changed='added'>&#x2064;</mo>
-				<mrow data-changed='added'>>
+				<mrow data-changed='added'>
 					<mn>3</mn>
 					<mo>/</mo>
 					<mn>4</mn>
@@ -4304,6 +6452,68 @@ mod canonicalize_tests {
 	}
 
 
+	#[test]
+    fn empty_table_cells() {
+        let test_str = "<math><mtable><mtr><mtd><mn>1</mn></mtd><mtd></mtd><mtd><mrow/></mtd><mtd><mspace width='1em'/></mtd></mtr></mtable></math>";
+        let target_str = " <math><mtable><mtr>
+				<mtd><mn>1</mn></mtd>
+				<mtd data-empty-cell='true'><mtext data-added='missing-content' data-changed='empty_content'>&#xA0;</mtext></mtd>
+				<mtd data-empty-cell='true'><mtext data-added='missing-content'>&#xA0;</mtext></mtd>
+				<mtd data-empty-cell='true'><mtext width='1em' data-changed='empty_content'>&#xA0;</mtext></mtd>
+			</mtr></mtable></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn augmented_matrix_columnlines() {
+        let test_str = "<math><mrow><mo>[</mo><mtable columnlines='none solid'>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>3</mn></mtd></mtr>
+				<mtr><mtd><mn>4</mn></mtd><mtd><mn>5</mn></mtd><mtd><mn>6</mn></mtd></mtr>
+			</mtable><mo>]</mo></mrow></math>";
+        let target_str = " <math><mrow><mo>[</mo><mtable columnlines='none solid' data-augmented-matrix='true'>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>3</mn></mtd></mtr>
+				<mtr><mtd><mn>4</mn></mtd><mtd><mn>5</mn></mtd><mtd><mn>6</mn></mtd></mtr>
+			</mtable><mo>]</mo></mrow></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+
+        // the last value is repeated, so there is a line between every column
+        let test_str = "<math><mtable columnlines='solid'>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>3</mn></mtd></mtr>
+			</mtable></math>";
+        let target_str = " <math><mtable columnlines='solid'>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>3</mn></mtd></mtr>
+			</mtable></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn linebreaks_to_lines() {
+        let test_str = "<math><mi>a</mi><mo>=</mo><mi>b</mi><mspace linebreak='newline'/><mo linebreak='newline' linebreakstyle='after'>=</mo><mi>c</mi></math>";
+        let target_str = "<math>
+			<mtable data-changed='added'>
+				<mtr><mtd><mrow data-changed='added'><mi>a</mi><mo>=</mo><mi>b</mi></mrow></mtd></mtr>
+				<mtr><mtd><mo linebreak='newline' linebreakstyle='after'>=</mo></mtd></mtr>
+				<mtr><mtd><mi>c</mi></mtd></mtr>
+			</mtable>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // a break at the end doesn't start a line
+        let test_str = "<math><mi>a</mi><mo>=</mo><mi>b</mi><mspace linebreak='newline'/></math>";
+        let target_str = "<math><mrow data-changed='added'><mi>a</mi><mo>=</mo><mi>b</mi></mrow></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+    }
+
+	#[test]
+    fn mtext_layout_spacing() {
+        let test_str = "<math><mtext>total\t\tcost</mtext><mo>=</mo><mtext>a\u{2007}\u{2007}b   c</mtext></math>";
+        let target_str = " <math><mrow data-changed='added'>
+				<mtext data-descriptive-identifier='true'>total cost</mtext>
+				<mo>=</mo>
+				<mtext>a b c</mtext>
+			</mrow></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
 	#[test]
     fn clean_semantics() {
 		// this comes from LateXML
@@ -4386,6 +6596,15 @@ mod canonicalize_tests {
         assert!(are_strs_canonically_equal(test_str, target_str));
 	}
 
+	#[test]
+    fn roman_numeral_forced() {
+        let test_str = "<math><mi data-number-kind='roman'>XLVIII</mi><mo>+</mo><mtext data-number-kind='roman'>iv</mtext></math>";
+        let target_str = "<math><mrow data-changed='added'>
+			<mn data-number-kind='roman' data-number='48'>XLVIII</mn><mo>+</mo><mn data-number-kind='roman' data-number='4'>iv</mn>
+			</mrow></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
 	// #[test]
     // fn roman_numeral_context() {
     //     let test_str = "<math><mi>vi</mi><mo>-</mo><mi mathvariant='normal'>i</mi><mo>=</mo><mtext>v</mtext></math>";
@@ -4462,6 +6681,392 @@ mod canonicalize_tests {
         assert!(are_strs_canonically_equal(test_str, target_str));
 	}
 
+	#[test]
+    fn digit_block_table_columns() {
+		// a row of a table written with wide spaces between the columns -- each column is a number
+        let test_str = "<math><mn>12</mn><mspace width='thickmathspace'/><mn>345</mn><mspace width='2em'/>
+								<mn>678</mn><mspace width='thickmathspace'/><mn>901</mn><mspace width='2em'/>
+								<mn>0110</mn><mspace width='thickmathspace'/><mn>1110</mn></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>12\u{A0}345</mn>
+				<mo data-changed='added' data-number-list='true' width='2em'>&#x2063;</mo>
+				<mn>678\u{A0}901</mn>
+				<mo data-changed='added' data-number-list='true' width='2em'>&#x2063;</mo>
+				<mn>0110\u{A0}1110</mn>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn digit_block_too_many_blocks() {
+		// 8 blocks is a number, but 9 blocks are more likely table data
+        let test_str = "<math><mn>602</mn><mo>,</mo><mn>214</mn><mo>,</mo><mn>076</mn><mo>,</mo><mn>000</mn><mo>,</mo>
+								<mn>000</mn><mo>,</mo><mn>000</mn><mo>,</mo><mn>000</mn><mo>,</mo><mn>000</mn></math>";
+        let target_str = " <math><mn>602,214,076,000,000,000,000,000</mn></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        let test_str = "<math><mn>1</mn><mspace width='thickmathspace'/><mn>234</mn><mspace width='thickmathspace'/><mn>567</mn>
+								<mspace width='thickmathspace'/><mn>890</mn><mspace width='thickmathspace'/><mn>123</mn>
+								<mspace width='thickmathspace'/><mn>456</mn><mspace width='thickmathspace'/><mn>789</mn>
+								<mspace width='thickmathspace'/><mn>012</mn><mspace width='thickmathspace'/><mn>345</mn></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>1</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>234</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>567</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>890</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>123</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>456</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>789</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>012</mn><mo data-changed='added' data-number-list='true' width='thickmathspace'>&#x2063;</mo>
+				<mn>345</mn>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn digit_block_table_cells() {
+		// numbers in different cells are never merged, but the digit blocks inside a cell are
+        let test_str = "<math><mtable>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>234</mn></mtd><mtd><mn>567</mn></mtd></mtr>
+				<mtr><mtd><mn>8</mn><mspace width='thickmathspace'/><mn>910</mn></mtd><mtd><mn>11</mn></mtd><mtd><mn>12</mn></mtd></mtr>
+			</mtable></math>";
+        let target_str = " <math><mtable>
+				<mtr><mtd><mn>1</mn></mtd><mtd><mn>234</mn></mtd><mtd><mn>567</mn></mtd></mtr>
+				<mtr><mtd><mn>8\u{A0}910</mn></mtd><mtd><mn>11</mn></mtd><mtd><mn>12</mn></mtd></mtr>
+			</mtable></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn digit_block_si_constants() {
+		// the exact values of the SI defining constants, once as a table and once as a row with wide column spacing
+        let test_str = "<math><mtable>
+				<mtr><mtd><mi>c</mi></mtd>
+					<mtd><mn>299</mn><mspace width='thickmathspace'/><mn>792</mn><mspace width='thickmathspace'/><mn>458</mn></mtd></mtr>
+				<mtr><mtd><mi>Δ</mi><msub><mi>ν</mi><mi>Cs</mi></msub></mtd>
+					<mtd><mn>9</mn><mspace width='thickmathspace'/><mn>192</mn><mspace width='thickmathspace'/><mn>631</mn><mspace width='thickmathspace'/><mn>770</mn></mtd></mtr>
+			</mtable></math>";
+        let target_str = " <math><mtable>
+				<mtr><mtd><mi>c</mi></mtd><mtd><mn>299\u{A0}792\u{A0}458</mn></mtd></mtr>
+				<mtr><mtd>
+					<mrow data-changed='added'><mi>Δ</mi><mo data-changed='added'>&#x2062;</mo><msub><mi>ν</mi><mi>Cs</mi></msub></mrow>
+				</mtd>
+				<mtd><mn>9\u{A0}192\u{A0}631\u{A0}770</mn></mtd></mtr>
+			</mtable></math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        let test_str = "<math>
+				<mn>299</mn><mspace width='thickmathspace'/><mn>792</mn><mspace width='thickmathspace'/><mn>458</mn>
+				<mspace width='2em'/>
+				<mn>9</mn><mspace width='thickmathspace'/><mn>192</mn><mspace width='thickmathspace'/><mn>631</mn><mspace width='thickmathspace'/><mn>770</mn>
+			</math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>299\u{A0}792\u{A0}458</mn>
+				<mo data-changed='added' data-number-list='true' width='2em'>&#x2063;</mo>
+				<mn>9\u{A0}192\u{A0}631\u{A0}770</mn>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn repeating_decimal_mover() {
+        let test_str = "<math><mn>0.1</mn><mover><mn>23</mn><mo>¯</mo></mover><mo>+</mo><mi>x</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mrow data-changed='added' data-number-kind='repeating-decimal'>
+					<mn>0.1</mn>
+					<mover><mn>23</mn><mo>¯</mo></mover>
+				</mrow>
+				<mo>+</mo>
+				<mi>x</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn repeating_decimal_combining_char() {
+        let test_str = "<math><mn>0.12&#x0307;3&#x0307;</mn></math>";
+        let target_str = " <math>
+				<mrow data-changed='added' data-number-kind='repeating-decimal'>
+					<mn>0.1</mn>
+					<mover data-changed='added'><mn>23</mn><mo>˙</mo></mover>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn repeating_decimal_ellipsis() {
+        let test_str = "<math><mn>0.1666</mn><mo>…</mo><mo>+</mo><mi>x</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mrow data-changed='added' data-number-kind='repeating-decimal' data-repeating-digits='6'>
+					<mn>0.1666</mn>
+					<mi>…</mi>
+				</mrow>
+				<mo>+</mo>
+				<mi>x</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+
+        let test_str = "<math><mn>0.142857142857</mn><mi>…</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added' data-number-kind='repeating-decimal' data-repeating-digits='142857'>
+					<mn>0.142857142857</mn>
+					<mi>…</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn scientific_notation_times() {
+        let test_str = "<math><mn>6.02</mn><mo>×</mo><msup><mn>10</mn><mrow><mo>−</mo><mn>23</mn></mrow></msup><mo>+</mo><mi>x</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mrow data-changed='added' data-number-kind='scientific-notation'>
+					<mn>6.02</mn>
+					<mo>×</mo>
+					<msup><mn>10</mn><mrow><mo>-</mo><mn>23</mn></mrow></msup>
+				</mrow>
+				<mo>+</mo>
+				<mi>x</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn scientific_notation_e() {
+        let test_str = "<math><mn>1.5E-3</mn></math>";
+        let target_str = " <math>
+				<mrow data-changed='added' data-number-kind='scientific-notation'>
+					<mn>1.5</mn>
+					<mi>E</mi>
+					<mrow data-changed='added'><mo data-changed='added'>-</mo><mn>3</mn></mrow>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // not scientific notation (not a power of 10)
+        let test_str = "<math><mn>3</mn><mo>×</mo><msup><mn>2</mn><mn>5</mn></msup></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+					<mn>3</mn>
+					<mo>×</mo>
+					<msup><mn>2</mn><mn>5</mn></msup>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn split_points() {
+        let test_str = "<math><mo>△</mo><mi>ABC</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+					<mi>△</mi>
+					<mo data-changed='added'>&#x2061;</mo>
+					<mrow data-split-points='geometry'>
+						<mi>A</mi><mo data-changed='added'>&#x2063;</mo><mi>B</mi><mo data-changed='added'>&#x2063;</mo><mi>C</mi>
+					</mrow>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        let test_str = "<math><mover><mi>XYZ</mi><mo>¯</mo></mover></math>";
+        let target_str = " <math>
+				<mover>
+					<mrow data-split-points='unconfirmed'>
+						<mi>X</mi><mo data-changed='added'>&#x2063;</mo><mi>Y</mi><mo data-changed='added'>&#x2063;</mo><mi>Z</mi>
+					</mrow>
+					<mo>¯</mo>
+				</mover>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+        // acronyms, long names, and names with an intent are not split
+        let test_str = "<math><mover><mi>GDP</mi><mo>¯</mo></mover><mo>+</mo><mover><mi>ABCD</mi><mo>¯</mo></mover><mo>+</mo>
+				<mover intent='mean($x)'><mi arg='x'>XY</mi><mo>¯</mo></mover></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+					<mover data-embellished-identifier='true'><mi>GDP</mi><mo>¯</mo></mover>
+					<mo>+</mo>
+					<mover data-embellished-identifier='true'><mi>ABCD</mi><mo>¯</mo></mover>
+					<mo>+</mo>
+					<mover intent='mean($x)' data-embellished-identifier='true'><mi arg='x'>XY</mi><mo>¯</mo></mover>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn superscript_chars_in_tokens() {
+        let test_str = "<math><mn>10⁻³</mn><mi mathvariant='normal'>m²</mi><mo>+</mo><mi>xⁿ</mi><mo>+</mo><mi>²x</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mrow data-changed='added'>
+					<msup data-changed='added'>
+					<mn>10</mn>
+					<mrow data-changed='added'><mo>-</mo><mn>3</mn></mrow>
+					</msup>
+					<mo data-changed='added'>&#x2062;</mo>
+					<msup data-changed='added'><mi mathvariant='normal'>m</mi><mn>2</mn></msup>
+				</mrow>
+				<mo>+</mo>
+				<msup data-changed='added'><mi>x</mi><mi>n</mi></msup>
+				<mo>+</mo>
+				<mi>²x</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn mfrac_kind() {
+        let test_str = "<math><mfrac linethickness='0px'><mi>n</mi><mi>k</mi></mfrac><mo>+</mo>
+								<mfrac bevelled='true'><mn>1</mn><mn>2</mn></mfrac><mo>+</mo>
+								<mfrac bevelled='false' linethickness='thick'><mn>1</mn><mn>3</mn></mfrac></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mfrac linethickness='0px' data-fraction-kind='no-line'><mi>n</mi><mi>k</mi></mfrac>
+				<mo>+</mo>
+				<mfrac bevelled='true' data-fraction-kind='bevelled'><mn>1</mn><mn>2</mn></mfrac>
+				<mo>+</mo>
+				<mfrac bevelled='false' linethickness='thick'><mn>1</mn><mn>3</mn></mfrac>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn accented_identifiers() {
+        let test_str = "<math><mi>x&#x302;</mi><mo>+</mo><mi>&#x1E8B;</mi><mo>+</mo><mover><mi>y</mi><mo>&#x303;</mo></mover><mo>+</mo><mi>ä</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mover data-changed='added' accent='true' data-embellished-identifier='true'><mi>x</mi><mo>^</mo></mover>
+				<mo>+</mo>
+				<mover data-changed='added' accent='true' data-embellished-identifier='true'><mi>x</mi><mo>˙</mo></mover>
+				<mo>+</mo>
+				<mover data-embellished-identifier='true'><mi>y</mi><mo>~</mo></mover>
+				<mo>+</mo>
+				<mi>ä</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn vector_identifiers() {
+        let test_str = "<math><mover><mi>v</mi><mo>&#x20D7;</mo></mover><mo>+</mo><mi mathvariant='bold'>u</mi><mo>+</mo>
+				<mi mathvariant='bold'>A</mi><mo>+</mo><mover><mi>x</mi><mo>^</mo></mover></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mover data-embellished-identifier='true' data-vector='arrow'><mi>v</mi><mo>→</mo></mover>
+				<mo>+</mo>
+				<mi mathvariant='bold' data-vector='bold'>𝐮</mi>
+				<mo>+</mo>
+				<mi mathvariant='bold'>𝐀</mi>
+				<mo>+</mo>
+				<mover data-embellished-identifier='true'><mi>x</mi><mo>^</mo></mover>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn plain_text_in_mi() {
+        let test_str = "<math><mi>f'(x)</mi><mo>=</mo><mi>2x-1</mi></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+					<mrow data-changed='added'>
+						<msup data-changed='added' data-pseudo-script='true'><mi>f</mi><mo>′</mo></msup>
+						<mo data-changed='added'>&#x2061;</mo>
+						<mrow data-changed='added'><mo>(</mo><mi>x</mi><mo>)</mo></mrow>
+					</mrow>
+					<mo>=</mo>
+					<mrow data-changed='added'>
+						<mrow data-changed='added'><mn>2</mn><mo data-changed='added'>&#x2062;</mo><mi>x</mi></mrow>
+						<mo>-</mo>
+						<mn>1</mn>
+					</mrow>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn plain_text_in_mi_not_split() {
+		// hyphenated words, apostrophes in words, and mtext are left alone
+        let test_str = "<math><mi>x-ray</mi><mo>+</mo><mi>don't</mi><mo>+</mo><mtext>x-1</mtext></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+					<mi>x-ray</mi>
+					<mo>+</mo>
+					<mi>don't</mi>
+					<mo>+</mo>
+					<mtext>x-1</mtext>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn ms_quotes() {
+        let test_str = "<math><ms>a</ms><mo>+</mo><ms lquote='``' rquote=\"''\">b</ms><mo>+</mo><ms lquote='‘' rquote='’'></ms></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<ms lquote='\"' rquote='\"'>a</ms>
+				<mo>+</mo>
+				<ms lquote='“' rquote='”'>b</ms>
+				<mo>+</mo>
+				<ms lquote='‘' rquote='’'></ms>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn not_repeating_decimal() {
+        let test_str = "<math><mn>2</mn><mover><mn>3</mn><mo>¯</mo></mover></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>2</mn>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mover><mn>3</mn><mo>¯</mo></mover>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn not_repeating_decimal_ellipsis() {
+		// the digits don't repeat
+        let test_str = "<math><mn>3.14159</mn><mo>…</mo></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>3.14159</mn>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mi>…</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+
+		// "," is not a decimal separator (English)
+        let test_str = "<math><mn>0,333</mn><mo>…</mo></math>";
+        let target_str = " <math>
+				<mrow data-changed='added'>
+				<mn>0,333</mn>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mi>…</mi>
+				</mrow>
+			</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
 	#[test]
     fn not_digit_block_parens() {
         let test_str = "<math><mo>(</mo><mn>451</mn><mo>,</mo><mn>231</mn><mo>)</mo></math>";
@@ -4622,7 +7227,7 @@ mod canonicalize_tests {
 		  <mrow>
 			<mn mathsize='normal' mathvariant='bold'>𝟎</mn>
 			<mo data-changed='added'>&#x2062;</mo>
-			<mn mathsize='normal' mathvariant='bold' data-changed='added'>.02</mn>
+			<mn mathsize='normal' mathvariant='bold' data-changed='added'>.𝟎𝟐</mn>
 		  </mrow>
 		</msqrt>
 	   </math>";
@@ -4673,7 +7278,7 @@ mod canonicalize_tests {
 		  <mrow data-changed='added'>
 			<munder>
 			  <mo>∑</mo>
-			  <mrow>
+			  <mrow data-relation-chain='inequality'>
 				<mn>0</mn>
 				<mo>≤</mo>
 				<mi>k</mi>
@@ -4709,13 +7314,93 @@ mod canonicalize_tests {
 		  <mrow data-changed='added'>
 			<mi>cos</mi>
 			<mo data-changed='added'>&#x2061;</mo>
-			<msup data-changed='added'><mn>30</mn><mo>°</mo></msup>
+			<msup data-changed='added' data-pseudo-script='true'><mn>30</mn><mo>°</mo></msup>
 		  </mrow>
 		  <mo data-changed='added'>&#x2062;</mo>
 		  <mrow data-changed='added'>
 			<mi>sin</mi>
 			<mo data-changed='added'>&#x2061;</mo>
-			<msup data-changed='added'><mn>60</mn><mo>′</mo></msup>
+			<msup data-changed='added' data-pseudo-script='true'><mn>60</mn><mo>′</mo></msup>
+		  </mrow>
+		</mrow>
+	   </math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn pseudo_scripts_context() {
+		// '*' followed by an operand is multiplication; a trailing '*' after a scripted mi is a pseudo-script
+        let test_str = "<math><mrow>
+				<mi>a</mi><mo>*</mo><mi>b</mi><mo>=</mo>
+				<msub><mi>x</mi><mn>1</mn></msub><mo>*</mo>
+				</mrow></math>";
+        let target_str = "<math>
+		<mrow>
+		  <mrow data-changed='added'>
+			<mi>a</mi>
+			<mo>*</mo>
+			<mi>b</mi>
+		  </mrow>
+		  <mo>=</mo>
+		  <msup data-changed='added' data-pseudo-script='true'>
+			<msub><mi>x</mi><mn>1</mn></msub>
+			<mo>*</mo>
+		  </msup>
+		</mrow>
+	   </math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn pseudo_scripts_embellished_and_fenced_bases() {
+		// an identifier with an accent and a parenthesized expression are bases too
+        let test_str = "<math><mrow>
+				<mover><mi>x</mi><mo>^</mo></mover><mo>′</mo><mo>=</mo>
+				<mo>(</mo><mi>f</mi><mi>g</mi><mo>)</mo><mo>′</mo>
+				</mrow></math>";
+        let target_str = "<math>
+		<mrow>
+		  <msup data-changed='added' data-pseudo-script='true'>
+			<mover data-embellished-identifier='true'><mi>x</mi><mo>^</mo></mover>
+			<mo>′</mo>
+		  </msup>
+		  <mo>=</mo>
+		  <msup data-changed='added' data-pseudo-script='true'>
+			<mrow data-changed='added'>
+			  <mo>(</mo>
+			  <mrow data-changed='added'>
+				<mi>f</mi>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mi>g</mi>
+			  </mrow>
+			  <mo>)</mo>
+			</mrow>
+			<mo>′</mo>
+		  </msup>
+		</mrow>
+	   </math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn modulus_group() {
+		// the "(mod n)" applies to the whole congruence, not to 'b' via implied times
+        let test_str = "<math><mi>a</mi><mo>≡</mo><mi>b</mi><mo>(</mo><mi>mod</mi><mi>n</mi><mo>)</mo></math>";
+        let target_str = "<math>
+		<mrow data-changed='added'>
+		  <mrow data-changed='added'>
+			<mi>a</mi>
+			<mo>≡</mo>
+			<mi>b</mi>
+		  </mrow>
+		  <mo data-changed='added'>&#x2063;</mo>
+		  <mrow data-changed='added'>
+			<mo>(</mo>
+			<mrow data-changed='added'>
+			  <mo>mod</mo>
+			  <mi>n</mi>
+			</mrow>
+			<mo>)</mo>
 		  </mrow>
 		</mrow>
 	   </math>";
@@ -4789,6 +7474,112 @@ mod canonicalize_tests {
 	}
 
 
+	#[test]
+    fn empty_base_script_not_operator_base() {
+		// "x^2 + 1" -- the "+" shouldn't become the base of prescripts
+        let test_str = "<math><mi>x</mi><msup><mi/><mn>2</mn></msup><mo>+</mo><mn>1</mn></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mmultiscripts><mi>x</mi><none/><mn>2</mn></mmultiscripts>
+				<mo>+</mo>
+				<mn>1</mn>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn empty_base_prescripts_after_relation() {
+		// TeX: y = {}^{14}_{6}\mathrm{C}
+        let test_str = "<math><mi>y</mi><mo>=</mo><msubsup><mi/><mn>6</mn><mn>14</mn></msubsup><mrow><mi mathvariant='normal'>C</mi></mrow></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mi>y</mi>
+				<mo>=</mo>
+				<mmultiscripts><mi mathvariant='normal'>C</mi><mprescripts/><mn>6</mn><mn>14</mn></mmultiscripts>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn empty_base_script_between_relations() {
+		// TeX: x = {}_2 = 3 -- there is no base, so the script is left alone and marked
+        let test_str = "<math><mi>x</mi><mo>=</mo><msub><mi/><mn>2</mn></msub><mo>=</mo><mn>3</mn></math>";
+        let target_str = "<math>
+			<mrow data-changed='added' data-relation-chain='equality'>
+				<mi>x</mi>
+				<mo>=</mo>
+				<msub data-unresolved-script='true'><mtext data-changed='empty_content'>&#xA0;</mtext><mn>2</mn></msub>
+				<mo>=</mo>
+				<mn>3</mn>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn empty_base_script_alone() {
+        let test_str = "<math><msub><mrow/><mn>2</mn></msub></math>";
+        let target_str = "<math>
+			<msub data-unresolved-script='true'><mtext data-changed='empty_content'>&#xA0;</mtext><mn>2</mn></msub>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn enumerated_parts() {
+        let test_str = "<math>
+				<mo>(</mo><mi>a</mi><mo>)</mo><mspace width='1em'/><mi>x</mi><mo>+</mo><mn>1</mn><mspace width='2em'/>
+				<mo>(</mo><mi>b</mi><mo>)</mo><mspace width='1em'/><msup><mi>x</mi><mn>2</mn></msup>
+			</math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mrow data-changed='added' data-enumerator='a'><mo>(</mo><mi>a</mi><mo>)</mo></mrow>
+				<mo data-changed='added'>&#x2063;</mo>
+				<mrow data-changed='added'><mi>x</mi><mo>+</mo><mn>1</mn></mrow>
+				<mo data-changed='added'>&#x2063;</mo>
+				<mrow data-changed='added' data-enumerator='b'><mo>(</mo><mi>b</mi><mo>)</mo></mrow>
+				<mo data-changed='added'>&#x2063;</mo>
+				<msup><mi>x</mi><mn>2</mn></msup>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
+	#[test]
+    fn not_enumerated_parts() {
+		// only one label
+        let test_str = "<math><mo>(</mo><mi>a</mi><mo>)</mo><mi>x</mi></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mrow data-changed='added'><mo>(</mo><mi>a</mi><mo>)</mo></mrow>
+				<mo data-changed='added'>&#x2062;</mo>
+				<mi>x</mi>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+
+		// labels aren't in sequence
+        let test_str = "<math><mo>(</mo><mi>a</mi><mo>)</mo><mi>x</mi><mo>+</mo><mo>(</mo><mi>c</mi><mo>)</mo><mi>y</mi></math>";
+        let target_str = "<math>
+			<mrow data-changed='added'>
+				<mrow data-changed='added'>
+					<mrow data-changed='added'><mo>(</mo><mi>a</mi><mo>)</mo></mrow>
+					<mo data-changed='added'>&#x2062;</mo>
+					<mi>x</mi>
+				</mrow>
+				<mo>+</mo>
+				<mrow data-changed='added'>
+					<mrow data-changed='added'><mo>(</mo><mi>c</mi><mo>)</mo></mrow>
+					<mo data-changed='added'>&#x2062;</mo>
+					<mi>y</mi>
+				</mrow>
+			</mrow>
+		</math>";
+        assert!(are_strs_canonically_equal(test_str, target_str));
+	}
+
 	#[test]
 	#[ignore]	// this fails -- need to figure out grabbing base from previous or next child
     fn tensor() {
@@ -366,16 +366,19 @@ pub fn convert_leaves_to_chem_elements(mathml: Element) -> Option<Vec<Element>>
 /// If it is, it is marked with either data-chem-equation or data-chem-formula
 /// This function assumes proper structure
 /// 
-/// Returns false if not chemistry -- added attrs, mrows, and leaves are removed in preparation for a second parse
-pub fn scan_and_mark_chemistry(mathml: Element) -> bool {
+/// If it isn't chemistry, the subtrees that need to be parsed again are returned (empty if nothing needs to be re-parsed).
+/// Only the parts of the tree with a MAYBE_CHEMISTRY mark are affected -- in those,
+///   added attrs, mrows, and leaves are removed in preparation for the second parse
+pub fn scan_and_mark_chemistry(mathml: Element) -> Vec<Element> {
     if is_chemistry_off() {
-        return true;
+        return vec![];
     }
 
     let child = as_element(mathml.children()[0]);
     // debug!("scan_and_mark_chemistry:\n{}", mml_to_string(&child));
     assert_eq!(name(&mathml), "math");
-    if is_chemistry_sanity_check(mathml) {
+    // an author can say that it isn't chemistry (see INTERPRETATION_ATTR)
+    if child.attribute_value(INTERPRETATION_ATTR) != Some("math") && is_chemistry_sanity_check(mathml) {
         assert_eq!(mathml.children().len(), 1);
         let likelihood = likely_chem_formula(child);
         if likelihood >= CHEMISTRY_THRESHOLD {
@@ -394,13 +397,61 @@ pub fn scan_and_mark_chemistry(mathml: Element) -> bool {
     }
     // debug!("...after marking:\n{}", mml_to_string(&child));
 
-    if child.attribute(CHEM_FORMULA).is_none() && child.attribute(CHEM_EQUATION).is_none() {
-        if !has_maybe_chemistry(mathml) {
-            return true;    // quick check avoids needing a second parse due to removing added elements
+    if child.attribute(CHEM_FORMULA).is_some() || child.attribute(CHEM_EQUATION).is_some() {
+        return vec![];
+    }
+
+    let mut subtrees = vec![];
+    find_subtrees_to_reparse(child, &mut subtrees);
+    // only keep the outermost subtrees -- the others get re-parsed along with them
+    let subtrees = subtrees.iter()
+            .filter(|&&subtree| !subtrees.iter().any(|&other| other != subtree && is_ancestor(other, subtree)))
+            .copied()
+            .collect::<Vec<Element>>();
+    for subtree in &subtrees {
+        subtree.remove_attribute(MAYBE_CHEMISTRY);
+        for child in subtree.children() {
+            unmark_chemistry(as_element(child));
         }
-        return !is_changed_after_unmarking_chemistry(mathml);
-    } else {
-        return true;
+    }
+    return subtrees;
+
+    /// Adds the elements that must be re-parsed because some part of them was parsed as if it might be chemistry.
+    /// The parse of an mrow looks at its operands, so that is the element that is re-parsed.
+    /// Added mrows are removed by unmarking (see 'unmark_chemistry') so we go up until we find one that stays.
+    fn find_subtrees_to_reparse<'a>(mathml: Element<'a>, subtrees: &mut Vec<Element<'a>>) {
+        if mathml.attribute(MAYBE_CHEMISTRY).is_some() || mathml.attribute(MERGED_TOKEN).is_some() || mathml.attribute(SPLIT_TOKEN).is_some() {
+            let mut parent = mathml.parent().unwrap().element().unwrap();   // 'math' is never marked, so parent always exists
+            while is_removed_when_unmarked(parent) {
+                parent = parent.parent().unwrap().element().unwrap();
+            }
+            if !subtrees.contains(&parent) {
+                subtrees.push(parent);
+            }
+        }
+        if !is_leaf(mathml) {
+            for child in mathml.children() {
+                find_subtrees_to_reparse(as_element(child), subtrees);
+            }
+        }
+    }
+
+    fn is_removed_when_unmarked(mathml: Element) -> bool {
+        if name(&mathml) != "mrow" || mathml.attribute_value(CHANGED_ATTR) != Some(ADDED_ATTR_VALUE) {
+            return false;
+        }
+        let parent = mathml.parent().unwrap().element().unwrap();   // mathml is mrow, so parent always exists
+        return mathml.children().len() == 1 || name(&parent) == "mrow";
+    }
+
+    fn is_ancestor(ancestor: Element, mut mathml: Element) -> bool {
+        while let Some(parent) = mathml.parent().and_then(|parent| parent.element()) {
+            if parent == ancestor {
+                return true;
+            }
+            mathml = parent;
+        }
+        return false;
     }
 }
 
@@ -457,29 +508,34 @@ pub fn set_marked_chemistry_attr(mathml: Element, chem: &str) {
     }
 }
 
-/// returns true if no MAYBE_CHEMISTRY's occur within the element
-fn has_maybe_chemistry(mathml: Element) -> bool {
-    if mathml.attribute(MAYBE_CHEMISTRY).is_some() {
-        return true;
-    }
-    if !is_leaf(mathml) {
-        for child in mathml.children() {
-            if has_maybe_chemistry(as_element(child)) {
-                return true;
+/// Clears MAYBE_CHEMISTRY from this element and its decedents
+/// Also deletes added mrows and leaves
+/// Remove the chemistry marks from 'mathml' because it isn't chemistry (e.g., the author said it is math).
+/// As is done when [`scan_and_mark_chemistry`] decides something isn't chemistry, the added leaves and mrows are removed
+///   and merged/split tokens are restored so that 'mathml' can be parsed again as math.
+pub fn unmark_chemistry_subtree(mathml: Element) {
+    remove_chemistry_attrs(mathml);
+    unmark_chemistry(mathml);
+
+    fn remove_chemistry_attrs(mathml: Element) {
+        for attr in mathml.attributes() {
+            if attr.name().local_part().starts_with("data-chem") {
+                mathml.remove_attribute(attr.name());
+            }
+        }
+        if !is_leaf(mathml) {
+            for child in mathml.children() {
+                remove_chemistry_attrs(as_element(child));
             }
         }
     }
-    return false;
 }
 
-/// Clears MAYBE_CHEMISTRY from this element and its decedents
-/// Also deletes added mrows and leaves; returns true if anything is deleted
-fn is_changed_after_unmarking_chemistry(mathml: Element) -> bool {
+fn unmark_chemistry(mathml: Element) {
     mathml.remove_attribute(MAYBE_CHEMISTRY);
     if is_leaf(mathml) {
         if mathml.attribute(MERGED_TOKEN).is_some() {
             unmerge_element(mathml);
-            return true;    // need to re-parse
         } else if mathml.attribute(SPLIT_TOKEN).is_some() {
             if let Err(err) = merge_element(mathml) {
                 panic!("{}", err);
@@ -487,14 +543,11 @@ fn is_changed_after_unmarking_chemistry(mathml: Element) -> bool {
         } else if let Some(changed_value) = mathml.attribute_value(CHANGED_ATTR) {
             if changed_value == ADDED_ATTR_VALUE {
                 mathml.remove_from_parent();
-                return true;
             }
         }
-        return false;
     } else {
-        let mut answer = false;
         for child in mathml.children() {
-            answer |= is_changed_after_unmarking_chemistry(as_element(child));
+            unmark_chemistry(as_element(child));
         }
         if name(&mathml) == "mrow" {
             if let Some(changed_value) = mathml.attribute_value(CHANGED_ATTR) {
@@ -512,9 +565,7 @@ fn is_changed_after_unmarking_chemistry(mathml: Element) -> bool {
                     }
                 }
             }
-            return true;
         }
-        return answer;
     }
 
     fn unmerge_element(mathml: Element) {
@@ -1259,7 +1310,7 @@ fn likely_chem_equation_operator(mathml: Element) -> isize {
     // mostly from chenzhijin.com/en/article/Useful%20Unicode%20for%20Chemists (Arrows and Other)
     static CHEM_EQUATION_OPERATORS: phf::Set<char> = phf_set! {
         '+', '=', '-',
-        '·', '℃', '°', '‡', '∆', '×',
+        '·', '⋅', '℃', '°', '‡', '∆', '×',      // the middle dots join the parts of an adduct or hydrate (e.g., CuSO₄·5H₂O)
     };
 
 
@@ -1734,6 +1785,35 @@ mod chem_tests {
         assert!(are_strs_canonically_equal(test, target));
     }
 
+    #[test]
+    fn reparse_non_chemistry_part() {
+        // "NaCl" is parsed as maybe chemistry -- only the fraction is re-parsed when the whole thing turns out not to be chemistry
+        let test = "<math><mrow><mn>2</mn><mi>x</mi></mrow><mo>+</mo><mfrac><mrow><mi>N</mi><mi>a</mi><mi>C</mi><mi>l</mi></mrow><mn>2</mn></mfrac></math>";
+        let target = " <math>
+        <mrow data-changed='added'>
+          <mrow>
+            <mn>2</mn>
+            <mo data-changed='added'>&#x2062;</mo>
+            <mi>x</mi>
+          </mrow>
+          <mo>+</mo>
+          <mfrac>
+            <mrow>
+              <mi>N</mi>
+              <mo data-changed='added'>&#x2062;</mo>
+              <mi>a</mi>
+              <mo data-changed='added'>&#x2062;</mo>
+              <mi>C</mi>
+              <mo data-changed='added'>&#x2062;</mo>
+              <mi>l</mi>
+            </mrow>
+            <mn>2</mn>
+          </mfrac>
+        </mrow>
+       </math>";
+        assert!(are_strs_canonically_equal(test, target));
+    }
+
     #[test]
     fn combine_mi() {
         let test = "<math><mi>H</mi><mi>C</mi><mi>l</mi></math>";
@@ -179,7 +179,7 @@ fn verify_definitions() -> Result<()> {
     //   numbers-plural, which should have a single entry
     lazy_static! {
         static ref USED_SETS: Vec<&'static str> = vec!["TrigFunctionNames", "AdditionalFunctionNames", "LikelyFunctionNames", 
-                                "LargeOperators"];
+                                "LargeOperators", "PseudoScripts"];
         static ref USED_VECTORS: Vec<&'static str> = vec![
                 "NumbersHundreds", "NumbersTens", "NumbersOnes",
                 "NumbersOrdinalPluralLarge", "NumbersOrdinalLarge", "NumbersLarge",
@@ -26,26 +26,28 @@ pub fn infer_intent<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRule
             if intent_preference == "Error" {
                 return Err(e);
             } else {
-                const INTENT_ATTR: &str = "intent";
-                let saved_intent_attr = mathml.attribute_value(INTENT_ATTR).unwrap();
-                mathml.remove_attribute(INTENT_ATTR);
-                // can't call intent_from_mathml() because we have already borrowed_mut -- we call a more internal version
-                let intent_tree =  match rules_with_context.match_pattern::<Element<'m>>(mathml)
-                                            .chain_err(|| "Pattern match/replacement failure!") {
-                    Err(e) => Err(e),
-                    Ok(intent) => {
-                        intent.set_attribute_value(INTENT_ATTR, saved_intent_attr); //  so attr can be potentially be viewed later
-                        Ok(intent)
-                    },
-                };
-                mathml.set_attribute_value(INTENT_ATTR, saved_intent_attr);
-                return intent_tree;
+                return infer_intent_ignoring_attr(rules_with_context, mathml);
             }
         }
     }
 
     fn catch_errors_building_intent<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRulesWithContext<'c,'s,'m>, mathml: Element<'c>) -> Result<Element<'m>> {
         if let Some(intent_str) = mathml.attribute_value("intent") {
+            if let Some(properties) = intent_str.trim().strip_prefix(':') {
+                // only properties (e.g., ":verbose" or ":verbose:function") -- the intent is what it would be without the attr, but with the properties
+                let mut properties = properties.split(':').map(|property| property.trim()).collect::<Vec<&str>>();
+                if let Some(property) = properties.iter().find(|property| !NC_NAME.is_match(property) || property.contains(':')) {
+                    bail!("Illegal 'intent' syntax: property '{}' in intent attribute value '{}'", property, intent_str);
+                }
+                let intent = infer_intent_ignoring_attr(rules_with_context, mathml)?;
+                if let Some(i) = properties.iter().position(|property| FIXITY_PROPERTIES.contains(property)) {
+                    intent.set_attribute_value(INTENT_HINT, properties.remove(i));
+                }
+                if !properties.is_empty() {
+                    intent.set_attribute_value(INTENT_TYPE, &properties.join(" "));
+                }
+                return Ok(intent);
+            }
             let mut lex_state = LexState::init(intent_str.trim())?;
             let result = build_intent(rules_with_context, &mut lex_state, mathml)
                         .chain_err(|| format!("in intent attribute value '{}'", intent_str))?;
@@ -53,6 +55,12 @@ pub fn infer_intent<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRule
                 bail!("Error in intent value: extra unparsed intent '{}' in intent attribute value '{}'", lex_state.remaining_str, intent_str);
             }
             assert!(lex_state.remaining_str.is_empty());
+            // a name or function built from the intent stands for 'mathml' (an arg keeps its own id)
+            if result.attribute("id").is_none() {
+                if let Some(id) = mathml.attribute_value("id") {
+                    result.set_attribute_value("id", id);
+                }
+            }
             debug!("Resulting intent: {}", crate::pretty_print::mml_to_string(&result));
             return Ok(result);
         }
@@ -60,12 +68,30 @@ pub fn infer_intent<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRule
     }
 }
 
+/// Infer the intent of 'mathml' as if it didn't have an 'intent' attr (the attr is copied to the result so it can be viewed later)
+fn infer_intent_ignoring_attr<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRulesWithContext<'c,'s,'m>, mathml: Element<'c>) -> Result<Element<'m>> {
+    const INTENT_ATTR: &str = "intent";
+    let saved_intent_attr = mathml.attribute_value(INTENT_ATTR).unwrap();
+    mathml.remove_attribute(INTENT_ATTR);
+    // can't call intent_from_mathml() because we have already borrowed_mut -- we call a more internal version
+    let intent_tree =  match rules_with_context.match_pattern::<Element<'m>>(mathml)
+                                .chain_err(|| "Pattern match/replacement failure!") {
+        Err(e) => Err(e),
+        Ok(intent) => {
+            intent.set_attribute_value(INTENT_ATTR, saved_intent_attr); //  so attr can be potentially be viewed later
+            Ok(intent)
+        },
+    };
+    mathml.set_attribute_value(INTENT_ATTR, saved_intent_attr);
+    return intent_tree;
+}
+
 // With isa/types
 // intent          := typed-name-or-literal | number | reference | application 
-// typed-name-or-literal := NCName type?
+// typed-name-or-literal := NCName type*
 // type            := ':' NCName
 // number          := '-'? digit+ ( '.' digit+ )?
-// reference       := '$' NCName
+// reference       := '$' NCName         ('$self' is the element itself when there is no 'arg' named 'self')
 // application     := intent hint? '(' arguments? ')'
 // arguments       := intent ( ',' intent )*
 // hint            := '@' ( 'prefix' | 'infix' | 'postfix' | 'function' | 'silent' | 'auto' )
@@ -73,7 +99,7 @@ lazy_static! {
     // The practical restrictions of NCName are that it cannot contain several symbol characters like
     //  !, ", #, $, %, &, ', (, ), *, +, ,, /, :, ;, <, =, >, ?, @, [, \, ], ^, `, {, |, }, ~, and whitespace characters
     //  Furthermore an NCName cannot begin with a number, dot or minus character although they can appear later in an NCName.
-    static ref NUMBER: Regex = Regex::new(r"^-?([0-9]+.?[0-9]*|.[0-9]+)$").unwrap();
+    static ref NUMBER: Regex = Regex::new(r"^-?([0-9]+\.?[0-9]*|\.[0-9]+)$").unwrap();
     static ref NC_NAME: Regex = Regex::new(r"^[:\pL_][:\pL\-_.0-9·]*$").unwrap();  // from www.w3.org/TR/REC-xml/#sec-common-syn, with "\pL" for letters
     static ref ARG_REF: Regex = Regex::new(r"^\$[:\pL_][:\pL\-.0-9·]*$").unwrap();  // $ NC_NAME
     static ref TYPE_PART: Regex = Regex::new(r"^.*?:-?([0-9]+.?[0-9]*|.[0-9]+)").unwrap();           // pull out a intent type from an intent
@@ -179,8 +205,13 @@ impl<'i> LexState<'i> {
     }
 }
 
-const INTENT_TYPE: &str = "data-intent-type";
+/// The types/properties of an intent (the ':' parts, e.g., "verbose" in `intent=':verbose'`) -- multiple properties are space separated
+pub const INTENT_TYPE: &str = "data-intent-type";
 const INTENT_HINT: &str = "data-intent-hint";
+/// MathML 4 properties that say how a function is spoken -- they are stored as a hint (same as '@prefix', etc)
+static FIXITY_PROPERTIES: [&str; 5] = ["prefix", "infix", "postfix", "function", "silent"];
+/// The name of the reference to the element itself (`$self`)
+const SELF_REF: &str = "self";
 /// Build an intent
 /// Start state: lex_state on token to build
 /// End state: after built intent (a terminal or None)
@@ -192,24 +223,25 @@ fn build_intent<'b, 'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRule
     // debug!("start build_intent:  state: {}", lex_state);
     let mut intent = get_element_from_token(rules_with_context, lex_state, mathml)?;
     let mut next_token = lex_state.get_next()?;
-    let intent_type = if next_token.is_terminal(":") {
-        let temp = Some(get_hint_or_type(lex_state, ":")?);
+    let mut intent_types = vec![];
+    let mut hint = None;
+    while next_token.is_terminal(":") {       // a property chain (e.g., ':prefix:verbose')
+        let property = get_hint_or_type(lex_state, ":")?;
+        if hint.is_none() && FIXITY_PROPERTIES.contains(&property.as_str()) {
+            hint = Some(property);
+        } else {
+            intent_types.push(property);
+        }
         next_token = lex_state.get_next()?;
-        temp
-    } else {
-        None
-    };
-    let hint = if next_token.is_terminal("@") {
-        let temp = Some(get_hint_or_type(lex_state, "@")?);
+    }
+    if next_token.is_terminal("@") {
+        hint = Some(get_hint_or_type(lex_state, "@")?);
         lex_state.get_next()?;
-        temp
-    } else {
-        None
-    };
+    }
 
     // types go on leaves and functions
-    if let Some(type_str) = intent_type {
-        intent.set_attribute_value(INTENT_TYPE, &type_str);
+    if !intent_types.is_empty() {
+        intent.set_attribute_value(INTENT_TYPE, &intent_types.join(" "));
     }
 
     if lex_state.is_terminal("(") {
@@ -219,7 +251,7 @@ fn build_intent<'b, 'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRule
     // debug!("intent='{}'", mml_to_string(&intent));
     if let Some(found_hint_str) = hint {
         hint_str = found_hint_str;
-    } else if name(&intent) == "_" {
+    } else if name(&intent) == "_" || (name(&intent) == LITERAL_NAME && as_text(intent) == "_") {
         hint_str = "silent".to_string();
     }
     if hint_str != "auto" {
@@ -308,20 +340,48 @@ fn get_element_from_token<'b, 'r, 'c, 's:'c, 'm:'c>(
     return match lex_state.token {
         Token::None => bail!("Illegal 'intent' value: empty string"),
         Token::Terminal(str) => bail!("Illegal 'intent' syntax: expected number, name, function but found {}", str),
-        Token::NCName(str) | Token::Number(str) => {
+        Token::NCName(str) => {
             let result = create_mathml_element(&doc, LITERAL_NAME);
             result.set_text(str);
             Ok(result)
         },
+        Token::Number(str) => Ok( create_number(doc, str) ),
         Token::ArgRef(str) => {
             match find_arg(rules_with_context, &str[1..], mathml, true, false)? {
                 Some(e) => Ok(e),
+                None if &str[1..] == SELF_REF => infer_intent_ignoring_attr(rules_with_context, mathml),
                 None => bail!("intent arg '{}' not found", str),
             }
         }
     }
 }
 
+/// Numbers become an `mn` so they are spoken as numbers (a negative number is wrapped in `negative`).
+/// The '.' in the intent is changed to the speech language's decimal separator.
+fn create_number<'m>(doc: Document<'m>, number: &str) -> Element<'m> {
+    let decimal_separator = crate::definitions::DEFINITIONS.with(|definitions| {
+        return match definitions.borrow().get_hashset("DecimalSeparators") {
+            Some(separators) if !separators.contains(".") => separators.iter().min().cloned(),
+            _ => None,
+        };
+    });
+    let (is_negative, digits) = match number.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, number),
+    };
+    let mn = create_mathml_element(&doc, "mn");
+    mn.set_text(&match decimal_separator {
+        Some(separator) => digits.replace('.', &separator),
+        None => digits.to_string(),
+    });
+    if !is_negative {
+        return mn;
+    }
+    let negative = create_mathml_element(&doc, "negative");
+    negative.append_child(mn);
+    return negative;
+}
+
 /// lift the children up to LITERAL_NAME
 fn lift_function_name<'m>(doc: Document<'m>, function_name: Element<'m>, mut children: Vec<Element<'m>>) -> Element<'m> {
     // debug!("    lift_function_name: {}", name(&function_name));
@@ -497,6 +557,13 @@ mod tests {
         assert!(test_intent(mathml, intent));
     }
 
+    #[test]
+    fn intent_property_only() {
+        let mathml = "<msup intent=':verbose'><mi>x</mi><mn>2</mn></msup>";
+        let intent = "<power data-intent-type='verbose' intent=':verbose'><mi>x</mi><mn>2</mn></power>";
+        assert!(test_intent(mathml, intent));
+    }
+
     #[test]
     fn intent_in_intent_first_arg() {
         let mathml = "<mrow intent='p(f(b), a)'>
@@ -556,11 +623,49 @@ mod tests {
                 <mi>x</mi>
             </mrow>";
         let intent = "<vector>
-            <intent-literal>1</intent-literal><intent-literal>0.</intent-literal><intent-literal>.1</intent-literal><intent-literal>-23</intent-literal><intent-literal>-.1234</intent-literal><intent-literal>last</intent-literal>
+            <mn>1</mn><mn>0.</mn><mn>.1</mn><negative><mn>23</mn></negative><negative><mn>.1234</mn></negative><intent-literal>last</intent-literal>
             </vector>";
         assert!(test_intent(mathml, intent));
     }
 
+    #[test]
+    fn intent_with_nested_literals() {
+        let mathml = "<mrow intent='_f(_g(-3, _), 2.5)'>
+                <mi>x</mi>
+            </mrow>";
+        let intent = "<_f><_g><negative><mn>3</mn></negative><intent-literal data-intent-hint='silent'>_</intent-literal></_g><mn>2.5</mn></_f>";
+        assert!(test_intent(mathml, intent));
+    }
+
+    #[test]
+    fn intent_self() {
+        let mathml = "<msup intent='squared($self)'><mi>x</mi><mn>2</mn></msup>";
+        let intent = "<squared><power intent='squared($self)'><mi>x</mi><mn>2</mn></power></squared>";
+        assert!(test_intent(mathml, intent));
+    }
+
+    #[test]
+    fn intent_self_arg() {
+        // an 'arg' named 'self' takes precedence
+        let mathml = "<mrow intent='f($self)'><mi arg='self'>x</mi><mo>+</mo><mn>1</mn></mrow>";
+        let intent = "<f><mi arg='self'>x</mi></f>";
+        assert!(test_intent(mathml, intent));
+    }
+
+    #[test]
+    fn intent_property_chain() {
+        let mathml = "<mrow intent='foo:function:verbose:unit($a)'><mi arg='a'>a</mi></mrow>";
+        let intent = "<foo data-intent-hint='function' data-intent-type='verbose unit'><mi arg='a'>a</mi></foo>";
+        assert!(test_intent(mathml, intent));
+    }
+
+    #[test]
+    fn intent_property_only_chain() {
+        let mathml = "<msup intent=':postfix:verbose'><mi>x</mi><mn>2</mn></msup>";
+        let intent = "<power data-intent-hint='postfix' data-intent-type='verbose' intent=':postfix:verbose'><mi>x</mi><mn>2</mn></power>";
+        assert!(test_intent(mathml, intent));
+    }
+
     #[test]
     fn intent_with_nested_head() {
         let mathml = "<mrow intent='$U27F6($U2245)($a,$b)'>
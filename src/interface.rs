@@ -3,6 +3,7 @@
 #![allow(non_snake_case)]
 #![allow(clippy::needless_return)]
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 use sxd_document::parser;
 use sxd_document::Package;
@@ -17,19 +18,32 @@ use crate::canonicalize::{name, as_element};
 use crate::navigate::*;
 use crate::pretty_print::mml_to_string;
 use crate::xpath_functions::is_leaf;
+pub use crate::symbol_category::SymbolCategory;
 
 // wrap up some common functionality between the call from 'main' and AT
 fn cleanup_mathml(mathml: Element) -> Result<Element> {
+    STRAY_TEXT.with(|stray_text| stray_text.borrow_mut().clear());
     trim_element(&mathml);
     let mathml = crate::canonicalize::canonicalize(mathml)?;
     let mathml = add_ids(mathml);
     return Ok(mathml);
 }
 
+fn quick_cleanup_mathml(mathml: Element) -> Result<Element> {
+    STRAY_TEXT.with(|stray_text| stray_text.borrow_mut().clear());
+    trim_element(&mathml);
+    let mathml = crate::canonicalize::quick_clean(mathml)?;
+    let mathml = add_ids(mathml);
+    return Ok(mathml);
+}
+
 
 thread_local!{
     /// The current node being navigated (also spoken and brailled) is stored in `MATHML_INSTANCE`.
     pub static MATHML_INSTANCE: RefCell<Package> = init_mathml_instance();
+
+    /// The text that was found next to elements (e.g., "[1]" in "<math><mi>x</mi>[1]</math>") and what was done with it
+    static STRAY_TEXT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
 }
 
 fn init_mathml_instance() -> RefCell<Package> {
@@ -57,6 +71,43 @@ pub fn get_version() -> String {
 /// This returns canonical MathML with 'id's set on any node that doesn't have an id.
 /// The ids can be used for sync highlighting if the `Bookmark` API preference is true.
 pub fn set_mathml(mathml_str: String) -> Result<String> {
+    return set_mathml_using(mathml_str, cleanup_mathml);
+}
+
+/// A low-latency version of [`set_mathml`] for previews such as speaking the math as it is typed in an editor.
+/// Only the character normalization and obvious repairs are done (e.g., the chemistry scan is skipped),
+///   so the speech might not be as good as after [`set_mathml`].
+/// Call [`set_mathml`] with the same MathML once it stops changing to get the full canonicalization.
+/// This returns the (partially) canonical MathML with 'id's set on any node that doesn't have an id.
+pub fn quick_clean(mathml_str: String) -> Result<String> {
+    return set_mathml_using(mathml_str, quick_cleanup_mathml);
+}
+
+fn set_mathml_using(mathml_str: String, cleanup: fn(Element) -> Result<Element>) -> Result<String> {
+    reset_expression_state();
+    return MATHML_INSTANCE.with(|old_package| {
+        let new_package = parse_mathml_string(&mathml_str)?;
+        crate::speech::SpeechRules::initialize_all_rules()?;
+
+        let mathml = get_element(&new_package);
+        let mathml = cleanup(mathml)?;
+        let mathml_string = mml_to_string(&mathml);
+        old_package.replace(new_package);
+
+        return Ok( mathml_string );
+    })
+}
+
+/// Reset the state that belongs to the previous expression (navigation position and chosen interpretations)
+fn reset_expression_state() {
+    NAVIGATION_STATE.with(|nav_stack| {
+        nav_stack.borrow_mut().reset();
+    });
+    INTERPRETATIONS.with(|interpretations| interpretations.replace( Interpretations{ original: None, choices: Vec::new() } ));
+}
+
+/// Parse `mathml_str` after replacing HTML entities and removing MathJax classes and namespace prefixes.
+fn parse_mathml_string(mathml_str: &str) -> Result<Package> {
     lazy_static! {
         // if these are present when resent to MathJaX, MathJaX crashes (https://github.com/mathjax/MathJax/issues/2822)
         static ref MATHJAX_V2: Regex = Regex::new(r#"class *= *['"]MJX-.*?['"]"#).unwrap();
@@ -65,57 +116,308 @@ pub fn set_mathml(mathml_str: String) -> Result<String> {
         static ref PREFIX: Regex = Regex::new(r#"(</?)[[:alpha:]]+:"#).unwrap();     // very limited namespace prefix match
         static ref HTML_ENTITIES: Regex = Regex::new(r#"&([a-zA-Z]+?);"#).unwrap();
     }
+    // FIX: convert this to an included file once I get the full entity list
+    static HTML_ENTITIES_MAPPING: phf::Map<&str, &str> = include!("entities.in");
+
+    let mut error_message = "".to_string();     // can't return a result inside the replace_all, so we do this hack of setting the message and then returning the error
+    // need to deal with character data and convert to something the parser knows
+    let mathml_str = HTML_ENTITIES.replace_all(mathml_str, |cap: &Captures| {
+        match HTML_ENTITIES_MAPPING.get(&cap[1]) {
+            None => {
+                error_message = format!("No entity named '{}'", &cap[0]);
+                cap[0].to_string()
+            },
+            Some(&ch) => ch.to_string(),
+        }
+    });
 
-    NAVIGATION_STATE.with(|nav_stack| {
-        nav_stack.borrow_mut().reset();
+    if !error_message.is_empty() {
+        bail!(error_message);
+    }
+    let mathml_str = MATHJAX_V2.replace_all(&mathml_str, "");
+    let mathml_str = MATHJAX_V3.replace_all(&mathml_str, "");
+
+    // the speech rules use the xpath "name" function and that includes the prefix
+    // getting rid of the prefix properly probably involves a recursive replacement in the tree
+    // if the prefix is used, it is almost certainly something like "m" or "mml", so this cheat will work.
+    let mathml_str = NAMESPACE_DECL.replace(&mathml_str, "xmlns");  // do this before the PREFIX replace!
+    let mathml_str = PREFIX.replace_all(&mathml_str, "$1");
+
+    return match parser::parse(&mathml_str) {
+        Ok(package) => Ok(package),
+        Err(e) => bail!("Invalid MathML input:\n{}\nError is: {}", &mathml_str, &e.to_string()),
+    };
+}
+
+/// Parse and canonicalize `mathml_str` without changing the MathML that was set (or its diagnostics, including [`get_stray_text`]).
+/// The canonicalized `math` element is the root element of the returned package.
+fn parse_and_canonicalize_detached(mathml_str: &str) -> Result<Package> {
+    let package = parse_mathml_string(mathml_str)?;
+    let mathml = get_element(&package);
+    let stray_text = STRAY_TEXT.with(|stray_text| stray_text.borrow().clone());
+    trim_element(&mathml);
+    let result = crate::canonicalize::canonicalize_detached(mathml);
+    STRAY_TEXT.with(|stray_text_found| *stray_text_found.borrow_mut() = stray_text);
+    result?;
+    return Ok(package);
+}
+
+/// Options for [`is_equivalent`] -- each option loosens what counts as "the same" canonical MathML.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EquivalenceOptions {
+    /// don't compare attributes (e.g., `id`, `mathvariant`, `intent`)
+    pub ignore_attributes: bool,
+    /// ignore whitespace inside of token elements and don't compare `mspace` and whitespace-only `mtext` elements
+    pub ignore_whitespace: bool,
+    /// don't compare invisible operators (function application, invisible times/separator/plus)
+    pub ignore_invisible_operators: bool,
+}
+
+/// Returns true if `mathml_a` and `mathml_b` have the same canonical form.
+/// Both are canonicalized the same way as in [`set_mathml`], but the MathML that was set is not changed.
+/// `options` loosens the comparison (see [`EquivalenceOptions`]).
+/// This is useful for grading and for removing duplicate expressions.
+pub fn is_equivalent(mathml_a: String, mathml_b: String, options: EquivalenceOptions) -> Result<bool> {
+    crate::speech::SpeechRules::initialize_all_rules()?;
+    let package_a = parse_and_canonicalize_detached(&mathml_a)?;
+    let package_b = parse_and_canonicalize_detached(&mathml_b)?;
+    return Ok( is_equivalent_element(get_element(&package_a), get_element(&package_b), &options) );
+
+    fn is_equivalent_element(e1: Element, e2: Element, options: &EquivalenceOptions) -> bool {
+        let e1 = skip_single_child_mrow(e1, options);
+        let e2 = skip_single_child_mrow(e2, options);
+        if name(&e1) != name(&e2) {
+            return false;
+        }
+        if !options.ignore_attributes && !attrs_are_equivalent(e1, e2) {
+            return false;
+        }
+        if is_leaf(e1) {
+            let text1 = crate::canonicalize::as_text(e1);
+            let text2 = crate::canonicalize::as_text(e2);
+            return if options.ignore_whitespace {
+                text1.split_whitespace().eq(text2.split_whitespace())
+            } else {
+                text1 == text2
+            };
+        }
+        let children1 = compared_children(e1, options);
+        let children2 = compared_children(e2, options);
+        return children1.len() == children2.len() &&
+               children1.into_iter().zip(children2).all(|(c1, c2)| is_equivalent_element(c1, c2, options));
+    }
+
+    /// An mrow that only has one child left after ignoring children is compared as that child
+    fn skip_single_child_mrow<'a>(e: Element<'a>, options: &EquivalenceOptions) -> Element<'a> {
+        if name(&e) == "mrow" {
+            let children = compared_children(e, options);
+            if children.len() == 1 {
+                return skip_single_child_mrow(children[0], options);
+            }
+        }
+        return e;
+    }
+
+    fn compared_children<'a>(e: Element<'a>, options: &EquivalenceOptions) -> Vec<Element<'a>> {
+        return e.children().into_iter()
+            .filter_map(|child| child.element())
+            .filter(|&child| !is_ignored(child, options))
+            .collect();
+    }
+
+    fn is_ignored(e: Element, options: &EquivalenceOptions) -> bool {
+        return match name(&e) {
+            "mo" => options.ignore_invisible_operators &&
+                    matches!(crate::canonicalize::as_text(e), "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}"),
+            "mspace" => options.ignore_whitespace,
+            "mtext" => options.ignore_whitespace && crate::canonicalize::as_text(e).trim().is_empty(),
+            _ => false,
+        };
+    }
+
+    /// `data-changed` records how canonicalization changed the input, so it is never compared
+    fn attrs_are_equivalent(e1: Element, e2: Element) -> bool {
+        use crate::canonicalize::CHANGED_ATTR;
+        let attrs1: Vec<Attribute> = e1.attributes().into_iter().filter(|attr| attr.name().local_part() != CHANGED_ATTR).collect();
+        let attrs2: Vec<Attribute> = e2.attributes().into_iter().filter(|attr| attr.name().local_part() != CHANGED_ATTR).collect();
+        return attrs1.len() == attrs2.len() &&
+               attrs1.iter().all(|attr1| attrs2.iter().any(|attr2|
+                    attr1.name().local_part() == attr2.name().local_part() && attr1.value() == attr2.value()
+               ));
+    }
+}
+
+/// Convert the Office Math (OMML) in `omml` (an `oMath` or `oMathPara` element, as found in Word documents) to MathML
+/// and set it as the MathML to use for speech, braille, and navigation (see [`set_mathml`]).
+/// The canonicalized MathML is returned.
+pub fn set_omml(omml: String) -> Result<String> {
+    let mathml = crate::omml::omml_to_mathml(&omml)?;
+    return set_mathml(mathml);
+}
+
+/// Find the subexpressions of the MathML that was set by [`set_mathml`] that structurally match `pattern_mathml`.
+/// The pattern is canonicalized the same way as the MathML that was set and attributes are not compared.
+/// A token element whose content is `?` in the pattern is a wildcard that matches any subexpression
+/// (e.g., `<math><msubsup><mo>∫</mo><mi>?</mi><mi>?</mi></msubsup></math>` matches any definite integral sign).
+/// The `id`s of the matching nodes are returned in document order (matches can be nested).
+pub fn find(pattern_mathml: String) -> Result<Vec<String>> {
+    crate::speech::SpeechRules::initialize_all_rules()?;
+    let pattern_package = parse_and_canonicalize_detached(&pattern_mathml)?;
+    let pattern = get_element(&pattern_package);
+    // the pattern is the expression inside of 'math'
+    let pattern = match pattern.children().as_slice() {
+        [child] => as_element(*child),
+        _ => pattern,
+    };
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let mut matches = Vec::new();
+        find_matches(pattern, mathml, &mut matches);
+        return Ok(matches);
     });
-    return MATHML_INSTANCE.with(|old_package| {
-        // FIX: convert this to an included file once I get the full entity list
-        static HTML_ENTITIES_MAPPING: phf::Map<&str, &str> = include!("entities.in");
-
-        let mut error_message = "".to_string();     // can't return a result inside the replace_all, so we do this hack of setting the message and then returning the error
-        // need to deal with character data and convert to something the parser knows
-        let mathml_str = HTML_ENTITIES.replace_all(&mathml_str, |cap: &Captures| {
-            match HTML_ENTITIES_MAPPING.get(&cap[1]) {
-                None => {
-                    error_message = format!("No entity named '{}'", &cap[0]);
-                    cap[0].to_string()
-                },
-                Some(&ch) => ch.to_string(),
+
+    fn find_matches(pattern: Element, mathml: Element, matches: &mut Vec<String>) {
+        if name(&mathml) != "math" && matches_pattern(pattern, mathml) {
+            if let Some(id) = mathml.attribute_value("id") {
+                matches.push(id.to_string());
             }
-        });
+        }
+        if !is_leaf(mathml) {
+            for child in mathml.children() {
+                find_matches(pattern, as_element(child), matches);
+            }
+        }
+    }
 
-        if !error_message.is_empty() {
-            bail!(error_message);
+    fn matches_pattern(pattern: Element, mathml: Element) -> bool {
+        if is_leaf(pattern) && crate::canonicalize::as_text(pattern) == "?" {
+            return true;
+        }
+        if name(&pattern) != name(&mathml) {
+            return false;
+        }
+        if is_leaf(pattern) {
+            return is_leaf(mathml) && crate::canonicalize::as_text(pattern) == crate::canonicalize::as_text(mathml);
         }
-        let mathml_str = MATHJAX_V2.replace_all(&mathml_str, "");
-        let mathml_str = MATHJAX_V3.replace_all(&mathml_str, "");
+        let pattern_children = pattern.children();
+        let children = mathml.children();
+        return pattern_children.len() == children.len() &&
+               pattern_children.iter().zip(children.iter())
+                    .all(|(pattern_child, child)| matches_pattern(as_element(*pattern_child), as_element(*child)));
+    }
+}
+
+/// Get the (canonicalized) MathML that was set by [`set_mathml`].
+/// If the `PreserveAttributes` API preference is true, attributes removed by canonicalization are kept as `data-orig-*` attributes.
+pub fn get_semantic_mathml() -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return Ok( mml_to_string(&mathml) );
+    });
+}
 
-        // the speech rules use the xpath "name" function and that includes the prefix
-        // getting rid of the prefix properly probably involves a recursive replacement in the tree
-        // if the prefix is used, it is almost certainly something like "m" or "mml", so this cheat will work.
-        let mathml_str = NAMESPACE_DECL.replace(&mathml_str, "xmlns");  // do this before the PREFIX replace!
-        let mathml_str = PREFIX.replace_all(&mathml_str, "$1");
+/// The version of the string made by [`serialize_canonical`].
+/// It should be increased if the form of the string changes.
+const CANONICAL_CACHE_VERSION: i64 = 1;
+/// The attribute on `math` in the string made by [`serialize_canonical`] that says when it can be reused
+const CANONICAL_CACHE_ATTR: &str = "data-mathcat-cache";
+/// The preferences that change the canonical MathML (besides the language's definitions)
+static CANONICAL_CACHE_PREFS: [&str; 9] = ["Language", "SubjectArea", "Chemistry", "RomanNumerals", "TextTables",
+                                           "Validation", "AnnotationFallback", "GeneratorQuirks", "PreserveAttributes"];
+
+/// Get the (canonicalized) MathML that was set by [`set_mathml`] as a compact string that can be saved by the caller
+/// (e.g., a document reader caching each expression between sessions) and later passed to [`deserialize_canonical`].
+/// The string records the MathCAT version and the preferences used to canonicalize the MathML so it is only reused when they haven't changed.
+pub fn serialize_canonical() -> Result<String> {
+    let cache_key = canonical_cache_key()?;
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        mathml.set_attribute_value(CANONICAL_CACHE_ATTR, &cache_key);
+        let mut serialized = String::with_capacity(1024);
+        write_compact_xml(mathml, &mut serialized);
+        mathml.remove_attribute(CANONICAL_CACHE_ATTR);
+        return Ok(serialized);
+    });
 
-        let new_package = parser::parse(&mathml_str);    
-        if let Err(e) = new_package {
-            bail!("Invalid MathML input:\n{}\nError is: {}", &mathml_str, &e.to_string());
+    /// Write 'element' as XML without any added whitespace and with sorted attributes (namespaces are dropped, as they are when the MathML is set)
+    fn write_compact_xml(element: Element, xml: &mut String) {
+        let element_name = name(&element);
+        xml.push('<');
+        xml.push_str(element_name);
+        let mut attrs = element.attributes();
+        attrs.sort_by_key(|attr| attr.name().local_part());     // sxd_document doesn't keep the order of the attributes
+        for attr in attrs {
+            xml.push_str(&format!(" {}='{}'", attr.name().local_part(), escape_xml(attr.value())));
         }
-        crate::speech::SpeechRules::initialize_all_rules()?;
+        xml.push('>');
+        for child in element.children() {
+            match child {
+                ChildOfElement::Element(child) => write_compact_xml(child, xml),
+                ChildOfElement::Text(text) => xml.push_str(&escape_xml(text.text())),
+                _ => (),      // comments and processing instructions
+            }
+        }
+        xml.push_str("</");
+        xml.push_str(element_name);
+        xml.push('>');
+    }
 
-        let new_package = new_package.unwrap();
-        let mathml = get_element(&new_package);
-        let mathml = cleanup_mathml(mathml)?;
-        let mathml_string = mml_to_string(&mathml);
-        old_package.replace(new_package);
+    fn escape_xml(text: &str) -> String {
+        return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('\'', "&apos;");
+    }
+}
 
-        return Ok( mathml_string );
-    })
+/// Set the MathML from a string made by [`serialize_canonical`], skipping the canonicalization done by [`set_mathml`].
+/// It is an error if the string was made by a different version of MathCAT or with different canonicalization preferences
+/// (e.g., `Language`) -- the caller should call [`set_mathml`] with the original MathML in that case.
+/// The diagnostics of canonicalization (e.g., [`get_generator_quirks`]) are not restored.
+/// This returns the canonical MathML in the same form as [`set_mathml`].
+pub fn deserialize_canonical(serialized: String) -> Result<String> {
+    crate::speech::SpeechRules::initialize_all_rules()?;
+    let new_package = match parser::parse(&serialized) {
+        Ok(package) => package,
+        Err(e) => bail!("deserialize_canonical: the string is not valid XML: {}", e),
+    };
+    let mathml = get_element(&new_package);
+    if name(&mathml) != "math" {
+        bail!("deserialize_canonical: the string does not contain 'math' (found '{}')", name(&mathml));
+    }
+    match mathml.attribute_value(CANONICAL_CACHE_ATTR) {
+        None => bail!("deserialize_canonical: the string was not made by 'serialize_canonical'"),
+        Some(cache_key) => {
+            if cache_key != canonical_cache_key()? {
+                bail!("deserialize_canonical: the string was made with a different MathCAT version or preferences ('{}')", cache_key);
+            }
+        }
+    }
+    mathml.remove_attribute(CANONICAL_CACHE_ATTR);
+    let mathml_string = mml_to_string(&mathml);
+    reset_expression_state();
+    MATHML_INSTANCE.with(|old_package| old_package.replace(new_package));
+    return Ok(mathml_string);
+}
+
+/// The value of `CANONICAL_CACHE_ATTR` for the current MathCAT version and preferences
+fn canonical_cache_key() -> Result<String> {
+    let mut cache_key = format!("{};{}", CANONICAL_CACHE_VERSION, get_version());
+    for pref_name in CANONICAL_CACHE_PREFS {
+        cache_key.push_str(&format!(";{}={}", pref_name, get_preference(pref_name.to_string())?));
+    }
+    return Ok(cache_key);
 }
 
 /// Get the spoken text of the MathML that was set.
 /// The speech takes into account any AT or user preferences.
+/// If the `math` element has a `data-speech-style` attribute (e.g., `data-speech-style="SimpleSpeak"`),
+///   that speech style is used for this expression instead of the `SpeechStyle` preference.
 pub fn get_spoken_text() -> Result<String> {
+    return with_expression_speech_style(spoken_text);
+}
+
+fn spoken_text() -> Result<String> {
     // use std::time::{Instant};
     // let instant = Instant::now();
     return MATHML_INSTANCE.with(|package_instance| {
@@ -126,7 +428,254 @@ pub fn get_spoken_text() -> Result<String> {
         debug!("Intent tree:\n{}", mml_to_string(&intent));
         let speech = crate::speech::speak_intent(intent)?;
         // info!("Time taken: {}ms", instant.elapsed().as_millis());
-        return Ok( speech );
+        return Ok( add_math_announcement(speech) );
+    });
+}
+
+/// Add the words (or earcons) for the start and end of the math if the `MathAnnouncement` preference asks for them
+/// and the speech has at least `MathAnnouncementLength` words.
+/// The words are the language's `MathAnnouncements_vec` in definitions.yaml.
+fn add_math_announcement(speech: String) -> String {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    let pref_manager = pref_manager.borrow();
+    let announcement = pref_manager.get_user_prefs().to_string("MathAnnouncement");
+    if announcement != "Words" && announcement != "Earcons" {
+        return speech;
+    }
+    let min_length = pref_manager.get_user_prefs().to_string("MathAnnouncementLength").parse::<usize>().unwrap_or(0);
+    if crate::speech::count_words(&speech) < min_length {
+        return speech;
+    }
+    let words = crate::definitions::DEFINITIONS.with(|definitions| {
+        return match definitions.borrow().get_vec("MathAnnouncements_vec") {
+            Some(words) if words.len() == 2 => Some( (words[0].clone(), words[1].clone()) ),
+            _ => None,
+        };
+    });
+    let (start, end) = match words {
+        Some(words) => words,
+        None => {
+            warn!("MathAnnouncement: 'MathAnnouncements_vec' with the two words is missing from definitions.yaml");
+            return speech;
+        },
+    };
+    let speech = speech.trim_end_matches([',', ';', ' ']);
+    if announcement == "Earcons" && pref_manager.get_tts() == crate::tts::TTS::SSML {
+        return format!("{} {} {}", crate::tts::add_earcon(start, "math-start"), speech, crate::tts::add_earcon(end, "math-end"));
+    }
+    return format!("{}, {}, {}", start, speech, end);
+}
+
+// the attribute on the 'math' element that overrides the 'SpeechStyle' preference for that expression
+const SPEECH_STYLE_ATTR: &str = "data-speech-style";
+
+/// Call 'f' with the 'SpeechStyle' preference set to the value of 'data-speech-style' on the MathML that was set (if present).
+/// The preference is restored afterwards. The rules for the other style are cached, so switching back and forth is cheap.
+/// An unknown speech style is ignored (with a warning).
+fn with_expression_speech_style<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let style = MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return mathml.attribute_value(SPEECH_STYLE_ATTR).map(|style| style.trim().to_string());
+    });
+    let style = match style {
+        None => return f(),
+        Some(style) => style,
+    };
+    let current_style = get_preference("SpeechStyle".to_string())?;
+    if style == current_style {
+        return f();
+    }
+    let language = get_preference("Language".to_string())?;
+    if !get_speech_styles(language.clone())?.contains(&style) {
+        warn!("{}='{}' is not a known speech style for language '{}' -- ignoring it", SPEECH_STYLE_ATTR, style, language);
+        return f();
+    }
+    return with_preference_snapshot(&PreferenceSnapshot::new().set("SpeechStyle", &style), f);
+}
+
+/// Preferences (name, value) that are used for a single call such as [`get_spoken_text_with_prefs`].
+/// Preferences that are not in the snapshot keep their current values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreferenceSnapshot {
+    prefs: Vec<(String, String)>,
+}
+
+impl PreferenceSnapshot {
+    pub fn new() -> Self {
+        return PreferenceSnapshot::default();
+    }
+
+    /// Snapshot the current values of the named preferences (e.g., to restore them later or to use them on another thread)
+    pub fn capture(names: &[&str]) -> Result<Self> {
+        let mut snapshot = PreferenceSnapshot::new();
+        for name in names {
+            snapshot = snapshot.set(name, &get_preference(name.to_string())?);
+        }
+        return Ok(snapshot);
+    }
+
+    /// Set `name` to `value` in the snapshot (replacing any value it already had)
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        match self.prefs.iter_mut().find(|(pref_name, _)| pref_name == name) {
+            Some(pref) => pref.1 = value.to_string(),
+            None => self.prefs.push( (name.to_string(), value.to_string()) ),
+        }
+        return self;
+    }
+
+    /// The (name, value) pairs in the order they were first set
+    pub fn prefs(&self) -> &[(String, String)] {
+        return &self.prefs;
+    }
+}
+
+/// Call `f` with a copy of the preferences that has the changes in `snapshot`.
+/// The changes are temporary: they don't count as preferences set by the caller (e.g., for [`export_preferences`] or 'MathLevel').
+/// The copy is used in place of the preferences during the call and the caller's preferences are put back afterwards,
+///   even if there is an error or a panic. Rules only need updating if the snapshot changes the files used (e.g., 'Language').
+fn with_preference_snapshot<T>(snapshot: &PreferenceSnapshot, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    if !pref_manager.borrow().get_error().is_empty() {
+        bail!("{}", pref_manager.borrow().get_error());
+    }
+    let mut prefs = pref_manager.borrow().clone();
+    // 'MathLevel' sets other prefs, so it goes first so that it doesn't overwrite values in the snapshot
+    let snapshot_prefs = snapshot.prefs.iter().filter(|(name, _)| name == "MathLevel")
+            .chain(snapshot.prefs.iter().filter(|(name, _)| name != "MathLevel"));
+    for (name, value) in snapshot_prefs {
+        change_preference(&mut prefs, name, value, true)?;
+    }
+    let _swap = PreferenceSwap::new(prefs)?;
+    return f();
+}
+
+/// Uses a copy of the preferences in place of the caller's preferences until it is dropped
+struct PreferenceSwap {
+    saved: Option<crate::prefs::PreferenceManager>,     // the caller's preferences
+}
+
+impl PreferenceSwap {
+    fn new(prefs: crate::prefs::PreferenceManager) -> Result<PreferenceSwap> {
+        let saved = std::mem::replace(&mut *crate::prefs::PreferenceManager::get().borrow_mut(), prefs);
+        let swap = PreferenceSwap { saved: Some(saved) };     // created first so that a failure below still restores the prefs
+        update_rules_for_files(swap.saved.as_ref().unwrap())?;
+        return Ok(swap);
+    }
+}
+
+impl Drop for PreferenceSwap {
+    fn drop(&mut self) {
+        if let Some(saved) = self.saved.take() {
+            let copy = std::mem::replace(&mut *crate::prefs::PreferenceManager::get().borrow_mut(), saved);
+            if let Err(e) = update_rules_for_files(&copy) {
+                error!("Restoring the preferences failed: {}", errors_to_string(&e));
+            }
+        }
+    }
+}
+
+/// Throw away the rules (and definitions) that were read from files that differ from the ones used by 'previous'.
+fn update_rules_for_files(previous: &crate::prefs::PreferenceManager) -> Result<()> {
+    return crate::speech::SPEECH_RULES.with(|rules| {
+        let mut rules = rules.borrow_mut();
+        let files_changed = rules.pref_manager.borrow().files_changed(previous);
+        if let Some(files_changed) = files_changed {
+            if files_changed.defs {
+                crate::definitions::read_definitions_file(rules.pref_manager.borrow().get_definitions_file())?;
+            }
+            crate::speech::BRAILLE_RULES.with(|braille_rules| braille_rules.borrow_mut().invalidate(files_changed.clone()));
+            rules.invalidate(files_changed);
+        }
+        return Ok( () );
+    });
+}
+
+/// Same as [`get_spoken_text`], but the preferences in `prefs` are used for this call only.
+/// The speech is computed with a copy of the preferences that has the changes, so this can be used to get different readings of the same expression
+///   (e.g., a terse reading for a tooltip and a full reading) without the callers' preference settings interfering with each other.
+/// The preferences in `prefs` are not treated as set by the caller (e.g., they are not included by [`export_preferences`]).
+pub fn get_spoken_text_with_prefs(prefs: &PreferenceSnapshot) -> Result<String> {
+    // a speech style given for this call takes precedence over the expression's 'data-speech-style'
+    if prefs.prefs.iter().any(|(name, _)| name == "SpeechStyle") {
+        return with_preference_snapshot(prefs, spoken_text);
+    }
+    return with_preference_snapshot(prefs, get_spoken_text);
+}
+
+/// Get an estimate (in milliseconds) of how long it takes to speak the MathML that was set.
+/// The estimate is for the speech returned by [`get_spoken_text`] and takes into account the `TTS`, `Rate`, and `MathRate` preferences
+/// along with the pauses in the speech.
+pub fn estimate_duration() -> Result<usize> {
+    let speech = get_spoken_text()?;
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    let pref_manager = pref_manager.borrow();
+    return Ok( pref_manager.get_tts().estimate_duration(&speech, &pref_manager) );
+}
+
+/// Get the spoken text of the MathML that was set, limited to (about) `max_words` words (e.g., for a preview bubble or a search snippet).
+/// Long speech is cut before a top-level operator (e.g., "x squared plus 2 x and more") so that the words spoken are a meaningful part of the expression.
+/// The localized "and more" marker counts against `max_words`.
+/// Along with the speech, the `id` of the node where the speech was cut is returned (`None` if all of the expression was spoken).
+pub fn get_spoken_text_truncated(max_words: usize) -> Result<(String, Option<String>)> {
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::speech::truncated_mathml(mathml, max_words);
+    }));
+}
+
+/// (Experimental) Get the earcons (non-speech sounds) that mark structure boundaries in the MathML that was set.
+/// Each entry is the `id` of the node and the name of the earcon ("open-fence", "close-fence", or "root-start") in reading order.
+/// If the `Earcons` API preference is true and `TTS` is `SSML`, the earcons are also part of the speech as `audio` elements.
+pub fn get_earcons() -> Result<Vec<(String, String)>> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return Ok( crate::tts::earcon_cues(mathml) );
+    });
+}
+
+/// Get the spoken text for the relational skeleton ("glance") of the MathML that was set.
+/// Only the top-level operators are spoken; the operands are spoken as a placeholder (e.g., "something equals something over something").
+/// During navigation, `ToggleGlance` switches between speaking the glance and the full speech.
+pub fn get_glance_text() -> Result<String> {
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::speech::glance_mathml(mathml);
+    }));
+}
+
+/// Start practice mode for the MathML that was set: the expression is revealed progressively with [`practice_reveal`].
+/// The spoken text for the structure of the whole expression is returned (operands are spoken as a placeholder).
+/// Practice mode ends when new MathML is set.
+pub fn start_practice() -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::navigate::start_practice(mathml);
+    });
+}
+
+/// Reveal the node with `id` in practice mode and make it the current navigation node.
+/// If `id` is empty, the next hidden operand in reading order is revealed so that an app can guide the student through the expression.
+/// The spoken text of the revealed node is returned (its operands remain hidden until they are revealed).
+/// An empty string is returned when everything has been revealed.
+pub fn practice_reveal(id: String) -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::navigate::practice_reveal(mathml, &id);
+    });
+}
+
+/// Get the spoken text of the whole expression in practice mode -- only the parts that have been revealed are spoken.
+pub fn get_practice_text() -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::navigate::get_practice_text(mathml);
     });
 }
 
@@ -136,13 +685,107 @@ pub fn get_spoken_text() -> Result<String> {
 pub fn get_overview_text() -> Result<String> {
     // use std::time::{Instant};
     // let instant = Instant::now();
-    return MATHML_INSTANCE.with(|package_instance| {
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
         let package_instance = package_instance.borrow();
         let mathml = get_element(&package_instance);
         let speech = crate::speech::overview_mathml(mathml)?;
         // info!("Time taken: {}ms", instant.elapsed().as_millis());
         return Ok( speech );
+    }));
+}
+
+/// Get the tool that likely produced the MathML that was set by [`set_mathml`] (based on class names, attributes, and annotations).
+/// The value is one of "MathType", "MathJax", "LaTeXML", "WIRIS", "Word", or "Unknown".
+/// When a generator is detected, only the quirks of that generator are repaired (see [`get_generator_quirks`]).
+pub fn get_generator() -> Result<String> {
+    return Ok( crate::canonicalize::detected_generator().to_string() );
+}
+
+/// Get the generator quirks that were repaired when the MathML was set by [`set_mathml`] (e.g., "whitespace-mo (WIRIS)").
+/// This is meant as a diagnostic to see why the canonical MathML differs from what was given.
+pub fn get_generator_quirks() -> Result<Vec<String>> {
+    return Ok( crate::canonicalize::generator_quirks_fired() );
+}
+
+/// Get the repairs that were made to invalid MathML when it was set by [`set_mathml`] with the `Validation` preference set to `Lenient`
+/// (e.g., "mfrac should have 2 children at /math/mrow[1]/mfrac[1]"). The location is XPath-like (the index counts siblings with the same name).
+/// An invalid element is turned into an `mrow` of its children or, if it has no element children, an `mtext` of its text.
+pub fn get_validation_repairs() -> Result<Vec<String>> {
+    return Ok( crate::canonicalize::validation_repairs() );
+}
+
+/// Get the text that was found outside of a token element when the MathML was set by [`set_mathml`] (e.g., a footnote marker "[1]"
+/// that an HTML extractor left inside `math`) along with what was done with it and where it was
+/// (e.g., "'[1]' wrapped in mtext at /math/mrow[1]").
+/// Text in `math`, `mrow`, and other elements that take any number of children is wrapped in an `mtext`; elsewhere it is removed.
+pub fn get_stray_text() -> Result<Vec<String>> {
+    return Ok( STRAY_TEXT.with(|stray_text| stray_text.borrow().clone()) );
+}
+
+/// Get the contents of each `merror` in the MathML that was set by [`set_mathml`] (typically the error messages of the software
+/// that generated the MathML, e.g., "Undefined control sequence \foo"). An empty result means the MathML has no errors.
+/// The `merror` contents are spoken after an announcement (e.g., "error in math").
+pub fn get_math_errors() -> Result<Vec<String>> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mut errors = vec![];
+        find_errors(get_element(&package_instance), &mut errors);
+        return Ok(errors);
     });
+
+    fn find_errors(mathml: Element, errors: &mut Vec<String>) {
+        if name(&mathml) == "merror" {
+            let mut words = vec![];
+            gather_words(mathml, &mut words);
+            errors.push(words.join(" "));
+            return;
+        }
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                find_errors(child, errors);
+            }
+        }
+    }
+
+    fn gather_words<'a>(mathml: Element<'a>, words: &mut Vec<&'a str>) {
+        for child in mathml.children() {
+            match child {
+                ChildOfElement::Element(child) => gather_words(child, words),
+                ChildOfElement::Text(t) if !t.text().trim().is_empty() => words.push(t.text().trim()),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Get the languages that have speech rules in the Rules directory as pairs of the language code and a human-readable name
+/// (e.g., `("en", "English")`, `("en-gb", "English (GB)")`). Regional variants follow their main language.
+/// The language code can be used as the value of the `Language` preference.
+pub fn get_supported_languages() -> Result<Vec<(String, String)>> {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    return pref_manager.borrow().get_supported_languages();
+}
+
+/// Get the speech styles (e.g., "ClearSpeak", "SimpleSpeak") that have rules for `language` (a code from [`get_supported_languages`]).
+/// The style can be used as the value of the `SpeechStyle` preference.
+pub fn get_speech_styles(language: String) -> Result<Vec<String>> {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    return pref_manager.borrow().get_speech_styles(&language);
+}
+
+/// Get the braille codes that have rules in the Rules directory as pairs of the code and a human-readable name
+/// (e.g., `("UEB", "Unified English Braille")`).
+/// The code can be used as the value of the `BrailleCode` preference.
+pub fn get_braille_codes() -> Result<Vec<(String, String)>> {
+    let pref_manager = crate::prefs::PreferenceManager::get();
+    return pref_manager.borrow().get_braille_codes();
+}
+
+/// Get the token text that was changed by Unicode (NFC) normalization when the MathML was set by [`set_mathml`]
+/// as pairs of the original and normalized text (e.g., "=" followed by U+0338 (combining long solidus) is normalized to "≠").
+/// This is meant as a diagnostic to see why the canonical MathML differs from what was given.
+pub fn get_unicode_normalizations() -> Result<Vec<(String, String)>> {
+    return Ok( crate::canonicalize::unicode_normalizations() );
 }
 
 /// Get the value of the named preference.
@@ -178,6 +821,23 @@ pub fn get_preference(name: String) -> Result<String> {
 /// * Voice -- set a voice to use (not implemented)
 /// * Gender -- set pick any voice of the given gender (not implemented)
 /// * Bookmark -- set to `true` if a `mark`/`bookmark` should be part of the returned speech (used for sync highlighting)
+/// * GrammaticalCase -- the grammatical case the surrounding sentence needs for the end of the expression (default `Nominative`).
+///   Only languages with case endings (currently Finnish) use this; see [`get_grammatical_category`]
+/// * PreserveAttributes -- set to `true` to keep attributes that canonicalization would remove as `data-orig-*` attributes
+///   (e.g., `columnalign` becomes `data-orig-columnalign`) so the MathML can be merged back into the source document
+/// * Earcons -- (experimental) set to `true` to mark structure boundaries (fences, roots) with sounds in SSML speech (see [`get_earcons`])
+/// * GeneratorQuirks -- the generators whose known MathML quirks are repaired during canonicalization [default: `All`]
+///   Either `All`, `None`, or a comma separated list of `mhchem`, `WIRIS`, and `MathType` (see [`get_generator_quirks`])
+/// * AnnotationFallback -- if the presentation in `semantics` is just an image (`mglyph`), the annotation to use in its place [default: `All`]
+///   `All` uses a MathML annotation or else a TeX annotation (as text), `MathML` uses only a MathML annotation, `None` keeps the image
+/// * TextTables -- set to `true` to turn lines of text whose columns are separated by tabs or runs of spaces (common in PDF conversions)
+///   into an `mtable` [default: false]. Layout spacing in text is always collapsed to a single space.
+/// * RomanNumerals -- set to `true` to treat tokens such as "XLVIII" or "iv" as roman numerals when the context makes it likely [default: false].
+///   Short tokens, units, and chemical elements need a nearby roman numeral to confirm them.
+///   Independent of this, a token with `data-number-kind='roman'` is always treated as a roman numeral.
+/// * Validation -- how MathML that isn't valid (e.g., an `mfrac` with one child) is handled [default: `Standard`].
+///   `Standard` makes it an error, `Strict` also makes deprecated elements (`mfenced`) and empty `mi`, `mn`, and `mo` elements errors,
+///   and `Lenient` repairs it instead (see [`get_validation_repairs`]). Errors include the location of the bad element (e.g., `/math/mrow[1]/mfrac[1]`).
 ///
 /// Important: both the preference name and value are case-sensitive
 /// 
@@ -187,6 +847,11 @@ pub fn get_preference(name: String) -> Result<String> {
 /// 
 /// FIX: Some preferences are both API and user preferences and something such as '!name' should be used for overrides. Not implemented yet.
 pub fn set_preference(name: String, value: String) -> Result<()> {
+    return set_preference_value(name, value);
+}
+
+/// Set the preference and update the rules that depend on it
+fn set_preference_value(name: String, value: String) -> Result<()> {
     return crate::speech::SPEECH_RULES.with(|rules| {
         let mut rules = rules.borrow_mut();
         if let Some(error_string) = rules.get_error() {
@@ -195,30 +860,7 @@ pub fn set_preference(name: String, value: String) -> Result<()> {
         // note: Rust complains if I set
         //    pref_manager = rules.pref_manager.borrow_mut()
         // here/upfront, so it is borrowed separately below. That way its borrowed lifetime is small
-        let files_changed;
-        {
-            use crate::prefs::NO_PREFERENCE;
-            let mut pref_manager = rules.pref_manager.borrow_mut();
-            if pref_manager.get_api_prefs().to_string(&name) != NO_PREFERENCE {
-                match name.as_str() {
-                    "Pitch" | "Rate" | "Volume" | "CapitalLetters_Pitch"=> {
-                        pref_manager.set_api_float_pref(&name, to_float(&name, &value)?);    
-                    },
-                    "Bookmark" | "CapitalLetters_UseWord" | "CapitalLetters_Beep" => {
-                        pref_manager.set_api_boolean_pref(&name, value.to_lowercase()=="true");    
-                    },
-                    _ => {
-                        pref_manager.set_api_string_pref(&name, &value);
-                    }
-                }
-                files_changed = None;
-            } else if pref_manager.get_user_prefs().to_string(name.as_str()) == NO_PREFERENCE {
-                bail!("set_preference: {} is not a known preference", &name); 
-            } else {
-                files_changed = pref_manager.set_user_prefs(&name, &value);     // assume string valued
-            }
-            pref_manager.merge_prefs();
-        }
+        let files_changed = change_preference(&mut rules.pref_manager.borrow_mut(), &name, &value, false)?;
 
         match name.as_str() {
             "SpeechStyle" => {
@@ -227,12 +869,11 @@ pub fn set_preference(name: String, value: String) -> Result<()> {
                 }
             },
             "Language" => {
-                // check the format
-                if !( value.len() == 2 ||
-                      (value.len() == 5 && value.as_bytes()[2] == b'-') ) {
-                        bail!("Improper format for 'Language' preference '{}'. Should be of form 'en' or 'en-gb'", value);
-                      }
                 if let Some(files_changed) = files_changed {
+                    if files_changed.defs {
+                        // the rules that were already built read the old language's definitions when they were created
+                        crate::definitions::read_definitions_file(rules.pref_manager.borrow().get_definitions_file())?;
+                    }
                     rules.invalidate(files_changed);
                 }
             },
@@ -248,6 +889,38 @@ pub fn set_preference(name: String, value: String) -> Result<()> {
         }
         return Ok( () );
     });
+}
+
+/// Change the preference in 'pref_manager' (without updating any rules) and return the files that changed.
+/// A 'temporary' change doesn't count as setting the pref individually.
+fn change_preference(pref_manager: &mut crate::prefs::PreferenceManager, name: &str, value: &str, temporary: bool) -> Result<Option<crate::prefs::FilesChanged>> {
+    use crate::prefs::NO_PREFERENCE;
+    if pref_manager.get_api_prefs().to_string(name) != NO_PREFERENCE {
+        match name {
+            "Pitch" | "Rate" | "Volume" | "CapitalLetters_Pitch"=> {
+                pref_manager.set_api_float_pref(name, to_float(name, value)?);    
+            },
+            "Bookmark" | "CapitalLetters_UseWord" | "CapitalLetters_Beep" | "PreserveAttributes" | "Earcons" | "TextTables" | "RomanNumerals" => {
+                pref_manager.set_api_boolean_pref(name, value.to_lowercase()=="true");    
+            },
+            _ => {
+                pref_manager.set_api_string_pref(name, value);
+            }
+        }
+        return Ok(None);
+    }
+    if pref_manager.get_user_prefs().to_string(name) == NO_PREFERENCE {
+        bail!("set_preference: {} is not a known preference", name); 
+    }
+    // check the format
+    if name == "Language" && !( value.len() == 2 || (value.len() == 5 && value.as_bytes()[2] == b'-') ) {
+        bail!("Improper format for 'Language' preference '{}'. Should be of form 'en' or 'en-gb'", value);
+    }
+    return Ok( if temporary {
+        pref_manager.set_temporary_user_pref(name, value)
+    } else {
+        pref_manager.set_user_prefs(name, value)     // assume string valued
+    } );
 
     fn to_float(name: &str, value: &str) -> Result<f64> {
         match value.parse::<f64>() {
@@ -257,6 +930,136 @@ pub fn set_preference(name: String, value: String) -> Result<()> {
     }
 }
 
+/// The version of the bundle made by [`export_preferences`].
+/// It should be increased if the meaning of a preference changes so that [`import_preferences`] can upgrade older bundles.
+const PREFERENCES_BUNDLE_VERSION: i64 = 1;
+
+/// Get the preferences that were changed (by [`set_preference`] or [`import_preferences`]) as a compact JSON string.
+/// The string can be saved by the caller and later passed to [`import_preferences`] to restore the preferences.
+/// The form is `{"version":1,"preferences":{"Language":"fi","Rate":"200"}}`; the values are strings as returned by [`get_preference`].
+/// Only preferences that differ from their initial values are included so that the preference files still apply to the others.
+pub fn export_preferences() -> Result<String> {
+    use crate::pretty_print::escape_str;
+    let names = crate::speech::SPEECH_RULES.with(|rules| rules.borrow().pref_manager.borrow().get_changed_pref_names());
+    let mut json = format!("{{\"version\":{},\"preferences\":{{", PREFERENCES_BUNDLE_VERSION);
+    for (i, name) in names.into_iter().enumerate() {
+        let value = get_preference(name.clone())?;
+        if i > 0 {
+            json.push(',');
+        }
+        escape_str(&mut json, &name).unwrap();       // writing to a String can't fail
+        json.push(':');
+        escape_str(&mut json, &value).unwrap();
+    }
+    json.push_str("}}");
+    return Ok(json);
+}
+
+/// Set the preferences in a bundle made by [`export_preferences`].
+/// Bundles made by older versions of MathCAT are accepted; a bundle from a newer version is an error.
+/// Preference names that aren't known (e.g., they were added in a newer version) are skipped and returned so they can be reported.
+/// The bundle is checked before any preference is set, but if [`set_preference`] rejects a value, the preferences before it remain set.
+pub fn import_preferences(json: String) -> Result<Vec<String>> {
+    use yaml_rust::{Yaml, YamlLoader};
+    // JSON is a subset of YAML (at least for what is in a bundle)
+    let docs = match YamlLoader::load_from_str(&json) {
+        Ok(docs) => docs,
+        Err(e) => bail!("import_preferences: the preferences are not valid JSON: {}", e),
+    };
+    let bundle = match docs.first() {
+        Some(bundle) if bundle.as_hash().is_some() => bundle,
+        _ => bail!("import_preferences: the preferences must be a JSON object"),
+    };
+    match &bundle["version"] {
+        Yaml::Integer(version) if (1..=PREFERENCES_BUNDLE_VERSION).contains(version) => (),   // FIX: upgrade old versions here when there are some
+        Yaml::Integer(version) => bail!("import_preferences: version {} is not supported (the latest version is {})", version, PREFERENCES_BUNDLE_VERSION),
+        Yaml::BadValue => bail!("import_preferences: 'version' is missing"),
+        _ => bail!("import_preferences: 'version' must be an integer"),
+    }
+    let prefs = match bundle["preferences"].as_hash() {
+        Some(prefs) => prefs,
+        None => bail!("import_preferences: 'preferences' is missing or isn't a JSON object"),
+    };
+
+    let known_prefs = crate::speech::SPEECH_RULES.with(|rules| rules.borrow().pref_manager.borrow().merge_prefs());
+    let mut new_prefs = Vec::with_capacity(prefs.len());
+    let mut unknown_names = Vec::new();
+    for (name, value) in prefs {
+        let name = match name.as_str() {
+            Some(name) => name.to_string(),
+            None => bail!("import_preferences: the preference name '{:?}' is not a string", name),
+        };
+        let value = match value {
+            Yaml::String(value) | Yaml::Real(value) => value.clone(),
+            Yaml::Integer(value) => value.to_string(),
+            Yaml::Boolean(value) => value.to_string(),
+            _ => bail!("import_preferences: the value of '{}' must be a string, number, or boolean", name),
+        };
+        if known_prefs.contains_key(&name) {
+            new_prefs.push( (name, value) );
+        } else {
+            warn!("import_preferences: skipping unknown preference '{}'", name);
+            unknown_names.push(name);
+        }
+    }
+    for (name, value) in new_prefs {
+        set_preference(name.clone(), value).chain_err(|| format!("import_preferences: while setting '{}'", name))?;
+    }
+    return Ok(unknown_names);
+}
+
+/// Override the speech for a single character (e.g., an institution specific reading of '∝').
+/// 
+/// The override is used for `language` (e.g., "en" or "fi") instead of the entry in that language's unicode files.
+/// Setting an override for the same character and language again replaces the previous value.
+/// The overrides are persistent and extend beyond calls to [`set_mathml`] -- use [`clear_character_overrides`] to remove them.
+pub fn set_character_override(ch: String, spoken_text: String, language: String) -> Result<()> {
+    let mut chars = ch.chars();
+    let character = match (chars.next(), chars.next()) {
+        (Some(character), None) => character,
+        _ => bail!("set_character_override: '{}' must be a single character", ch),
+    };
+    if language.is_empty() {
+        bail!("set_character_override: a language must be given for the override of '{}'", ch);
+    }
+    crate::speech::SPEECH_CHARACTER_OVERRIDES.with(|overrides|
+        overrides.borrow_mut().insert( (language, character), spoken_text )
+    );
+    return Ok( () );
+}
+
+/// Remove all the overrides set by [`set_character_override`].
+pub fn clear_character_overrides() {
+    crate::speech::SPEECH_CHARACTER_OVERRIDES.with(|overrides| overrides.borrow_mut().clear());
+}
+
+/// Replace `phrase` with `replacement` in the speech for `language` (e.g., "over" with "divided by" to match an organization's terminology).
+///
+/// Only whole words are replaced, so "over" doesn't change "overline". A phrase that starts or ends with a symbol (e.g., "≤")
+///   must have whitespace (or the start/end of the speech) next to that end. The replacement is done on the generated speech
+///   (including navigation speech), so a phrase can span what several rules say.
+/// Setting a mapping for the same phrase and language again replaces the previous value.
+/// The mappings are persistent and extend beyond calls to [`set_mathml`] -- use [`clear_phrase_mappings`] to remove them.
+pub fn set_phrase_mapping(phrase: String, replacement: String, language: String) -> Result<()> {
+    if phrase.trim().is_empty() {
+        bail!("set_phrase_mapping: the phrase to replace with '{}' can't be empty", replacement);
+    }
+    if language.is_empty() {
+        bail!("set_phrase_mapping: a language must be given for the mapping of '{}'", phrase);
+    }
+    crate::speech::SPEECH_PHRASE_MAPPINGS.with(|mappings| {
+        let mut mappings = mappings.borrow_mut();
+        let language_mappings = crate::speech::PhraseMappings::insert(mappings.remove(&language), &phrase, replacement);
+        mappings.insert(language, language_mappings);
+    });
+    return Ok( () );
+}
+
+/// Remove all the mappings set by [`set_phrase_mapping`].
+pub fn clear_phrase_mappings() {
+    crate::speech::SPEECH_PHRASE_MAPPINGS.with(|mappings| mappings.borrow_mut().clear());
+}
+
 /// Get the braille associated with the MathML that was set by [`set_mathml`].
 /// The braille returned depends upon the preference for the `code` preference (default `Nemeth`).
 pub fn get_braille(nav_node_id: String) -> Result<String> {
@@ -271,15 +1074,80 @@ pub fn get_braille(nav_node_id: String) -> Result<String> {
     });
 }
 
+/// Same as [`get_braille`], but the preferences in `prefs` (e.g., `BrailleCode`) are used for this call only.
+/// The braille is computed with a copy of the preferences that has the changes (see [`get_spoken_text_with_prefs`]).
+pub fn get_braille_with_prefs(prefs: &PreferenceSnapshot, nav_node_id: String) -> Result<String> {
+    return with_preference_snapshot(prefs, || get_braille(nav_node_id));
+}
+
+/// Get the places where a line can be broken in the braille returned by [`get_braille`] (with an empty `nav_node_id`).
+/// Each entry is a (0-based) char index into the braille string where a new line can start.
+/// Following the braille codes' line breaking rules, these are after comparison signs and before operation signs
+///   at the base level of the expression. Formatting software (e.g., for embossers) can use them to wrap long math.
+pub fn get_braille_break_points() -> Result<Vec<usize>> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::braille::braille_break_points(mathml);
+    });
+}
+
+/// Get the braille associated with the MathML that was set by [`set_mathml`] split into lines of at most `cells_per_line` cells.
+/// The lines are separated by '\n'. The lines are broken at the places given by [`get_braille_break_points`] when possible.
+/// As in the braille codes' formatting rules, runover lines are indented two cells and, for UEB, a line that is broken
+///   in the middle of an unspaced expression ends with the line continuation indicator (⠐).
+pub fn get_braille_formatted(cells_per_line: usize) -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return crate::braille::braille_formatted(mathml, cells_per_line);
+    });
+}
+
+/// Speaks and brailles each MathML expression in `mathml_exprs` and reports which speech/braille rules were used.
+/// The report also lists the expressions that fell back to default handling (e.g., a char not in the unicode files)
+/// and the expressions that resulted in an error. The report's `Display` output is sorted by how often a rule was used.
+///
+/// Note: this replaces the MathML that was set by [`set_mathml`].
+pub fn collect_rule_coverage<I: IntoIterator<Item=String>>(mathml_exprs: I) -> Result<crate::speech::RuleCoverage> {
+    use crate::speech::{RuleCoverage, RULE_COVERAGE};
+    RULE_COVERAGE.with(|coverage| coverage.replace(Some(RuleCoverage::default())));
+    for mathml in mathml_exprs {
+        let result = set_mathml(mathml.clone())
+                .and_then(|canonical_mathml| {
+                    get_spoken_text()?;
+                    get_braille("".to_string())?;
+                    return Ok(canonical_mathml);
+                });
+        RULE_COVERAGE.with(|coverage| {
+            let mut coverage = coverage.borrow_mut();
+            let coverage = coverage.as_mut().unwrap();
+            match result {
+                Ok(canonical_mathml) => coverage.end_expression(canonical_mathml, None),
+                Err(e) => coverage.end_expression(mathml, Some(errors_to_string(&e))),
+            }
+        });
+    }
+    return Ok( RULE_COVERAGE.with(|coverage| coverage.take()).unwrap() );
+}
+
+/// Checks the rule files in `dir` (e.g., a language directory in the Rules directory) without using them and returns the problems found.
+/// This finds problems that otherwise only show up when an expression triggers the rule: YAML and XPath syntax errors,
+/// missing included and unicode files, and rules that are duplicated or can never be used because an earlier rule always matches.
+/// An empty result means no problems were found.
+pub fn check_rules(dir: String) -> Vec<crate::speech::RuleDiagnostic> {
+    return crate::speech::check_rules(std::path::Path::new(&dir));
+}
+
 /// Given a key code along with the modifier keys, the current node is moved accordingly (or value reported in some cases).
 /// `key` is the [keycode](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/keyCode#constants_for_keycode_value) for the key (in JavaScript, `ev.key_code`)
 /// The spoken text for the new current node is returned.
 pub fn do_navigate_keypress(key: usize, shift_key: bool, control_key: bool, alt_key: bool, meta_key: bool) -> Result<String> {
-    return MATHML_INSTANCE.with(|package_instance| {
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
         let package_instance = package_instance.borrow();
         let mathml = get_element(&package_instance);
         return do_mathml_navigate_key_press(mathml, key, shift_key, control_key, alt_key, meta_key);
-    });
+    }));
 }
 
 /// Given a navigation command, the current node is moved accordingly.
@@ -291,18 +1159,25 @@ pub fn do_navigate_keypress(key: usize, shift_key: bool, control_key: bool, alt_
 /// `MoveCellPrevious`, `MoveCellNext`, `MoveCellUp`, `MoveCellDown`, `MoveColumnStart`, `MoveColumnEnd`
 /// * Moving into children or out to parents:
 /// `ZoomIn`, `ZoomOut`, `ZoomOutAll`, `ZoomInAll`
+/// * Jumping between the open and close fence of a bracketed group (what is skipped is briefly described)
+///   and moving directly out to the closest bracketed group or 2D structure (e.g., fraction) that contains the current node:
+///   `MoveMatchingFence`, `MoveOutOfContainer`
 /// * Undo the last movement command:
 /// `MoveLastLocation`
 /// * Read commands (standard speech):
 /// `ReadPrevious`, `ReadNext`, `ReadCurrent`, `ReadCellCurrent`, `ReadStart`, `ReadEnd`, `ReadLineStart`, `ReadLineEnd`
 /// * Describe commands (overview):
 /// `DescribePrevious`, `DescribeNext`, `DescribeCurrent`
+/// * Describe the special form of the matrix containing the current node (zero, identity, diagonal, upper/lower triangular, or symmetric):
+///   `DescribeMatrix`
 /// * Location information:
 /// `WhereAmI`, `WhereAmIAll`
 /// * Change navigation modes (circle up/down):
 ///  `ToggleZoomLockUp`, `ToggleZoomLockDown`
 /// * Speak the current navigation mode
 /// `ToggleSpeakMode`
+/// * Switch between speaking the relational skeleton of the expression (see [`get_glance_text`]) and the full speech
+///   `ToggleGlance`
 /// 
 /// There are 10 place markers that can be set/read/described or moved to.
 /// * Setting:
@@ -321,42 +1196,688 @@ pub fn do_navigate_command(command: String) -> Result<String> {
         bail!("Unknown command in call to DoNavigateCommand()");
     };
     let command = *command.unwrap();
-    return MATHML_INSTANCE.with(|package_instance| {
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
         let package_instance = package_instance.borrow();
         let mathml = get_element(&package_instance);
         return do_navigate_command_string(mathml, command);
+    }));
+}
+
+/// Return the MathML associated with the current (navigation) node.
+/// The returned result is the `id` of the node and the offset (0-based) from that node (not yet implemented)
+/// The offset is needed for token elements that have multiple characters.
+pub fn get_navigation_mathml() -> Result<(String, usize)> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return NAVIGATION_STATE.with(|nav_stack| {
+            return match nav_stack.borrow_mut().get_navigation_mathml(mathml) {
+                Err(e) => Err(e),
+                Ok( (found, offset) ) => Ok( (mml_to_string(&found), offset) ),
+            }
+        } )
+    });
+}
+
+/// Return the `id` and `offset` (0-based) associated with the current (navigation) node.
+/// `offset` (not yet implemented)
+/// The offset is needed for token elements that have multiple characters.
+pub fn get_navigation_mathml_id() -> Result<(String, usize)> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        return Ok( NAVIGATION_STATE.with(|nav_stack| {
+            return nav_stack.borrow().get_navigation_mathml_id(mathml);
+        }) )
+    });
+}
+
+/// The role of an [`AccessibilityNode`] -- hosts map these onto the roles of the platform accessibility API (e.g., UIA or ATK)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityRole {
+    Math,
+    /// An mrow or any other element that just groups its children (e.g., `menclose`)
+    Group,
+    Fraction,
+    /// A square root or a root with an index
+    Root,
+    /// A base with sub/superscripts or under/overscripts
+    Script,
+    Table,
+    Row,
+    Cell,
+    Number,
+    Identifier,
+    Operator,
+    Fence,
+    Text,
+}
+
+/// The location of an [`AccessibilityNode`] on the screen.
+/// MathCAT doesn't know how the math is laid out, so the host fills this in (e.g., from the rendered element with the same `id`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AccessibilityBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A node in the tree returned by [`get_accessibility_tree`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityNode {
+    /// The `id` of the node in the canonical MathML (the same ids that navigation uses)
+    pub id: String,
+    pub role: AccessibilityRole,
+    /// The (terse) spoken text for the node
+    pub name: String,
+    /// Always `None` -- a placeholder for the host
+    pub bounds: Option<AccessibilityBounds>,
+    pub children: Vec<AccessibilityNode>,
+}
+
+/// Return the navigation tree of the MathML that was set by [`set_mathml`] as a hierarchy of [`AccessibilityNode`]s.
+/// The tree is meant to be mapped into the platform accessibility API by the host so that the parts of the math
+///   are objects that can be explored (not just one string). Each node has a role, a name (its terse speech as navigation reads it,
+///   without any TTS markup), and a placeholder for its bounds. Invisible operators (e.g., invisible times) are not included.
+/// The navigation state is not changed.
+pub fn get_accessibility_tree() -> Result<AccessibilityNode> {
+    let prefs = PreferenceSnapshot::new().set("Verbosity", "Terse").set("TTS", "None");
+    return with_preference_snapshot(&prefs, || with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        if mathml.children().is_empty() {
+            bail!("MathML has not been set -- can't build the accessibility tree");
+        }
+        return accessibility_node(mathml);
+    })));
+
+    fn accessibility_node(mathml: Element) -> Result<AccessibilityNode> {
+        let role = match name(&mathml) {
+            "math" => AccessibilityRole::Math,
+            "mfrac" => AccessibilityRole::Fraction,
+            "msqrt" | "mroot" => AccessibilityRole::Root,
+            "msub" | "msup" | "msubsup" | "munder" | "mover" | "munderover" | "mmultiscripts" => AccessibilityRole::Script,
+            "mtable" => AccessibilityRole::Table,
+            "mtr" | "mlabeledtr" => AccessibilityRole::Row,
+            "mtd" => AccessibilityRole::Cell,
+            "mn" => AccessibilityRole::Number,
+            "mi" => AccessibilityRole::Identifier,
+            "mo" => if crate::canonicalize::is_fence(mathml) {AccessibilityRole::Fence} else {AccessibilityRole::Operator},
+            "mtext" | "ms" => AccessibilityRole::Text,
+            _ => AccessibilityRole::Group,
+        };
+        let mut children = vec![];
+        if !is_leaf(mathml) {
+            let element_children = mathml.children();
+            let n_children = if name(&mathml) == "semantics" {1} else {element_children.len()};     // skip annotations
+            for child in element_children.iter().take(n_children) {
+                if let Some(child) = child.element() {
+                    if !is_invisible_operator(child) && !matches!(name(&child), "none" | "mprescripts" | "mspace") {
+                        children.push( accessibility_node(child)? );
+                    }
+                }
+            }
+        }
+        return Ok( AccessibilityNode {
+            id: mathml.attribute_value("id").unwrap_or_default().to_string(),
+            role,
+            name: crate::navigate::speak_node(mathml)?,
+            bounds: None,
+            children,
+        } );
+    }
+
+    fn is_invisible_operator(mathml: Element) -> bool {
+        return name(&mathml) == "mo" && matches!(crate::canonicalize::as_text(mathml), "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}");
+    }
+}
+
+/// The category of a [`Token`] returned by [`get_token_stream`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenCategory {
+    Number,
+    Identifier,
+    /// The priority comes from the operator dictionary -- higher priorities bind more tightly
+    Operator(usize),
+    Fence,
+    Text,
+}
+
+/// A leaf of the canonical MathML tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub id: String,
+    pub text: String,
+    pub category: TokenCategory,
+}
+
+/// Return the leaves of the canonical MathML (set by [`set_mathml`]) in document order along with their category.
+/// Invisible operators added during canonicalization (e.g., invisible times) are included.
+/// Annotations in `semantics` are not included.
+/// This is meant for tools that want to analyze math (e.g., building a search index), not for speech/braille.
+pub fn get_token_stream() -> Result<Vec<Token>> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let mut tokens = Vec::new();
+        add_tokens(mathml, &mut tokens);
+        return Ok( tokens );
+    });
+
+    fn add_tokens(mathml: Element, tokens: &mut Vec<Token>) {
+        if is_leaf(mathml) {
+            let category = match name(&mathml) {
+                "mn" => TokenCategory::Number,
+                "mi" => TokenCategory::Identifier,
+                "mo" => if crate::canonicalize::is_fence(mathml) {
+                    TokenCategory::Fence
+                } else {
+                    TokenCategory::Operator( crate::canonicalize::operator_priority(mathml) )
+                },
+                "mtext" | "ms" => TokenCategory::Text,
+                _ => return,    // mspace, none, mprescripts, ...
+            };
+            tokens.push( Token {
+                id: mathml.attribute_value("id").unwrap_or_default().to_string(),
+                text: crate::canonicalize::as_text(mathml).to_string(),
+                category,
+            });
+            return;
+        }
+
+        let children = mathml.children();
+        let n_children = if name(&mathml) == "semantics" {1} else {children.len()};     // skip annotations
+        for child in children.iter().take(n_children) {
+            if let Some(child) = child.element() {
+                add_tokens(child, tokens);
+            }
+        }
+    }
+}
+
+/// Simple measures of how complicated the MathML is (see [`get_complexity`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Complexity {
+    /// The number of elements in the canonical MathML (not counting `math` or annotations)
+    pub node_count: usize,
+    /// The deepest nesting of elements below `math` (a single leaf has depth 1)
+    pub depth: usize,
+    /// The number of different identifiers (e.g., `x+x·y` has 2)
+    pub variables: usize,
+    /// The number of different visible operators (fences and invisible operators are not counted)
+    pub operators: usize,
+}
+
+/// Return measures of the complexity of the canonical MathML that was set by [`set_mathml`].
+/// This is cheap to compute and is meant for clients (e.g., tutoring systems) that want to decide things
+///   such as whether to start in an overview mode.
+pub fn get_complexity() -> Result<Complexity> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let mut complexity = Complexity { node_count: 0, depth: 0, variables: 0, operators: 0 };
+        let mut variables = HashSet::new();
+        let mut operators = HashSet::new();
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                add_complexity(child, 1, &mut complexity, &mut variables, &mut operators);
+            }
+        }
+        complexity.variables = variables.len();
+        complexity.operators = operators.len();
+        return Ok( complexity );
     });
+
+    fn add_complexity<'a>(mathml: Element<'a>, depth: usize, complexity: &mut Complexity,
+                          variables: &mut HashSet<&'a str>, operators: &mut HashSet<&'a str>) {
+        complexity.node_count += 1;
+        complexity.depth = complexity.depth.max(depth);
+        if is_leaf(mathml) {
+            let text = crate::canonicalize::as_text(mathml);
+            match name(&mathml) {
+                "mi" => {variables.insert(text);},
+                "mo" if !crate::canonicalize::is_fence(mathml) && !matches!(text, "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}") => {
+                    operators.insert(text);
+                },
+                _ => (),
+            }
+            return;
+        }
+
+        let children = mathml.children();
+        let n_children = if name(&mathml) == "semantics" {1} else {children.len()};     // skip annotations
+        for child in children.iter().take(n_children) {
+            if let Some(child) = child.element() {
+                add_complexity(child, depth+1, complexity, variables, operators);
+            }
+        }
+    }
 }
 
-/// Return the MathML associated with the current (navigation) node.
-/// The returned result is the `id` of the node and the offset (0-based) from that node (not yet implemented)
-/// The offset is needed for token elements that have multiple characters.
-pub fn get_navigation_mathml() -> Result<(String, usize)> {
-    return MATHML_INSTANCE.with(|package_instance| {
-        let package_instance = package_instance.borrow();
-        let mathml = get_element(&package_instance);
-        return NAVIGATION_STATE.with(|nav_stack| {
-            return match nav_stack.borrow_mut().get_navigation_mathml(mathml) {
-                Err(e) => Err(e),
-                Ok( (found, offset) ) => Ok( (mml_to_string(&found), offset) ),
+/// A part of the MathML returned by [`extract_operands`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand {
+    /// The `id` of the node
+    pub id: String,
+    /// The text of the node without invisible operators (e.g., "3", "x", or "2x+1")
+    pub text: String,
+}
+
+/// Return the parts of the canonical MathML that was set by [`set_mathml`] that `kind` asks for (in document order):
+/// * `Numbers` -- all the numbers (`mn`)
+/// * `Variables` -- all the identifiers except function names and units (an accented identifier such as "x̂" is one operand)
+/// * `RelationSides` -- the operands of the top level relation (e.g., both sides of an equation); empty if there isn't one
+///
+/// This is meant for tools (e.g., tutoring systems) that build on the structure of the math instead of the speech.
+pub fn extract_operands(kind: String) -> Result<Vec<Operand>> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let mut operands = Vec::new();
+        match kind.as_str() {
+            "Numbers" | "Variables" => add_operands(mathml, kind == "Numbers", &mut operands),
+            "RelationSides" => {
+                let mut top = mathml;
+                while matches!(name(&top), "math" | "semantics") || (name(&top) == "mrow" && top.children().len() == 1) {
+                    match top.children().first().and_then(|child| child.element()) {
+                        Some(child) => top = child,
+                        None => break,
+                    }
+                }
+                let children = top.children();
+                if name(&top) == "mrow" && children.len() >= 3 && children.len() % 2 == 1 &&
+                   children.iter().skip(1).step_by(2).all(|child| {
+                        let child = as_element(*child);
+                        name(&child) == "mo" && crate::canonicalize::is_relational_op(child)
+                   }) {
+                    for child in children.iter().step_by(2) {
+                        operands.push( new_operand(as_element(*child)) );
+                    }
+                }
+            },
+            _ => bail!("extract_operands: unknown kind '{}' -- should be one of 'Numbers', 'Variables', or 'RelationSides'", kind),
+        };
+        return Ok( operands );
+    });
+
+    fn add_operands(mathml: Element, is_number: bool, operands: &mut Vec<Operand>) {
+        let is_operand = match name(&mathml) {
+            "mn" => is_number,
+            "mi" => !is_number && mathml.attribute_value("class") != Some("MathML-unit") &&
+                    mathml.following_siblings().first().is_none_or(|next| {
+                        let next = as_element(*next);
+                        !is_leaf(next) || crate::canonicalize::as_text(next) != "\u{2061}"     // not a function name
+                    }),
+            "mover" => !is_number && mathml.attribute(crate::canonicalize::EMBELLISHED_IDENTIFIER_ATTR).is_some(),
+            _ => false,
+        };
+        if is_operand {
+            operands.push( new_operand(mathml) );
+            return;
+        }
+        if is_leaf(mathml) {
+            return;
+        }
+
+        let children = mathml.children();
+        let n_children = if name(&mathml) == "semantics" {1} else {children.len()};     // skip annotations
+        for child in children.iter().take(n_children) {
+            if let Some(child) = child.element() {
+                add_operands(child, is_number, operands);
+            }
+        }
+    }
+
+    fn new_operand(mathml: Element) -> Operand {
+        return Operand {
+            id: mathml.attribute_value("id").unwrap_or_default().to_string(),
+            text: gather_text(mathml),
+        };
+    }
+
+    fn gather_text(mathml: Element) -> String {
+        if is_leaf(mathml) {
+            let text = crate::canonicalize::as_text(mathml);
+            return if matches!(text, "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}") {String::new()} else {text.to_string()};
+        }
+        return mathml.children().iter()
+                .filter_map(|child| child.element())
+                .map(gather_text)
+                .collect::<String>();
+    }
+}
+
+/// Return the categories (e.g., [`SymbolCategory::Relation`] or [`SymbolCategory::Arrow`]) that the operator `symbol` is in.
+/// These are the same categories that are used for speech (`IsInCategory` in the rules), navigation, and braille.
+pub fn get_symbol_categories(symbol: String) -> Result<Vec<SymbolCategory>> {
+    crate::speech::SpeechRules::initialize_all_rules()?;     // the categories from definitions.yaml need to be read
+    let package = Package::new();
+    let doc = package.as_document();
+    let mo = crate::canonicalize::create_mathml_element(&doc, "mo");
+    mo.set_text(symbol.trim());
+    doc.root().append_child(mo);
+    return Ok( crate::symbol_category::symbol_categories(mo) );
+}
+
+/// Return the grammatical category of the end of the MathML that was set by [`set_mathml`].
+/// This is meant for hosts that embed the speech in a sentence and need it to agree grammatically (e.g., Finnish case endings).
+/// The result is one of:
+/// * `Number` -- the expression ends with a number
+/// * `Unit` -- the expression ends with a unit (e.g., the "m" in "3 m")
+/// * `Identifier` -- the expression ends with a variable or other identifier
+/// * `Other` -- the end of the expression isn't a simple leaf (e.g., `x²`) or it is an operator or text
+///
+/// The category returned is what is inflected when the `GrammaticalCase` preference is set (if the language supports it).
+pub fn get_grammatical_category() -> Result<String> {
+    return MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mut mathml = get_element(&package_instance);
+        // the inflected element must be at the top level of the expression (not, for example, in a script)
+        while !is_leaf(mathml) {
+            let children = mathml.children();
+            let last_child = match name(&mathml) {
+                "math" | "mrow" => children.last(),
+                "semantics" => children.first(),
+                _ => return Ok( "Other".to_string() ),
+            };
+            mathml = match last_child {
+                None => return Ok( "Other".to_string() ),
+                Some(child) => as_element(*child),
+            };
+        }
+
+        let category = match name(&mathml) {
+            "mn" => "Number",
+            "mi" if mathml.attribute_value("class") == Some("MathML-unit") => "Unit",
+            "mi" => "Identifier",
+            _ => "Other",
+        };
+        return Ok( category.to_string() );
+    });
+}
+
+/// A guess that was made about the meaning of part of the MathML (see [`get_ambiguities`])
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ambiguity {
+    /// The `id` of the node the guess is about
+    pub id: String,
+    /// The meaning that is used for speech/braille (e.g., "times")
+    pub chosen: String,
+    /// The other meanings that might have been intended (e.g., "function-application")
+    pub alternatives: Vec<String>,
+}
+
+const FUNCTION_MEANINGS: [&str; 2] = ["times", "function-application"];
+const VERTICAL_BAR_MEANINGS: [&str; 3] = ["absolute-value", "determinant", "cardinality"];
+const INFIX_BAR_MEANINGS: [&str; 3] = ["divides", "such-that", "given"];
+const CHEMISTRY_MEANINGS: [&str; 2] = ["chemistry", "math"];
+// records the meaning chosen by [`set_interpretation`] (it is also the hint canonicalization uses for "math")
+use crate::canonicalize::INTERPRETATION_ATTR;
+
+/// What is needed to (re)apply the choices made by [`set_interpretation`]
+struct Interpretations {
+    /// The canonical MathML before any choices were made
+    original: Option<Package>,
+    /// The (id, choice) pairs in the order they were made
+    choices: Vec<(String, String)>,
+}
+
+thread_local!{
+    /// The choices made by [`set_interpretation`] for the MathML that was set (reset by [`set_mathml`])
+    static INTERPRETATIONS: RefCell<Interpretations> = const { RefCell::new( Interpretations{ original: None, choices: Vec::new() } ) };
+}
+
+/// Return the places where heuristics guessed at the meaning of the MathML that was set by [`set_mathml`] in document order.
+/// The guesses are:
+/// * an invisible operator that might be either "times" or "function-application" (e.g., `t(x+1)`) -- the `id` is that of the invisible `mo`.
+///   How it is spoken depends on the `FunctionGuess` preference; "function-application" is the chosen meaning if that is `Function`
+/// * a pair of vertical bars that might be "absolute-value", "determinant", or "cardinality" -- the `id` is that of the `mrow`
+/// * a single vertical bar that might be "divides", "such-that", or "given" -- the `id` is that of the `mo`
+/// * an expression that might be "chemistry" or "math" -- the `id` is that of the outermost chemical formula or equation
+///
+/// The `chosen` meaning is the one the intent rules used for speech.
+///
+/// An interactive client can ask the user which was meant and call [`set_interpretation`] with the answer.
+pub fn get_ambiguities() -> Result<Vec<Ambiguity>> {
+    // the intent rules depend on the speech style
+    return with_expression_speech_style(|| MATHML_INSTANCE.with(|package_instance| {
+        let package_instance = package_instance.borrow();
+        let mathml = get_element(&package_instance);
+        let new_package = Package::new();
+        let intent = crate::speech::intent_from_mathml(mathml, new_package.as_document())?;
+        let mut intent_nodes = HashMap::new();
+        gather_intent_nodes(intent, &mut intent_nodes);
+        let mut ambiguities = Vec::new();
+        add_ambiguities(mathml, &intent_nodes, &mut ambiguities);
+        return Ok( ambiguities );
+    }));
+
+    /// Map the ids in the intent tree to their (outermost) node
+    fn gather_intent_nodes<'a>(intent: Element<'a>, intent_nodes: &mut HashMap<String, Element<'a>>) {
+        if let Some(id) = intent.attribute_value("id") {
+            intent_nodes.entry(id.to_string()).or_insert(intent);
+        }
+        for child in intent.children() {
+            if let Some(child) = child.element() {
+                gather_intent_nodes(child, intent_nodes);
+            }
+        }
+    }
+
+    fn add_ambiguities(mathml: Element, intent_nodes: &HashMap<String, Element>, ambiguities: &mut Vec<Ambiguity>) {
+        let id = mathml.attribute_value("id").unwrap_or_default();
+        if let Some(meanings) = ambiguous_meanings(mathml) {
+            if let Some(chosen) = intent_nodes.get(id).and_then(|&intent| chosen_meaning(intent, meanings)) {
+                ambiguities.push( Ambiguity {
+                    id: id.to_string(),
+                    alternatives: meanings.iter().filter(|&&meaning| meaning != chosen).map(|meaning| meaning.to_string()).collect(),
+                    chosen,
+                });
+            }
+            if meanings == CHEMISTRY_MEANINGS.as_slice() {
+                return;     // only the outermost chemistry is reported
+            }
+        }
+
+        let children = mathml.children();
+        let n_children = if name(&mathml) == "semantics" {1} else {children.len()};     // skip annotations
+        for child in children.iter().take(n_children) {
+            if let Some(child) = child.element() {
+                add_ambiguities(child, intent_nodes, ambiguities);
+            }
+        }
+    }
+}
+
+/// Choose the meaning (`choice`) of the node with `node_id` that was reported by [`get_ambiguities`] (e.g., "absolute-value" instead of "determinant").
+/// The choice is recorded as an author hint (e.g., an explicit U+2061 for "function-application" or `data-interpretation="math"` for "math"),
+///   the part of the MathML it affects is canonicalized again, and the regenerated speech is returned.
+/// Choices made for other nodes are kept until new MathML is set.
+pub fn set_interpretation(node_id: String, choice: String) -> Result<String> {
+    MATHML_INSTANCE.with(|package_instance| -> Result<()> {
+        return INTERPRETATIONS.with(|interpretations| {
+            let mut interpretations = interpretations.borrow_mut();
+            {
+                let package_instance = package_instance.borrow();
+                let mathml = get_element(&package_instance);
+                let node = match get_node_by_id(mathml, &node_id) {
+                    None => bail!("set_interpretation: no node with id '{}'", node_id),
+                    Some(node) => node,
+                };
+                match ambiguous_meanings(node) {
+                    None => bail!("set_interpretation: the node with id '{}' doesn't have alternative meanings", node_id),
+                    Some(meanings) => if !meanings.contains(&choice.as_str()) {
+                        bail!("set_interpretation: '{}' is not one of the meanings of the node with id '{}' ({})", choice, node_id, meanings.join(", "));
+                    },
+                };
+            }
+            let mut choices = interpretations.choices.clone();
+            choices.retain(|(id, _)| id != &node_id);
+            choices.push( (node_id, choice) );
+
+            // start from a copy of the original each time so that a choice can be undone (e.g., going back to "chemistry")
+            // the new MathML is built on the side so that nothing changes if a choice can't be applied
+            let new_package = Package::new();
+            let doc = new_package.as_document();
+            let mathml = match &interpretations.original {
+                Some(original) => copy_mathml(get_element(original), doc),
+                None => copy_mathml(get_element(&package_instance.borrow()), doc),
+            };
+            doc.root().append_child(mathml);
+            for (id, choice) in &choices {
+                interpret(mathml, id, choice)?;
+            }
+            let old_package = package_instance.replace(new_package);
+            if interpretations.original.is_none() {
+                interpretations.original = Some(old_package);
+            }
+            interpretations.choices = choices;
+            return Ok( () );
+        });
+    })?;
+    return get_spoken_text();
+
+    fn copy_mathml<'d>(mathml: Element, doc: Document<'d>) -> Element<'d> {
+        let copy = crate::canonicalize::create_mathml_element(&doc, name(&mathml));
+        for attr in mathml.attributes() {
+            copy.set_attribute_value(attr.name().local_part(), attr.value());
+        }
+        for child in mathml.children() {
+            match child {
+                ChildOfElement::Element(element) => copy.append_child(copy_mathml(element, doc)),
+                ChildOfElement::Text(text) => copy.append_child(doc.create_text(text.text())),
+                _ => (),
+            }
+        }
+        return copy;
+    }
+
+    /// Record 'choice' as an author hint on the node with 'id' and canonicalize the part of 'mathml' that it affects again
+    fn interpret(mathml: Element, id: &str, choice: &str) -> Result<()> {
+        let node = match get_node_by_id(mathml, id) {
+            None => bail!("set_interpretation: no node with id '{}' after the other choices were applied", id),
+            Some(node) => node,
+        };
+        node.set_attribute_value(INTERPRETATION_ATTR, choice);
+        match choice {
+            "times" | "function-application" => {
+                // an explicit invisible operator (rather than one that canonicalization added)
+                node.set_text(if choice == "times" {"\u{2062}"} else {"\u{2061}"});
+                node.remove_attribute(crate::canonicalize::CHANGED_ATTR);
+                recanonicalize(mathml, node)?;
+            },
+            "math" => recanonicalize(mathml, node)?,
+            "chemistry" => (),
+            _ if VERTICAL_BAR_MEANINGS.contains(&choice) => {
+                let contents = as_element(node.children()[1]);
+                let arg_name = match contents.attribute_value("arg") {
+                    Some(arg_name) => arg_name.to_string(),
+                    None => {
+                        contents.set_attribute_value("arg", "contents");
+                        "contents".to_string()
+                    },
+                };
+                node.set_attribute_value("intent", &format!("{}(${})", choice, arg_name));
+            },
+            _ => (),    // infix vertical bar -- the intent rules use the interpretation
+        }
+        return Ok( () );
+    }
+
+    /// Canonicalize the part of 'mathml' around 'node' again.
+    /// The mrows that are rebuilt keep their ids (and chosen meaning) if they group the same children as before.
+    fn recanonicalize(mathml: Element, node: Element) -> Result<()> {
+        let mut old_mrows = HashMap::new();
+        gather_mrows(mathml, &mut old_mrows);
+        crate::canonicalize::recanonicalize(node)?;
+        restore_mrow_ids(mathml, &old_mrows);
+        add_ids(mathml);
+        return Ok( () );
+    }
+
+    /// The ids of the first and last children of an mrow (they identify the mrow when it is rebuilt)
+    fn mrow_key(mrow: Element) -> Option<(String, String)> {
+        let children = mrow.children();
+        let id = |child: &ChildOfElement| as_element(*child).attribute_value("id").map(|id| id.to_string());
+        return Some( (id(children.first()?)?, id(children.last()?)?) );
+    }
+
+    fn gather_mrows<'a>(mathml: Element<'a>, mrows: &mut HashMap<(String, String), Element<'a>>) {
+        if crate::xpath_functions::is_leaf(mathml) {
+            return;
+        }
+        if name(&mathml) == "mrow" {
+            if let Some(key) = mrow_key(mathml) {
+                mrows.insert(key, mathml);
             }
-        } )
-    });
+        }
+        for child in mathml.children() {
+            gather_mrows(as_element(child), mrows);
+        }
+    }
+
+    fn restore_mrow_ids(mathml: Element, old_mrows: &HashMap<(String, String), Element>) {
+        if crate::xpath_functions::is_leaf(mathml) {
+            return;
+        }
+        // the key uses the children's ids, so they are restored first
+        for child in mathml.children() {
+            restore_mrow_ids(as_element(child), old_mrows);
+        }
+        if name(&mathml) == "mrow" && mathml.attribute("id").is_none() {
+            if let Some(old_mrow) = mrow_key(mathml).and_then(|key| old_mrows.get(&key)) {
+                for attr_name in ["id", "data-id-added", INTERPRETATION_ATTR] {
+                    if let Some(value) = old_mrow.attribute_value(attr_name) {
+                        mathml.set_attribute_value(attr_name, value);
+                    }
+                }
+            }
+        }
+    }
 }
 
-/// Return the `id` and `offset` (0-based) associated with the current (navigation) node.
-/// `offset` (not yet implemented)
-/// The offset is needed for token elements that have multiple characters.
-pub fn get_navigation_mathml_id() -> Result<(String, usize)> {
-    return MATHML_INSTANCE.with(|package_instance| {
-        let package_instance = package_instance.borrow();
-        let mathml = get_element(&package_instance);
-        return Ok( NAVIGATION_STATE.with(|nav_stack| {
-            return nav_stack.borrow().get_navigation_mathml_id(mathml);
-        }) )
-    });
+/// If heuristics guessed at the meaning of 'mathml', returns all the possible meanings.
+/// The meaning that was chosen is found in the intent tree (see [`chosen_meaning`]).
+fn ambiguous_meanings(mathml: Element) -> Option<&'static [&'static str]> {
+    if mathml.attribute("data-chem-formula").is_some() || mathml.attribute("data-chem-equation").is_some() ||
+       mathml.attribute_value(INTERPRETATION_ATTR) == Some("math") {
+        return Some(&CHEMISTRY_MEANINGS);
+    }
+    return match name(&mathml) {
+        "mo" if mathml.attribute("data-function-guess").is_some() => Some(&FUNCTION_MEANINGS),
+        "mo" if crate::canonicalize::as_text(mathml) == "|" && !is_vertical_bar_fence(mathml) => Some(&INFIX_BAR_MEANINGS),
+        // a matrix in bars is a determinant
+        "mrow" if is_vertical_bar_pair(mathml) && name(&as_element(mathml.children()[1])) != "mtable" => Some(&VERTICAL_BAR_MEANINGS),
+        _ => None,
+    };
+
+    fn is_vertical_bar_pair(mrow: Element) -> bool {
+        return mrow.children().len() == 3 && crate::xpath_functions::IsBracketed::is_bracketed(&mrow, "|", "|", false, true);
+    }
+
+    /// true if 'mo' is the open or close bar of a pair of vertical bars
+    fn is_vertical_bar_fence(mo: Element) -> bool {
+        let parent = mo.parent().unwrap().element().unwrap();
+        return is_vertical_bar_pair(parent) && (mo == as_element(parent.children()[0]) || mo == as_element(parent.children()[2]));
+    }
 }
 
+/// The meaning the intent rules chose for a node that has 'meanings' ('intent' is the intent tree node with the node's id).
+/// Vertical bar pairs become the intent element of that name, chemistry keeps its `data-chem-*` attributes,
+///   and the intent rules record the meaning of other guesses in `data-meaning`.
+/// Returns `None` if the intent (e.g., one given by the author) isn't one of 'meanings'.
+fn chosen_meaning(intent: Element, meanings: &[&str]) -> Option<String> {
+    let chosen = if meanings == CHEMISTRY_MEANINGS.as_slice() {
+        if intent.attribute("data-chem-formula").is_some() || intent.attribute("data-chem-equation").is_some() {"chemistry"} else {"math"}
+    } else if meanings == VERTICAL_BAR_MEANINGS.as_slice() {
+        name(&intent)
+    } else {
+        intent.attribute_value("data-meaning")?
+    };
+    // a bar that isn't spoken with any of its meanings is still ambiguous (e.g., "vertical line")
+    return if meanings.contains(&chosen) || meanings == INFIX_BAR_MEANINGS.as_slice() {Some(chosen.to_string())} else {None};
+}
 
 /// Convert the returned error from set_mathml, etc., to a useful string for display
 pub fn errors_to_string(e:&Error) -> String {
@@ -442,6 +1963,7 @@ pub fn trim_element(e: &Element) {
         return;
     }
 
+    clean_stray_text(*e);
     let mut single_text = "".to_string();
     for child in e.children() {
         match child {
@@ -461,18 +1983,47 @@ pub fn trim_element(e: &Element) {
     // hack to avoid non-breaking whitespace from being removed -- move to a unique non-whitespace char then back
     let trimmed_text = single_text.replace(' ', TEMP_NBSP).trim().replace(TEMP_NBSP, " ");
     if !(is_leaf(*e) || name(e) == "intent-literal" || single_text.is_empty()) {  // intent-literal comes from testing intent
-        // FIX: we have a problem -- what should happen???
-        // FIX: For now, just keep the children and ignore the text and log an error -- shouldn't panic/crash
-        if !trimmed_text.is_empty() {
-            error!("trim_element: both element and textual children which shouldn't happen -- ignoring text '{}'", single_text);
-        }
-        return;
+        return;     // whitespace between the element children (other text was handled by 'clean_stray_text')
     }
     if e.children().is_empty() && !single_text.is_empty() {
         // debug!("Combining text in {}: '{}' -> '{}'", e.name().local_part(), single_text, trimmed_text);
         e.set_text(&trimmed_text);
     }
 
+    /// Text next to element children (or any text directly in 'math') can't be kept as is.
+    /// In elements that take any number of children, the text is wrapped in an 'mtext' (in place); elsewhere it is removed.
+    /// Whitespace is left for the caller to remove. What was done is recorded for [`get_stray_text`].
+    fn clean_stray_text(mathml: Element) {
+        let children = mathml.children();
+        let has_element_child = children.iter().any(|child| child.element().is_some());
+        let is_stray = |child: &ChildOfElement| matches!(child, ChildOfElement::Text(t) if !t.text().trim().is_empty());
+        if !children.iter().any(is_stray) || !(has_element_child || name(&mathml) == "math") {
+            return;
+        }
+
+        let is_wrapped = matches!(name(&mathml),
+                "math" | "mrow" | "mstyle" | "mpadded" | "mphantom" | "menclose" | "msqrt" | "merror" | "mtd");
+        let location = crate::canonicalize::xpath_location(mathml);
+        let mut new_children = Vec::with_capacity(children.len());
+        for child in children {
+            if let ChildOfElement::Text(t) = child {
+                let text = t.text().trim();
+                if !text.is_empty() {
+                    let action = if is_wrapped {"wrapped in mtext"} else {"removed"};
+                    STRAY_TEXT.with(|stray_text| stray_text.borrow_mut().push(format!("'{}' {} at {}", text, action, location)));
+                    if is_wrapped {
+                        let mtext = crate::canonicalize::create_mathml_element(&mathml.document(), "mtext");
+                        mtext.set_text(text);
+                        new_children.push(ChildOfElement::Element(mtext));
+                    }
+                    continue;
+                }
+            }
+            new_children.push(child);
+        }
+        mathml.replace_children(new_children);
+    }
+
     fn make_leaf_element(mathml_leaf: Element) {
         // MathML leaves like <mn> really shouldn't have non-textual content, but you could have embedded HTML
         // Here, we take convert them to leaves by grabbing up all the text and making that the content
@@ -775,6 +2326,977 @@ mod tests {
         assert_eq!(entity_str, converted_str);
     }
 
+    #[test]
+    fn token_stream() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mn>2</mn><mi>x</mi><mo>+</mo><mo>(</mo><mi>y</mi><mo>=</mo><mn>1</mn><mo>)</mo></math>".to_string()).unwrap();
+        let tokens = get_token_stream().unwrap();
+        let tokens: Vec<(&str, &TokenCategory)> = tokens.iter().map(|token| (token.text.as_str(), &token.category)).collect();
+        assert_eq!(tokens.len(), 9);
+        assert_eq!(tokens[0], ("2", &TokenCategory::Number));
+        assert!(matches!(tokens[1], ("\u{2062}", TokenCategory::Operator(_))));
+        assert_eq!(tokens[2], ("x", &TokenCategory::Identifier));
+        assert_eq!(tokens[4], ("(", &TokenCategory::Fence));
+        assert_eq!(tokens[8], (")", &TokenCategory::Fence));
+        if let (TokenCategory::Operator(times), TokenCategory::Operator(plus), TokenCategory::Operator(equals)) =
+                    (tokens[1].1, tokens[3].1, tokens[6].1) {
+            assert!(times > plus && plus > equals);
+        } else {
+            panic!("operators not found in token stream {:?}", tokens);
+        }
+    }
+
+    #[test]
+    fn accessibility_tree() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        set_mathml("<math><mn>2</mn><mi>x</mi><mo>=</mo><mfrac><mn>1</mn><mi>y</mi></mfrac></math>".to_string()).unwrap();
+        let tree = get_accessibility_tree().unwrap();
+        assert_eq!(tree.role, AccessibilityRole::Math);
+        assert!(tree.bounds.is_none());
+        let mrow = &tree.children[0];
+        assert_eq!(mrow.role, AccessibilityRole::Group);
+        assert_eq!(mrow.children.iter().map(|child| child.role).collect::<Vec<AccessibilityRole>>(),
+                   vec![AccessibilityRole::Group, AccessibilityRole::Operator, AccessibilityRole::Fraction]);
+        // invisible times is not part of the tree
+        let times = &mrow.children[0];
+        assert_eq!(times.children.iter().map(|child| child.name.as_str()).collect::<Vec<&str>>(), vec!["2", "x"]);
+        let fraction = &mrow.children[2];
+        assert_eq!(fraction.name, "1 over y");
+        assert_eq!(fraction.children[1].role, AccessibilityRole::Identifier);
+        assert!(!fraction.id.is_empty());
+        // no TTS markup in the names and the preferences are restored
+        assert!(!tree.name.contains('<'));
+        assert_eq!(get_preference("TTS".to_string()).unwrap(), "SSML");
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+    }
+
+    #[test]
+    fn complexity() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mi>x</mi><mi>y</mi><mo>=</mo><msup><mi>y</mi><mn>2</mn></msup></math>".to_string()).unwrap();
+        // canonical: mrow(mrow(x + mrow(x ⁢ y)) = msup(y 2))
+        assert_eq!(get_complexity().unwrap(), Complexity { node_count: 12, depth: 4, variables: 2, operators: 2 });
+
+        set_mathml("<math><mn>3</mn></math>".to_string()).unwrap();
+        assert_eq!(get_complexity().unwrap(), Complexity { node_count: 1, depth: 1, variables: 0, operators: 0 });
+    }
+
+    #[test]
+    fn operands() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mn>2</mn><mi>x</mi><mo>+</mo><mi>sin</mi><mi>y</mi><mo>=</mo><mover><mi>z</mi><mo>^</mo></mover><mo>-</mo><mn>1.5</mn></math>".to_string()).unwrap();
+        let texts = |kind: &str| extract_operands(kind.to_string()).unwrap().into_iter().map(|operand| operand.text).collect::<Vec<String>>();
+        assert_eq!(texts("Numbers"), vec!["2", "1.5"]);
+        assert_eq!(texts("Variables"), vec!["x", "y", "z^"]);
+        assert_eq!(texts("RelationSides"), vec!["2x+siny", "z^-1.5"]);
+        assert!(extract_operands("RelationSides".to_string()).unwrap().iter().all(|operand| !operand.id.is_empty()));
+        assert!(extract_operands("Operators".to_string()).is_err());
+
+        set_mathml("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string()).unwrap();
+        assert!(texts("RelationSides").is_empty());
+    }
+
+    #[test]
+    fn stray_text() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let mathml = set_mathml("<math><mi>x</mi> [1] <mfrac>a<mn>1</mn><mn>2</mn></mfrac></math>".to_string()).unwrap();
+        assert!(mathml.contains("<mtext") && mathml.contains(">[1]</mtext>"), "{}", mathml);
+        assert!(!mathml.contains(">a<"), "{}", mathml);
+        assert_eq!(get_stray_text().unwrap(), vec![
+            "'[1]' wrapped in mtext at /math".to_string(),
+            "'a' removed at /math/mfrac[1]".to_string(),
+        ]);
+        // comparing MathML doesn't change what was found in the MathML that was set
+        assert!(is_equivalent("<math><mi>y</mi> [2] </math>".to_string(), "<math><mi>y</mi></math>".to_string(), EquivalenceOptions::default()).is_ok());
+        assert_eq!(get_stray_text().unwrap().len(), 2);
+
+        set_mathml("<math><mi>x</mi></math>".to_string()).unwrap();
+        assert!(get_stray_text().unwrap().is_empty());
+    }
+
+    #[test]
+    fn symbol_categories() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        assert_eq!(get_symbol_categories("=".to_string()).unwrap(), vec![SymbolCategory::Relation]);
+        assert_eq!(get_symbol_categories("→".to_string()).unwrap(), vec![SymbolCategory::Arrow]);
+        assert_eq!(get_symbol_categories("∪".to_string()).unwrap(), vec![SymbolCategory::SetOperator]);
+        assert_eq!(get_symbol_categories("∑".to_string()).unwrap(), vec![SymbolCategory::LargeOperator]);
+        assert_eq!(get_symbol_categories("(".to_string()).unwrap(), vec![SymbolCategory::Fence]);
+        assert!(get_symbol_categories("+".to_string()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ambiguities() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo><mo>=</mo><mo>|</mo><mi>A</mi><mo>|</mo></math>".to_string()).unwrap();
+        let ambiguities: Vec<(String, Vec<String>)> = get_ambiguities().unwrap().into_iter()
+                .map(|ambiguity| (ambiguity.chosen, ambiguity.alternatives))
+                .collect();
+        assert_eq!(ambiguities, vec![
+            ("times".to_string(), vec!["function-application".to_string()]),
+            ("determinant".to_string(), vec!["absolute-value".to_string(), "cardinality".to_string()]),
+        ]);
+        set_mathml("<math subject='SetTheory'><mo>|</mo><mi>A</mi><mo>|</mo></math>".to_string()).unwrap();
+        assert_eq!(get_ambiguities().unwrap()[0].chosen, "cardinality");     // the meaning comes from the intent rules
+
+        set_mathml("<math><mo>{</mo><mi>x</mi><mo>|</mo><mi>x</mi><mo>&gt;</mo><mn>0</mn><mo>}</mo></math>".to_string()).unwrap();
+        let ambiguities = get_ambiguities().unwrap();
+        assert_eq!(ambiguities.len(), 1);
+        assert_eq!(ambiguities[0].chosen, "such-that");
+        set_preference("ClearSpeak_VerticalLine".to_string(), "Given".to_string()).unwrap();
+        assert_eq!(get_ambiguities().unwrap()[0].chosen, "such-that");     // the set rules say "such that" whatever the pref is
+        set_mathml("<math><mi>a</mi><mo>|</mo><mi>b</mi></math>".to_string()).unwrap();
+        assert_eq!(get_ambiguities().unwrap()[0].chosen, "given");
+
+        set_mathml("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string()).unwrap();
+        assert!(get_ambiguities().unwrap().is_empty());
+
+        set_preference("FunctionGuess".to_string(), "Function".to_string()).unwrap();
+        set_mathml("<math><mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo></math>".to_string()).unwrap();
+        let ambiguities = get_ambiguities().unwrap();
+        assert_eq!(ambiguities[0].chosen, "function-application");
+        assert_eq!(ambiguities[0].alternatives, vec!["times".to_string()]);
+    }
+
+    #[test]
+    fn interpretation() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mn>2</mn><mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo><mo>=</mo><mo>|</mo><mi>A</mi><mo>|</mo></math>".to_string()).unwrap();
+        let ambiguities = get_ambiguities().unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "2 t, open paren x plus 1, close paren; is equal to determinant of  cap eigh");
+        assert_eq!(set_interpretation(ambiguities[0].id.clone(), "function-application".to_string()).unwrap(),
+                   "2, t of, open paren x plus 1, close paren; is equal to determinant of  cap eigh");
+        assert_eq!(set_interpretation(ambiguities[1].id.clone(), "cardinality".to_string()).unwrap(),
+                   "2, t of, open paren x plus 1, close paren; is equal to, the cardinality of  cap eigh,");
+        let chosen: Vec<String> = get_ambiguities().unwrap().into_iter().map(|ambiguity| ambiguity.chosen).collect();
+        assert_eq!(chosen, vec!["function-application", "cardinality"]);
+        assert!(set_interpretation(ambiguities[1].id.clone(), "times".to_string()).is_err());
+        assert!(set_interpretation("no-such-id".to_string(), "times".to_string()).is_err());
+        // a rejected choice doesn't change the MathML or the choices
+        assert_eq!(get_spoken_text().unwrap(), "2, t of, open paren x plus 1, close paren; is equal to, the cardinality of  cap eigh,");
+        INTERPRETATIONS.with(|interpretations| assert_eq!(interpretations.borrow().choices.len(), 2));
+        // the function call is grouped by canonicalization (as if the author had used U+2061)
+        MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let apply = get_node_by_id(get_element(&package_instance), &ambiguities[0].id).unwrap();
+            assert_eq!(crate::canonicalize::as_text(apply), "\u{2061}");
+            assert_eq!(apply.parent().unwrap().element().unwrap().children().len(), 3);   // t ⁡ (x+1)
+        });
+
+        // the ids stay the same when other choices are made, so the choices can be made in any order
+        set_mathml("<math><mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo><mi>s</mi><mo>(</mo><mi>y</mi><mo>+</mo><mn>1</mn><mo>)</mo></math>".to_string()).unwrap();
+        let ids: Vec<String> = get_ambiguities().unwrap().into_iter().map(|ambiguity| ambiguity.id).collect();
+        assert_eq!(set_interpretation(ids[1].clone(), "function-application".to_string()).unwrap(),
+                   "t, open paren x plus 1, close paren; times, s of, open paren y plus 1, close paren");
+        assert_eq!(set_interpretation(ids[0].clone(), "function-application".to_string()).unwrap(),
+                   "t of, open paren x plus 1, close paren; s of, open paren y plus 1, close paren");
+        assert_eq!(set_interpretation(ids[1].clone(), "times".to_string()).unwrap(),
+                   "t of, open paren x plus 1, close paren; s times, open paren y plus 1, close paren");
+        let ambiguities: Vec<(String, String)> = get_ambiguities().unwrap().into_iter().map(|ambiguity| (ambiguity.id, ambiguity.chosen)).collect();
+        assert_eq!(ambiguities, vec![(ids[0].clone(), "function-application".to_string()), (ids[1].clone(), "times".to_string())]);
+
+        // choices can be undone
+        set_mathml("<math><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>".to_string()).unwrap();
+        let id = get_ambiguities().unwrap()[0].id.clone();
+        let chemistry_speech = get_spoken_text().unwrap();
+        assert_eq!(set_interpretation(id.clone(), "math".to_string()).unwrap(), "cap h sub 2  cap o");
+        assert_eq!(get_ambiguities().unwrap()[0].chosen, "math");
+        assert_eq!(set_interpretation(id, "chemistry".to_string()).unwrap(), chemistry_speech);
+    }
+
+    #[test]
+    fn grammatical_category() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>=</mo><mn>3</mn></math>".to_string()).unwrap();
+        assert_eq!(get_grammatical_category().unwrap(), "Number");
+        set_mathml("<math><mn>3</mn><mi class='MathML-unit'>m</mi></math>".to_string()).unwrap();
+        assert_eq!(get_grammatical_category().unwrap(), "Unit");
+        set_mathml("<math><mn>3</mn><mo>+</mo><mi>y</mi></math>".to_string()).unwrap();
+        assert_eq!(get_grammatical_category().unwrap(), "Identifier");
+        set_mathml("<math><mi>y</mi><mo>=</mo><msup><mi>x</mi><mn>2</mn></msup></math>".to_string()).unwrap();
+        assert_eq!(get_grammatical_category().unwrap(), "Other");
+    }
+
+    #[test]
+    fn grammatical_case_finnish() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>=</mo><mn>3</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x on yhtä suuri kuin 3");
+        set_preference("GrammaticalCase".to_string(), "Genitive".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x on yhtä suuri kuin 3:n");
+        set_preference("GrammaticalCase".to_string(), "Partitive".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x on yhtä suuri kuin 3:a");
+        set_mathml("<math><mn>20</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "20:tä");
+        set_preference("GrammaticalCase".to_string(), "Illative".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "20:een");
+        // only the end of the expression is inflected
+        set_mathml("<math><msup><mi>x</mi><mn>2</mn></msup></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x neliöön");
+    }
+
+    #[test]
+    fn accented_letters_not_split() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        for letter in ["š", "ž", "ñ", "ê", "ă", "ë"] {
+            let canonical = set_mathml(format!("<math><mi>{letter}</mi></math>")).unwrap();
+            assert!(!canonical.contains("mover"), "'{letter}' was split:\n{canonical}");
+        }
+        // an accent written by the author as a combining char is split unless the letter is in the language's alphabet
+        set_mathml("<math><mi>e&#x302;</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "e hat,");
+        set_preference("Language".to_string(), "vi".to_string()).unwrap();
+        set_mathml("<math><mi>e&#x302;</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "ê");
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>a&#x308;</mi><mo>+</mo><mi>s&#x30C;</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "ä plus š");
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn ranges_finnish() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mtext>sivut</mtext><mspace width='0.2em'/><mn>12</mn><mo>–</mo><mn>15</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "sivut 12:sta 15:een");
+        set_mathml("<math><mn>5</mn><mo>–</mo><mn>10</mn><mi>km</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "5:stä 10:een kilometriä");
+        set_mathml("<math><mo>$</mo><mn>3</mn><mo>–</mo><mo>$</mo><mn>5</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "dollari 3 väliviiva dollari 5");
+        set_preference("RangeDash".to_string(), "Minus".to_string()).unwrap();
+        set_mathml("<math><mn>12</mn><mo>–</mo><mn>15</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "12 miinus 15");
+    }
+
+    #[test]
+    fn character_overrides() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>y</mi><mo>∝</mo><mi>x</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "y is proportional to x");
+        set_character_override("∝".to_string(), "varies as".to_string(), "en".to_string()).unwrap();
+        set_character_override("∝".to_string(), "on verrannollinen".to_string(), "fi".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "y varies as x");
+        clear_character_overrides();
+        assert_eq!(get_spoken_text().unwrap(), "y is proportional to x");
+        assert!(set_character_override("ab".to_string(), "x".to_string(), "en".to_string()).is_err());
+        assert!(set_character_override("∝".to_string(), "x".to_string(), "".to_string()).is_err());
+    }
+
+    #[test]
+    fn phrase_mappings() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_mathml("<math><mfrac><mi>x</mi><mi>y</mi></mfrac><mo>=</mo><mover><mi>z</mi><mo>¯</mo></mover></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x over y, is equal to z bar,");
+        set_phrase_mapping("over".to_string(), "divided by".to_string(), "en".to_string()).unwrap();
+        set_phrase_mapping("is equal to".to_string(), "equals".to_string(), "en".to_string()).unwrap();
+        set_phrase_mapping("bar".to_string(), "viiva".to_string(), "fi".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x divided by y, equals z bar,");
+        clear_phrase_mappings();
+        assert_eq!(get_spoken_text().unwrap(), "x over y, is equal to z bar,");
+        // phrases that start or end with a symbol
+        set_phrase_mapping("bar,".to_string(), "with a bar".to_string(), "en".to_string()).unwrap();
+        set_phrase_mapping("≤".to_string(), "at most".to_string(), "en".to_string()).unwrap();
+        set_phrase_mapping("x+".to_string(), "x plus".to_string(), "en".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x over y, is equal to z with a bar");
+        assert_eq!(crate::speech::map_phrases("a ≤ b, x+ x+ 1".to_string(), "en"), "a at most b, x plus x plus 1");
+        assert_eq!(crate::speech::map_phrases("a≤b, xx+".to_string(), "en"), "a≤b, xx+");
+        clear_phrase_mappings();
+        assert!(set_phrase_mapping(" ".to_string(), "x".to_string(), "en".to_string()).is_err());
+        assert!(set_phrase_mapping("over".to_string(), "x".to_string(), "".to_string()).is_err());
+    }
+
+    #[test]
+    fn script_order() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><msub><mi>x</mi><mn>1</mn></msub></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x ala 1");
+        set_preference("ScriptOrder_Subscript".to_string(), "ScriptFirst".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "ala 1 x");
+        set_preference("ScriptOrder_Subscript".to_string(), "BaseFirst".to_string()).unwrap();
+        set_mathml("<math><msup><mi>x</mi><mi>n</mi></msup></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x potenssiin n-th");
+        set_preference("ScriptOrder_Superscript".to_string(), "ScriptFirst".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "potenssiin n-th x");
+        set_mathml("<math><msubsup><mi>x</mi><mn>1</mn><mn>5</mn></msubsup></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "to the viides power x ala 1");
+        set_preference("ScriptOrder_Superscript".to_string(), "BaseFirst".to_string()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn finnish_root_degree() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mroot><mi>x</mi><mn>4</mn></mroot></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "neljäs juuri x,");
+        set_mathml("<math><mroot><mi>x</mi><mn>121</mn></mroot></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "sadas kahdeskymmenes ensimmäinen juuri x,");
+        set_mathml("<math><mroot><mi>x</mi><mi>n</mi></mroot></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "n:s juuri x,");
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn preserve_attributes() {
+        let mathml = "<math><mrow><mpadded width='2em'><mi>x</mi></mpadded><mo>+</mo><mi>y</mi></mrow></math>";
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml.to_string()).unwrap();
+        assert!(!get_semantic_mathml().unwrap().contains("width"));
+        set_preference("PreserveAttributes".to_string(), "true".to_string()).unwrap();
+        set_mathml(mathml.to_string()).unwrap();
+        assert!(get_semantic_mathml().unwrap().contains("data-orig-width='2em'"));
+        set_preference("PreserveAttributes".to_string(), "false".to_string()).unwrap();
+    }
+
+    #[test]
+    fn earcons() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_mathml("<math><mo id='a'>(</mo><mi>x</mi><mo>+</mo><msqrt id='b'><mi>y</mi></msqrt><mo id='c'>)</mo></math>".to_string()).unwrap();
+        assert_eq!(get_earcons().unwrap(), vec![
+            ("a".to_string(), "open-fence".to_string()),
+            ("b".to_string(), "root-start".to_string()),
+            ("c".to_string(), "close-fence".to_string()),
+        ]);
+        assert!(!get_spoken_text().unwrap().contains("<audio"));
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        set_preference("Earcons".to_string(), "true".to_string()).unwrap();
+        let speech = get_spoken_text().unwrap();
+        assert!(speech.contains("<audio src='open-fence.mp4'>"));
+        assert!(speech.contains("<audio src='root-start.mp4'/>"));
+        set_preference("Earcons".to_string(), "false".to_string()).unwrap();
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+    }
+
+    #[test]
+    fn math_announcement() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x plus 1");
+        set_preference("MathAnnouncement".to_string(), "Words".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "math, x plus 1, end math");
+        set_preference("MathAnnouncementLength".to_string(), "4".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x plus 1");
+        set_preference("MathAnnouncementLength".to_string(), "3".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "math, x plus 1, end math");
+
+        set_preference("MathAnnouncement".to_string(), "Earcons".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "math, x plus 1, end math");     // no earcons without SSML
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        let speech = get_spoken_text().unwrap();
+        assert!(speech.starts_with("<audio src='math-start.mp4'>math</audio>"));
+        assert!(speech.ends_with("<audio src='math-end.mp4'>end math</audio>"));
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "matematiikka, x plus 1, matematiikka loppu");
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("MathAnnouncement".to_string(), "None".to_string()).unwrap();
+        set_preference("MathAnnouncementLength".to_string(), "0".to_string()).unwrap();
+    }
+
+    #[test]
+    fn quick_clean_preview() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        let mathml = "<math><mn>2</mn><mi>x</mi><mo>+</mo><msup><mi>y</mi><mn>2</mn></msup></math>";
+        quick_clean(mathml.to_string()).unwrap();
+        let quick_speech = get_spoken_text().unwrap();
+        assert_eq!(quick_speech, "2 x plus y squared");
+        set_mathml(mathml.to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), quick_speech);
+        // the chemistry scan is skipped
+        let mathml = "<math><msub><mi>H</mi><mn>2</mn></msub><mi mathvariant='normal'>O</mi></math>";
+        assert!(!quick_clean(mathml.to_string()).unwrap().contains("data-chem"));
+        assert!(set_mathml(mathml.to_string()).unwrap().contains("data-chem"));
+    }
+
+    #[test]
+    fn spoken_text_with_prefs() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_preference("Verbosity".to_string(), "Medium".to_string()).unwrap();
+        set_mathml("<math><msqrt><mi>x</mi></msqrt><mo>+</mo><mfrac><mi>a</mi><mi>b</mi></mfrac></math>".to_string()).unwrap();
+        let medium = get_spoken_text().unwrap();
+        let terse = PreferenceSnapshot::new().set("Verbosity", "Terse");
+        let verbose = PreferenceSnapshot::new().set("Verbosity", "Verbose").set("SpeechStyle", "SimpleSpeak");
+        assert_eq!(get_spoken_text_with_prefs(&terse).unwrap(), "square root, x; plus eigh over b");
+        assert_eq!(get_spoken_text_with_prefs(&verbose).unwrap(), "the square root of x; plus eigh over b,");
+        // the global preferences are unchanged
+        assert_eq!(get_preference("Verbosity".to_string()).unwrap(), "Medium");
+        assert_eq!(get_preference("SpeechStyle".to_string()).unwrap(), "ClearSpeak");
+        assert_eq!(get_spoken_text().unwrap(), medium);
+        assert_eq!(PreferenceSnapshot::capture(&["Verbosity"]).unwrap(), PreferenceSnapshot::new().set("Verbosity", "Medium"));
+        // a bad preference is an error, but the ones set before it are still restored
+        let bad = PreferenceSnapshot::new().set("Verbosity", "Terse").set("NotAPref", "x");
+        assert!(get_spoken_text_with_prefs(&bad).is_err());
+        assert_eq!(get_preference("Verbosity".to_string()).unwrap(), "Medium");
+        // the prefs in a snapshot don't count as set by the caller
+        let exported = export_preferences().unwrap();
+        let level = PreferenceSnapshot::new().set("ClearSpeak_Fractions", "Over").set("MathLevel", "University").set("Rate", "150");
+        get_spoken_text_with_prefs(&level).unwrap();
+        assert_eq!(export_preferences().unwrap(), exported);
+        assert_eq!(get_preference("MathLevel".to_string()).unwrap(), "Auto");
+        set_preference("BrailleCode".to_string(), "Nemeth".to_string()).unwrap();
+        let ueb = PreferenceSnapshot::new().set("BrailleCode", "UEB");
+        assert_ne!(get_braille_with_prefs(&ueb, "".to_string()).unwrap(), get_braille("".to_string()).unwrap());
+        assert_eq!(get_preference("BrailleCode".to_string()).unwrap(), "Nemeth");
+        // the caller's preferences are put back even if there is a panic
+        let finnish = PreferenceSnapshot::new().set("Language", "fi").set("Verbosity", "Terse");
+        let result = std::panic::catch_unwind(|| with_preference_snapshot(&finnish, || -> Result<()> {
+            assert_eq!(get_preference("Language".to_string()).unwrap(), "fi");
+            panic!("panic while the snapshot is used");
+        }));
+        assert!(result.is_err());
+        assert_eq!(get_preference("Language".to_string()).unwrap(), "en");
+        assert_eq!(get_preference("Verbosity".to_string()).unwrap(), "Medium");
+        assert_eq!(get_spoken_text().unwrap(), medium);
+    }
+
+    #[test]
+    fn text_voice() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>&gt;</mo><mn>0</mn><mtext>for all</mtext><mi>x</mi><mo>∈</mo><mi>S</mi></math>".to_string()).unwrap();
+        set_preference("TextVoice".to_string(), "Emphasis".to_string()).unwrap();
+        // no markup, so the text is spoken the same as the math
+        assert!(get_spoken_text().unwrap().contains("; for all;"));
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        let speech = get_spoken_text().unwrap();
+        assert!(speech.contains("<emphasis level='moderate'>"), "{}", speech);
+        assert_eq!(speech.matches("<emphasis").count(), 1);
+        set_preference("TextVoice".to_string(), "Zira".to_string()).unwrap();
+        assert!(get_spoken_text().unwrap().contains("<voice name='Zira'>"));
+        set_preference("TTS".to_string(), "SAPI5".to_string()).unwrap();
+        assert!(get_spoken_text().unwrap().contains("<voice required=\"Name=Zira\">"));
+        set_preference("TextVoice".to_string(), "None".to_string()).unwrap();
+        assert!(!get_spoken_text().unwrap().contains("<voice"));
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+    }
+
+    #[test]
+    fn braille_break_points() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("BrailleCode".to_string(), "Nemeth".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mn>2</mn></mfrac>
+                        <mo>=</mo><mo>-</mo><mn>2</mn></math>".to_string()).unwrap();
+        let braille = get_braille("".to_string()).unwrap().chars().collect::<Vec<char>>();
+        let break_points = get_braille_break_points().unwrap();
+        assert_eq!(break_points.len(), 2);
+        assert_eq!(braille[break_points[0]], '⠬');
+        assert_eq!(braille[break_points[1]-1], '⠀');
+        assert_eq!(braille[break_points[1]], '⠤');
+        // highlighting used to find the break points shouldn't change the braille
+        assert_eq!(get_braille("".to_string()).unwrap().chars().collect::<Vec<char>>(), braille);
+
+        // the new line starts with the number indicator of the operand after the comparison
+        set_preference("BrailleCode".to_string(), "UEB".to_string()).unwrap();
+        set_preference("BrailleNavHighlight".to_string(), "All".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>=</mo><mn>2</mn></math>".to_string()).unwrap();
+        let braille = get_braille("".to_string()).unwrap().chars().collect::<Vec<char>>();
+        let break_points = get_braille_break_points().unwrap();
+        assert_eq!(break_points.len(), 1);
+        assert_eq!(braille[break_points[0]], '⠼');
+        assert_eq!(get_preference("BrailleNavHighlight".to_string()).unwrap(), "All");
+        set_preference("BrailleNavHighlight".to_string(), "EndPoints".to_string()).unwrap();
+        set_preference("BrailleCode".to_string(), "Nemeth".to_string()).unwrap();
+    }
+
+    #[test]
+    fn math_errors() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><merror><mtext>Undefined control sequence \\foo</mtext></merror></math>".to_string()).unwrap();
+        assert_eq!(get_math_errors().unwrap(), vec!["Undefined control sequence \\foo".to_string()]);
+        // invalid contents are kept as text
+        set_mathml("<math><merror><msup><mi>x</mi></msup><mtext>Missing superscript</mtext></merror></math>".to_string()).unwrap();
+        assert_eq!(get_math_errors().unwrap(), vec!["x Missing superscript".to_string()]);
+        set_mathml("<math><mi>x</mi></math>".to_string()).unwrap();
+        assert!(get_math_errors().unwrap().is_empty());
+    }
+
+    #[test]
+    fn braille_formatted() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("BrailleCode".to_string(), "Nemeth".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mi>y</mi><mo>+</mo><mi>z</mi><mo>=</mo><mi>a</mi><mo>-</mo><mi>b</mi><mo>-</mo><mi>c</mi></math>".to_string()).unwrap();
+        let braille = get_braille("".to_string()).unwrap();
+        assert_eq!(get_braille_formatted(40).unwrap(), braille);
+        // break before '+', after '=' (and its space); runovers are indented two cells
+        assert_eq!(get_braille_formatted(8).unwrap(), "⠭⠬⠽\n⠀⠀⠬⠵⠀⠨⠅\n⠀⠀⠁⠤⠃⠤⠉");
+        // UEB ends a line broken in an unspaced expression with the line continuation indicator
+        set_preference("BrailleCode".to_string(), "UEB".to_string()).unwrap();
+        assert_eq!(get_braille_formatted(8).unwrap(), "⠭⠐⠖⠽⠐\n⠀⠀⠐⠖⠵\n⠀⠀⠐⠶⠀⠁⠐\n⠀⠀⠐⠤⠃⠐⠤⠉");
+        assert!(get_braille_formatted(3).is_err());
+        set_preference("BrailleCode".to_string(), "Nemeth".to_string()).unwrap();
+    }
+
+    #[test]
+    fn glance() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_mathml("<math><mi>a</mi><mo>=</mo><mfrac><mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow><mi>d</mi></mfrac></math>".to_string()).unwrap();
+        assert_eq!(get_glance_text().unwrap(), "something is equal to, something over something,");
+        set_mathml("<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>2</mn><mi>x</mi><mo>=</mo><mn>0</mn></math>".to_string()).unwrap();
+        assert_eq!(get_glance_text().unwrap(), "something is equal to something");
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        assert_eq!(get_glance_text().unwrap(), "jokin on yhtä suuri kuin jokin");
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn repeating_decimal_separators() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        set_mathml("<math><mn>0,1666</mn><mo>…</mo></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "jaksollinen desimaali, 0 pilkku 1, jakso 6");
+        set_mathml("<math><mn>0</mn><mo>,</mo><mn>1</mn><mover><mn>6</mn><mo>¯</mo></mover></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "jaksollinen desimaali, 0 pilkku 1, jakso 6");
+        set_mathml("<math><mn>0,1</mn><mover><mn>6</mn><mo>¯</mo></mover></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "jaksollinen desimaali, 0 pilkku 1, jakso 6");
+        assert!(set_mathml("<math><mn>0,16&#x0305;</mn></math>".to_string()).unwrap().contains("repeating-decimal"));
+        // "." is not a decimal separator in Finnish
+        assert!(!set_mathml("<math><mn>0.333</mn><mo>…</mo></math>".to_string()).unwrap().contains("repeating-decimal"));
+        assert!(!set_mathml("<math><mn>0.1</mn><mover><mn>6</mn><mo>¯</mo></mover></math>".to_string()).unwrap().contains("repeating-decimal"));
+        assert!(!set_mathml("<math><mn>0.16&#x0305;</mn></math>".to_string()).unwrap().contains("repeating-decimal"));
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn generator_quirks() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let union = "<math><munder><mo>∪</mo><mi>i</mi></munder><msub><mi>A</mi><mi>i</mi></msub></math>";
+        assert!(set_mathml(union.to_string()).unwrap().contains("⋃"));
+        assert_eq!(get_generator_quirks().unwrap(), vec!["binary-large-operator (MathType)".to_string()]);
+        set_preference("GeneratorQuirks".to_string(), "mhchem, WIRIS".to_string()).unwrap();
+        assert!(!set_mathml(union.to_string()).unwrap().contains("⋃"));
+        assert!(get_generator_quirks().unwrap().is_empty());
+        set_mathml("<math><mi>x</mi><mo>&#xA0;</mo><mi>y</mi></math>".to_string()).unwrap();
+        assert_eq!(get_generator_quirks().unwrap(), vec!["whitespace-mo (WIRIS)".to_string()]);
+        set_preference("GeneratorQuirks".to_string(), "All".to_string()).unwrap();
+    }
+
+    #[test]
+    fn validation_levels() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let bad_mfrac = "<math><mrow><mi>a</mi><mo>+</mo><mfrac><mn>1</mn></mfrac></mrow></math>";
+        let error = errors_to_string(&set_mathml(bad_mfrac.to_string()).unwrap_err());
+        assert!(error.contains("mfrac should have 2 children at /math/mrow[1]/mfrac[1]"), "{}", error);
+        assert!(error.contains("<mn>1</mn>"), "{}", error);
+
+        let mfenced = "<math><mfenced><mi>x</mi></mfenced><mo>+</mo><mi></mi></math>";
+        assert!(set_mathml(mfenced.to_string()).is_ok());
+        set_preference("Validation".to_string(), "Strict".to_string()).unwrap();
+        let error = errors_to_string(&set_mathml(mfenced.to_string()).unwrap_err());
+        assert!(error.contains("'mfenced' is deprecated at /math/mfenced[1]"), "{}", error);
+
+        set_preference("Validation".to_string(), "Lenient".to_string()).unwrap();
+        set_mathml(bad_mfrac.to_string()).unwrap();
+        assert_eq!(get_validation_repairs().unwrap(), vec!["mfrac should have 2 children at /math/mrow[1]/mfrac[1]".to_string()]);
+        assert_eq!(get_spoken_text().unwrap(), "eigh plus 1");
+        set_mathml("<math><mi>x</mi><mo>=</mo><foo>bar</foo></math>".to_string()).unwrap();
+        assert_eq!(get_validation_repairs().unwrap(), vec!["'foo' is not a valid MathML element at /math/foo[1]".to_string()]);
+        set_mathml("<math><mi>x</mi></math>".to_string()).unwrap();
+        assert!(get_validation_repairs().unwrap().is_empty());
+        set_preference("Validation".to_string(), "Standard".to_string()).unwrap();
+    }
+
+    #[test]
+    fn generator_fingerprint() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mi>x</mi></math>".to_string()).unwrap();
+        assert_eq!(get_generator().unwrap(), "Unknown");
+        set_mathml("<math class='ltx_Math' alttext='x'><mi>x</mi></math>".to_string()).unwrap();
+        assert_eq!(get_generator().unwrap(), "LaTeXML");
+        set_mathml("<math><mrow data-mjx-texclass='ORD'><mi>x</mi></mrow></math>".to_string()).unwrap();
+        assert_eq!(get_generator().unwrap(), "MathJax");
+        set_mathml("<math><mfenced separators='|'><mi>x</mi></mfenced></math>".to_string()).unwrap();
+        assert_eq!(get_generator().unwrap(), "Word");
+        // only MathType quirks are repaired for MathType
+        set_mathml("<math><semantics><mrow><munder><mo>∪</mo><mi>i</mi></munder><mi>x</mi><mo>&#xA0;</mo><mi>y</mi></mrow>
+                        <annotation encoding='MathType-MTEF'>abc</annotation></semantics></math>".to_string()).unwrap();
+        assert_eq!(get_generator().unwrap(), "MathType");
+        assert_eq!(get_generator_quirks().unwrap(), vec!["binary-large-operator (MathType)".to_string()]);
+    }
+
+    #[test]
+    fn equivalence() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let options = EquivalenceOptions::default();
+        assert!(is_equivalent("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string(),
+                              "<math> <mrow><mi> x </mi><mo>+</mo><mn>1</mn></mrow> </math>".to_string(), options).unwrap());
+        assert!(!is_equivalent("<math><mi>x</mi><mo>+</mo><mn>1</mn></math>".to_string(),
+                               "<math><mi>x</mi><mo>+</mo><mn>2</mn></math>".to_string(), options).unwrap());
+
+        let attrs_a = "<math><mi id='a'>x</mi></math>".to_string();
+        let attrs_b = "<math><mi>x</mi></math>".to_string();
+        assert!(!is_equivalent(attrs_a.clone(), attrs_b.clone(), options).unwrap());
+        assert!(is_equivalent(attrs_a, attrs_b, EquivalenceOptions{ignore_attributes: true, ..options}).unwrap());
+
+        let invisible_a = "<math><mn>2</mn><mo>&#x2062;</mo><mi>x</mi></math>".to_string();
+        let invisible_b = "<math><mn>2</mn><mi>x</mi></math>".to_string();
+        let invisible_c = "<math><mn>2</mn><mo>&#x2063;</mo><mi>x</mi></math>".to_string();
+        assert!(is_equivalent(invisible_a.clone(), invisible_b, options).unwrap());     // canonicalization adds the invisible times
+        assert!(!is_equivalent(invisible_a.clone(), invisible_c.clone(), options).unwrap());
+        assert!(is_equivalent(invisible_a, invisible_c, EquivalenceOptions{ignore_invisible_operators: true, ..options}).unwrap());
+
+        let space_a = "<math><mtext>if x</mtext><mspace width='1em'/><mi>y</mi></math>".to_string();
+        let space_b = "<math><mtext>if  x</mtext><mi>y</mi></math>".to_string();
+        assert!(!is_equivalent(space_a.clone(), space_b.clone(), options).unwrap());
+        assert!(is_equivalent(space_a, space_b, EquivalenceOptions{ignore_whitespace: true, ..options}).unwrap());
+    }
+
+    #[test]
+    fn find_pattern() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><msubsup id='int1'><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><msup id='sq'><mi>x</mi><mn>2</mn></msup>
+                        <mo>+</mo><msubsup id='int2'><mo>∫</mo><mn>1</mn><mi>b</mi></msubsup><mi>x</mi></math>".to_string()).unwrap();
+        let generator = get_generator().unwrap();
+        assert_eq!(find("<math><msubsup><mo>∫</mo><mi>?</mi><mi>?</mi></msubsup></math>".to_string()).unwrap(),
+                   vec!["int1".to_string(), "int2".to_string()]);
+        assert_eq!(find("<math><msubsup><mo>∫</mo><mn>1</mn><mi>?</mi></msubsup></math>".to_string()).unwrap(),
+                   vec!["int2".to_string()]);
+        assert_eq!(find("<math><msup><mi>?</mi><mn>2</mn></msup></math>".to_string()).unwrap(), vec!["sq".to_string()]);
+        assert!(find("<math><msup><mi>?</mi><mn>3</mn></msup></math>".to_string()).unwrap().is_empty());
+        assert_eq!(get_generator().unwrap(), generator);
+    }
+
+    #[test]
+    fn supported_rules() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let languages = get_supported_languages().unwrap();
+        assert!(languages.contains(&("en".to_string(), "English".to_string())));
+        assert!(languages.contains(&("fi".to_string(), "Finnish".to_string())));
+        assert!(!languages.iter().any(|(code, _)| code.starts_with("zz")));
+        assert_eq!(get_speech_styles("en".to_string()).unwrap(), vec!["ClearSpeak".to_string(), "SimpleSpeak".to_string()]);
+        assert_eq!(get_speech_styles("zz-aa".to_string()).unwrap(), vec!["ClearSpeak".to_string()]);
+        assert!(get_speech_styles("xx".to_string()).is_err());
+        let braille_codes = get_braille_codes().unwrap();
+        assert!(braille_codes.contains(&("UEB".to_string(), "Unified English Braille".to_string())));
+        assert!(braille_codes.contains(&("Nemeth".to_string(), "Nemeth Code".to_string())));
+        assert!(!braille_codes.iter().any(|(code, _)| code == "SharedComponents"));
+        assert!(braille_codes.contains(&("Spoken".to_string(), "Word Braille (the spoken form)".to_string())));
+        // every code should have a human-readable name (the code itself is used when there isn't one)
+        assert!(braille_codes.iter().all(|(code, name)| code != name), "braille codes={:?}", braille_codes);
+    }
+
+    #[test]
+    fn annotation_fallback() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        let mathml_annotation = "<math><semantics><mglyph src='eq.png' alt='equation'/>
+                <annotation-xml encoding='application/mathml+xml'><msup><mi>x</mi><mn>2</mn></msup></annotation-xml>
+                <annotation encoding='application/x-tex'>x^2</annotation></semantics></math>";
+        let tex_annotation = "<math><semantics><mrow><mglyph src='eq.png' alt='equation'/></mrow>
+                <annotation encoding='application/x-tex'>x^2</annotation></semantics></math>";
+        set_mathml(mathml_annotation.to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x squared");
+        assert!(set_mathml(tex_annotation.to_string()).unwrap().contains("<mtext id='"));
+
+        set_preference("AnnotationFallback".to_string(), "MathML".to_string()).unwrap();
+        set_mathml(mathml_annotation.to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x squared");
+        assert!(set_mathml(tex_annotation.to_string()).unwrap().contains("<mglyph"));
+
+        set_preference("AnnotationFallback".to_string(), "None".to_string()).unwrap();
+        assert!(set_mathml(mathml_annotation.to_string()).unwrap().contains("<mglyph"));
+        set_preference("AnnotationFallback".to_string(), "All".to_string()).unwrap();
+    }
+
+    #[test]
+    fn text_tables() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let columns = "<math><mtext>x\t\t1</mtext><mspace linebreak='newline'/><mtext>y  \u{2007} 22</mtext></math>";
+        // without 'TextTables', the linebreak just splits the text into two lines (a table with one column)
+        assert_eq!(set_mathml(columns.to_string()).unwrap().matches("<mtd").count(), 2);
+
+        set_preference("TextTables".to_string(), "true".to_string()).unwrap();
+        let canonical = set_mathml(columns.to_string()).unwrap();
+        assert!(canonical.contains("<mtable"));
+        assert_eq!(canonical.matches("<mtd").count(), 4);
+        // different numbers of columns aren't a table
+        let ragged = "<math><mtext>x\t\t1\ny\t2\t3</mtext></math>";
+        assert!(!set_mathml(ragged.to_string()).unwrap().contains("<mtable"));
+        set_preference("TextTables".to_string(), "false".to_string()).unwrap();
+    }
+
+    #[test]
+    fn roman_numerals() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        let numerals = "<math><mi>vii</mi><mo>-</mo><mi>ii</mi><mo>=</mo><mtext>v</mtext></math>";
+        set_mathml(numerals.to_string()).unwrap();
+        assert!(!get_spoken_text().unwrap().contains('7'));
+
+        set_preference("RomanNumerals".to_string(), "true".to_string()).unwrap();
+        set_mathml(numerals.to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "7 minus 2, is equal to 5");
+        set_mathml("<math><mtext>XLVIII</mtext></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "48");
+        // units, chemical elements, and short tokens need a confirmed roman numeral nearby
+        set_mathml("<math><mi>cm</mi><mo>+</mo><mi>mm</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "centimeter plus millimeter");
+        set_mathml("<math><mi>x</mi><mo>-</mo><mi>i</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x minus i");
+        set_preference("RomanNumerals".to_string(), "false".to_string()).unwrap();
+    }
+
+    #[test]
+    fn expression_speech_style() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string()).unwrap();
+        let fraction = "<mfrac><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mn>2</mn></mfrac>";
+        set_mathml(format!("<math>{}</math>", fraction)).unwrap();
+        let clearspeak = get_spoken_text().unwrap();
+        set_mathml(format!("<math data-speech-style='SimpleSpeak'>{}</math>", fraction)).unwrap();
+        let simplespeak = get_spoken_text().unwrap();
+        assert_eq!(simplespeak, "fraction, x plus 1, over 2, end fraction;");
+        assert_ne!(clearspeak, simplespeak);
+        assert_eq!(get_preference("SpeechStyle".to_string()).unwrap(), "ClearSpeak");
+        // a style given for the call wins
+        let prefs = PreferenceSnapshot::new().set("SpeechStyle", "ClearSpeak");
+        assert_eq!(get_spoken_text_with_prefs(&prefs).unwrap(), clearspeak);
+        // unknown styles are ignored
+        set_mathml(format!("<math data-speech-style='NoSuchStyle'>{}</math>", fraction)).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), clearspeak);
+    }
+
+    #[test]
+    fn practice() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_mathml("<math><mrow id='lhs'><msup id='sq'><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></mrow><mo>=</mo><mn id='five'>5</mn></math>".to_string()).unwrap();
+        assert!(practice_reveal("".to_string()).is_err());
+        assert_eq!(start_practice().unwrap(), "something is equal to something");
+        assert_eq!(practice_reveal("".to_string()).unwrap(), "something plus something");
+        assert_eq!(get_navigation_mathml_id().unwrap().0, "lhs");
+        assert_eq!(practice_reveal("".to_string()).unwrap(), "something to the something-th");
+        assert_eq!(practice_reveal("five".to_string()).unwrap(), "5");
+        assert_eq!(get_practice_text().unwrap(), "something to the something-th, plus something; is equal to 5");
+        while !practice_reveal("".to_string()).unwrap().is_empty() {}
+        assert_eq!(get_practice_text().unwrap(), "x squared plus 1, is equal to 5");
+    }
+
+    #[test]
+    fn unicode_normalization() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>&#x2264;</mo><mn>2</mn></math>".to_string()).unwrap();
+        assert!(get_unicode_normalizations().unwrap().is_empty());
+        set_mathml("<math><mi>x</mi><mo>=&#x338;</mo><mi>a&#x308;</mi></math>".to_string()).unwrap();
+        assert_eq!(get_unicode_normalizations().unwrap(),
+                   vec![("=\u{338}".to_string(), "≠".to_string()), ("a\u{308}".to_string(), "ä".to_string())]);
+    }
+
+    #[test]
+    fn omml_input() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_omml("<m:oMathPara xmlns:m='http://schemas.openxmlformats.org/officeDocument/2006/math'><m:oMath>
+                    <m:f><m:num><m:r><m:t>a</m:t></m:r></m:num><m:den><m:r><m:t>2</m:t></m:r></m:den></m:f>
+                    <m:r><m:t>=</m:t></m:r>
+                    <m:rad><m:radPr><m:degHide m:val='1'/></m:radPr><m:deg/><m:e><m:r><m:t>x+1</m:t></m:r></m:e></m:rad>
+                  </m:oMath></m:oMathPara>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "eigh over 2, is equal to, the square root of x plus 1 end root,");
+    }
+
+    #[test]
+    fn export_import_preferences() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        assert_eq!(export_preferences().unwrap(), r#"{"version":1,"preferences":{}}"#);
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        set_preference("Verbosity".to_string(), "Verbose".to_string()).unwrap();
+        set_preference("Rate".to_string(), "200".to_string()).unwrap();
+        let bundle = export_preferences().unwrap();
+        assert_eq!(bundle, r#"{"version":1,"preferences":{"Language":"fi","Rate":"200","Verbosity":"Verbose"}}"#);
+
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();      // resets the user prefs
+        set_preference("Rate".to_string(), "180".to_string()).unwrap();
+        assert!(import_preferences(bundle).unwrap().is_empty());
+        assert_eq!(get_preference("Language".to_string()).unwrap(), "fi");
+        assert_eq!(get_preference("Verbosity".to_string()).unwrap(), "Verbose");
+        assert_eq!(get_preference("Rate".to_string()).unwrap(), "200");
+
+        // unknown names are skipped and reported; numbers and booleans can be used as values
+        let unknown = import_preferences(r#"{"version": 1, "preferences": {"NewPref": "x", "Rate": 150, "Bookmark": true}}"#.to_string()).unwrap();
+        assert_eq!(unknown, vec!["NewPref".to_string()]);
+        assert_eq!(get_preference("Rate".to_string()).unwrap(), "150");
+        assert_eq!(get_preference("Bookmark".to_string()).unwrap(), "true");
+
+        // nothing is set if the bundle is bad
+        assert!(import_preferences(r#"{"version": 2, "preferences": {"Rate": "100"}}"#.to_string()).is_err());
+        assert!(import_preferences(r#"{"preferences": {"Rate": "100"}}"#.to_string()).is_err());
+        assert!(import_preferences(r#"{"version": 1, "preferences": {"Rate": ["100"]}}"#.to_string()).is_err());
+        assert!(import_preferences("not json".to_string()).is_err());
+        assert_eq!(get_preference("Rate".to_string()).unwrap(), "150");
+
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("Rate".to_string(), "180".to_string()).unwrap();
+        set_preference("Bookmark".to_string(), "false".to_string()).unwrap();
+    }
+
+    #[test]
+    fn rule_coverage() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        let coverage = collect_rule_coverage(vec![
+            "<math><mfrac><mn>1</mn><mi>x</mi></mfrac></math>".to_string(),
+            "<math><mi>\u{E000}</mi></math>".to_string(),
+            "<math><mi>x</mi>".to_string(),
+        ]).unwrap();
+        assert_eq!(coverage.n_expressions, 3);
+        assert!(coverage.rules_fired.keys().any(|rule| rule.starts_with("Speech: ")));
+        assert!(coverage.rules_fired.keys().any(|rule| rule.starts_with("Braille: ")));
+        assert_eq!(coverage.fallbacks.len(), 1);
+        assert!(coverage.fallbacks[0].1.iter().any(|reason| reason.contains("0xe000")));
+        assert_eq!(coverage.errors.len(), 1);
+        assert!(coverage.to_string().starts_with("Rule coverage for 3 expressions (1 with fallbacks, 1 with errors)"));
+        assert!(crate::speech::RULE_COVERAGE.with(|coverage| coverage.borrow().is_none()));
+    }
+
+    #[test]
+    fn check_rule_files() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        let en_dir = std::path::Path::new(&super::super::abs_rules_dir_path()).join("Languages").join("en");
+        let diagnostics = check_rules(en_dir.to_str().unwrap().to_string());
+        assert!(diagnostics.iter().all(|d| d.level == crate::speech::RuleDiagnosticLevel::Warning), "{:?}", diagnostics);
+
+        let test_dir = std::env::temp_dir().join("mathcat_check_rules");
+        std::fs::create_dir_all(&test_dir).unwrap();
+        std::fs::write(test_dir.join("Test_Rules.yaml"), r#"---
+- name: bad-xpath
+  tag: mi
+  match: "(text()='x'"
+  replace: [t: "x"]
+- name: default
+  tag: mn
+  match: "."
+  replace: [x: "text()"]
+- name: too-late
+  tag: mn
+  match: "text()='1'"
+  replace: [t: "one"]
+- name: default
+  tag: mn
+  match: "."
+  replace: [t: "number"]
+- include: "missing.yaml"
+"#).unwrap();
+        let diagnostics = check_rules(test_dir.to_str().unwrap().to_string());
+        std::fs::remove_dir_all(&test_dir).unwrap();
+        let messages = diagnostics.iter()
+                .map(|d| format!("{:?} {}: {}", d.level, d.rule, d.message.lines().next().unwrap()))
+                .collect::<Vec<String>>();
+        assert_eq!(messages.len(), 6, "{:?}", messages);
+        assert!(messages[0].starts_with("Warning : 'unicode.yaml' not found"));
+        assert!(messages[1].starts_with("Warning : 'unicode-full.yaml' not found"));
+        assert_eq!(messages[2], "Error bad-xpath [mi]: value for 'match' in rule (mi: bad-xpath):");
+        assert_eq!(messages[3], "Warning too-late [mn]: unreachable -- the earlier rule 'default' always matches");
+        assert_eq!(messages[4], "Warning default [mn]: duplicate name and tag -- it replaces the earlier rule");
+        assert_eq!(messages[5], "Error : included file 'missing.yaml' does not exist");
+    }
+
+    #[test]
+    fn spoken_text_truncated() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+        set_mathml("<math><mrow id='r'><mrow id='lhs'><msup id='sq'><mi>x</mi><mn>2</mn></msup><mo id='plus1'>+</mo><mn>2</mn><mi>x</mi>\
+                    <mo id='plus2'>+</mo><mn>1</mn></mrow><mo id='eq'>=</mo><mn id='zero'>0</mn></mrow></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text_truncated(20).unwrap(), ("x squared plus 2 x plus 1; is equal to 0".to_string(), None));
+        assert_eq!(get_spoken_text_truncated(9).unwrap(), ("x squared plus 2 x plus 1; and more".to_string(), Some("eq".to_string())));
+        assert_eq!(get_spoken_text_truncated(7).unwrap(), ("x squared plus 2 x, and more".to_string(), Some("plus2".to_string())));
+        assert_eq!(get_spoken_text_truncated(4).unwrap(), ("x squared and more".to_string(), Some("plus1".to_string())));
+        assert_eq!(get_spoken_text_truncated(1).unwrap(), ("and more".to_string(), Some("sq".to_string())));
+
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        assert!(get_spoken_text_truncated(4).unwrap().0.ends_with("ja niin edelleen"));
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+    }
+
+    #[test]
+    fn canonical_cache() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+        let canonical = set_mathml("<math><mfrac><mn>1</mn><mi>x</mi></mfrac><mo>&#x2062;</mo><mtext> &amp; </mtext></math>".to_string()).unwrap();
+        let speech = get_spoken_text().unwrap();
+        let serialized = serialize_canonical().unwrap();
+        assert!(!get_semantic_mathml().unwrap().contains("data-mathcat-cache"));
+
+        set_mathml("<math><mi>y</mi></math>".to_string()).unwrap();
+        let restored = deserialize_canonical(serialized.clone()).unwrap();
+        assert_eq!(restored.len(), canonical.len());     // the attributes might be in a different order
+        assert_eq!(get_spoken_text().unwrap(), speech);
+        assert_eq!(serialize_canonical().unwrap(), serialized);
+
+        // a change to a preference that affects canonicalization makes the string unusable
+        set_preference("Language".to_string(), "fi".to_string()).unwrap();
+        assert!(deserialize_canonical(serialized.clone()).is_err());
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        assert!(deserialize_canonical(serialized.replace("data-mathcat-cache", "data-other")).is_err());
+        assert!(deserialize_canonical("<math><mi>x</mi>".to_string()).is_err());
+    }
+
+    #[test]
+    fn speech_duration() {
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string()).unwrap();
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+        set_preference("Rate".to_string(), "180".to_string()).unwrap();
+        set_mathml("<math><mi>x</mi><mo>+</mo><mi>y</mi></math>".to_string()).unwrap();
+        assert_eq!(get_spoken_text().unwrap(), "x plus y");
+        assert_eq!(estimate_duration().unwrap(), 1000);     // 3 words at 180 words/minute
+        set_preference("Rate".to_string(), "90".to_string()).unwrap();
+        assert_eq!(estimate_duration().unwrap(), 2000);
+
+        // pauses are included
+        set_preference("Rate".to_string(), "180".to_string()).unwrap();
+        set_mathml("<math><mfrac><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow><mi>y</mi></mfrac></math>".to_string()).unwrap();
+        let none_duration = estimate_duration().unwrap();
+        assert!(none_duration > 333 * get_spoken_text().unwrap().split_whitespace().count());
+        set_preference("TTS".to_string(), "SSML".to_string()).unwrap();
+        let speech = get_spoken_text().unwrap();
+        assert!(speech.contains("<break time="));
+        let ssml_duration = estimate_duration().unwrap();
+        assert!(ssml_duration > 333 * 6 && ssml_duration < 2 * none_duration, "SSML '{}' estimated at {}ms", speech, ssml_duration);
+        set_preference("TTS".to_string(), "None".to_string()).unwrap();
+    }
+
     #[test]
     fn can_recover_from_invalid_set_rules_dir() {
         use std::env;
@@ -57,6 +57,14 @@ mod xpath_functions;
 mod definitions;
 mod pretty_print;
 mod chemistry;
+mod symbol_category;
+mod omml;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "profile")]
+pub mod profile;
+#[cfg(feature = "python")]
+mod python;
 
 pub mod shim_filesystem; // really just for override_file_for_debugging_rules, but the config seems to throw it off
 pub use shim_filesystem::ZIPPED_RULE_FILES;
@@ -10,10 +10,13 @@ use sxd_document::Package;
 use std::fmt;
 use crate::pretty_print::mml_to_string;
 use crate::speech::{NAVIGATION_RULES, CONCAT_INDICATOR, CONCAT_STRING, SpeechRules, SpeechRulesWithContext};
+use crate::tts::PAUSE_SHORT;
 #[cfg(not(target_family = "wasm"))]
 use std::time::{Instant};
+use std::collections::HashSet;
 use crate::errors::*;
-use crate::canonicalize::as_element;
+use crate::canonicalize::{as_element, name};
+use crate::xpath_functions::is_leaf;
 use phf::phf_set;
 
 
@@ -31,11 +34,11 @@ pub static NAV_COMMANDS: phf::Set<&str> = phf_set! {
     "MovePrevious", "MoveNext", "MoveStart", "MoveEnd", "MoveLineStart", "MoveLineEnd", 
     "MoveCellPrevious", "MoveCellNext", "MoveCellUp", "MoveCellDown", "MoveColumnStart", "MoveColumnEnd", 
     "ZoomIn", "ZoomOut", "ZoomOutAll", "ZoomInAll", 
-    "MoveLastLocation", 
+    "MoveLastLocation", "MoveMatchingFence", "MoveOutOfContainer", 
     "ReadPrevious", "ReadNext", "ReadCurrent", "ReadCellCurrent", "ReadStart", "ReadEnd", "ReadLineStart", "ReadLineEnd", 
-    "DescribePrevious", "DescribeNext", "DescribeCurrent", 
+    "DescribePrevious", "DescribeNext", "DescribeCurrent", "DescribeMatrix", 
     "WhereAmI", "WhereAmIAll", 
-    "ToggleZoomLockUp", "ToggleZoomLockDown", "ToggleSpeakMode", 
+    "ToggleZoomLockUp", "ToggleZoomLockDown", "ToggleSpeakMode", "ToggleGlance", 
     "Exit", 
     "MoveTo0","MoveTo1","MoveTo2","MoveTo3","MoveTo4","MoveTo5","MoveTo6","MoveTo7","MoveTo8","MoveTo9",
     "Read0","Read1","Read2","Read3","Read4","Read5","Read6","Read7","Read8","Read9",
@@ -84,6 +87,8 @@ pub struct NavigationState {
     where_am_i_start_time: Instant,
     mode: String,                         // one of "Character", "Simple", or "Enhanced"
     speak_overview: bool,                       // true => describe after move; false => (standard) speech rules
+    speak_glance: bool,                         // true => speak the relational skeleton of (non-leaf) nodes
+    practice_revealed: Option<HashSet<String>>, // Some(ids of the revealed nodes) => in practice mode (see 'start_practice')
 }
 
 impl fmt::Display for NavigationState {
@@ -100,7 +105,8 @@ impl fmt::Display for NavigationState {
         }
         writeln!(f)?;
         writeln!(f, "  where_am_i: {}, start_time: {:?}", self.where_am_i, self.where_am_i_start_time)?;
-        writeln!(f, "  mode: {}, speak_overview: {}", self.mode, self.speak_overview)?;
+        writeln!(f, "  mode: {}, speak_overview: {}, speak_glance: {}, practice: {:?}",
+                 self.mode, self.speak_overview, self.speak_glance, self.practice_revealed)?;
         writeln!(f, "}}")?;
         return Ok( () );
     }
@@ -120,6 +126,8 @@ impl NavigationState {
             where_am_i_start_time: Instant::now(),      // need to give it some value, and "default()" isn't an option
             mode: "".to_string(),                       // set latter when we have some context
             speak_overview: false,                      // FIX should be $Overview
+            speak_glance: false,
+            practice_revealed: None,
         };
     }
 
@@ -127,6 +135,7 @@ impl NavigationState {
         self.position_stack.clear();
         self.command_stack.clear();
         self.where_am_i = NavigationPosition::default();
+        self.practice_revealed = None;
         self.reset_start_time()
         
     }
@@ -227,6 +236,7 @@ impl NavigationState {
         // used by nav rules for speech -- needs an initial value so tests don't fail
         context.set_variable("Move2D", "" );
         context.set_variable("SpeakExpression","true" );    // default is to speak the expr after navigation
+        context.set_variable("SkippedNode", "");            // id of a node that was jumped over (e.g., by MoveMatchingFence)
 
 
         return;
@@ -265,6 +275,106 @@ pub fn get_node_by_id<'a>(mathml: Element<'a>, id: &str) -> Option<Element<'a>>
     return None;
 }
 
+/// Start practice mode: the expression is revealed progressively with [`practice_reveal`].
+/// Navigation is reset to the start of the expression.
+/// The spoken text for the structure of the whole expression (all operands are hidden) is returned.
+pub fn start_practice(mathml: Element) -> Result<String> {
+    if mathml.children().is_empty() {
+        bail!("MathML has not been set -- can't practice");
+    };
+    let revealed = HashSet::new();
+    let speech = crate::speech::practice_mathml(mathml, &revealed)?;
+    NAVIGATION_STATE.with(|nav_state| {
+        let mut nav_state = nav_state.borrow_mut();
+        nav_state.reset();
+        nav_state.practice_revealed = Some(revealed);
+    });
+    return Ok(speech);
+}
+
+/// Reveal the node with 'id' (and any of its hidden ancestors) in practice mode and make it the current navigation node.
+/// If 'id' is empty, the next hidden operand in reading order is revealed (a guided traversal of the expression).
+/// The spoken text of the revealed node is returned -- its structure is spoken, but its operands remain hidden.
+/// An empty string is returned if everything has already been revealed.
+pub fn practice_reveal(mathml: Element, id: &str) -> Result<String> {
+    return NAVIGATION_STATE.with(|nav_state| {
+        let mut nav_state = nav_state.borrow_mut();
+        let revealed = match &mut nav_state.practice_revealed {
+            None => bail!("Not in practice mode -- call 'start_practice' first"),
+            Some(revealed) => revealed,
+        };
+        let top = crate::speech::skeleton_top(mathml);
+        let node = if id.is_empty() {
+            match next_hidden_node(top, revealed) {
+                None => return Ok( "".to_string() ),
+                Some(node) => node,
+            }
+        } else {
+            match get_node_by_id(mathml, id) {
+                None => bail!("practice_reveal: id '{}' was not found", id),
+                Some(node) => node,
+            }
+        };
+
+        let mut ancestor = Some(node);
+        while let Some(element) = ancestor {
+            if element.attribute_value("id") == top.attribute_value("id") {
+                break;
+            }
+            revealed.insert(element.attribute_value("id").unwrap_or_default().to_string());
+            ancestor = element.parent().and_then(|parent| parent.element());
+        }
+        let speech = crate::speech::practice_mathml(node, revealed)?;
+        nav_state.push(NavigationPosition{
+            current_node: node.attribute_value("id").unwrap_or_default().to_string(),
+            current_node_offset: 0
+        }, "PracticeReveal");
+        return Ok(speech);
+    });
+
+    fn next_hidden_node<'a>(mathml: Element<'a>, revealed: &HashSet<String>) -> Option<Element<'a>> {
+        if is_leaf(mathml) {
+            return None;
+        }
+        for child in mathml.children() {
+            let child = as_element(child);
+            if name(&child) == "mo" {
+                continue;
+            }
+            if !child.attribute_value("id").is_some_and(|id| revealed.contains(id)) {
+                return Some(child);
+            }
+            if let Some(hidden) = next_hidden_node(child, revealed) {
+                return Some(hidden);
+            }
+        }
+        return None;
+    }
+}
+
+/// Return the spoken text for the whole expression in practice mode (only the revealed parts are spoken).
+pub fn get_practice_text(mathml: Element) -> Result<String> {
+    return NAVIGATION_STATE.with(|nav_state| {
+        return match &nav_state.borrow().practice_revealed {
+            None => bail!("Not in practice mode -- call 'start_practice' first"),
+            Some(revealed) => crate::speech::practice_mathml(mathml, revealed),
+        };
+    });
+}
+
+/// Return the spoken text for 'node' as navigation reads it (the node is spoken in the context of its parent).
+/// The navigation state is not changed.
+pub fn speak_node(node: Element) -> Result<String> {
+    SpeechRules::update();
+    NAVIGATION_RULES.with(|rules| { rules.borrow_mut().read_files() })?;
+    return NAVIGATION_RULES.with(|rules| {
+        let rules = rules.borrow();
+        let new_package = Package::new();
+        let mut rules_with_context = SpeechRulesWithContext::new(&rules, new_package.as_document(), "".to_string());
+        return speak(&mut rules_with_context, node, true);
+    });
+}
+
 // FIX: think of a better place to put this, and maybe a better interface
 pub fn context_get_variable<'c>(context: &Context<'c>, var_name: &str, mathml: Element<'c>) -> Result<(Option<String>, Option<f64>)> {
     // First return tuple value is string-value (if string, bool, or single node) or None
@@ -355,6 +465,23 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
                 nav_state.mode = rules.pref_manager.as_ref().borrow().get_user_prefs().to_string("NavMode");
             // }
 
+            if nav_command == "ToggleGlance" {
+                // handled here (not in the rules) -- speak the current node in the new mode
+                nav_state.speak_glance = !nav_state.speak_glance;
+                let current_node = get_start_node(mathml, &nav_state)?;
+                return if nav_state.speak_glance && !is_leaf(current_node) {
+                    crate::speech::glance_mathml(current_node)
+                } else {
+                    speak(&mut rules_with_context, current_node, true)
+                };
+            }
+
+            if nav_command == "DescribeMatrix" {
+                // handled here (not in the rules) -- analyzes the table structure; the navigation position doesn't change
+                let current_node = get_start_node(mathml, &nav_state)?;
+                return crate::speech::describe_matrix(current_node);
+            }
+
             nav_state.init_navigation_context(rules_with_context.get_context(), nav_command, nav_state.top());
             
             // start navigation off at the right node
@@ -416,6 +543,7 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
                                                 .replace(CONCAT_INDICATOR, "")                            
                                     )
                     .trim());
+        let speech = crate::speech::map_phrases(speech, &rules.pref_manager.borrow().get_language());
         // debug!("Nav Speech: {}", speech);
 
         // FIX: add things that need to do a speech replacement based on some marker for "where am i" and others that loop ([Speak: id])???
@@ -463,10 +591,26 @@ pub fn do_navigate_command_string(mathml: Element, nav_command: &'static str) ->
             }
         }
 
+        // a quick reading of what was jumped over (if anything) so the user knows what was skipped
+        let speech = match context_get_variable(context, "SkippedNode", mathml)?.0.and_then(|id| get_node_by_id(mathml, &id)) {
+            None => speech,
+            Some(skipped) => {
+                let skipped_speech = crate::speech::glance_mathml(skipped)?;
+                let pref_manager = rules.pref_manager.borrow();
+                let pause = pref_manager.get_tts().pause(&pref_manager, PAUSE_SHORT).replace(CONCAT_INDICATOR, "");
+                speech + " " + &skipped_speech + &pause
+            },
+        };
+
         let nav_mathml = get_node_by_id(mathml, &nav_position.current_node);
         if nav_mathml.is_some() && context_get_variable(context, "SpeakExpression", mathml)?.0.unwrap() == "true" {
             // Speak/Overview of where we landed (if we are supposed to speak it)
-            let node_speech = speak(&mut rules_with_context, nav_mathml.unwrap(), use_read_rules)?;
+            let nav_mathml = nav_mathml.unwrap();
+            let node_speech = if use_read_rules && nav_state.speak_glance && !is_leaf(nav_mathml) {
+                crate::speech::glance_mathml(nav_mathml)?
+            } else {
+                speak(&mut rules_with_context, nav_mathml, use_read_rules)?
+            };
             // debug!("node_speech: '{}'", node_speech);
             if node_speech.is_empty() {
                 // try again in loop
@@ -513,7 +657,7 @@ fn speak<'r, 'c, 's:'c, 'm:'c>(rules_with_context: &'r mut SpeechRulesWithContex
         // Here, we temporarily mark the current node, get the intent reading of the parent and then find the node in the parent.
         // If it isn't present, we skip context and retry
         mathml.set_attribute_value(MARKED_NODE, "nav");
-        let context_mathml = if let Some(parent) = mathml.parent() {parent.element().unwrap()} else {mathml};
+        let context_mathml = mathml.parent().and_then(|parent| parent.element()).unwrap_or(mathml);   // 'math' has no parent element
         // debug!("context_mathml: {}", mml_to_string(&context_mathml));
         let intent = crate::speech::intent_from_mathml(context_mathml, rules_with_context.get_document())?;
         debug!("intent: {}", mml_to_string(&intent));
@@ -885,6 +1029,45 @@ mod tests {
         });
     }
     
+    #[test]
+    fn zoom_in_alt_text() -> Result<()> {
+        // the author's text replaces the speech for the mrow, but its parts can still be reached
+        let mathml_str = "<math id='math'><mrow id='mrow'>
+                <mrow id='sum' aria-label='sum of x and y'><mi id='x'>x</mi><mo id='plus'>+</mo><mi id='y'>y</mi></mrow>
+                <mo id='eq'>=</mo><mn id='two'>2</mn>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("AltText".to_string(), "AriaLabel".to_string())?;
+        set_mathml(mathml_str.to_string()).unwrap();
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            assert_eq!(test_command("ZoomIn", mathml, "sum"), "sum of x and y");
+            assert_eq!(test_command("ZoomIn", mathml, "x"), "x");
+            assert_eq!(test_command("MoveNext", mathml, "plus"), "plus");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn zoom_in_accented_identifier() -> Result<()> {
+        // x̂ is a single identifier, so zooming in stops at it
+        let mathml_str = "<math id='math'><mfrac id='mfrac'>
+                <mover id='mover'><mi id='base'>x</mi><mo id='hat'>^</mo></mover>
+                <mi id='denom'>d</mi>
+            </mfrac></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomIn", mathml, "mover");
+            test_command("ZoomIn", mathml, "mover");
+            test_command("MoveNext", mathml, "denom");
+            return Ok( () );
+        });
+    }
+
     #[test]
     fn zoom_in_parens() -> Result<()> {
         // init_logger();
@@ -1087,6 +1270,33 @@ mod tests {
         });
     }
     
+    #[test]
+    fn move_by_linebreak_lines() -> Result<()> {
+        // the linebreak splits the expression into lines (rows of a table)
+        let mathml_str = "<math id='math'>
+                <mi id='a'>a</mi><mo id='eq1'>=</mo><mi id='b'>b</mi><mo id='plus'>+</mo><mn id='one'>1</mn>
+                <mspace linebreak='newline'/>
+                <mi id='c'>c</mi><mo id='eq2'>=</mo><mn id='two'>2</mn>
+            </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("NavMode".to_string(), "Character".to_string())?;
+        set_mathml(mathml_str.to_string()).unwrap();
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            NAVIGATION_STATE.with(|nav_stack| {
+                nav_stack.borrow_mut().push(NavigationPosition{
+                    current_node: "a".to_string(),
+                    current_node_offset: 0
+                }, "None")
+            });
+            test_command("MoveLineEnd", mathml, "one");
+            test_command("MoveCellDown", mathml, "c");
+            test_command("MoveLineEnd", mathml, "two");
+            return Ok( () );
+        });
+    }
+
     #[test]
     fn text_extremes_and_move_last_location() -> Result<()> {
         let mathml_str = "<math id='math'><mfrac id='mfrac'>
@@ -1210,6 +1420,39 @@ mod tests {
         });
     }
 
+    #[test]
+    fn move_over_ellipsis() -> Result<()> {
+        // init_logger();
+        // a₁, …, aₙ -- the commas next to the ellipsis are skipped
+        let mathml_str = "<math display='block' id='id-0'>
+        <mrow id='id-1'>
+          <msub id='id-2'><mi id='id-3'>a</mi><mn id='id-4'>1</mn></msub>
+          <mo id='id-5'>,</mo>
+          <mi id='id-6'>…</mi>
+          <mo id='id-7'>,</mo>
+          <msub id='id-8'><mi id='id-9'>a</mi><mi id='id-10'>n</mi></msub>
+        </mrow>
+        </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            NAVIGATION_STATE.with(|nav_stack| {
+                nav_stack.borrow_mut().push(NavigationPosition{
+                    current_node: "id-2".to_string(),
+                    current_node_offset: 0
+                }, "None")
+            });
+            set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+            test_command("MoveNext", mathml, "id-6");
+            test_command("MoveNext", mathml, "id-8");
+            test_command("MovePrevious", mathml, "id-6");
+            test_command("MovePrevious", mathml, "id-2");
+            return Ok( () );
+        });
+    }
+
     
     #[test]
     fn move_right_char() -> Result<()> {
@@ -1284,6 +1527,153 @@ mod tests {
         });
     }
     
+    #[test]
+    fn move_by_relation_chain_link() -> Result<()> {
+        let mathml_str = "<math id='math'><mrow id='mrow'>
+                <mi id='a'>a</mi><mo id='lt1'>&lt;</mo><mi id='b'>b</mi>
+                <mo id='lt2'>&lt;</mo><mi id='c'>c</mi><mo id='le'>≤</mo><mi id='d'>d</mi>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Verbose".to_string())?;
+        set_preference("Language".to_string(), "en".to_string())?;
+        set_preference("RelationChains".to_string(), "Summary".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomIn", mathml, "a");
+            assert_eq!(test_command("MoveNext", mathml, "b"), "move right, is less than, b");
+            assert_eq!(test_command("MoveNext", mathml, "c"), "move right, is less than, c");
+            assert_eq!(test_command("MoveNext", mathml, "d"), "move right, is less than or equal to, d");
+            assert_eq!(test_command("MovePrevious", mathml, "c"), "move left, is less than or equal to, c");
+
+            set_preference("RelationChains".to_string(), "Linear".to_string())?;
+            test_command("MoveNext", mathml, "le");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn labeled_arrow_is_one_node() -> Result<()> {
+        let mathml_str = "<math id='math'><mrow id='mrow'>
+                <mi id='A'>A</mi>
+                <mover id='arrow'><mo id='to'>→</mo><mi id='f'>f</mi></mover>
+                <mi id='B'>B</mi>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("Language".to_string(), "en".to_string())?;
+        for nav_mode in ["Enhanced", "Simple", "Character"] {
+            set_preference("NavMode".to_string(), nav_mode.to_string())?;
+            MATHML_INSTANCE.with(|package_instance| {
+                let package_instance = package_instance.borrow();
+                let mathml = get_element(&*package_instance);
+                test_command("ZoomInAll", mathml, "A");
+                test_command("MoveNext", mathml, "arrow");
+                test_command("ZoomIn", mathml, "arrow");
+                test_command("MoveNext", mathml, "B");
+                test_command("MovePrevious", mathml, "arrow");
+                test_command("ZoomOut", mathml, "mrow");
+            });
+        }
+        return Ok( () );
+    }
+
+    #[test]
+    fn move_matching_fence_and_out_of_container() -> Result<()> {
+        let mathml_str = "<math id='math'><mrow id='mrow'>
+                <mi id='x'>x</mi><mo id='times'>&#x2062;</mo>
+                <mrow id='group'>
+                    <mo id='open'>(</mo>
+                    <mrow id='contents'><mi id='a'>a</mi><mo id='plus'>+</mo><mfrac id='frac'><mi id='b'>b</mi><mn id='two'>2</mn></mfrac></mrow>
+                    <mo id='close'>)</mo>
+                </mrow>
+            </mrow></math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Simple".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Verbose".to_string())?;
+        set_preference("Language".to_string(), "en".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            assert_eq!(test_command("MoveMatchingFence", mathml, "math"), "not inside brackets;");
+            test_command("ZoomInAll", mathml, "x");
+            test_command("MoveNext", mathml, "open");
+            assert_eq!(test_command("MoveMatchingFence", mathml, "close"), "move to matching, skipping something plus something over something, close paren");
+            assert_eq!(test_command("MoveMatchingFence", mathml, "open"), "move to matching, skipping something plus something over something, open paren");
+            test_command("MoveNext", mathml, "a");
+            assert_eq!(test_command("MoveMatchingFence", mathml, "close"), "move to end of group, close paren");
+            test_command("MovePrevious", mathml, "frac");
+            test_command("ZoomIn", mathml, "b");
+            assert_eq!(test_command("MoveOutOfContainer", mathml, "frac"), "move out, b over 2");
+            assert_eq!(test_command("MoveOutOfContainer", mathml, "group"), "move out, open paren, eigh plus b over 2, close paren");
+            assert_eq!(test_command("MoveOutOfContainer", mathml, "group"), "not inside a container;");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn toggle_glance() -> Result<()> {
+        let mathml_str = "<math display='block' id='id-0' data-id-added='true'>
+                <mrow id='id-1' data-id-added='true'>
+                <mi id='id-2' data-id-added='true'>a</mi>
+                <mo id='id-3' data-id-added='true'>=</mo>
+                <mfrac id='id-4' data-id-added='true'>
+                    <mi id='id-5' data-id-added='true'>x</mi>
+                    <mi id='id-6' data-id-added='true'>y</mi>
+                </mfrac>
+                </mrow>
+            </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("Language".to_string(), "en".to_string())?;
+        set_preference("SpeechStyle".to_string(), "SimpleSpeak".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            assert_eq!(test_command("ToggleGlance", mathml, "id-0"), "something is equal to, something over something,");
+            assert_eq!(test_command("ToggleGlance", mathml, "id-0"), "eigh is equal to x over y,");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn describe_matrix() -> Result<()> {
+        fn matrix(rows: &[&[&str]]) -> String {
+            let rows = rows.iter()
+                    .map(|row| format!("<mtr>{}</mtr>", row.iter().map(|cell| format!("<mtd><mn>{}</mn></mtd>", cell)).collect::<String>()))
+                    .collect::<String>();
+            return format!("<math id='id-0'><mrow><mo>(</mo><mtable id='table'>{}</mtable><mo>)</mo></mrow></math>", rows);
+        }
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("Language".to_string(), "en".to_string())?;
+        set_preference("SpeechStyle".to_string(), "ClearSpeak".to_string())?;
+        let tests = [
+            (matrix(&[&["1", "0"], &["0", "1"]]), "identity matrix"),
+            (matrix(&[&["2", "0"], &["0", "3"]]), "diagonal matrix"),
+            (matrix(&[&["1", "2", "3"], &["0", "4", "5"], &["0", "0", "6"]]), "upper triangular matrix"),
+            (matrix(&[&["1", "0"], &["7", "1"]]), "lower triangular matrix"),
+            (matrix(&[&["1", "2"], &["", "3"]]).replace("<mn></mn>", ""), "upper triangular matrix"),
+            (matrix(&[&["1", "2"], &["2", "1"]]), "symmetric matrix"),
+            (matrix(&[&["1", "2"], &["3", "4"]]), "no special form"),
+            (matrix(&[&["1", "2", "3"], &["4", "5", "6"]]), "not a square matrix"),
+            ("<math id='id-0'><mi>x</mi></math>".to_string(), "not a matrix"),
+        ];
+        for (mathml_str, speech) in tests {
+            set_mathml(mathml_str).unwrap();
+            MATHML_INSTANCE.with(|package_instance| {
+                let package_instance = package_instance.borrow();
+                let mathml = get_element(&*package_instance);
+                assert_eq!(test_command("DescribeMatrix", mathml, "id-0"), speech);
+            });
+        }
+        return Ok( () );
+    }
+
     #[test]
     fn move_enhanced_times() -> Result<()> {
         let mathml_str = "<math display='block' id='id-0' data-id-added='true'>
@@ -1438,6 +1828,147 @@ mod tests {
         });
     }
     
+    #[test]
+    fn move_cell_with_header() -> Result<()> {
+        let mathml_str = "<math id='nav-0'>
+        <mtable id='nav-1'>
+          <mtr id='nav-2'>
+            <mtd id='nav-3'><mtext id='nav-4'>x</mtext></mtd>
+            <mtd id='nav-5'><mtext id='nav-6'>y</mtext></mtd>
+          </mtr>
+          <mtr id='nav-7'>
+            <mtd id='nav-8'><mn id='nav-9'>1</mn></mtd>
+            <mtd id='nav-10'><mn id='nav-11'>3</mn></mtd>
+          </mtr>
+          <mtr id='nav-12'>
+            <mtd id='nav-13'><mn id='nav-14'>2</mn></mtd>
+            <mtd id='nav-15'><mn id='nav-16'>5</mn></mtd>
+          </mtr>
+        </mtable>
+       </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Medium".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomInAll", mathml, "nav-4");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-6"), "y");
+            assert_eq!(test_command("MoveCellDown", mathml, "nav-11"), "3");
+            assert_eq!(test_command("MoveCellPrevious", mathml, "nav-9"), "x column, 1");
+            assert_eq!(test_command("MoveCellDown", mathml, "nav-14"), "2");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-16"), "y column, 5");
+            set_preference("NavTableHeaders".to_string(), "false".to_string())?;
+            assert_eq!(test_command("MoveCellPrevious", mathml, "nav-14"), "2");
+            set_preference("NavTableHeaders".to_string(), "true".to_string())?;
+            return Ok( () );
+        });
+    }
+    
+    #[test]
+    fn move_cell_augmented_matrix() -> Result<()> {
+        let mathml_str = "<math id='nav-0'>
+        <mtable id='nav-1' columnlines='none solid'>
+          <mtr id='nav-2'>
+            <mtd id='nav-3'><mn id='nav-4'>1</mn></mtd>
+            <mtd id='nav-5'><mn id='nav-6'>2</mn></mtd>
+            <mtd id='nav-7'><mn id='nav-8'>5</mn></mtd>
+          </mtr>
+          <mtr id='nav-9'>
+            <mtd id='nav-10'><mn id='nav-11'>3</mn></mtd>
+            <mtd id='nav-12'><mn id='nav-13'>4</mn></mtd>
+            <mtd id='nav-14'><mn id='nav-15'>6</mn></mtd>
+          </mtr>
+        </mtable>
+       </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Medium".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomInAll", mathml, "nav-4");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-6"), "2");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-8"), "constants column, 5");
+            assert_eq!(test_command("MoveCellDown", mathml, "nav-15"), "6");
+            assert_eq!(test_command("ReadCellCurrent", mathml, "nav-15"), "row 2 constants column, 6");
+            assert_eq!(test_command("MoveCellPrevious", mathml, "nav-13"), "4");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn enumerated_parts() -> Result<()> {
+        let mathml_str = "<math id='nav-0'>
+            <mo id='nav-1'>(</mo><mi id='nav-2'>a</mi><mo id='nav-3'>)</mo>
+            <mi id='nav-4'>x</mi><mo id='nav-5'>+</mo><mn id='nav-6'>1</mn>
+            <mo id='nav-7'>(</mo><mi id='nav-8'>b</mi><mo id='nav-9'>)</mo>
+            <mi id='nav-10'>y</mi>
+           </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Medium".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            // the labels and parts are grouped by canonicalization: "(a)", separator, "x+1", separator, "(b)", separator, "y"
+            let mrow = crate::canonicalize::as_element(mathml.children()[0]);
+            let ids: Vec<String> = mrow.children().iter()
+                    .map(|&child| crate::canonicalize::as_element(child).attribute_value("id").unwrap().to_string())
+                    .collect();
+            assert_eq!(test_command("ZoomIn", mathml, &ids[0]), "part eigh;");
+            assert_eq!(test_command("MoveNext", mathml, &ids[2]), "x plus 1");
+            assert_eq!(test_command("MoveNext", mathml, &ids[4]), "part b;");
+            assert_eq!(test_command("MoveNext", mathml, "nav-10"), "y");
+            assert_eq!(test_command("MovePrevious", mathml, &ids[4]), "part b;");
+            return Ok( () );
+        });
+    }
+
+    #[test]
+    fn move_cell_with_header_labeled_rows() -> Result<()> {
+        // the labels of mlabeledtr rows aren't columns, so the headers are offset by one in those rows
+        let mathml_str = "<math id='nav-0'>
+        <mtable id='nav-1'>
+          <mlabeledtr id='nav-2'>
+            <mtd id='nav-3'><mn id='nav-4'>0</mn></mtd>
+            <mtd id='nav-5'><mtext id='nav-6'>x</mtext></mtd>
+            <mtd id='nav-7'><mtext id='nav-8'>y</mtext></mtd>
+          </mlabeledtr>
+          <mlabeledtr id='nav-9'>
+            <mtd id='nav-10'><mn id='nav-11'>1</mn></mtd>
+            <mtd id='nav-12'><mn id='nav-13'>1</mn></mtd>
+            <mtd id='nav-14'><mn id='nav-15'>3</mn></mtd>
+          </mlabeledtr>
+          <mlabeledtr id='nav-16'>
+            <mtd id='nav-17'><mn id='nav-18'>2</mn></mtd>
+            <mtd id='nav-19'><mn id='nav-20'>2</mn></mtd>
+            <mtd id='nav-21'><mn id='nav-22'>5</mn></mtd>
+          </mlabeledtr>
+        </mtable>
+       </math>";
+        crate::interface::set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_mathml(mathml_str.to_string()).unwrap();
+        set_preference("NavMode".to_string(), "Enhanced".to_string())?;
+        set_preference("NavVerbosity".to_string(), "Medium".to_string())?;
+        return MATHML_INSTANCE.with(|package_instance| {
+            let package_instance = package_instance.borrow();
+            let mathml = get_element(&*package_instance);
+            test_command("ZoomInAll", mathml, "nav-4");
+            assert_eq!(test_command("MoveCellDown", mathml, "nav-11"), "1");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-13"), "x column, 1");
+            assert_eq!(test_command("MoveCellNext", mathml, "nav-15"), "y column, 3");
+            assert_eq!(test_command("MoveCellDown", mathml, "nav-22"), "5");
+            assert_eq!(test_command("MoveCellPrevious", mathml, "nav-20"), "x column, 2");
+            // the label isn't a column
+            assert_eq!(test_command("MoveCellPrevious", mathml, "nav-18"), "2");
+            return Ok( () );
+        });
+    }
+    
     #[test]
     fn move_cell_char_mode() -> Result<()> {
         let mathml_str = "<math id='nav-0' data-id-added='true'>
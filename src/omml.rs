@@ -0,0 +1,307 @@
+#![allow(clippy::needless_return)]
+//! Converts Office Math (OMML), the format Word uses for equations, into MathML.
+//!
+//! Only the structure is converted (fractions, scripts, radicals, delimiters, n-ary operators, matrices, ...).
+//! The resulting MathML is not structured into operators and operands -- that is left to canonicalization,
+//!   just as it is for MathML from any other source.
+//! The element names are matched without their namespace prefix (normally `m:`).
+//! Formatting (`rPr`, `ctrlPr`, ...) other than the plain/normal text settings of a run is ignored.
+
+use sxd_document::dom::{ChildOfElement, Element};
+use sxd_document::parser;
+use crate::errors::*;
+
+/// Convert the OMML string (an `oMath` or `oMathPara` element) to a MathML string.
+pub fn omml_to_mathml(omml: &str) -> Result<String> {
+    let package = match parser::parse(omml) {
+        Err(e) => bail!("Invalid OMML input:\n{}\nError is: {}", omml, &e.to_string()),
+        Ok(package) => package,
+    };
+    let doc = package.as_document();
+    let root = match doc.root().children().iter().find_map(|child| child.element()) {
+        None => bail!("OMML input does not contain an element"),
+        Some(root) => root,
+    };
+    let content = match local_name(root) {
+        "oMath" => convert_children(root),
+        "oMathPara" => {
+            // a paragraph with more than one equation is a (one column) table of them
+            let equations = child_elements(root).into_iter()
+                    .filter(|&child| local_name(child) == "oMath")
+                    .collect::<Vec<Element>>();
+            if equations.len() == 1 {
+                convert_children(equations[0])
+            } else {
+                let rows = equations.into_iter()
+                        .map(|equation| format!("<mtr><mtd><mrow>{}</mrow></mtd></mtr>", convert_children(equation)))
+                        .collect::<String>();
+                format!("<mtable>{}</mtable>", rows)
+            }
+        },
+        name => bail!("OMML input must start with 'oMath' or 'oMathPara', not '{}'", name),
+    };
+    return Ok( format!("<math><mrow>{}</mrow></math>", content) );
+}
+
+fn convert(element: Element) -> String {
+    let name = local_name(element);
+    return match name {
+        "r" => convert_run(element),
+        "f" => convert_fraction(element),
+        "rad" => {
+            if property(element, "radPr", "degHide").is_some_and(|hide| is_on(&hide)) || child_named(element, "deg").is_none() {
+                format!("<msqrt>{}</msqrt>", arg(element, "e"))
+            } else {
+                format!("<mroot>{}{}</mroot>", arg(element, "e"), arg(element, "deg"))
+            }
+        },
+        "d" => convert_delimiter(element),
+        "nary" => convert_nary(element),
+        "func" => format!("<mrow>{}<mo>&#x2061;</mo>{}</mrow>", arg(element, "fName"), arg(element, "e")),
+        "sSub" => format!("<msub>{}{}</msub>", arg(element, "e"), arg(element, "sub")),
+        "sSup" => format!("<msup>{}{}</msup>", arg(element, "e"), arg(element, "sup")),
+        "sSubSup" => format!("<msubsup>{}{}{}</msubsup>", arg(element, "e"), arg(element, "sub"), arg(element, "sup")),
+        "sPre" => format!("<mmultiscripts>{}<mprescripts/>{}{}</mmultiscripts>", arg(element, "e"), arg(element, "sub"), arg(element, "sup")),
+        "acc" => {
+            let accent = property(element, "accPr", "chr").unwrap_or_else(|| "\u{0302}".to_string());
+            format!("<mover accent='true'>{}<mo>{}</mo></mover>", arg(element, "e"), escape(&accent))
+        },
+        "bar" => {
+            if property(element, "barPr", "pos").as_deref() == Some("top") {
+                format!("<mover accent='true'>{}<mo>¯</mo></mover>", arg(element, "e"))
+            } else {
+                format!("<munder accentunder='true'>{}<mo>_</mo></munder>", arg(element, "e"))
+            }
+        },
+        "groupChr" => {
+            let ch = escape(&property(element, "groupChrPr", "chr").unwrap_or_else(|| "\u{23DF}".to_string()));
+            if property(element, "groupChrPr", "pos").as_deref() == Some("top") {
+                format!("<mover>{}<mo>{}</mo></mover>", arg(element, "e"), ch)
+            } else {
+                format!("<munder>{}<mo>{}</mo></munder>", arg(element, "e"), ch)
+            }
+        },
+        "limLow" => format!("<munder>{}{}</munder>", arg(element, "e"), arg(element, "lim")),
+        "limUpp" => format!("<mover>{}{}</mover>", arg(element, "e"), arg(element, "lim")),
+        "m" => {
+            let rows = child_elements(element).into_iter()
+                    .filter(|&row| local_name(row) == "mr")
+                    .map(|row| format!("<mtr>{}</mtr>", table_cells(row)))
+                    .collect::<String>();
+            format!("<mtable>{}</mtable>", rows)
+        },
+        "eqArr" => {
+            let rows = child_elements(element).into_iter()
+                    .filter(|&row| local_name(row) == "e")
+                    .map(|row| format!("<mtr><mtd><mrow>{}</mrow></mtd></mtr>", convert_children(row)))
+                    .collect::<String>();
+            format!("<mtable>{}</mtable>", rows)
+        },
+        "borderBox" => format!("<menclose notation='box'>{}</menclose>", arg(element, "e")),
+        "phant" => format!("<mphantom>{}</mphantom>", arg(element, "e")),
+        "box" | "e" | "num" | "den" | "sub" | "sup" | "deg" | "lim" | "fName" | "oMath" => {
+            format!("<mrow>{}</mrow>", convert_children(element))
+        },
+        _ if name.ends_with("Pr") => "".to_string(),      // properties are handled by the element they are part of
+        _ => convert_children(element),      // e.g., bookmarks or Word run wrappers -- just keep what is inside
+    };
+}
+
+fn convert_children(element: Element) -> String {
+    return child_elements(element).into_iter().map(convert).collect::<String>();
+}
+
+/// Convert the child named 'name' (e.g., "e" or "sub") to an mrow -- if it doesn't exist, an empty mrow is returned
+fn arg(element: Element, name: &str) -> String {
+    return match child_named(element, name) {
+        None => "<mrow></mrow>".to_string(),
+        Some(child) => format!("<mrow>{}</mrow>", convert_children(child)),
+    };
+}
+
+fn table_cells(row: Element) -> String {
+    return child_elements(row).into_iter()
+            .filter(|&cell| local_name(cell) == "e")
+            .map(|cell| format!("<mtd><mrow>{}</mrow></mtd>", convert_children(cell)))
+            .collect::<String>();
+}
+
+/// A run of text is split into numbers (mn), identifiers (mi), and operators (mo).
+/// Plain text (`sty` is `p`) keeps runs of letters together (e.g., "sin") and normal text (`nor`) is an mtext.
+fn convert_run(run: Element) -> String {
+    let text = child_elements(run).into_iter()
+            .filter(|&child| local_name(child) == "t")
+            .map(text_of)
+            .collect::<String>();
+    if child_named(run, "rPr").and_then(|rpr| child_named(rpr, "nor")).is_some() {
+        return format!("<mtext>{}</mtext>", escape(&text));
+    }
+    let is_plain = property(run, "rPr", "sty").as_deref() == Some("p");
+
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut result = String::with_capacity(8 * chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let start = i;
+        i += 1;
+        if ch.is_ascii_digit() {
+            while i < chars.len() &&
+                  (chars[i].is_ascii_digit() || (chars[i] == '.' && i+1 < chars.len() && chars[i+1].is_ascii_digit())) {
+                i += 1;
+            }
+            result += &format!("<mn>{}</mn>", chars[start..i].iter().collect::<String>());
+        } else if ch.is_alphabetic() {
+            if is_plain {
+                while i < chars.len() && chars[i].is_alphabetic() {
+                    i += 1;
+                }
+                let word = chars[start..i].iter().collect::<String>();
+                result += &if i - start == 1 {
+                    format!("<mi mathvariant='normal'>{}</mi>", escape(&word))
+                } else {
+                    format!("<mi>{}</mi>", escape(&word))
+                };
+            } else {
+                result += &format!("<mi>{}</mi>", escape(&ch.to_string()));
+            }
+        } else if !ch.is_whitespace() {
+            result += &format!("<mo>{}</mo>", escape(&ch.to_string()));
+        }
+    }
+    return result;
+}
+
+/// Fractions can be stacked (bar), stacked without a bar (noBar), linear (lin), or skewed (skw)
+fn convert_fraction(fraction: Element) -> String {
+    let num = arg(fraction, "num");
+    let den = arg(fraction, "den");
+    return match property(fraction, "fPr", "type").as_deref() {
+        Some("noBar") => format!("<mfrac linethickness='0'>{}{}</mfrac>", num, den),
+        Some("lin") => format!("<mrow>{}<mo>/</mo>{}</mrow>", num, den),
+        Some("skw") => format!("<mfrac bevelled='true'>{}{}</mfrac>", num, den),
+        _ => format!("<mfrac>{}{}</mfrac>", num, den),
+    };
+}
+
+/// The delimiters default to parens and the separator defaults to "|". An empty value means there is no character.
+fn convert_delimiter(delimiter: Element) -> String {
+    let open = property(delimiter, "dPr", "begChr").unwrap_or_else(|| "(".to_string());
+    let close = property(delimiter, "dPr", "endChr").unwrap_or_else(|| ")".to_string());
+    let separator = property(delimiter, "dPr", "sepChr").unwrap_or_else(|| "|".to_string());
+    let contents = child_elements(delimiter).into_iter()
+            .filter(|&child| local_name(child) == "e")
+            .map(|child| format!("<mrow>{}</mrow>", convert_children(child)))
+            .collect::<Vec<String>>()
+            .join(&format!("<mo>{}</mo>", escape(&separator)));
+    let open = if open.is_empty() {"".to_string()} else {format!("<mo>{}</mo>", escape(&open))};
+    let close = if close.is_empty() {"".to_string()} else {format!("<mo>{}</mo>", escape(&close))};
+    return format!("<mrow>{}{}{}</mrow>", open, contents, close);
+}
+
+/// N-ary operators (sums, integrals, ...) have limits either under/over (undOvr) or as scripts (subSup).
+/// The default character is an integral and the default location depends upon the operator.
+fn convert_nary(nary: Element) -> String {
+    let op = property(nary, "naryPr", "chr").unwrap_or_else(|| "∫".to_string());
+    let is_under_over = match property(nary, "naryPr", "limLoc").as_deref() {
+        Some("undOvr") => true,
+        Some("subSup") => false,
+        _ => !matches!(op.as_str(), "∫" | "∬" | "∭" | "∮" | "∯" | "∰"),
+    };
+    let has_sub = !property(nary, "naryPr", "subHide").is_some_and(|hide| is_on(&hide));
+    let has_sup = !property(nary, "naryPr", "supHide").is_some_and(|hide| is_on(&hide));
+    let op = format!("<mo>{}</mo>", escape(&op));
+    let operator = match (has_sub, has_sup, is_under_over) {
+        (false, false, _) => op,
+        (true, false, true) => format!("<munder>{}{}</munder>", op, arg(nary, "sub")),
+        (true, false, false) => format!("<msub>{}{}</msub>", op, arg(nary, "sub")),
+        (false, true, true) => format!("<mover>{}{}</mover>", op, arg(nary, "sup")),
+        (false, true, false) => format!("<msup>{}{}</msup>", op, arg(nary, "sup")),
+        (true, true, true) => format!("<munderover>{}{}{}</munderover>", op, arg(nary, "sub"), arg(nary, "sup")),
+        (true, true, false) => format!("<msubsup>{}{}{}</msubsup>", op, arg(nary, "sub"), arg(nary, "sup")),
+    };
+    return format!("<mrow>{}{}</mrow>", operator, arg(nary, "e"));
+}
+
+fn local_name<'a>(element: Element<'a>) -> &'a str {
+    return element.name().local_part();
+}
+
+fn child_elements(element: Element) -> Vec<Element> {
+    return element.children().into_iter()
+            .filter_map(|child| match child {
+                ChildOfElement::Element(child) => Some(child),
+                _ => None,
+            })
+            .collect();
+}
+
+fn child_named<'a>(element: Element<'a>, name: &str) -> Option<Element<'a>> {
+    return child_elements(element).into_iter().find(|&child| local_name(child) == name);
+}
+
+/// Returns the 'val' attribute of the 'property_name' child of the 'properties_name' (e.g., "fPr") child of 'element'.
+/// A property without a 'val' attribute (e.g., `<m:degHide/>`) has the value "on".
+fn property(element: Element, properties_name: &str, property_name: &str) -> Option<String> {
+    let property = child_named(child_named(element, properties_name)?, property_name)?;
+    return Some(
+        property.attributes().iter()
+            .find(|attr| attr.name().local_part() == "val")
+            .map_or_else(|| "on".to_string(), |attr| attr.value().to_string())
+    );
+}
+
+fn is_on(value: &str) -> bool {
+    return matches!(value, "on" | "1" | "true");
+}
+
+fn text_of(element: Element) -> String {
+    return element.children().into_iter()
+            .filter_map(|child| child.text())
+            .map(|text| text.text())
+            .collect::<String>();
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OMML_NS: &str = "xmlns:m='http://schemas.openxmlformats.org/officeDocument/2006/math'";
+
+    #[test]
+    fn fraction_and_script() {
+        let omml = format!("<m:oMath {}><m:f><m:num><m:r><m:t>1</m:t></m:r></m:num><m:den><m:r><m:t>x</m:t></m:r></m:den></m:f>
+                <m:r><m:t>+</m:t></m:r><m:sSup><m:e><m:r><m:t>y</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup></m:oMath>", OMML_NS);
+        assert_eq!(omml_to_mathml(&omml).unwrap(),
+            "<math><mrow><mfrac><mrow><mn>1</mn></mrow><mrow><mi>x</mi></mrow></mfrac><mo>+</mo>\
+             <msup><mrow><mi>y</mi></mrow><mrow><mn>2</mn></mrow></msup></mrow></math>");
+    }
+
+    #[test]
+    fn delimiter_and_function() {
+        let omml = format!("<m:oMath {}><m:func><m:fName><m:r><m:rPr><m:sty m:val='p'/></m:rPr><m:t>sin</m:t></m:r></m:fName>
+                <m:e><m:d><m:dPr><m:begChr m:val='['/><m:endChr m:val=']'/></m:dPr><m:e><m:r><m:t>x</m:t></m:r></m:e></m:d></m:e></m:func></m:oMath>", OMML_NS);
+        assert_eq!(omml_to_mathml(&omml).unwrap(),
+            "<math><mrow><mrow><mrow><mi>sin</mi></mrow><mo>&#x2061;</mo><mrow><mrow><mo>[</mo><mrow><mi>x</mi></mrow><mo>]</mo></mrow></mrow></mrow></mrow></math>");
+    }
+
+    #[test]
+    fn nary_and_radical() {
+        let omml = format!("<m:oMath {}><m:nary><m:naryPr><m:chr m:val='∑'/></m:naryPr><m:sub><m:r><m:t>i=1</m:t></m:r></m:sub>
+                <m:sup><m:r><m:t>n</m:t></m:r></m:sup><m:e><m:rad><m:radPr><m:degHide m:val='1'/></m:radPr><m:deg/><m:e><m:r><m:t>i</m:t></m:r></m:e></m:rad></m:e></m:nary></m:oMath>", OMML_NS);
+        assert_eq!(omml_to_mathml(&omml).unwrap(),
+            "<math><mrow><mrow><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mrow><mi>n</mi></mrow></munderover>\
+             <mrow><msqrt><mrow><mi>i</mi></mrow></msqrt></mrow></mrow></mrow></math>");
+    }
+
+    #[test]
+    fn bad_input() {
+        assert!(omml_to_mathml("<m:oMath").is_err());
+        assert!(omml_to_mathml("<math><mi>x</mi></math>").is_err());
+    }
+}
@@ -29,13 +29,23 @@ use std::time::SystemTime;
 use std::env;
 use crate::speech::{as_str_checked, RulesFor};
 use crate::interface::errors_to_string;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::shim_filesystem::*;
 use crate::errors::*;
+use phf::phf_map;
 
 /// Use to indicate preference not found with Preference::to_string()
 pub static NO_PREFERENCE: &str = "\u{FFFF}";
 
+/// The user prefs that each `MathLevel` sets. All the levels set the same prefs.
+/// A pref that is set individually (with `set_user_prefs`) overrides the level's value.
+static MATH_LEVEL_PRESETS: phf::Map<&str, &[(&str, &str)]> = phf_map! {
+    "Elementary" => &[("Verbosity", "Verbose"), ("ClearSpeak_Fractions", "Auto"), ("ClearSpeak_ImpliedTimes", "MoreImpliedTimes"), ("Chemistry", "Off")],
+    "MiddleSchool" => &[("Verbosity", "Medium"), ("ClearSpeak_Fractions", "Auto"), ("ClearSpeak_ImpliedTimes", "MoreImpliedTimes"), ("Chemistry", "Off")],
+    "HighSchool" => &[("Verbosity", "Medium"), ("ClearSpeak_Fractions", "Auto"), ("ClearSpeak_ImpliedTimes", "Auto"), ("Chemistry", "SpellOut")],
+    "University" => &[("Verbosity", "Terse"), ("ClearSpeak_Fractions", "Over"), ("ClearSpeak_ImpliedTimes", "None"), ("Chemistry", "SpellOut")],
+};
+
 // Preferences are recorded here
 /// Preferences are stored in a HashMap. It maps the name of the pref (a String) to its value (stored as YAML string/float)
 pub type PreferenceHashMap = HashMap<String, Yaml>;
@@ -59,19 +69,41 @@ impl fmt::Display for Preferences {
 impl Preferences{
     // default values needed in case nothing else gets set 
     fn user_defaults() -> Preferences {
-        let mut prefs = PreferenceHashMap::with_capacity(39);
+        let mut prefs = PreferenceHashMap::with_capacity(44);
         prefs.insert("Language".to_string(), Yaml::String("en".to_string()));
         prefs.insert("SpeechStyle".to_string(), Yaml::String("ClearSpeak".to_string()));
         prefs.insert("Verbosity".to_string(), Yaml::String("medium".to_string()));
         prefs.insert("SpeechOverrides_CapitalLetters".to_string(), Yaml::String("".to_string())); // important for testing
+        prefs.insert("ScriptOrder_Subscript".to_string(), Yaml::String("BaseFirst".to_string()));
+        prefs.insert("ScriptOrder_Superscript".to_string(), Yaml::String("BaseFirst".to_string()));
         prefs.insert("Blind".to_string(), Yaml::Boolean(true));
         prefs.insert("MathRate".to_string(), Yaml::String("100.0".to_string()));
         prefs.insert("PauseFactor".to_string(), Yaml::String("100.0".to_string()));
+        prefs.insert("SentenceLength".to_string(), Yaml::String("0".to_string()));
+        prefs.insert("Solidus".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("RangeDash".to_string(), Yaml::String("To".to_string()));
+        prefs.insert("AltText".to_string(), Yaml::String("Ignore".to_string()));
+        prefs.insert("ConstantNames".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("RelationChains".to_string(), Yaml::String("Linear".to_string()));
+        prefs.insert("StructureAnnouncement".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("TextVoice".to_string(), Yaml::String("None".to_string()));
+        prefs.insert("EmptyCells".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("FunctionGuess".to_string(), Yaml::String("Neutral".to_string()));
+        prefs.insert("Vectors".to_string(), Yaml::String("Arrow".to_string()));
+        prefs.insert("FractionOrder".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("Lists".to_string(), Yaml::String("Off".to_string()));
+        prefs.insert("ListSummaryLength".to_string(), Yaml::String("10".to_string()));
+        prefs.insert("ListSummaryItems".to_string(), Yaml::String("3".to_string()));
+        prefs.insert("MathAnnouncement".to_string(), Yaml::String("None".to_string()));
+        prefs.insert("MathAnnouncementLength".to_string(), Yaml::String("0".to_string()));
+        prefs.insert("MathLevel".to_string(), Yaml::String("Auto".to_string()));
+        prefs.insert("SubjectArea".to_string(), Yaml::String("General".to_string()));
         prefs.insert("NavMode".to_string(), Yaml::String("enhanced".to_string()));
         prefs.insert("Overview".to_string(), Yaml::String("read".to_string()));
         prefs.insert("ResetOverView".to_string(), Yaml::Boolean(true));
         prefs.insert("NavVerbosity".to_string(), Yaml::String("verbose".to_string()));
         prefs.insert("AutoZoomOut".to_string(), Yaml::Boolean(true));
+        prefs.insert("NavTableHeaders".to_string(), Yaml::Boolean(true));
         prefs.insert("BrailleCode".to_string(), Yaml::String("Nemeth".to_string()));
         prefs.insert("BrailleNavHighlight".to_string(), Yaml::String("EndPoints".to_string()));
     
@@ -80,7 +112,7 @@ impl Preferences{
 
     // default values needed in case nothing else gets set 
     fn api_defaults() -> Preferences {
-        let mut prefs = PreferenceHashMap::with_capacity(19);
+        let mut prefs = PreferenceHashMap::with_capacity(20);
         prefs.insert("TTS".to_string(), Yaml::String("none".to_string()));
         prefs.insert("Pitch".to_string(), Yaml::Real("0.0".to_string()));
         prefs.insert("Rate".to_string(), Yaml::Real("180.0".to_string()));
@@ -92,20 +124,36 @@ impl Preferences{
         prefs.insert("CapitalLetters_Pitch".to_string(), Yaml::Real("0.0".to_string()));
         prefs.insert("CapitalLetters_Beep".to_string(), Yaml::Boolean(false));
         prefs.insert("IntentErrorRecovery".to_string(), Yaml::String("IgnoreIntent".to_string()));    // also Error
+        prefs.insert("GrammaticalCase".to_string(), Yaml::String("Nominative".to_string()));    // case of the end of the expr (e.g., "Genitive")
+        prefs.insert("PreserveAttributes".to_string(), Yaml::Boolean(false));    // keep removed attrs as 'data-orig-*'
+        prefs.insert("Earcons".to_string(), Yaml::Boolean(false));    // experimental: mark structure with sounds
+        prefs.insert("GeneratorQuirks".to_string(), Yaml::String("All".to_string()));    // generators whose quirks are repaired (or "None")
+        prefs.insert("AnnotationFallback".to_string(), Yaml::String("All".to_string()));    // annotation used for an image in semantics (or "MathML", "None")
+        prefs.insert("TextTables".to_string(), Yaml::Boolean(false));    // turn columns of text (e.g., from PDFs) into an mtable
+        prefs.insert("RomanNumerals".to_string(), Yaml::Boolean(false));    // detect roman numerals (e.g., "XLVIII") from context
+        prefs.insert("Validation".to_string(), Yaml::String("Standard".to_string()));    // how invalid MathML is handled (also "Strict", "Lenient")
         return Preferences{ prefs };
     }
 
     // Before we can get the other files, we need the preferences.
     // To get them we need to read pref files, so the pref file reading is different than the other files
-    fn from_file(rules_dir: &Path) -> Result<(Preferences, FileAndTime)> {
+    // Also returns the names of the prefs set in the user's prefs file (they override 'MathLevel')
+    fn from_file(rules_dir: &Path) -> Result<(Preferences, HashSet<String>, FileAndTime)> {
         let files = Preferences::get_prefs_file_and_time(rules_dir);
         return DEFAULT_USER_PREFERENCES.with(|defaults| {
             let system_prefs = Preferences::read_file(&files.files[0], defaults.clone())?;
             let system_prefs = Preferences::read_file(&files.files[1], system_prefs)?;
-            return Ok((system_prefs, files));
+            let user_file_pref_names = Preferences::pref_names_in_file(&files.files[1])?;
+            return Ok((system_prefs, user_file_pref_names, files));
         });
     }
 
+    /// The names of the prefs set in 'file' ('MathLevel' is not included because it isn't overridden by itself)
+    fn pref_names_in_file(file: &Option<PathBuf>) -> Result<HashSet<String>> {
+        let file_prefs = Preferences::read_file(file, Preferences::default())?;
+        return Ok( file_prefs.prefs.into_keys().filter(|name| name != "MathLevel").collect() );
+    }
+
     fn get_prefs_file_and_time(rules_dir: &Path) -> FileAndTime {
         let mut system_prefs_file = rules_dir.to_path_buf();
         system_prefs_file.push("prefs.yaml");
@@ -271,8 +319,8 @@ struct FileAndTime {
 
 impl PartialEq for FileAndTime {
     fn eq(&self, other: &Self) -> bool {
-        // FIX: anticipating changing Locations to single PathBuf
-        return self.files[0] == other.files[0] && self.time == other.time;
+        // all the files are compared -- for definitions.yaml, files[0] is the shared file and the language's file comes after it
+        return self.files == other.files && self.time == other.time;
     }
 }
 impl Eq for FileAndTime {}
@@ -293,11 +341,14 @@ thread_local!{
 // Note: I experimented with PREF_MANAGER being a Result<PreferenceManager> in the case of no rule files,
 //   but it ended up being a mess (lots of unwrapping). Having a field is much cleaner.
 //   Also note that if 'error' is not an empty string, SpeechRules can't work so using those requires a check.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct PreferenceManager {
     rules_dir: Option<PathBuf>,         // full path to rules dir
     error: String,                      // empty/default string if fields are set, otherwise error message
     user_prefs: Preferences,
+    file_prefs: Preferences,            // the user prefs as read from the files (before 'MathLevel' and individual changes)
+    explicit_prefs: HashSet<String>,    // names of the user prefs changed individually (they override 'MathLevel')
+    user_file_pref_names: HashSet<String>,  // names of the prefs set in the user's prefs.yaml (they also override 'MathLevel')
     api_prefs: Preferences,
     pref_files: FileAndTime,            // the "raw" user preference files (converted to 'user_prefs')
     intent: FileAndTime,                // the intent rule style file(s)
@@ -331,7 +382,7 @@ impl fmt::Display for PreferenceManager {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct FilesChanged {
     pub speech_rules: bool,
     pub speech_unicode_short: bool,
@@ -375,13 +426,19 @@ impl PreferenceManager {
         if self.api_prefs.prefs.is_empty() {
             self.api_prefs = Preferences{ prefs: DEFAULT_API_PREFERENCES.with(|defaults| defaults.prefs.clone()) };
         }
+        // the user prefs are reset to the values in the files, so nothing has been changed individually
+        self.explicit_prefs.clear();
+        self.user_file_pref_names.clear();
 
         match PreferenceManager::find_rules_dir(&rules_dir) {
             Ok(rules_dir) => {
-                let (user_prefs, pref_files) = Preferences::from_file(&rules_dir)?;
+                let (user_prefs, user_file_pref_names, pref_files) = Preferences::from_file(&rules_dir)?;
+                self.file_prefs = user_prefs.clone();
+                self.user_file_pref_names = user_file_pref_names;
                 match self.set_all_files(&rules_dir, user_prefs, pref_files) {
                     Ok(_) => {
                         self.error = String::new();
+                        self.apply_math_level();
                         return Ok(())
                     },
                     Err(e) => self.error = errors_to_string(&e),
@@ -771,6 +828,84 @@ impl PreferenceManager {
         return self.user_prefs.to_string("Language");
     }
 
+    /// Return the languages (e.g., "en", "en-gb") that have speech rules in the Rules dir along with a human-readable name.
+    pub fn get_supported_languages(&self) -> Result<Vec<(String, String)>> {
+        static LANGUAGE_NAMES: phf::Map<&str, &str> = phf_map! {
+            "en" => "English",
+            "fi" => "Finnish",
+            "id" => "Indonesian",
+            "vi" => "Vietnamese",
+        };
+        let languages_dir = self.get_rules_dir()?.join("Languages");
+        let mut languages = Vec::new();
+        for language in sorted_dirs(&languages_dir) {
+            if language == "zz" {
+                continue;       // "zz" is a made-up language that is used for testing
+            }
+            let language_name = LANGUAGE_NAMES.get(language.as_str()).map_or(language.clone(), |name| name.to_string());
+            languages.push( (language.clone(), language_name.clone()) );
+            // regional variants are subdirs (e.g., "en/gb")
+            for region in sorted_dirs(&languages_dir.join(&language)) {
+                if region != "SharedRules" {
+                    languages.push( (format!("{}-{}", language, region), format!("{} ({})", language_name, region.to_ascii_uppercase())) );
+                }
+            }
+        }
+        return Ok(languages);
+    }
+
+    /// Return the speech styles (e.g., "ClearSpeak") that have rules for 'language' (including the rules of the main language of a regional variant).
+    pub fn get_speech_styles(&self, language: &str) -> Result<Vec<String>> {
+        let mut language_dir = self.get_rules_dir()?.join("Languages");
+        let mut styles = Vec::new();
+        for part in language.split('-') {
+            language_dir.push(part);
+            if !is_dir_shim(&language_dir) {
+                break;
+            }
+            for file_name in read_dir_shim(&language_dir) {
+                if let Some(style) = file_name.strip_suffix("_Rules.yaml") {
+                    if !styles.iter().any(|s| s == style) {
+                        styles.push(style.to_string());
+                    }
+                }
+            }
+        }
+        if styles.is_empty() {
+            bail!("No speech styles found for language '{}'", language);
+        }
+        styles.sort();
+        return Ok(styles);
+    }
+
+    /// Return the braille codes (e.g., "UEB") that have rules in the Rules dir along with a human-readable name.
+    pub fn get_braille_codes(&self) -> Result<Vec<(String, String)>> {
+        static BRAILLE_CODE_NAMES: phf::Map<&str, &str> = phf_map! {
+            "Nemeth" => "Nemeth Code",
+            "UEB" => "Unified English Braille",
+            "Finnish" => "Finnish Braille",
+            "Vietnam" => "Vietnamese Braille",
+            "Spoken" => "Word Braille (the spoken form)",
+        };
+        let braille_dir = self.get_rules_dir()?.join("Braille");
+        return Ok(
+            sorted_dirs(&braille_dir).into_iter()
+                .filter(|code| is_file_shim(&braille_dir.join(code).join(code.clone() + "_Rules.yaml")))   // skips "SharedComponents"
+                .map(|code| {
+                    let name = BRAILLE_CODE_NAMES.get(code.as_str()).map_or(code.clone(), |name| name.to_string());
+                    (code, name)
+                })
+                .collect()
+        );
+    }
+
+    fn get_rules_dir(&self) -> Result<&Path> {
+        return match &self.rules_dir {
+            None => bail!("The Rules directory has not been set: {}", self.error),
+            Some(rules_dir) => Ok(rules_dir),
+        };
+    }
+
     pub fn get_api_prefs(&self) -> &Preferences {
         return &self.api_prefs;
     }
@@ -779,44 +914,137 @@ impl PreferenceManager {
         return &self.user_prefs;
     }
 
+    /// Return the names (sorted) of the preferences that were changed from their initial values.
+    /// For user prefs, these are the ones set individually and 'MathLevel' (but not the prefs it sets);
+    ///   for api prefs, these are the ones that differ from the defaults.
+    pub fn get_changed_pref_names(&self) -> Vec<String> {
+        let mut names = self.explicit_prefs.iter().cloned().collect::<Vec<String>>();
+        if self.user_prefs.prefs.get("MathLevel") != self.file_prefs.prefs.get("MathLevel") {
+            names.push("MathLevel".to_string());
+        }
+        DEFAULT_API_PREFERENCES.with(|defaults| {
+            names.extend(self.api_prefs.prefs.iter()
+                    .filter(|(name, value)| defaults.prefs.get(*name) != Some(value))
+                    .map(|(name, _)| name.clone()));
+        });
+        names.sort();
+        names.dedup();
+        return names;
+    }
+
     // occasionally useful to check a pref value when debugging
     // fn get_pref(&self, pref_name: &str) -> String {
     //     return yaml_to_string(self.user_prefs.prefs.get(pref_name).unwrap(), 1);
     // }
 
     pub fn set_user_prefs(&mut self, name: &str, value: &str) -> Option<FilesChanged> {
+        if name != "MathLevel" {
+            self.explicit_prefs.insert(name.to_string());
+        }
+        return self.change_user_pref(name, value);
+    }
+
+    /// Same as `set_user_prefs`, but the change is temporary (it is made to a copy of the preferences used for a single call),
+    ///   so the pref doesn't count as being set individually (it doesn't override 'MathLevel' and isn't exported).
+    pub fn set_temporary_user_pref(&mut self, name: &str, value: &str) -> Option<FilesChanged> {
+        return self.change_user_pref(name, value);
+    }
+
+    /// The files used by this manager that differ from the ones used by 'other' (e.g., because 'Language' differs).
+    /// Only the file names are compared (not when they were read). Returns 'None' if they all are the same.
+    pub fn files_changed(&self, other: &PreferenceManager) -> Option<FilesChanged> {
+        let changed = FilesChanged {
+            speech_rules: self.speech.files != other.speech.files,
+            speech_unicode_short: self.speech_unicode.files != other.speech_unicode.files,
+            speech_unicode_full: self.speech_unicode_full.files != other.speech_unicode_full.files,
+            braille_rules: self.braille.files != other.braille.files,
+            braille_unicode_short: self.braille_unicode.files != other.braille_unicode.files,
+            braille_unicode_full: self.braille_unicode_full.files != other.braille_unicode_full.files,
+            intent: self.intent.files != other.intent.files,
+            defs: self.defs.files != other.defs.files,
+        };
+        return if changed.speech_rules || changed.speech_unicode_short || changed.speech_unicode_full ||
+                  changed.braille_rules || changed.braille_unicode_short || changed.braille_unicode_full ||
+                  changed.intent || changed.defs {
+            Some(changed)
+        } else {
+            None
+        };
+    }
+
+    fn change_user_pref(&mut self, name: &str, value: &str) -> Option<FilesChanged> {
         if !self.error.is_empty() {
             panic!("Internal error: set_user_prefs called on invalid PreferenceManager -- error message\n{}", &self.error);
         };
 
         self.user_prefs.set_string_value(name, value);
+        if name == "MathLevel" {
+            self.apply_math_level();
+        }
         if name == "Language" || name == "SpeechStyle" || name == "BrailleCode" {
-            let old_speech = self.speech.clone();
-            let old_speech_unicode= self.speech_unicode.clone();
-            let old_speech_unicode_full = self.speech_unicode_full.clone();
-            let old_braille = self.braille.clone();
-            let old_braille_unicode= self.braille_unicode.clone();
-            let old_braille_unicode_full = self.braille_unicode_full.clone();
-            let old_intent= self.intent.clone();
-            let old_defs= self.defs.clone();
-
-            if let Some(rules_dir) = self.rules_dir.clone() {
-                self.set_all_files(&rules_dir, self.user_prefs.clone(), self.pref_files.clone()).unwrap();
-                let changed = FilesChanged {
-                    speech_rules: old_speech != self.speech,
-                    speech_unicode_short: old_speech_unicode != self.speech_unicode,
-                    speech_unicode_full: old_speech_unicode_full != self.speech_unicode_full,
-                    braille_rules: old_braille != self.braille,
-                    braille_unicode_short: old_braille_unicode != self.braille_unicode,
-                    braille_unicode_full: old_braille_unicode_full != self.braille_unicode_full,
-                    intent: old_intent != self.intent,
-                    defs: old_defs != self.defs,
-                };
-                return Some(changed);
-            }
+            return self.update_files();
+        }
+        return None;
+    }
+
+    /// Find the files for the current 'Language', 'SpeechStyle', and 'BrailleCode' and return which of them changed
+    fn update_files(&mut self) -> Option<FilesChanged> {
+        let old_speech = self.speech.clone();
+        let old_speech_unicode= self.speech_unicode.clone();
+        let old_speech_unicode_full = self.speech_unicode_full.clone();
+        let old_braille = self.braille.clone();
+        let old_braille_unicode= self.braille_unicode.clone();
+        let old_braille_unicode_full = self.braille_unicode_full.clone();
+        let old_intent= self.intent.clone();
+        let old_defs= self.defs.clone();
+
+        if let Some(rules_dir) = self.rules_dir.clone() {
+            self.set_all_files(&rules_dir, self.user_prefs.clone(), self.pref_files.clone()).unwrap();
+            let changed = FilesChanged {
+                speech_rules: old_speech != self.speech,
+                speech_unicode_short: old_speech_unicode != self.speech_unicode,
+                speech_unicode_full: old_speech_unicode_full != self.speech_unicode_full,
+                braille_rules: old_braille != self.braille,
+                braille_unicode_short: old_braille_unicode != self.braille_unicode,
+                braille_unicode_full: old_braille_unicode_full != self.braille_unicode_full,
+                intent: old_intent != self.intent,
+                defs: old_defs != self.defs,
+            };
+            return Some(changed);
         }
         return None;
     }
+
+    /// Set the user prefs that are controlled by 'MathLevel' unless they were changed individually or set in the user's prefs.yaml.
+    /// For "Auto" (or an unknown level), the values from the preference files are used.
+    fn apply_math_level(&mut self) {
+        let level = self.user_prefs.to_string("MathLevel");
+        let preset = MATH_LEVEL_PRESETS.get(level.as_str());
+        if preset.is_none() && level != "Auto" {
+            warn!("Unknown MathLevel '{}' -- using the individual preference values", level);
+        }
+        for (name, _) in MATH_LEVEL_PRESETS.values().next().unwrap().iter() {
+            if self.explicit_prefs.contains(*name) || self.user_file_pref_names.contains(*name) {
+                continue;
+            }
+            let value = match preset {
+                Some(preset) => preset.iter().find(|(preset_name, _)| preset_name == name).unwrap().1.to_string(),
+                None => self.file_prefs.to_string(name),
+            };
+            if value != NO_PREFERENCE {
+                self.user_prefs.set_string_value(name, &value);
+            }
+        }
+    }
+}
+
+/// Return the (sorted) names of the dirs in 'dir'
+fn sorted_dirs(dir: &Path) -> Vec<String> {
+    let mut dirs: Vec<String> = read_dir_shim(dir).into_iter()
+            .filter(|name| is_dir_shim(&dir.join(name)))
+            .collect();
+    dirs.sort();
+    return dirs;
 }
 
 
@@ -990,6 +1218,55 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_math_level() {
+        PREF_MANAGER.with(|pref_manager| {
+            let mut pref_manager = pref_manager.borrow_mut();
+            pref_manager.initialize(abs_rules_dir_path()).unwrap();
+            pref_manager.set_user_prefs("MathLevel", "Elementary");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("Verbosity"), "Verbose");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("ClearSpeak_ImpliedTimes"), "MoreImpliedTimes");
+
+            // an individual change overrides the level
+            pref_manager.set_user_prefs("Verbosity", "Medium");
+            pref_manager.set_user_prefs("MathLevel", "University");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("Verbosity"), "Medium");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("ClearSpeak_ImpliedTimes"), "None");
+
+            pref_manager.set_user_prefs("MathLevel", "Auto");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("ClearSpeak_ImpliedTimes"), "Auto");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("Chemistry"), "SpellOut");
+        });
+    }
+
+    #[test]
+    fn test_math_level_user_prefs_file() {
+        let user_prefs_file = env::temp_dir().join("mathcat_test_math_level_prefs.yaml");
+        fs::write(&user_prefs_file, "Speech:\n  Verbosity: Terse\n  MathLevel: Elementary\nNavigation: {}\nBraille: {}\n").unwrap();
+        let names = Preferences::pref_names_in_file(&Some(user_prefs_file.clone())).unwrap();
+        fs::remove_file(&user_prefs_file).unwrap();
+        assert_eq!(names, HashSet::from(["Verbosity".to_string()]));
+
+        PREF_MANAGER.with(|pref_manager| {
+            let mut pref_manager = pref_manager.borrow_mut();
+            pref_manager.initialize(abs_rules_dir_path()).unwrap();
+            pref_manager.user_file_pref_names = names;
+            pref_manager.set_user_prefs("Verbosity", "Terse");
+            pref_manager.explicit_prefs.clear();
+            pref_manager.set_user_prefs("MathLevel", "Elementary");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("Verbosity"), "Terse");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("ClearSpeak_ImpliedTimes"), "MoreImpliedTimes");
+
+            // resetting the prefs forgets the individual changes
+            pref_manager.set_user_prefs("Chemistry", "Off");
+            pref_manager.initialize(abs_rules_dir_path()).unwrap();
+            assert!(pref_manager.explicit_prefs.is_empty());
+            pref_manager.set_user_prefs("MathLevel", "University");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("Chemistry"), "SpellOut");
+            assert_eq!(&pref_manager.get_user_prefs().to_string("ClearSpeak_ImpliedTimes"), "None");
+        });
+    }
+
     use std::fs;
     #[test]
     fn test_up_to_date() {
@@ -0,0 +1,131 @@
+//! Per-rule timing of the speech, navigation, and braille rules (only built with the `profile` feature).
+//!
+//! [`start_rule_profile`] starts recording how often each rule's pattern is tried, how often it matches,
+//! and how much time is spent evaluating the pattern. [`stop_rule_profile`] ends the recording and returns it.
+//! Everything that uses the rules in between (e.g., [`get_spoken_text`], [`get_braille`], navigation)
+//! is included, so a run over a corpus shows which XPath patterns dominate the time.
+//! The [`RuleProfile`] `Display` output is sorted so that the most expensive patterns are listed first.
+//!
+//! [`get_spoken_text`]: crate::interface::get_spoken_text
+//! [`get_braille`]: crate::interface::get_braille
+#![allow(clippy::needless_return)]
+
+use crate::errors::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// The number of rules shown in the `Display` output of [`RuleProfile`]
+const REPORTED_RULES: usize = 50;
+
+/// How often a rule's pattern was tried and matched along with the time spent evaluating it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleTiming {
+    pub n_tries: usize,
+    pub n_matches: usize,
+    pub match_time: Duration,
+}
+
+impl RuleTiming {
+    /// The average time to evaluate the pattern (zero if it was never tried)
+    pub fn average(&self) -> Duration {
+        if self.n_tries == 0 {
+            return Duration::ZERO;
+        }
+        return self.match_time / self.n_tries as u32;
+    }
+}
+
+/// The result of [`stop_rule_profile`]
+#[derive(Debug, Clone, Default)]
+pub struct RuleProfile {
+    /// The timings for each rule that was tried -- the key is "<rules for>: <rule name> [<tag>] (<file>)"
+    pub rules: HashMap<String, RuleTiming>,
+}
+
+impl RuleProfile {
+    /// The total time spent evaluating patterns
+    pub fn total(&self) -> Duration {
+        return self.rules.values().map(|timing| timing.match_time).sum();
+    }
+
+    /// The rules sorted by the total time spent evaluating their pattern (most expensive first)
+    pub fn sorted(&self) -> Vec<(&String, &RuleTiming)> {
+        let mut rules = self.rules.iter().collect::<Vec<(&String, &RuleTiming)>>();
+        rules.sort_by(|(name1, timing1), (name2, timing2)|
+            timing2.match_time.cmp(&timing1.match_time).then(name1.cmp(name2))
+        );
+        return rules;
+    }
+}
+
+impl fmt::Display for RuleProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rule profile for {} rules (total {:.3}ms)", self.rules.len(), self.total().as_secs_f64() * 1000.0)?;
+        writeln!(f, "{:>10} {:>9} {:>8} {:>8}  rule", "total ms", "avg µs", "tries", "matches")?;
+        for (name, timing) in self.sorted().into_iter().take(REPORTED_RULES) {
+            writeln!(f, "{:>10.3} {:>9.2} {:>8} {:>8}  {}",
+                     timing.match_time.as_secs_f64() * 1000.0, timing.average().as_secs_f64() * 1_000_000.0,
+                     timing.n_tries, timing.n_matches, name)?;
+        }
+        return Ok( () );
+    }
+}
+
+thread_local!{
+    /// The profile being collected (`None` when rules aren't being profiled)
+    static RULE_PROFILE: RefCell<Option<RuleProfile>> = const { RefCell::new(None) };
+}
+
+/// Start recording the time spent matching each rule. Any profile that was being recorded is discarded.
+pub fn start_rule_profile() {
+    RULE_PROFILE.with(|profile| profile.replace(Some(RuleProfile::default())));
+}
+
+/// Stop recording and return the times recorded since [`start_rule_profile`] was called
+pub fn stop_rule_profile() -> Result<RuleProfile> {
+    return match RULE_PROFILE.with(|profile| profile.take()) {
+        Some(profile) => Ok(profile),
+        None => bail!("stop_rule_profile: 'start_rule_profile' was not called"),
+    };
+}
+
+/// Record an attempt to match a rule. 'key' is only called when a profile is being recorded.
+pub(crate) fn add_match<F: FnOnce() -> String>(key: F, is_match: bool, match_time: Duration) {
+    RULE_PROFILE.with(|profile| {
+        if let Some(profile) = profile.borrow_mut().as_mut() {
+            let timing = profile.rules.entry(key()).or_default();
+            timing.n_tries += 1;
+            if is_match {
+                timing.n_matches += 1;
+            }
+            timing.match_time += match_time;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::*;
+
+    #[test]
+    fn rule_profile() {
+        assert!(stop_rule_profile().is_err());
+        set_rules_dir(super::super::abs_rules_dir_path()).unwrap();
+        set_preference("Language".to_string(), "en".to_string()).unwrap();
+        set_mathml("<math><mfrac><mn>1</mn><mi>x</mi></mfrac></math>".to_string()).unwrap();
+        get_spoken_text().unwrap();     // not recorded
+        start_rule_profile();
+        get_spoken_text().unwrap();
+        get_braille("".to_string()).unwrap();
+        let profile = stop_rule_profile().unwrap();
+        assert!(profile.rules.keys().any(|rule| rule.starts_with("Speech: ")));
+        assert!(profile.rules.keys().any(|rule| rule.starts_with("Braille: ")));
+        assert!(profile.rules.values().all(|timing| timing.n_matches <= timing.n_tries));
+        assert!(profile.rules.values().any(|timing| timing.n_matches > 0));
+        assert!(profile.to_string().starts_with(&format!("Rule profile for {} rules", profile.rules.len())));
+        assert!(stop_rule_profile().is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Python bindings for the MathCAT interface (only built with the `python` feature).
+//!
+//! The module is named `libmathcat` and contains the class `MathCAT`. The method names match the ones used by
+//! [MathCATForPython](https://github.com/NSoiffer/MathCATForPython) (e.g., `SetRulesDir`, `GetSpokenText`).
+//! Errors are raised as `RuntimeError` with the same message as [`errors_to_string`].
+//!
+//! MathCAT keeps its state (rules, preferences, and the current MathML) per thread, so instances are emulated:
+//! each `MathCAT` instance remembers its rules dir, the preferences it set, and its MathML, and replays them
+//!   before doing anything if some other instance was used on that thread in the meantime.
+//! This means instances don't interfere with each other and can be used from different Python threads.
+//! Switching between instances on a thread is expensive though: the preferences are set again (which can cause the rules
+//!   to be reloaded) and the MathML is canonicalized again. If instances are used alternately, give each one its own thread.
+//! The GIL is released while MathCAT is working so other Python threads can run.
+#![allow(clippy::needless_return)]
+#![allow(clippy::useless_conversion)]     // false positive from the code generated by #[pymethods]
+
+use crate::errors::*;
+use crate::interface::*;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Used to give each instance a unique id (0 means no instance)
+static NEXT_INSTANCE_ID: AtomicUsize = AtomicUsize::new(1);
+
+thread_local!{
+    /// The id of the instance whose state is the current MathCAT state on this thread
+    static ACTIVE_INSTANCE: Cell<usize> = const { Cell::new(0) };
+
+    /// The value a preference had before any instance set it -- used to undo another instance's preferences
+    static DEFAULT_PREFERENCES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+/// A MathCAT instance with its own rules dir, preferences, and MathML
+#[pyclass(name = "MathCAT")]
+pub struct PyMathCAT {
+    id: usize,
+    rules_dir: Option<String>,
+    /// preferences in the order they were set (a name is only present once)
+    prefs: Vec<(String, String)>,
+    /// the canonical MathML returned by `set_mathml` (it has the ids that were added)
+    mathml: Option<String>,
+}
+
+impl PyMathCAT {
+    /// Make this instance's state the current MathCAT state on this thread (if it isn't already).
+    /// The thread's state is rebuilt from what this instance remembers (see the module documentation for the cost).
+    fn activate(&self) -> Result<()> {
+        if ACTIVE_INSTANCE.with(|active| active.get()) == self.id {
+            return Ok( () );
+        }
+        // in case of failure part way through, don't leave this instance marked as active
+        ACTIVE_INSTANCE.with(|active| active.set(0));
+        if let Some(dir) = &self.rules_dir {
+            set_rules_dir(dir.clone())?;
+        }
+        let defaults = DEFAULT_PREFERENCES.with(|defaults| defaults.borrow().clone());
+        for (name, value) in defaults {
+            set_preference(name, value)?;
+        }
+        for (name, value) in &self.prefs {
+            set_preference(name.clone(), value.clone())?;
+        }
+        if let Some(mathml) = &self.mathml {
+            set_mathml(mathml.clone())?;
+        }
+        ACTIVE_INSTANCE.with(|active| active.set(self.id));
+        return Ok( () );
+    }
+}
+
+/// Convert a MathCAT error to a Python exception
+fn to_py_err(e: Error) -> PyErr {
+    return PyRuntimeError::new_err(errors_to_string(&e));
+}
+
+#[pymethods]
+impl PyMathCAT {
+    #[new]
+    fn new() -> Self {
+        return PyMathCAT {
+            id: NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed),
+            rules_dir: None,
+            prefs: Vec::new(),
+            mathml: None,
+        };
+    }
+
+    /// Set the location of the `Rules` directory. This should be called before anything else.
+    /// Any preferences set by this instance are kept.
+    #[pyo3(name = "SetRulesDir")]
+    fn set_rules_dir(&mut self, py: Python<'_>, dir: String) -> PyResult<()> {
+        return py.allow_threads(|| {
+            self.rules_dir = Some(dir);
+            ACTIVE_INSTANCE.with(|active| active.set(0));   // force the rules dir and prefs to be set
+            return self.activate();
+        }).map_err(to_py_err);
+    }
+
+    /// Set the MathML and return the canonical MathML (with 'id's added to nodes that didn't have them)
+    #[pyo3(name = "SetMathML")]
+    fn set_mathml(&mut self, py: Python<'_>, mathml: String) -> PyResult<String> {
+        return py.allow_threads(|| {
+            self.activate()?;
+            let canonical_mathml = set_mathml(mathml)?;
+            self.mathml = Some(canonical_mathml.clone());
+            return Ok(canonical_mathml);
+        }).map_err(to_py_err);
+    }
+
+    /// Get the speech for the MathML that was set
+    #[pyo3(name = "GetSpokenText")]
+    fn get_spoken_text(&self, py: Python<'_>) -> PyResult<String> {
+        return py.allow_threads(|| {
+            self.activate()?;
+            return get_spoken_text();
+        }).map_err(to_py_err);
+    }
+
+    /// Get the (Unicode) braille for the MathML that was set. If 'nav_node_id' is not empty, the braille for that node is highlighted.
+    #[pyo3(name = "GetBraille", signature = (nav_node_id = String::new()))]
+    fn get_braille(&self, py: Python<'_>, nav_node_id: String) -> PyResult<String> {
+        return py.allow_threads(|| {
+            self.activate()?;
+            return get_braille(nav_node_id);
+        }).map_err(to_py_err);
+    }
+
+    /// Set a preference (e.g., "Language" or "SpeechStyle"). The preference only applies to this instance.
+    #[pyo3(name = "SetPreference")]
+    fn set_preference(&mut self, py: Python<'_>, name: String, value: String) -> PyResult<()> {
+        return py.allow_threads(|| {
+            self.activate()?;
+            let is_new_name = DEFAULT_PREFERENCES.with(|defaults| !defaults.borrow().contains_key(&name));
+            if is_new_name {
+                // no instance has set this yet, so the current value is the default
+                let default_value = get_preference(name.clone())?;
+                DEFAULT_PREFERENCES.with(|defaults| defaults.borrow_mut().insert(name.clone(), default_value));
+            }
+            set_preference(name.clone(), value.clone())?;
+            match self.prefs.iter_mut().find(|(pref_name, _)| *pref_name == name) {
+                Some(pref) => pref.1 = value,
+                None => self.prefs.push( (name, value) ),
+            }
+            return Ok( () );
+        }).map_err(to_py_err);
+    }
+
+    /// Get the value of a preference
+    #[pyo3(name = "GetPreference")]
+    fn get_preference(&self, py: Python<'_>, name: String) -> PyResult<String> {
+        return py.allow_threads(|| {
+            self.activate()?;
+            return get_preference(name);
+        }).map_err(to_py_err);
+    }
+}
+
+/// Returns the version number of MathCAT
+#[pyfunction]
+#[pyo3(name = "GetVersion")]
+fn py_get_version() -> String {
+    return get_version();
+}
+
+/// The Python module (`import libmathcat`)
+#[pymodule]
+fn libmathcat(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyMathCAT>()?;
+    m.add_function(wrap_pyfunction!(py_get_version, m)?)?;
+    return Ok( () );
+}
@@ -82,6 +82,36 @@ cfg_if! {
             };
         }
         
+        pub fn read_dir_shim(path: &Path) -> Vec<String> {
+            // Return the names of the files and dirs in 'path' (the path should be "Rules/...")
+            use sxd_document::dom::*;
+            use std::path::Component;
+            use crate::interface::get_element;
+            use crate::canonicalize::name;
+
+            return DIRECTORY_TREE.with(|files| {
+                let files = files.borrow();
+                let mut dir = get_element(&*files);
+                let mut components = path.components().filter(|component| matches!(component, Component::Normal(_)));
+                if components.next().and_then(|component| component.as_os_str().to_str()) != dir.attribute_value("name") {
+                    return vec![];
+                }
+                for component in components {
+                    let component_name = component.as_os_str().to_str().unwrap();
+                    match dir.children().iter()
+                            .filter_map(|child| child.element())
+                            .find(|child| name(child) == "dir" && child.attribute_value("name") == Some(component_name)) {
+                        None => return vec![],
+                        Some(child) => dir = child,
+                    }
+                }
+                return dir.children().iter()
+                        .filter_map(|child| child.element())
+                        .filter_map(|child| child.attribute_value("name").map(|name| name.to_string()))
+                        .collect();
+            });
+        }
+
         pub fn canonicalize_shim(path: &Path) -> std::io::Result<PathBuf> {
             // FIX:  need to deal with ".."???
             return Ok( path.to_path_buf() );
@@ -197,6 +227,17 @@ cfg_if! {
             return path.is_dir();
         }
         
+        pub fn read_dir_shim(path: &Path) -> Vec<String> {
+            // Return the names of the files and dirs in 'path' (empty if 'path' can't be read)
+            return match std::fs::read_dir(path) {
+                Err(_) => vec![],
+                Ok(entries) => entries
+                        .filter_map(|entry| entry.ok())
+                        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+                        .collect(),
+            };
+        }
+
         pub fn canonicalize_shim(path: &Path) -> std::io::Result<PathBuf> {
             return path.canonicalize();
         }
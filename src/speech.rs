@@ -4,14 +4,15 @@
 //! A number of useful utility functions used by other modules are defined here.
 #![allow(clippy::needless_return)]
 use std::path::PathBuf;
-use std::collections::HashMap;
-use std::cell::{RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
 use sxd_document::dom::{ChildOfElement, Document, Element};
 use sxd_document::{Package, QName};
 use sxd_xpath::context::Evaluation;
 use sxd_xpath::{Context, Factory, Value, XPath};
 use sxd_xpath::nodeset::Node;
 use std::fmt;
+use regex::Regex;
 use crate::errors::*;
 use crate::prefs::*;
 use yaml_rust::{YamlLoader, Yaml, yaml::Hash};
@@ -19,6 +20,8 @@ use crate::tts::*;
 use crate::pretty_print::{mml_to_string, yaml_to_string};
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use crate::shim_filesystem::read_to_string_shim;
 use crate::canonicalize::{as_element, create_mathml_element, set_mathml_name, name};
 
@@ -40,7 +43,428 @@ pub fn intent_from_mathml<'a, 'm>(mathml: Element<'a>, doc: Document<'m>) -> Res
 }
 
 pub fn speak_intent(mathml: Element) -> Result<String> {
-    return speak_rules(&SPEECH_RULES, mathml);
+    let sentences = split_into_sentences(mathml);
+    if sentences.len() < 2 {
+        return speak_rules(&SPEECH_RULES, mathml);
+    }
+    let speech = sentences.iter()
+                .map(|sentence| speak_rules(&SPEECH_RULES, *sentence))
+                .collect::<Result<Vec<String>>>()?;
+    let pref_manager = PreferenceManager::get();
+    let pref_manager = pref_manager.borrow();
+    return Ok( pref_manager.get_tts().join_sentences(&speech, &pref_manager) );
+}
+
+/// If 'SentenceLength' is set (non-zero) and the top-level mrow of 'mathml' has more than that many operands,
+/// the children of the mrow are grouped into new mrows that are each spoken as a sentence.
+/// When the top-level mrow contains relations, the operands (mrows) of the relations are flattened into it first
+/// (function applications such as "sin x" are kept intact).
+/// A new sentence is started before the left operand of each top-level relation (after the first operand)
+///   and before any (visible) operator that follows 'SentenceLength' operands.
+/// The (restructured) mrows are returned -- if the speech should not be split, the return value is empty.
+fn split_into_sentences(mathml: Element) -> Vec<Element> {
+    let sentence_length = PreferenceManager::get().borrow().get_user_prefs()
+                .to_string("SentenceLength").parse::<usize>().unwrap_or(0);
+    if sentence_length == 0 || name(&mathml) != "math" || mathml.children().len() != 1 {
+        return vec![];
+    }
+    let mrow = as_element(mathml.children()[0]);
+    if name(&mrow) != "mrow" {
+        return vec![];
+    }
+    // each piece is a top-level child -- the operands of relations are split into their children
+    let has_relation = mrow.children().iter().any(|&child| is_relation(as_element(child)));
+    let pieces = mrow.children().into_iter()
+                .map(|child| {
+                    let element = as_element(child);
+                    if has_relation && name(&element) == "mrow" && !is_function_application(element) {
+                        element.children()
+                    } else {
+                        vec![child]
+                    }
+                })
+                .collect::<Vec<Vec<ChildOfElement>>>();
+    let n_operands = pieces.iter().flatten().filter(|&&child| name(&as_element(child)) != "mo").count();
+    if n_operands <= sentence_length {
+        return vec![];
+    }
+
+    let mut groups: Vec<Vec<ChildOfElement>> = vec![vec![]];
+    let mut n_operands = 0;         // number of operands in the current group
+    for (i, piece) in pieces.iter().enumerate() {
+        let is_left_operand = pieces.get(i+1).is_some_and(|next| next.len() == 1 && is_relation(as_element(next[0])));
+        if is_left_operand && n_operands > 0 {
+            groups.push(vec![]);
+            n_operands = 0;
+        }
+        for &child in piece {
+            let element = as_element(child);
+            if name(&element) == "mo" {
+                if n_operands >= sentence_length && !is_relation(element) &&
+                   !matches!(crate::canonicalize::as_text(element), "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}") {
+                    groups.push(vec![]);
+                    n_operands = 0;
+                }
+            } else {
+                n_operands += 1;
+            }
+            groups.last_mut().unwrap().push(child);
+        }
+    }
+    if groups.len() < 2 {
+        return vec![];
+    }
+    let doc = mrow.document();
+    let sentences = groups.into_iter()
+                .map(|group| {
+                    let sentence = create_mathml_element(&doc, "mrow");
+                    sentence.append_children(group);
+                    sentence
+                })
+                .collect::<Vec<Element>>();
+    mrow.replace_children(sentences.clone());
+    return sentences;
+
+    fn is_relation(element: Element) -> bool {
+        return crate::symbol_category::SymbolCategory::Relation.contains(element);
+    }
+
+    fn is_function_application(mrow: Element) -> bool {
+        let children = mrow.children();
+        return children.len() == 3 && name(&as_element(children[1])) == "mo" &&
+               crate::canonicalize::as_text(as_element(children[1])) == "\u{2061}";
+    }
+}
+
+/// Speak the relational skeleton ("glance") of 'mathml': the top-level operators are spoken and the operands are hidden.
+/// The canonical tree groups operators by priority, so the top-level operators are the lowest priority ones
+///   (the relations if there are any). Each operand is replaced by a placeholder ("something"),
+///   except that a fraction keeps its structure so that the glance is "something equals something over something".
+pub fn glance_mathml(mathml: Element) -> Result<String> {
+    let skeleton_package = Package::new();
+    let skeleton_doc = skeleton_package.as_document();
+    let math = create_mathml_element(&skeleton_doc, "math");
+    skeleton_doc.root().append_child(math);
+    let top = skeleton_top(mathml);
+    if name(&top) == "mrow" {
+        let mrow = create_mathml_element(&skeleton_doc, "mrow");
+        for child in top.children() {
+            let child = as_element(child);
+            mrow.append_child( if name(&child) == "mo" {copy_mo(child, skeleton_doc)} else {glance_operand(child, skeleton_doc)} );
+        }
+        math.append_child(mrow);
+    } else {
+        math.append_child(glance_operand(top, skeleton_doc));
+    }
+
+    let intent_package = Package::new();
+    let intent = intent_from_mathml(math, intent_package.as_document())?;
+    return speak_intent(intent);
+
+    fn copy_mo<'d>(mo: Element, doc: Document<'d>) -> Element<'d> {
+        let new_mo = create_mathml_element(&doc, "mo");
+        new_mo.set_text(crate::canonicalize::as_text(mo));
+        return new_mo;
+    }
+
+    fn glance_operand<'d>(operand: Element, doc: Document<'d>) -> Element<'d> {
+        if name(&operand) == "mfrac" {
+            let mfrac = create_mathml_element(&doc, "mfrac");
+            mfrac.append_child(glance_placeholder(doc));
+            mfrac.append_child(glance_placeholder(doc));
+            return mfrac;
+        }
+        return glance_placeholder(doc);
+    }
+}
+
+/// The element that is spoken in place of a hidden operand (e.g., "something")
+fn glance_placeholder(doc: Document) -> Element {
+    let mi = create_mathml_element(&doc, "mi");
+    mi.set_text("□");
+    mi.set_attribute_value("data-glance", "placeholder");
+    return mi;
+}
+
+/// Speak 'mathml' for practice mode: only the parts of the expression that have been revealed are spoken.
+/// The structure (operators, fraction bars, scripts, ...) of 'mathml' and of the revealed nodes (their ids are in 'revealed')
+///   is spoken, but their operands are spoken as a placeholder ("something") until they are revealed.
+pub fn practice_mathml(mathml: Element, revealed: &std::collections::HashSet<String>) -> Result<String> {
+    let skeleton_package = Package::new();
+    let skeleton_doc = skeleton_package.as_document();
+    let math = create_mathml_element(&skeleton_doc, "math");
+    skeleton_doc.root().append_child(math);
+    math.append_child(practice_node(skeleton_top(mathml), skeleton_doc, revealed, true));
+
+    let intent_package = Package::new();
+    let intent = intent_from_mathml(math, intent_package.as_document())?;
+    return speak_intent(intent);
+
+    fn practice_node<'d>(mathml: Element, doc: Document<'d>, revealed: &std::collections::HashSet<String>, is_shown: bool) -> Element<'d> {
+        let is_shown = is_shown || name(&mathml) == "mo" ||
+                       mathml.attribute_value("id").is_some_and(|id| revealed.contains(id));
+        if !is_shown {
+            return glance_placeholder(doc);
+        }
+        let element = create_mathml_element(&doc, name(&mathml));
+        for attr in mathml.attributes() {
+            element.set_attribute_value(attr.name(), attr.value());
+        }
+        if crate::xpath_functions::is_leaf(mathml) {
+            element.set_text(crate::canonicalize::as_text(mathml));
+        } else {
+            for child in mathml.children() {
+                element.append_child(practice_node(as_element(child), doc, revealed, false));
+            }
+        }
+        return element;
+    }
+}
+
+/// Speak 'mathml' using at most 'max_words' words (the "and more" marker counts as words).
+/// If the speech is too long, it is cut before a top-level operator and the marker is spoken in place of the rest of the expression.
+/// If not even the first operand fits, its own top-level operators are tried (and so on).
+/// The speech is returned along with the `id` of the node where the speech was cut (`None` if the speech was not truncated).
+pub fn truncated_mathml(mathml: Element, max_words: usize) -> Result<(String, Option<String>)> {
+    let intent_package = Package::new();
+    let intent = intent_from_mathml(mathml, intent_package.as_document())?;
+    let speech = speak_intent(intent)?;
+    if count_words(&speech) <= max_words {
+        return Ok( (speech, None) );
+    }
+
+    let mut top = skeleton_top(mathml);
+    let mut truncated = None;       // the speech and cut for the longest prefix that fits
+    while name(&top) == "mrow" && !top.children().is_empty() {
+        let children = top.children();
+        for (i, &child) in children.iter().enumerate().skip(1) {
+            let cut = as_element(child);
+            if !is_cut_point(cut) || name(&as_element(children[i-1])) == "mo" {
+                continue;
+            }
+            let speech = speak_truncated(mathml, cut)?;
+            if count_words(&speech) > max_words {
+                break;
+            }
+            truncated = Some( (speech, cut) );
+        }
+        if truncated.is_some() {
+            break;
+        }
+        top = skeleton_top(as_element(children[0]));
+    }
+    let (speech, cut) = match truncated {
+        Some(truncated) => truncated,
+        None => (speak_truncated(mathml, top)?, top),   // only the marker is spoken
+    };
+    return Ok( (speech, cut.attribute_value("id").map(|id| id.to_string())) );
+
+    fn is_cut_point(mo: Element) -> bool {
+        return name(&mo) == "mo" && !crate::canonicalize::is_fence(mo) &&
+               !matches!(crate::canonicalize::as_text(mo), "\u{2061}" | "\u{2062}" | "\u{2063}" | "\u{2064}");
+    }
+
+    /// Speak the part of 'mathml' before 'cut' (in document order) followed by the "and more" marker
+    fn speak_truncated(mathml: Element, cut: Element) -> Result<String> {
+        let truncated_package = Package::new();
+        let truncated_doc = truncated_package.as_document();
+        let math = create_mathml_element(&truncated_doc, "math");
+        truncated_doc.root().append_child(math);
+        let mrow = create_mathml_element(&truncated_doc, "mrow");
+        if let Some(prefix) = copy_before(skeleton_top(mathml), cut, truncated_doc) {
+            mrow.append_child(prefix);
+        }
+        let marker = create_mathml_element(&truncated_doc, "mi");
+        marker.set_text("…");
+        marker.set_attribute_value("data-truncated", "more");
+        mrow.append_child(marker);
+        math.append_child(mrow);
+
+        let intent_package = Package::new();
+        let intent = intent_from_mathml(math, intent_package.as_document())?;
+        return speak_intent(intent);
+    }
+
+    /// Copy 'mathml' leaving out 'cut' and everything after it ('None' if nothing is left)
+    fn copy_before<'d>(mathml: Element, cut: Element, doc: Document<'d>) -> Option<Element<'d>> {
+        if mathml == cut {
+            return None;
+        }
+        let element = create_mathml_element(&doc, name(&mathml));
+        for attr in mathml.attributes() {
+            element.set_attribute_value(attr.name(), attr.value());
+        }
+        if crate::xpath_functions::is_leaf(mathml) {
+            element.set_text(crate::canonicalize::as_text(mathml));
+            return Some(element);
+        }
+        for child in mathml.children() {
+            let child = as_element(child);
+            if child == cut {
+                break;
+            }
+            let is_ancestor = std::iter::successors(cut.parent().and_then(|parent| parent.element()),
+                                                     |ancestor| ancestor.parent().and_then(|parent| parent.element()))
+                        .any(|ancestor| ancestor == child);
+            if let Some(copy) = copy_before(child, cut, doc) {
+                element.append_child(copy);
+            }
+            if is_ancestor {
+                break;
+            }
+        }
+        return Some(element);
+    }
+}
+
+/// The number of words in 'speech' (any TTS markup is ignored)
+pub fn count_words(speech: &str) -> usize {
+    lazy_static! {
+        static ref XML_TAG: Regex = Regex::new(r"<.+?>").unwrap();
+    }
+    return XML_TAG.replace_all(speech, " ").split_whitespace()
+                .filter(|word| word.chars().any(|ch| ch.is_alphanumeric()))
+                .count();
+}
+
+/// The node whose structure is spoken by a glance or in practice mode (`math` and mrows with a single child are skipped over)
+pub fn skeleton_top(mathml: Element) -> Element {
+    let mut top = mathml;
+    while (name(&top) == "math" || name(&top) == "mrow") && top.children().len() == 1 {
+        top = as_element(top.children()[0]);
+    }
+    return top;
+}
+
+/// The special forms of a matrix reported by [`describe_matrix`].
+/// The order matches the words in the 'MatrixForms_vec' definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatrixForm {
+    Zero,
+    Identity,
+    Diagonal,
+    UpperTriangular,
+    LowerTriangular,
+    Symmetric,
+    NoSpecialForm,
+    NotSquare,
+    NotMatrix,
+}
+
+/// Symmetry is only checked for numeric matrices up to this size (larger ones are too much to take in as an overview)
+const MAX_SYMMETRIC_MATRIX_SIZE: usize = 4;
+
+/// Describe the most specific special form (zero, identity, diagonal, triangular, symmetric) of the matrix containing 'mathml'.
+/// The matrix is the nearest 'mtable' ancestor-or-self of 'mathml', or if there isn't one, the first 'mtable' inside of it.
+/// Cells that are empty or contain the number 0 are considered to be zero.
+pub fn describe_matrix(mathml: Element) -> Result<String> {
+    let form = match find_mtable(mathml) {
+        None => MatrixForm::NotMatrix,
+        Some(mtable) => matrix_form(mtable),
+    };
+    return crate::definitions::DEFINITIONS.with(|definitions| {
+        let definitions = definitions.borrow();
+        return match definitions.get_vec("MatrixForms_vec").and_then(|words| words.get(form as usize).cloned()) {
+            Some(words) => Ok(words),
+            None => bail!("'MatrixForms_vec' is missing or doesn't have an entry for {:?}", form),
+        };
+    });
+
+    fn find_mtable(mathml: Element) -> Option<Element> {
+        let mut node = mathml;
+        loop {
+            if name(&node) == "mtable" {
+                return Some(node);
+            }
+            match node.parent().and_then(|parent| parent.element()) {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+        return find_descendant_mtable(mathml);
+    }
+
+    fn find_descendant_mtable(mathml: Element) -> Option<Element> {
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                if name(&child) == "mtable" {
+                    return Some(child);
+                }
+                if let Some(mtable) = find_descendant_mtable(child) {
+                    return Some(mtable);
+                }
+            }
+        }
+        return None;
+    }
+
+    fn matrix_form(mtable: Element) -> MatrixForm {
+        // the canonical table model is mtable/(mtr|mlabeledtr)/mtd -- the label of an mlabeledtr isn't part of the matrix
+        let rows = mtable.children().into_iter()
+                .map(as_element)
+                .filter(|row| name(row) == "mtr" || name(row) == "mlabeledtr")
+                .map(|row| {
+                    let n_skip = if name(&row) == "mlabeledtr" {1} else {0};
+                    row.children().into_iter().skip(n_skip).map(as_element).collect::<Vec<Element>>()
+                })
+                .collect::<Vec<Vec<Element>>>();
+        let n = rows.len();
+        if n == 0 || rows.iter().any(|row| row.len() != n) {
+            return MatrixForm::NotSquare;
+        }
+
+        let values = rows.iter()
+                .map(|row| row.iter().map(|&mtd| cell_value(mtd)).collect::<Vec<Option<f64>>>())
+                .collect::<Vec<Vec<Option<f64>>>>();
+        let is_zero = |i: usize, j: usize| values[i][j] == Some(0.0);
+        let is_upper = (0..n).all(|i| (0..i).all(|j| is_zero(i, j)));
+        let is_lower = (0..n).all(|i| (i+1..n).all(|j| is_zero(i, j)));
+        if is_upper && is_lower {
+            if (0..n).all(|i| is_zero(i, i)) {
+                return MatrixForm::Zero;
+            } else if (0..n).all(|i| values[i][i] == Some(1.0)) {
+                return MatrixForm::Identity;
+            } else {
+                return MatrixForm::Diagonal;
+            }
+        } else if is_upper {
+            return MatrixForm::UpperTriangular;
+        } else if is_lower {
+            return MatrixForm::LowerTriangular;
+        } else if n <= MAX_SYMMETRIC_MATRIX_SIZE &&
+                  values.iter().flatten().all(|value| value.is_some()) &&
+                  (0..n).all(|i| (0..i).all(|j| values[i][j] == values[j][i])) {
+            return MatrixForm::Symmetric;
+        } else {
+            return MatrixForm::NoSpecialForm;
+        }
+    }
+
+    /// The numeric value of the cell (None if it isn't a number); an empty cell is 0
+    fn cell_value(mtd: Element) -> Option<f64> {
+        let children = mtd.children();
+        if children.is_empty() || mtd.attribute(crate::canonicalize::EMPTY_CELL_ATTR).is_some() {
+            return Some(0.0);
+        }
+        if children.len() != 1 {
+            return None;
+        }
+        let mut cell = as_element(children[0]);
+        let mut sign = 1.0;
+        if name(&cell) == "mrow" && cell.children().len() == 2 {
+            // negative number: mrow(mo(-), mn)
+            let first = as_element(cell.children()[0]);
+            if name(&first) != "mo" || !matches!(crate::canonicalize::as_text(first), "-" | "−") {
+                return None;
+            }
+            sign = -1.0;
+            cell = as_element(cell.children()[1]);
+        }
+        if name(&cell) != "mn" {
+            return None;
+        }
+        return crate::canonicalize::as_text(cell).trim().parse::<f64>().ok().map(|value| sign * value);
+    }
 }
 
 pub fn overview_mathml(mathml: Element) -> Result<String> {
@@ -76,15 +500,94 @@ fn speak_rules(rules: &'static std::thread::LocalKey<RefCell<SpeechRules>>, math
         let mut rules_with_context = SpeechRulesWithContext::new(&rules, new_package.as_document(), "".to_string());
         let speech_string = rules_with_context.match_pattern::<String>(mathml)
                     .chain_err(|| "Pattern match/replacement failure!")?;
-        return Ok( rules.pref_manager.borrow().get_tts()
+        let pref_manager = rules.pref_manager.borrow();
+        let speech = pref_manager.get_tts()
                     .merge_pauses(remove_optional_indicators(
                         &speech_string.replace(CONCAT_STRING, "")
                                             .replace(CONCAT_INDICATOR, "")                            
                                     )
-                    .trim()) );
+                    .trim());
+        return Ok( map_phrases(speech, &pref_manager.get_language()) );
     })
 }
 
+/// The phrase replacements for a language set with [`crate::interface::set_phrase_mapping`] along with the
+/// compiled pattern that matches any of the phrases (rebuilt when a mapping is added).
+#[derive(Debug, Clone)]
+pub struct PhraseMappings {
+    replacements: HashMap<String, String>,
+    pattern: Regex,
+}
+
+impl PhraseMappings {
+    /// Add (or replace) a mapping for 'phrase' -- leading and trailing whitespace in 'phrase' is ignored
+    pub fn insert(mappings: Option<PhraseMappings>, phrase: &str, replacement: String) -> PhraseMappings {
+        let mut replacements = mappings.map(|mappings| mappings.replacements).unwrap_or_default();
+        replacements.insert(phrase.trim().to_string(), replacement);
+        let pattern = PhraseMappings::build_pattern(&replacements);
+        return PhraseMappings { replacements, pattern };
+    }
+
+    /// Builds a pattern that matches any of the phrases as whole words.
+    /// An end of a phrase that is a word character must be at a word boundary (so "over" doesn't match in "overline").
+    /// An end that isn't a word character (e.g., "≤" or "x+") must be next to whitespace or the start/end of the text.
+    /// Note: that whitespace is part of the match (the regex crate doesn't support lookaround), so [`map_phrases`] puts it back.
+    fn build_pattern(replacements: &HashMap<String, String>) -> Regex {
+        let is_word_char = |ch: Option<char>| ch.is_some_and(|ch| ch.is_alphanumeric() || ch == '_');
+        // longer phrases first so that "divided by" is replaced before "by"
+        let mut phrases = replacements.keys().collect::<Vec<&String>>();
+        phrases.sort_by_key(|phrase| (std::cmp::Reverse(phrase.len()), phrase.as_str()));
+        let alternatives = phrases.iter()
+            .map(|phrase| format!("{}{}{}",
+                    if is_word_char(phrase.chars().next()) {r"\b"} else {r"(?:^|\s)"},
+                    regex::escape(phrase),
+                    if is_word_char(phrase.chars().last()) {r"\b"} else {r"(?:\s|$)"}))
+            .collect::<Vec<String>>();
+        return Regex::new(&format!("(?:{})", alternatives.join("|"))).unwrap();
+    }
+
+    /// Replace the phrases in 'text' (which should not contain TTS markup)
+    fn map_text(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut copied_to = 0;
+        while let Some(found) = self.pattern.find_at(text, copied_to) {
+            let matched = found.as_str();
+            let leading_space = matched.len() - matched.trim_start().len();
+            let trailing_space = matched.len() - matched.trim_end().len();
+            result.push_str(&text[copied_to..found.start() + leading_space]);
+            result.push_str(&self.replacements[matched.trim()]);
+            // don't copy the trailing space -- it can be the leading space for the next phrase
+            copied_to = found.end() - trailing_space;
+        }
+        result.push_str(&text[copied_to..]);
+        return result;
+    }
+}
+
+/// Replace the phrases in `speech` that were set for `language` with [`crate::interface::set_phrase_mapping`].
+/// Only whole words are replaced and text inside of TTS markup (e.g., `<break time='200ms'/>`) is left alone.
+pub fn map_phrases(speech: String, language: &str) -> String {
+    lazy_static! {
+        static ref TTS_MARKUP: Regex = Regex::new(r"<[^>]*>").unwrap();
+    }
+    return SPEECH_PHRASE_MAPPINGS.with(|mappings| {
+        let mappings = mappings.borrow();
+        let mappings = match mappings.get(language) {
+            None => return speech,
+            Some(mappings) => mappings,
+        };
+        let mut result = String::with_capacity(speech.len());
+        let mut start = 0;
+        for markup in TTS_MARKUP.find_iter(&speech) {
+            result.push_str(&mappings.map_text(&speech[start..markup.start()]));
+            result.push_str(markup.as_str());
+            start = markup.end();
+        }
+        result.push_str(&mappings.map_text(&speech[start..]));
+        return result;
+    });
+}
+
 
 /// Converts its argument to a string that can be used in a debugging message.
 pub fn yaml_to_type(yaml: &Yaml) -> String {
@@ -216,6 +719,9 @@ pub trait TreeOrString<'c, 'm:'c, T> {
     fn replace<'s:'c, 'r>(ra: &ReplacementArray, rules_with_context: &'r mut SpeechRulesWithContext<'c, 's,'m>, mathml: Element<'c>) -> Result<T>;
     fn replace_nodes<'s:'c, 'r>(rules: &'r mut SpeechRulesWithContext<'c, 's,'m>, nodes: Vec<Node<'c>>, mathml: Element<'c>) -> Result<T>;
     fn highlight_braille(braille: T, highlight_style: String) -> T;
+    fn add_earcon(speech: T, earcon: &str) -> Result<T>;
+    fn add_text_voice(speech: T, start_tag: &str, end_tag: &str) -> Result<T>;
+    fn announce_structure(speech: T, announcement: &str, pause: &str, is_before: bool) -> Result<T>;
 }
 
 impl<'c, 'm:'c> TreeOrString<'c, 'm, String> for String {
@@ -242,6 +748,21 @@ impl<'c, 'm:'c> TreeOrString<'c, 'm, String> for String {
     fn highlight_braille(braille: String, highlight_style: String) -> String {
         return SpeechRulesWithContext::highlight_braille_string(braille, highlight_style);
     }
+
+    fn add_earcon(speech: String, earcon: &str) -> Result<String> {
+        return Ok( crate::tts::add_earcon(speech, earcon) );
+    }
+
+    fn add_text_voice(speech: String, start_tag: &str, end_tag: &str) -> Result<String> {
+        if speech.trim().is_empty() {
+            return Ok(speech);
+        }
+        return Ok( format!("{}{}{}", start_tag, speech, end_tag) );
+    }
+
+    fn announce_structure(speech: String, announcement: &str, pause: &str, is_before: bool) -> Result<String> {
+        return Ok( add_structure_announcement(speech, announcement, pause, is_before) );
+    }
 }
 
 impl<'c, 'm:'c> TreeOrString<'c, 'm, Element<'m>> for Element<'m> {
@@ -271,6 +792,64 @@ impl<'c, 'm:'c> TreeOrString<'c, 'm, Element<'m>> for Element<'m> {
     fn highlight_braille(_braille: Element<'c>, _highlight_style: String) -> Element<'m> {
         panic!("Internal error: highlight_braille called on a tree");
     }
+
+    fn add_earcon(_speech: Element<'m>, _earcon: &str) -> Result<Element<'m>> {
+        bail!("Internal error: add_earcon called on a tree");
+    }
+
+    fn add_text_voice(_speech: Element<'m>, _start_tag: &str, _end_tag: &str) -> Result<Element<'m>> {
+        bail!("Internal error: add_text_voice called on a tree");
+    }
+
+    fn announce_structure(_speech: Element<'m>, _announcement: &str, _pause: &str, _is_before: bool) -> Result<Element<'m>> {
+        bail!("Internal error: announce_structure called on a tree");
+    }
+}
+
+/// Returns the index into the 'StructureNames_vec' and 'StructureEndNames_vec' definitions for 'mathml' (if any).
+/// The order is: fraction, square root, root, subscript, superscript, scripts, table.
+/// Simple scripts (e.g., "x squared") are not counted as structures.
+/// 'mathml' can be either MathML or an intent tree.
+fn structure_index(mathml: Element) -> Option<usize> {
+    return match name(&mathml) {
+        "mfrac" | "fraction" | "bevelled-fraction" => Some(0),
+        "msqrt" | "square-root" => Some(1),
+        "mroot" | "root" => Some(2),
+        "msub" | "particular-value-of" if !is_simple_script(mathml) => Some(3),
+        "msup" | "power" if !is_simple_script(mathml) => Some(4),
+        "msubsup" | "mmultiscripts" if !is_simple_script(mathml) => Some(5),
+        "mtable" | "piecewise" | "equations" | "lines" => Some(6),    // matrices and determinants are always named
+        _ => None,
+    };
+}
+
+/// True if the base and the scripts are all leaves (e.g., "x squared" or "x sub i") -- these are short enough to not need an announcement
+fn is_simple_script(mathml: Element) -> bool {
+    return mathml.children().iter().all(|&child| {
+        let child = as_element(child);
+        return crate::xpath_functions::is_leaf(child) || name(&child) == "none" || name(&child) == "mprescripts";
+    });
+}
+
+/// Adds 'announcement' (e.g., "fraction") before or after 'speech', separated by 'pause'.
+/// Nothing is added if the rules already said it (e.g., "the square root of x" or "... end fraction").
+fn add_structure_announcement(speech: String, announcement: &str, pause: &str, is_before: bool) -> String {
+    // look a couple of words beyond the announcement to allow for an article and a modifier ("the cube root of ...")
+    let n_words = announcement.split_whitespace().count() + 2;
+    let words: Vec<&str> = speech.split_whitespace().collect();
+    let nearby_words = if is_before {
+        words[..n_words.min(words.len())].join(" ")
+    } else {
+        words[words.len().saturating_sub(n_words)..].join(" ")
+    };
+    if nearby_words.contains(announcement) {
+        return speech;
+    }
+    if is_before {
+        return format!("{} {} {}", announcement, pause, speech);
+    } else {
+        return format!("{} {} {}", speech, pause, announcement);
+    }
 }
 
 /// 'Replacement' is an enum that contains all the potential replacement types/structs
@@ -287,6 +866,7 @@ enum Replacement {
     With(Box<With>),
     SetVariables(Box<SetVariables>),
     Insert(Box<InsertChildren>),
+    ScriptOrder(Box<ScriptOrder>),
     Translate(TranslateExpression),
 }
 
@@ -302,6 +882,7 @@ impl fmt::Display for Replacement {
                 Replacement::With(w) => w.to_string(),
                 Replacement::SetVariables(v) => v.to_string(),
                 Replacement::Insert(ic) => ic.to_string(),
+                Replacement::ScriptOrder(so) => so.to_string(),
                 Replacement::Translate(x) => x.to_string(),
             }
         );
@@ -361,6 +942,9 @@ impl Replacement {
             "insert" => {
                 return Ok( Replacement::Insert( InsertChildren::build(value)? ) );
             },
+            "script_order" => {
+                return Ok( Replacement::ScriptOrder( ScriptOrder::build(value)? ) );
+            },
             "translate" => {
                 return Ok( Replacement::Translate( TranslateExpression::build(value)
                     .chain_err(|| "while trying to evaluate value of 'speak:'")? ) );
@@ -454,12 +1038,67 @@ impl<'r> InsertChildren {
 }
 
 
+// structure used when "script_order:" is encountered in a rule
+// 'base' and 'script' are spoken in the order given by evaluating 'order' ("ScriptFirst" or anything else for base first).
+// This lets a rule set (or user pref such as 'ScriptOrder_Subscript') swap the order without duplicating the rule.
+#[derive(Debug, Clone)]
+struct ScriptOrder {
+    order: MyXPath,                     // evaluates to "ScriptFirst" or "BaseFirst"
+    base: ReplacementArray,
+    script: ReplacementArray,
+}
+
+impl fmt::Display for ScriptOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "ScriptOrder:\n  order {}\n  base {}\n  script {}", self.order, &self.base, &self.script);
+    }
+}
+
+impl<'r> ScriptOrder {
+    fn build(script_order: &Yaml) -> Result<Box<ScriptOrder>> {
+        // 'script_order:' -- 'order': xxx 'base': xxx 'script': xxx
+        if script_order.as_hash().is_none() {
+            bail!("'script_order' must be a dictionary with 'order', 'base', and 'script' keys");
+        }
+        let order = &script_order["order"];
+        if order.is_badvalue() { 
+            bail!("Missing 'order' as part of 'script_order'.\n    \
+                  Suggestion: add 'order:' or if present, indent so it is contained in 'script_order'");
+        }
+        let base = &script_order["base"];
+        let script = &script_order["script"];
+        if base.is_badvalue() || script.is_badvalue() { 
+            bail!("Missing 'base' or 'script' as part of 'script_order'.\n    \
+                  Suggestion: add 'base:' and 'script:' or if present, indent so they are contained in 'script_order'");
+        }
+        return Ok( Box::new( ScriptOrder {
+            order: MyXPath::new(as_str_checked(order)?.to_string())?,
+            base: ReplacementArray::build(base).chain_err(|| "'base:'")?,
+            script: ReplacementArray::build(script).chain_err(|| "'script:'")?,
+        } ) );
+    }
+
+    // As with 'insert:', the parts are put into a single ReplacementArray so that "pause: auto" can see its neighbors
+    fn replace<'c, 's:'c, 'm: 'c, T:TreeOrString<'c, 'm, T>>(&self, rules_with_context: &'r mut SpeechRulesWithContext<'c, 's,'m>, mathml: Element<'c>) -> Result<T> {
+        let order = self.order.evaluate(&rules_with_context.context_stack.base, mathml)
+                .chain_err(||format!("in '{}' replacing after pattern match", &self.order.rc.string) )?
+                .into_string();
+        let (first, second) = if order == "ScriptFirst" {(&self.script, &self.base)} else {(&self.base, &self.script)};
+        let mut replacements = Vec::with_capacity(first.replacements.len() + second.replacements.len());
+        replacements.extend_from_slice(&first.replacements);
+        replacements.extend_from_slice(&second.replacements);
+        return ReplacementArray{ replacements }.replace(rules_with_context, mathml);
+    }
+}
+
+
 // structure used when "intent:" is encountered in a rule
 // the name is either a string or an xpath that needs evaluation. 99% of the time it is a string
 #[derive(Debug, Clone)]
 struct Intent {
     name: Option<String>,           // name of node
     xpath: Option<MyXPath>,         // alternative to directly using the string
+    attrs: VariableDefinitions,     // attributes (and their xpath values) added to the node (e.g., the length of a list)
     children: ReplacementArray,     // children of node
 }
 
@@ -477,7 +1116,7 @@ impl fmt::Display for Intent {
 
 impl<'r> Intent {
     fn build(yaml_dict: &Yaml) -> Result<Box<Intent>> {
-        // 'intent:' -- 'name': xxx 'children': xxx
+        // 'intent:' -- 'name': xxx 'children': xxx (and optionally 'attrs': xxx)
         if yaml_dict.as_hash().is_none() {
             bail!("Array found for contents of 'intent' -- should be dictionary with keys 'name' and 'children'")
         }
@@ -495,6 +1134,7 @@ impl<'r> Intent {
         return Ok( Box::new( Intent {
             name: if name.is_badvalue() {None} else {Some(as_str_checked(name).chain_err(|| "'name'")?.to_string())},
             xpath: if xpath_name.is_badvalue() {None} else {Some(MyXPath::build(xpath_name).chain_err(|| "'intent'")?)},
+            attrs: VariableDefinitions::build(&yaml_dict["attrs"]).chain_err(|| "'attrs:'")?,
             children: ReplacementArray::build(replace).chain_err(|| "'children:'")?,
         } ) );
     }
@@ -521,6 +1161,11 @@ impl<'r> Intent {
         for attr in mathml.attributes() {
             result.set_attribute_value(attr.name(), attr.value());           
         }
+        for attr in &self.attrs.defs {
+            let value = attr.value.evaluate(rules_with_context.get_context(), mathml)
+                    .chain_err(|| format!("in 'attrs:' value for '{}'", attr.name))?;
+            result.set_attribute_value(attr.name.as_str(), value.into_string().as_str());
+        }
 
         // debug!("Result from 'intent:'\n{}", mml_to_string(&result));
         return T::from_element(result);
@@ -1114,6 +1759,28 @@ impl SpeechPattern  {
             return process_include(file, include_file_name, do_include_fn);
         }
 
+        for speech_pattern in SpeechPattern::build_patterns(dict, file)? {
+            let speech_pattern = Box::new(speech_pattern);
+            // get the array of rules for the tag name
+            let rule_value = rules.rules.entry(speech_pattern.tag_name.clone()).or_default();
+
+            // if the name exists, replace it. Otherwise add the new rule
+            match rule_value.iter().enumerate().find(|&pattern| pattern.1.pattern_name == speech_pattern.pattern_name) {
+                None => rule_value.push(speech_pattern),
+                Some((i, _old_pattern)) => {
+                    let old_rule = &rule_value[i];
+                    info!("\n***WARNING: replacing {}/'{}' in {} with rule from {}\n",
+                            old_rule.tag_name, old_rule.pattern_name, old_rule.file_name, speech_pattern.file_name);
+                    rule_value[i] = speech_pattern;
+                },
+            }
+        }
+
+        return Ok( () );
+    }
+
+    /// Build the patterns (one per tag name) for a rule that is not an 'include'
+    fn build_patterns(dict: &Yaml, file: &Path) -> Result<Vec<SpeechPattern>> {
         let pattern_name = find_str(dict, "name");
 
         // tag_named can be either a string (most common) or an array of strings
@@ -1160,6 +1827,7 @@ impl SpeechPattern  {
         }
     
         // xpath's can't be cloned, so we need to do a 'build_xxx' for each tag name
+        let mut speech_patterns = Vec::with_capacity(tag_names.len());
         for tag_name in tag_names {
             let tag_name = tag_name.to_string();
             let pattern_xpath = MyXPath::build(&dict["match"])
@@ -1168,7 +1836,7 @@ impl SpeechPattern  {
                                 tag_name, pattern_name, yaml_to_string(dict, 1))
                     })?;
             let speech_pattern = 
-                Box::new( SpeechPattern{
+                SpeechPattern{
                     pattern_name: pattern_name.clone(),
                     tag_name: tag_name.clone(),
                     file_name: file.to_str().unwrap().to_string(),
@@ -1184,23 +1852,11 @@ impl SpeechPattern  {
                             format!("value for 'replace' in rule ({}: {}). Replacements:\n{}",
                                     tag_name, pattern_name, yaml_to_string(&dict["replace"], 1))
                     })?
-                } );
-            // get the array of rules for the tag name
-            let rule_value = rules.rules.entry(tag_name).or_default();
-
-            // if the name exists, replace it. Otherwise add the new rule
-            match rule_value.iter().enumerate().find(|&pattern| pattern.1.pattern_name == speech_pattern.pattern_name) {
-                None => rule_value.push(speech_pattern),
-                Some((i, _old_pattern)) => {
-                    let old_rule = &rule_value[i];
-                    info!("\n***WARNING: replacing {}/'{}' in {} with rule from {}\n",
-                            old_rule.tag_name, old_rule.pattern_name, old_rule.file_name, speech_pattern.file_name);
-                    rule_value[i] = speech_pattern;
-                },
-            }
+                };
+            speech_patterns.push(speech_pattern);
         }
 
-        return Ok( () );
+        return Ok( speech_patterns );
     }
 
     fn is_match(&self, context: &Context, mathml: Element) -> Result<bool> {
@@ -1219,6 +1875,12 @@ impl SpeechPattern  {
             }
         );
     }
+
+    /// The name used to report on the rule: "<rules for>: <rule name> [<tag>] (<file>)"
+    fn rule_key(&self, rules_for: RulesFor) -> String {
+        let file_name = Path::new(&self.file_name).file_name().map_or(self.file_name.to_string(), |name| name.to_string_lossy().to_string());
+        return format!("{}: {} [{}] ({})", rules_for, self.pattern_name, self.tag_name, file_name);
+    }
 }
 
 
@@ -1588,6 +2250,14 @@ impl<'c, 'r> ContextStack<'c> {
         return Ok( () );
     }
 
+    /// Like 'push', but for a single variable whose (string) value is known
+    fn push_value(&'r mut self, name: &str, value: &str, mathml: Element<'c>) {
+        let qname = QName::new(name);
+        let old_value = Evaluation::new(&self.base, Node::Element(mathml)).value_of(qname).cloned();
+        self.base.set_variable(qname, Value::String(value.to_string()));
+        self.old_values.push( VariableValues{ defs: vec![VariableValue{ name: name.to_string(), value: old_value }] } );
+    }
+
     fn pop(&mut self) {
         const MISSING_VALUE: &str = "-- unset value --";     // can't remove a variable from context, so use this value
         let old_values = self.old_values.pop().unwrap();
@@ -1630,10 +2300,12 @@ impl  fmt::Display for UnicodeDef {
 }
 
 impl UnicodeDef {
-    fn build(unicode_def: &Yaml, file_name: &Path, speech_rules: &SpeechRules, use_short: bool) -> Result<()> {
+    /// Add the definition in 'unicode_def' to 'defs' (a definition for a range or list of chars becomes a definition for each char).
+    /// The definition is compiled to check it, but only the uncompiled definition is kept (see [`UnicodeDefs`]).
+    fn build(unicode_def: &Yaml, file_name: &Path, defs: &mut UnicodeDefs) -> Result<()> {
         if let Some(include_file_name) = find_str(unicode_def, "include") {
             let do_include_fn = |new_file: &Path| {
-                speech_rules.read_unicode(Some(new_file.to_path_buf()), use_short)
+                read_unicode_defs(new_file, defs)
             };
             return process_include(file_name, include_file_name, do_include_fn);
         }
@@ -1649,11 +2321,6 @@ impl UnicodeDef {
         }
 
         let (ch, replacements) = dictionary.iter().next().ok_or_else(||  format!("Expected a unicode definition (e.g, '+':[t: \"plus\"]'), found {}", yaml_to_string(unicode_def, 0)))?;
-        let mut unicode_table = if use_short {
-            speech_rules.unicode_short.borrow_mut()
-        } else {
-            speech_rules.unicode_full.borrow_mut()
-        };
         if let Some(str) = ch.as_str() {
             if str.is_empty() {
                 bail!("Empty character definition. Replacement is {}", replacements.as_str().unwrap());
@@ -1662,12 +2329,12 @@ impl UnicodeDef {
             let first_ch = chars.next().unwrap();       // non-empty string, so a char exists
             if chars.next().is_some() {                       // more than one char
                 if str.contains('-')  {
-                    return process_range(str, replacements, unicode_table);
+                    return process_range(str, replacements, defs);
                 } else if first_ch != '0' {     // exclude 0xDDDD
                     for ch in str.chars() {     // restart the iterator
                         let ch_as_str = ch.to_string();
-                        unicode_table.insert(ch as u32, ReplacementArray::build(&substitute_ch(replacements, &ch_as_str))
-                                            .chain_err(|| format!("In definition of char: '{}'", str))?.replacements);
+                        add_def(defs, ch as u32, UnicodeDef::substitute_ch(replacements, &ch_as_str))
+                                .chain_err(|| format!("In definition of char: '{}'", str))?;
                     }
                     return Ok( () );
                 }
@@ -1675,12 +2342,11 @@ impl UnicodeDef {
         }
 
         let ch = UnicodeDef::get_unicode_char(ch)?;
-        unicode_table.insert(ch, ReplacementArray::build(replacements)
-                                        .chain_err(|| format!("In definition of char: '{}' (0x{})",
-                                                                        char::from_u32(ch).unwrap(), ch))?.replacements);
+        add_def(defs, ch, replacements.clone())
+                .chain_err(|| format!("In definition of char: '{}' (0x{})", char::from_u32(ch).unwrap(), ch))?;
         return Ok( () );
 
-        fn process_range(def_range: &str, replacements: &Yaml, mut unicode_table: RefMut<HashMap<u32,Vec<Replacement>>>) -> Result<()> {
+        fn process_range(def_range: &str, replacements: &Yaml, defs: &mut UnicodeDefs) -> Result<()> {
             // should be a character range (e.g., "A-Z")
             // iterate over that range and also substitute the char for '.' in the 
             let mut range = def_range.split('-');
@@ -1692,35 +2358,74 @@ impl UnicodeDef {
 
             for ch in first..last+1 {
                 let ch_as_str = char::from_u32(ch).unwrap().to_string();
-                unicode_table.insert(ch, ReplacementArray::build(&substitute_ch(replacements, &ch_as_str))
-                                        .chain_err(|| format!("In definition of char: '{}'", def_range))?.replacements);
+                add_def(defs, ch, UnicodeDef::substitute_ch(replacements, &ch_as_str))
+                        .chain_err(|| format!("In definition of char: '{}'", def_range))?;
             };
 
             return Ok( () );            
         }
 
-        fn substitute_ch(yaml: &Yaml, ch: &str) -> Yaml {
-            return match yaml {
-                Yaml::Array(ref v) => {
-                    Yaml::Array(
-                        v.iter()
-                         .map(|e| substitute_ch(e, ch))
-                         .collect::<Vec<Yaml>>()
-                    )
-                },
-                Yaml::Hash(ref h) => {
-                    Yaml::Hash(
-                        h.iter()
-                         .map(|(key,val)| (key.clone(), substitute_ch(val, ch)) )
-                         .collect::<Hash>()
-                    )
-                },
-                Yaml::String(s) => Yaml::String( s.replace('.', ch) ),
-                _ => yaml.clone(),
-            }
+        fn add_def(defs: &mut UnicodeDefs, ch: u32, replacements: Yaml) -> Result<()> {
+            ReplacementArray::build(&replacements)?;     // report errors when the file is read rather than when the char is spoken
+            defs.insert(ch, replacements);
+            return Ok( () );
         }
     }
     
+    fn substitute_ch(yaml: &Yaml, ch: &str) -> Yaml {
+        return match yaml {
+            Yaml::Array(ref v) => {
+                Yaml::Array(
+                    v.iter()
+                     .map(|e| UnicodeDef::substitute_ch(e, ch))
+                     .collect::<Vec<Yaml>>()
+                )
+            },
+            Yaml::Hash(ref h) => {
+                Yaml::Hash(
+                    h.iter()
+                     .map(|(key,val)| (key.clone(), UnicodeDef::substitute_ch(val, ch)) )
+                     .collect::<Hash>()
+                )
+            },
+            Yaml::String(s) => Yaml::String( s.replace('.', ch) ),
+            _ => yaml.clone(),
+        }
+    }
+
+    /// Check 'unicode_def' for errors (e.g., a bad char or a bad replacement) without adding it to a unicode table
+    fn check(unicode_def: &Yaml) -> Result<()> {
+        let dictionary = unicode_def.as_hash();
+        if dictionary.is_none() || dictionary.unwrap().len() != 1 {
+            bail!("Expected a unicode definition (e.g, '+':[t: \"plus\"]'), found {}", yaml_to_string(unicode_def, 0));
+        }
+        let (ch, replacements) = dictionary.unwrap().iter().next().unwrap();
+        if let Some(str) = ch.as_str() {
+            let mut chars = str.chars();
+            let first_ch = match chars.next() {
+                None => bail!("Empty character definition"),
+                Some(first_ch) => first_ch,
+            };
+            if chars.next().is_some() {
+                if str.contains('-') {
+                    let range = str.split('-').collect::<Vec<&str>>();
+                    if range.len() != 2 || range.iter().any(|end| end.chars().count() != 1) {
+                        bail!("Character range definition should be of the form 'a-z': '{}'", str);
+                    }
+                } else if first_ch == '0' {
+                    UnicodeDef::get_unicode_char(ch)?;
+                }
+                ReplacementArray::build(&UnicodeDef::substitute_ch(replacements, &first_ch.to_string()))
+                        .chain_err(|| format!("In definition of char: '{}'", str))?;
+                return Ok( () );
+            }
+        }
+        let ch = UnicodeDef::get_unicode_char(ch)?;
+        ReplacementArray::build(replacements)
+                .chain_err(|| format!("In definition of char: '{}' (0x{:X})", char::from_u32(ch).unwrap_or(' '), ch))?;
+        return Ok( () );
+    }
+
     fn get_unicode_char(ch: &Yaml) -> Result<u32> {
         // either "a" or 0x1234 (number)
         if let Some(ch) = ch.as_str() {
@@ -1747,7 +2452,100 @@ impl UnicodeDef {
 //   If this turns out to be something that others actually do, then a cache > 1 would be good
 
  type RuleTable = HashMap<String, Vec<Box<SpeechPattern>>>;
- type UnicodeTable = Rc<RefCell<HashMap<u32,Vec<Replacement>>>>;
+
+/// The definitions in a unicode file (and the files it includes) keyed by char.
+/// They are kept uncompiled because the compiled XPaths in a [`Replacement`] can't be shared between threads.
+type UnicodeDefs = HashMap<u32, Yaml>;
+/// The definitions of the unicode files that have been read keyed by file (along with the file's modification time when it was read)
+type UnicodeFiles = HashMap<PathBuf, (Option<SystemTime>, Arc<UnicodeDefs>)>;
+
+lazy_static! {
+    /// The unicode files read by any thread -- the definitions are never changed, so all the threads (and rule sets) share them.
+    static ref UNICODE_FILES: Mutex<UnicodeFiles> = Mutex::new( HashMap::new() );
+}
+
+/// Return the definitions in the unicode file 'path' -- the file is only read if no thread has read it since it was last changed.
+/// Only the modification time of 'path' is checked, so a change to a file it includes doesn't cause it to be read again.
+fn get_unicode_defs(path: &Path) -> Result<Arc<UnicodeDefs>> {
+    let modified = if cfg!(target_family = "wasm") {None} else {std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()};
+    let mut unicode_files = UNICODE_FILES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((time, defs)) = unicode_files.get(path) {
+        if *time == modified {
+            return Ok( Arc::clone(defs) );
+        }
+    }
+
+    // FIX: should read first (lang), then supplement with second (region)
+    info!("Reading unicode file {}", path.to_str().unwrap());
+    let mut defs = HashMap::with_capacity(997);
+    read_unicode_defs(path, &mut defs)?;
+    let defs = Arc::new(defs);
+    unicode_files.insert(path.to_path_buf(), (modified, Arc::clone(&defs)));
+    return Ok(defs);
+}
+
+fn read_unicode_defs(path: &Path, defs: &mut UnicodeDefs) -> Result<()> {
+    let unicode_file_contents = read_to_string_shim(path)?;
+    let unicode_build_fn = |unicode_def_list: &Yaml| {
+        let unicode_defs = unicode_def_list.as_vec();
+        if unicode_defs.is_none() {
+            bail!("File '{}' does not begin with an array", yaml_to_type(unicode_def_list));
+        };
+        for unicode_def in unicode_defs.unwrap() {
+            UnicodeDef::build(unicode_def, path, defs)
+                    .chain_err(|| {format!("In file {:?}", path.to_str())})?;
+        };
+        return Ok(());
+    };
+
+    return compile_rule(&unicode_file_contents, unicode_build_fn)
+                .chain_err(||format!("in file {:?}", path.to_str().unwrap()));
+}
+
+/// The unicode definitions used by the rules of a thread (the per-thread layer on top of the shared [`UnicodeDefs`]).
+/// A definition is compiled the first time its char is looked up.
+#[derive(Default)]
+struct UnicodeTableEntries {
+    defs: Option<Arc<UnicodeDefs>>,                 // None until the unicode file is read
+    compiled: HashMap<u32, Rc<Vec<Replacement>>>,   // the definitions that have been looked up
+}
+
+impl UnicodeTableEntries {
+    fn is_loaded(&self) -> bool {
+        return self.defs.is_some();
+    }
+
+    fn len(&self) -> usize {
+        return self.defs.as_ref().map_or(0, |defs| defs.len());
+    }
+
+    fn set(&mut self, defs: Arc<UnicodeDefs>) {
+        self.defs = Some(defs);
+        self.compiled.clear();
+    }
+
+    fn clear(&mut self) {
+        self.defs = None;
+        self.compiled.clear();
+    }
+
+    /// The (compiled) definition of 'ch' or None if there isn't one
+    fn get(&mut self, ch: u32) -> Result<Option<Rc<Vec<Replacement>>>> {
+        if let Some(replacements) = self.compiled.get(&ch) {
+            return Ok( Some( Rc::clone(replacements) ) );
+        }
+        let def = match self.defs.as_ref().and_then(|defs| defs.get(&ch)) {
+            None => return Ok(None),
+            Some(def) => def,
+        };
+        let replacements = Rc::new( ReplacementArray::build(def)
+                .chain_err(|| format!("In definition of char: '{}' (0x{:X})", char::from_u32(ch).unwrap_or(' '), ch))?.replacements );
+        self.compiled.insert(ch, Rc::clone(&replacements));
+        return Ok( Some(replacements) );
+    }
+}
+
+ type UnicodeTable = Rc<RefCell<UnicodeTableEntries>>;
 
  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
  pub enum RulesFor {
@@ -1781,6 +2579,8 @@ pub struct SpeechRules {
     name: RulesFor,
     pub pref_manager: Rc<RefCell<PreferenceManager>>,
     rules: RuleTable,                       // the speech rules used (partitioned into MathML tags in hashmap, then linearly searched)
+    rules_file: Option<PathBuf>,            // the file 'rules' were read from
+    cached_rules: HashMap<PathBuf, RuleTable>,  // rules read earlier (e.g., for another SpeechStyle) keyed by their file
     translate_single_chars_only: bool,      // strings like "half" don't want 'a's translated, but braille does
     unicode_short: UnicodeTable,            // the short list of rules used for Unicode characters
     unicode_full:  UnicodeTable,            // the long remaining rules used for Unicode characters
@@ -1807,7 +2607,11 @@ pub struct SpeechRulesWithContext<'c, 's:'c, 'm:'c> {
     context_stack: ContextStack<'c>,   // current value of (context) variables
     doc: Document<'m>,
     nav_node_id: String,
+    highlighted_ids: HashSet<String>,   // nodes whose braille starts with a highlighted char (used to find braille line break points)
     pub inside_spell: bool,     // hack to allow 'spell' to avoid infinite loop (see 'spell' implementation in tts.rs)
+    earcons: bool,              // add (experimental) earcons to the speech (only for SSML speech)
+    text_voice: Option<(String, String)>,   // start/end tags that distinguish text (mtext) from math (only for SSML/SAPI5 speech)
+    structure_announcement: String, // "Before" or "After" if structures are announced, "" otherwise (only for speech)
 }
 
 impl<'c, 's:'c, 'm:'c> fmt::Display for SpeechRulesWithContext<'c, 's,'m> {
@@ -1817,12 +2621,250 @@ impl<'c, 's:'c, 'm:'c> fmt::Display for SpeechRulesWithContext<'c, 's,'m> {
     }
 }
 
+/// Records which rules fired (see [`crate::interface::collect_rule_coverage`]).
+#[derive(Debug, Default, Clone)]
+pub struct RuleCoverage {
+    /// Number of times a rule fired -- the key is "<rules for>: <rule name> [<tag>] (<file>)"
+    pub rules_fired: HashMap<String, usize>,
+    /// Expressions (canonical MathML) that used a fallback (e.g., an unknown char or a '*' rule) along with the reasons
+    pub fallbacks: Vec<(String, Vec<String>)>,
+    /// Expressions that could not be spoken/brailled along with the error
+    pub errors: Vec<(String, String)>,
+    /// Number of expressions processed
+    pub n_expressions: usize,
+    current_fallbacks: Vec<String>,       // fallbacks for the expression being processed
+}
+
 thread_local!{
+    /// The rule coverage being collected (`None` when coverage isn't being collected)
+    pub static RULE_COVERAGE: RefCell<Option<RuleCoverage>> = const { RefCell::new(None) };
+}
+
+impl RuleCoverage {
+    fn add_rule(rules_for: RulesFor, pattern: &SpeechPattern, mathml: Element) {
+        RULE_COVERAGE.with(|coverage| {
+            if let Some(coverage) = coverage.borrow_mut().as_mut() {
+                *coverage.rules_fired.entry(pattern.rule_key(rules_for)).or_insert(0) += 1;
+                if pattern.tag_name == "*" && rules_for != RulesFor::Intent {   // intent's '*' rule just copies the element
+                    coverage.current_fallbacks.push( format!("{}: '*' rule used for '{}'", rules_for, name(&mathml)) );
+                }
+            }
+        });
+    }
+
+    /// 'reason' is only called when coverage is being collected
+    pub fn add_fallback<F: FnOnce() -> String>(reason: F) {
+        RULE_COVERAGE.with(|coverage| {
+            if let Some(coverage) = coverage.borrow_mut().as_mut() {
+                coverage.current_fallbacks.push(reason());
+            }
+        });
+    }
+
+    /// Record the end of processing 'mathml' (if 'error' is not None, processing failed)
+    pub fn end_expression(&mut self, mathml: String, error: Option<String>) {
+        self.n_expressions += 1;
+        let mut fallbacks = std::mem::take(&mut self.current_fallbacks);
+        if !fallbacks.is_empty() {
+            fallbacks.dedup();
+            self.fallbacks.push( (mathml.clone(), fallbacks) );
+        }
+        if let Some(error) = error {
+            self.errors.push( (mathml, error) );
+        }
+    }
+}
+
+impl fmt::Display for RuleCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rule coverage for {} expressions ({} with fallbacks, {} with errors)",
+                 self.n_expressions, self.fallbacks.len(), self.errors.len())?;
+        let mut rules_fired = self.rules_fired.iter().collect::<Vec<(&String, &usize)>>();
+        rules_fired.sort_by(|(name1, count1), (name2, count2)| count2.cmp(count1).then(name1.cmp(name2)));
+        for (name, count) in rules_fired {
+            writeln!(f, "{:>7} {}", count, name)?;
+        }
+        for (mathml, reasons) in &self.fallbacks {
+            writeln!(f, "Fallback: {}\n  {}", mathml.trim(), reasons.join("\n  "))?;
+        }
+        for (mathml, error) in &self.errors {
+            writeln!(f, "Error: {}\n  {}", mathml.trim(), error)?;
+        }
+        return Ok( () );
+    }
+}
+
+/// How serious a problem found by [`check_rules`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleDiagnosticLevel {
+    /// The file or rule can't be used (e.g., a syntax error in an XPath expression)
+    Error,
+    /// Likely a mistake (e.g., a rule that can never be used), but the rules still work
+    Warning,
+}
+
+/// A problem found in a rule file by [`check_rules`]
+#[derive(Debug, Clone)]
+pub struct RuleDiagnostic {
+    pub level: RuleDiagnosticLevel,
+    /// The file with the problem
+    pub file: String,
+    /// The rule ("name [tag]") or char with the problem -- empty if the problem is with the file as a whole
+    pub rule: String,
+    pub message: String,
+}
+
+impl fmt::Display for RuleDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.rule.is_empty() {
+            return write!(f, "{:?}: {}: {}", self.level, self.file, self.message);
+        }
+        return write!(f, "{:?}: {} ({}): {}", self.level, self.file, self.rule, self.message);
+    }
+}
+
+/// Check all the rule files in 'dir' and its subdirectories without using them.
+/// The checks are:
+/// * the files are valid YAML and each rule/unicode definition can be built (this includes the syntax of the XPath expressions)
+/// * included files exist
+/// * the speech and braille rules have unicode files (possibly in a parent directory)
+/// * a rule doesn't have the same name and tag as an earlier rule in the same file (the later one replaces the earlier one)
+/// * a rule isn't preceded in the same file by a rule for the same tag that always matches (the later one can never be used)
+pub fn check_rules(dir: &Path) -> Vec<RuleDiagnostic> {
+    let mut diagnostics = vec![];
+    if !crate::shim_filesystem::is_dir_shim(dir) {
+        diagnostics.push( RuleDiagnostic{
+            level: RuleDiagnosticLevel::Error,
+            file: dir.to_string_lossy().to_string(),
+            rule: String::default(),
+            message: "not a directory".to_string(),
+        });
+        return diagnostics;
+    }
+    check_rules_dir(dir, &mut diagnostics);
+    return diagnostics;
+
+    fn check_rules_dir(dir: &Path, diagnostics: &mut Vec<RuleDiagnostic>) {
+        use crate::shim_filesystem::{is_dir_shim, is_file_shim, read_dir_shim};
+        let mut names = read_dir_shim(dir);
+        names.sort();
+        if names.iter().any(|name| name.ends_with("_Rules.yaml")) {
+            // like the rule files, unicode files are searched for in the parent dirs up to the Rules dir
+            for unicode_file in ["unicode.yaml", "unicode-full.yaml"] {
+                let mut found = false;
+                for ancestor in dir.ancestors() {
+                    if is_file_shim(&ancestor.join(unicode_file)) {
+                        found = true;
+                        break;
+                    }
+                    if ancestor.ends_with("Rules") {
+                        break;
+                    }
+                }
+                if !found {
+                    diagnostics.push( RuleDiagnostic{
+                        level: RuleDiagnosticLevel::Warning,
+                        file: dir.to_string_lossy().to_string(),
+                        rule: String::default(),
+                        message: format!("'{}' not found -- the one for the default language or braille code will be used", unicode_file),
+                    });
+                }
+            }
+        }
+        for name in names {
+            let path = dir.join(&name);
+            if is_dir_shim(&path) {
+                check_rules_dir(&path, diagnostics);
+            } else if name.ends_with(".yaml") {
+                check_rule_file(&path, diagnostics);
+            }
+        }
+    }
+
+    fn check_rule_file(path: &Path, diagnostics: &mut Vec<RuleDiagnostic>) {
+        let file = path.to_string_lossy().to_string();
+        let mut add_diagnostic = |level: RuleDiagnosticLevel, rule: String, message: String| {
+            diagnostics.push( RuleDiagnostic{ level, file: file.clone(), rule, message } );
+        };
+        let contents = match read_to_string_shim(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                add_diagnostic(RuleDiagnosticLevel::Error, String::default(), crate::interface::errors_to_string(&e));
+                return;
+            },
+        };
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        let is_rule_file = file_name != "prefs.yaml" && file_name != "definitions.yaml";    // those have their own format
+        let check_fn = |entries: &Yaml| {
+            if !is_rule_file {
+                return Ok( () );
+            }
+            let entries = as_vec_checked(entries)?;
+            let mut rules_seen: Vec<(String, String)> = vec![];     // (tag, name)
+            let mut always_matches: HashMap<String, String> = HashMap::new();  // tag -> name
+            for entry in entries {
+                if let Some(include_file_name) = find_str(entry, "include") {
+                    if !crate::shim_filesystem::is_file_shim(&path.parent().unwrap().join(include_file_name)) {
+                        add_diagnostic(RuleDiagnosticLevel::Error, String::default(),
+                                       format!("included file '{}' does not exist", include_file_name));
+                    }
+                } else if !entry["name"].is_badvalue() || !entry["tag"].is_badvalue() {
+                    let rule = format!("{} [{}]", find_str(entry, "name").unwrap_or("?"), find_str(entry, "tag").unwrap_or("?"));
+                    match SpeechPattern::build_patterns(entry, path) {
+                        Err(e) => add_diagnostic(RuleDiagnosticLevel::Error, rule, crate::interface::errors_to_string(&e)),
+                        Ok(patterns) => {
+                            for pattern in patterns {
+                                let rule = format!("{} [{}]", pattern.pattern_name, pattern.tag_name);
+                                let key = (pattern.tag_name.clone(), pattern.pattern_name.clone());
+                                if rules_seen.contains(&key) {
+                                    add_diagnostic(RuleDiagnosticLevel::Warning, rule,
+                                                   "duplicate name and tag -- it replaces the earlier rule".to_string());
+                                    continue;
+                                }
+                                if let Some(earlier_rule) = always_matches.get(&pattern.tag_name) {
+                                    add_diagnostic(RuleDiagnosticLevel::Warning, rule,
+                                                   format!("unreachable -- the earlier rule '{}' always matches", earlier_rule));
+                                } else if matches!(pattern.pattern.rc.string.trim(), "." | "true()") {
+                                    always_matches.insert(pattern.tag_name.clone(), pattern.pattern_name.clone());
+                                }
+                                rules_seen.push(key);
+                            }
+                        },
+                    }
+                } else if let Err(e) = UnicodeDef::check(entry) {
+                    add_diagnostic(RuleDiagnosticLevel::Error, yaml_to_string(entry, 0).lines().next().unwrap_or("").to_string(),
+                                   crate::interface::errors_to_string(&e));
+                }
+            }
+            return Ok( () );
+        };
+        if let Err(e) = compile_rule(&contents, check_fn) {
+            diagnostics.push( RuleDiagnostic{
+                level: RuleDiagnosticLevel::Error,
+                file: path.to_string_lossy().to_string(),
+                rule: String::default(),
+                message: crate::interface::errors_to_string(&e),
+            });
+        }
+    }
+}
+
+thread_local!{
+    /// Host supplied speech for individual characters, keyed by (language, char).
+    /// These take precedence over the entries in the unicode files.
+    pub static SPEECH_CHARACTER_OVERRIDES: RefCell<HashMap<(String, char), String>> =
+        RefCell::new( HashMap::new() );
+
+    /// Host supplied replacements for phrases in the speech, keyed by language and then by phrase.
+    /// These are applied after the speech is generated (see [`map_phrases`]).
+    pub static SPEECH_PHRASE_MAPPINGS: RefCell<HashMap<String, PhraseMappings>> =
+        RefCell::new( HashMap::new() );
+
     static SPEECH_UNICODE_SHORT: UnicodeTable =
-        Rc::new( RefCell::new( HashMap::with_capacity(6997) ) );
+        Rc::new( RefCell::new( UnicodeTableEntries::default() ) );
         
     static SPEECH_UNICODE_FULL: UnicodeTable =
-        Rc::new( RefCell::new( HashMap::with_capacity(497) ) );
+        Rc::new( RefCell::new( UnicodeTableEntries::default() ) );
         
     /// The current set of speech rules
     // maybe this should be a small cache of rules in case people switch rules/prefs?
@@ -1855,8 +2897,8 @@ impl SpeechRules {
                     // debug!("SpeechRules new for {}, tts {}", name, pref_manager.borrow().get_api_prefs().to_string("TTS"));
                     let unicode = if name == RulesFor::Braille {
                         (
-                            Rc::new( RefCell::new (HashMap::with_capacity(497)) ),
-                            Rc::new( RefCell::new (HashMap::with_capacity(2497)) )
+                            Rc::new( RefCell::new (UnicodeTableEntries::default()) ),
+                            Rc::new( RefCell::new (UnicodeTableEntries::default()) )
                         )
                     } else {
                         (
@@ -1869,6 +2911,8 @@ impl SpeechRules {
                         error: Default::default(),
                         name,
                         rules: HashMap::with_capacity(if name == RulesFor::Intent {1023} else {31}),                       // lazy load them
+                        rules_file: None,
+                        cached_rules: HashMap::new(),
                         unicode_short: unicode.0,       // lazy load them
                         unicode_full: unicode.1,        // lazy load them
                         translate_single_chars_only,
@@ -1883,8 +2927,10 @@ impl SpeechRules {
             error,
             name,
             rules: HashMap::with_capacity(1),
-            unicode_short: Rc::new( RefCell::new (HashMap::with_capacity(1)) ),
-            unicode_full: Rc::new( RefCell::new (HashMap::with_capacity(1)) ),
+            rules_file: None,
+            cached_rules: HashMap::new(),
+            unicode_short: Rc::new( RefCell::new (UnicodeTableEntries::default()) ),
+            unicode_full: Rc::new( RefCell::new (UnicodeTableEntries::default()) ),
             translate_single_chars_only: true,
             pref_manager,
         };
@@ -1921,10 +2967,14 @@ impl SpeechRules {
     pub fn read_files(&mut self) -> Result<()> {
         if self.rules.is_empty() {
             let rule_file = self.pref_manager.borrow().get_rule_file(&self.name).clone();
-            self.read_patterns(&rule_file)?;
+            match rule_file[0].as_ref().and_then(|file| self.cached_rules.remove(file)) {
+                Some(rules) => self.rules = rules,
+                None => self.read_patterns(&rule_file)?,
+            }
+            self.rules_file = rule_file[0].clone();
         }
-        if self.unicode_short.borrow().is_empty()  {
-            self.read_unicode(None, true)?;
+        if !self.unicode_short.borrow().is_loaded()  {
+            self.read_unicode(true)?;
         }
         return Ok( () );
     }
@@ -1942,7 +2992,14 @@ impl SpeechRules {
             }
         } else {
             if changes.speech_rules {
-                self.rules.clear();
+                // the rule file changed because of a preference (e.g., SpeechStyle) -- keep the rules in case it changes back
+                // (e.g., for an expression with 'data-speech-style')
+                let rules = std::mem::take(&mut self.rules);
+                if let Some(file) = self.rules_file.take() {
+                    if !rules.is_empty() {
+                        self.cached_rules.insert(file, rules);
+                    }
+                }
             }
             if changes.speech_unicode_short {
                 self.unicode_short.borrow_mut().clear();
@@ -1954,11 +3011,15 @@ impl SpeechRules {
     }
 
     pub fn update() {
-        if let Some(files_changed) = PreferenceManager::get().borrow_mut().is_up_to_date() {
+        // the borrow must end before the rules are used (creating them borrows the preferences)
+        let files_changed = PreferenceManager::get().borrow_mut().is_up_to_date();
+        if let Some(files_changed) = files_changed {
             SPEECH_RULES.with(|rules| {
                 let mut rules = rules.borrow_mut();
                 if files_changed.speech_rules {
+                    // a rule file might have been edited, so the cached rules can't be trusted
                     rules.rules.clear();
+                    rules.cached_rules.clear();
                 }
                 if files_changed.speech_unicode_short  {
                     rules.unicode_short.borrow_mut().clear();
@@ -2019,38 +3080,21 @@ impl SpeechRules {
         return Ok( () );  
     }
     
-    fn read_unicode(&self, path: Option<PathBuf>, use_short: bool) -> Result<()> {
-        let path = match path {
-            Some(p) => p,
-            None => {
-                // get the path to either the short or long unicode file
-                let pref_manager = self.pref_manager.borrow();
-                let unicode_files = if self.name == RulesFor::Braille {
-                    pref_manager.get_braille_unicode_file()
-                } else {
-                    pref_manager.get_speech_unicode_file()
-                };
-                if use_short {unicode_files.0} else {unicode_files.1}
-            }
-        };
-
-        // FIX: should read first (lang), then supplement with second (region)
-        info!("Reading unicode file {}", path.to_str().unwrap());
-        let unicode_file_contents = read_to_string_shim(&path)?;
-        let unicode_build_fn = |unicode_def_list: &Yaml| {
-            let unicode_defs = unicode_def_list.as_vec();
-            if unicode_defs.is_none() {
-                bail!("File '{}' does not begin with an array", yaml_to_type(unicode_def_list));
-            };
-            for unicode_def in unicode_defs.unwrap() {
-                UnicodeDef::build(unicode_def, &path, self, use_short)
-                        .chain_err(|| {format!("In file {:?}", path.to_str())})?;
+    fn read_unicode(&self, use_short: bool) -> Result<()> {
+        // get the path to either the short or long unicode file
+        let path = {
+            let pref_manager = self.pref_manager.borrow();
+            let unicode_files = if self.name == RulesFor::Braille {
+                pref_manager.get_braille_unicode_file()
+            } else {
+                pref_manager.get_speech_unicode_file()
             };
-            return Ok(());
+            if use_short {unicode_files.0} else {unicode_files.1}
         };
-
-        return compile_rule(&unicode_file_contents, unicode_build_fn)
-                    .chain_err(||format!("in file {:?}", path.to_str().unwrap()));
+        let defs = get_unicode_defs(&path)?;
+        let unicode_table = if use_short {&self.unicode_short} else {&self.unicode_full};
+        unicode_table.borrow_mut().set(defs);
+        return Ok( () );
     }
 }
 
@@ -2061,15 +3105,37 @@ use crate::prefs::FilesChanged;
 ///   'r -- the lifetime of the reference (this seems to be key to keep the rust memory checker happy)
 impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
     pub fn new(speech_rules: &'s SpeechRules, doc: Document<'m>, nav_node_id: String) -> SpeechRulesWithContext<'c, 's, 'm> {
+        let pref_manager = speech_rules.pref_manager.borrow();
+        let earcons = speech_rules.name == RulesFor::Speech &&
+                      pref_manager.get_api_prefs().to_string("Earcons") == "true" &&
+                      pref_manager.get_tts() == TTS::SSML;
+        let text_voice = if speech_rules.name == RulesFor::Speech {
+            pref_manager.get_tts().text_voice_tags(&pref_manager.get_user_prefs().to_string("TextVoice"))
+        } else {
+            None
+        };
+        let structure_announcement = match pref_manager.get_user_prefs().to_string("StructureAnnouncement").as_str() {
+            announcement @ ("Before" | "After") if speech_rules.name == RulesFor::Speech => announcement.to_string(),
+            _ => "".to_string(),
+        };
         return SpeechRulesWithContext {
             speech_rules,
-            context_stack: ContextStack::new(&speech_rules.pref_manager.borrow()),
+            context_stack: ContextStack::new(&pref_manager),
             doc,
             nav_node_id,
+            highlighted_ids: HashSet::new(),
             inside_spell: false,
+            earcons,
+            text_voice,
+            structure_announcement,
         }
     }
 
+    /// The first char of the braille for each node with an id in 'ids' gets dots 7 & 8 (independent of the 'BrailleNavHighlight' pref)
+    pub fn set_highlighted_ids(&mut self, ids: HashSet<String>) {
+        self.highlighted_ids = ids;
+    }
+
     pub fn get_rules(&mut self) -> &SpeechRules {
         return self.speech_rules;
     }
@@ -2083,6 +3149,61 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
     }
 
     pub fn match_pattern<T:TreeOrString<'c, 'm, T>>(&'r mut self, mathml: Element<'c>) -> Result<T> {
+        // an intent property (e.g., intent=':verbose') overrides the Verbosity preference for the subtree
+        let verbosity = mathml.attribute_value(crate::infer_intent::INTENT_TYPE).and_then(|properties|
+            properties.split_whitespace().find_map(|property| match property {
+                "terse" => Some("Terse"),
+                "medium" => Some("Medium"),
+                "verbose" => Some("Verbose"),
+                _ => None,
+            })
+        );
+        if let Some(verbosity) = verbosity {
+            self.context_stack.push_value("Verbosity", verbosity, mathml);
+            let result = self.match_pattern_with_structure_marks(mathml);
+            self.context_stack.pop();
+            return result;
+        }
+        return self.match_pattern_with_structure_marks(mathml);
+    }
+
+    /// Matches the rules and then marks structures with earcons and/or announcements (e.g, "fraction") if requested.
+    /// Text (`mtext`) is also spoken with a different voice or emphasis if requested.
+    fn match_pattern_with_structure_marks<T:TreeOrString<'c, 'm, T>>(&'r mut self, mathml: Element<'c>) -> Result<T> {
+        let earcon = if self.earcons {crate::tts::earcon_name(mathml)} else {None};
+        let announcement = if self.structure_announcement.is_empty() {None} else {self.get_structure_announcement(mathml)};
+        let text_voice = if name(&mathml) == "mtext" {self.text_voice.clone()} else {None};
+        if earcon.is_none() && announcement.is_none() && text_voice.is_none() {
+            return self.match_pattern_rules(mathml);
+        }
+
+        let mut speech = self.match_pattern_rules::<T>(mathml)?;
+        if let Some((start_tag, end_tag)) = text_voice {
+            speech = T::add_text_voice(speech, &start_tag, &end_tag)?;
+        }
+        if let Some(announcement) = announcement {
+            let pref_manager = self.speech_rules.pref_manager.borrow();
+            let pause = pref_manager.get_tts().pause(&pref_manager, PAUSE_SHORT);
+            speech = T::announce_structure(speech, &announcement, &pause, self.structure_announcement == "Before")?;
+        }
+        if let Some(earcon) = earcon {
+            speech = T::add_earcon(speech, earcon)?;
+        }
+        return Ok(speech);
+    }
+
+    /// Returns the words from definitions.yaml that announce the structure of 'mathml' (None if not a structure or no words are defined)
+    fn get_structure_announcement(&self, mathml: Element<'c>) -> Option<String> {
+        let index = structure_index(mathml)?;
+        let definition_name = if self.structure_announcement == "Before" {"StructureNames_vec"} else {"StructureEndNames_vec"};
+        return crate::definitions::DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            let words = definitions.get_vec(definition_name)?;
+            return words.get(index).filter(|word| !word.is_empty()).cloned();
+        });
+    }
+
+    fn match_pattern_rules<T:TreeOrString<'c, 'm, T>>(&'r mut self, mathml: Element<'c>) -> Result<T> {
         // debug!("Looking for a match for: \n{}", mml_to_string(&mathml));
         let tag_name = mathml.name().local_part();
         let rules = &self.speech_rules.rules;
@@ -2125,11 +3246,17 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
             if pattern.match_uses_var_defs {
                 self.context_stack.push(pattern.var_defs.clone(), mathml)?;
             }
-            if pattern.is_match(&self.context_stack.base, mathml)
-                    .chain_err(|| error_string(pattern, mathml) )? {
+            #[cfg(feature = "profile")]
+            let start = std::time::Instant::now();
+            let is_match = pattern.is_match(&self.context_stack.base, mathml)
+                    .chain_err(|| error_string(pattern, mathml) )?;
+            #[cfg(feature = "profile")]
+            crate::profile::add_match(|| pattern.rule_key(self.speech_rules.name), is_match, start.elapsed());
+            if is_match {
                 if !pattern.match_uses_var_defs && pattern.var_defs.len() > 0 { // don't push them on twice
                     self.context_stack.push(pattern.var_defs.clone(), mathml)?;
                 }
+                RuleCoverage::add_rule(self.speech_rules.name, pattern, mathml);
                 let result: Result<T> = pattern.replacements.replace(self, mathml);
                 if pattern.var_defs.len() > 0 {
                     self.context_stack.pop();
@@ -2137,13 +3264,15 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
                 return match result {
                     Ok(s) => {
                         // for all except braille, nav_node_id will be an empty string and will not match
-                        if !self.nav_node_id.is_empty() {
+                        if !self.nav_node_id.is_empty() || !self.highlighted_ids.is_empty() {
                             match mathml.attribute_value("id") {
                                 None => {},
                                 Some(id) => {
                                     if self.nav_node_id == id {
                                         let highlight_style =  self.speech_rules.pref_manager.borrow().get_user_prefs().to_string("BrailleNavHighlight");
                                         return Ok( Some( T::highlight_braille(s, highlight_style) ) );
+                                    } else if self.highlighted_ids.contains(id) {
+                                        return Ok( Some( T::highlight_braille(s, "FirstChar".to_string()) ) );
                                     }
                                 }
                             }
@@ -2261,6 +3390,9 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
                 Replacement::Insert(ic) => {
                     ic.replace(self, mathml)?                     
                 },
+                Replacement::ScriptOrder(so) => {
+                    so.replace(self, mathml)?                     
+                },
                 Replacement::Translate(id) => {
                     id.replace(self, mathml)?                     
                 },
@@ -2353,21 +3485,30 @@ impl<'c, 's:'c, 'r, 'm:'c> SpeechRulesWithContext<'c, 's,'m> {
         return Ok( result );
 
         fn replace_single_char<'c, 's:'c, 'm, 'r>(rules_with_context: &'r mut SpeechRulesWithContext<'c,'s,'m>, ch: char, mathml: Element<'c>) -> Result<String> {
+            if rules_with_context.speech_rules.name == RulesFor::Speech {
+                let language = rules_with_context.speech_rules.pref_manager.borrow().get_language();
+                let spoken_text = SPEECH_CHARACTER_OVERRIDES.with(|overrides|
+                    overrides.borrow().get( &(language, ch) ).cloned()
+                );
+                if let Some(spoken_text) = spoken_text {
+                    return Ok(spoken_text);
+                }
+            }
             let ch_as_u32 = ch as u32;
-            let mut unicode = rules_with_context.speech_rules.unicode_short.borrow();
-            let mut replacements = unicode.get( &ch_as_u32 );
+            let speech_rules = rules_with_context.speech_rules;
+            let mut replacements = speech_rules.unicode_short.borrow_mut().get(ch_as_u32)?;
             if replacements.is_none() {
                 // see if it in the full unicode table (if it isn't loaded already)
-                if rules_with_context.speech_rules.unicode_full.borrow().is_empty() {
-                    info!("*** Loading full unicode {} for char '{}'/{:#06x}", rules_with_context.speech_rules.name, ch, ch_as_u32);
-                    rules_with_context.speech_rules.read_unicode(None, false)?;
-                    info!("# Unicode defs = {}/{}", rules_with_context.speech_rules.unicode_short.borrow().len(), rules_with_context.speech_rules.unicode_full.borrow().len());
+                if !speech_rules.unicode_full.borrow().is_loaded() {
+                    info!("*** Loading full unicode {} for char '{}'/{:#06x}", speech_rules.name, ch, ch_as_u32);
+                    speech_rules.read_unicode(false)?;
+                    info!("# Unicode defs = {}/{}", speech_rules.unicode_short.borrow().len(), speech_rules.unicode_full.borrow().len());
 
                 }
-                unicode = rules_with_context.speech_rules.unicode_full.borrow();
-                replacements = unicode.get( &ch_as_u32 );
+                replacements = speech_rules.unicode_full.borrow_mut().get(ch_as_u32)?;
                 if replacements.is_none() {
-                    // debug!("*** Did not find unicode {} for char '{}'/{:#06x}", rules_with_context.speech_rules.name, ch, ch_as_u32);
+                    // debug!("*** Did not find unicode {} for char '{}'/{:#06x}", speech_rules.name, ch, ch_as_u32);
+                    RuleCoverage::add_fallback(|| format!("{}: no unicode entry for '{}' ({:#06x})", speech_rules.name, ch, ch_as_u32));
                     return Ok(String::from(ch));   // no replacement, so just return the char and hope for the best
                 }
             };
@@ -2507,6 +3648,22 @@ mod tests {
         assert_eq!(result.unwrap(), r#"DEBUG(ClearSpeak_Matrix = 'Combinatorics', "ClearSpeak_Matrix = 'Combinatorics'" ) and IsBracketed(., '(', ')')"#);
     }
 
+    #[test]
+    fn unicode_defs_shared_by_threads() {
+        let path = Path::new(&super::super::abs_rules_dir_path()).join("Languages").join("en").join("unicode.yaml");
+        let defs = get_unicode_defs(&path).unwrap();
+        let path_for_thread = path.clone();
+        let defs_in_thread = std::thread::spawn(move || get_unicode_defs(&path_for_thread).unwrap()).join().unwrap();
+        assert!(Arc::ptr_eq(&defs, &defs_in_thread));
+
+        // only the chars that are looked up are compiled
+        let mut unicode_table = UnicodeTableEntries::default();
+        unicode_table.set(defs);
+        assert!(unicode_table.get('+' as u32).unwrap().is_some());
+        assert!(unicode_table.get(0xE000).unwrap().is_none());
+        assert_eq!(unicode_table.compiled.len(), 1);
+    }
+
     // #[test]
     // fn test_nested_debug_quoted_paren() {
     //     let str = r#"DEBUG(*[2]/*[3][DEBUG(text()='(')])"#;
@@ -0,0 +1,79 @@
+//! Categories of operators (e.g., relations, arrows, set operators, and large operators).
+//! Speech, navigation, and braille all need to know things like "is this a relation?".
+//! They should all ask here so that the answers are consistent.
+//! The rules use the xpath function `IsInCategory(node, 'Relation')` (see `xpath_functions.rs`).
+//!
+//! Membership in a category is either computed from the operator dictionary (e.g., relations) or is a list in `definitions.yaml`.
+//! A new category is added to [`SymbolCategory`], [`SymbolCategory::ALL`], and [`SymbolCategory::contains`].
+#![allow(clippy::needless_return)]
+
+use sxd_document::dom::Element;
+use crate::canonicalize::{as_text, name, is_fence, is_relational_op};
+use crate::definitions::DEFINITIONS;
+
+/// A category of operator (`mo`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolCategory {
+    /// Operators with the priority of "=" in the operator dictionary (e.g., "=", "<", and "∈")
+    Relation,
+    /// Arrows and harpoons (the `Arrows` list in definitions.yaml)
+    Arrow,
+    /// Operators that combine sets (e.g., "∪" and "∖") -- the `SetOperators` list in definitions.yaml
+    SetOperator,
+    /// Operators such as "∑" and "∫" that take scripts as limits -- the `LargeOperators` list in definitions.yaml
+    LargeOperator,
+    /// Parens, brackets, and other fences in the operator dictionary
+    Fence,
+}
+
+impl SymbolCategory {
+    pub const ALL: [SymbolCategory; 5] = [
+        SymbolCategory::Relation, SymbolCategory::Arrow, SymbolCategory::SetOperator,
+        SymbolCategory::LargeOperator, SymbolCategory::Fence,
+    ];
+
+    /// The name used for the category in the rules (e.g., "Relation")
+    pub fn name(&self) -> &'static str {
+        return match self {
+            SymbolCategory::Relation => "Relation",
+            SymbolCategory::Arrow => "Arrow",
+            SymbolCategory::SetOperator => "SetOperator",
+            SymbolCategory::LargeOperator => "LargeOperator",
+            SymbolCategory::Fence => "Fence",
+        };
+    }
+
+    /// The category with the given name (see [`SymbolCategory::name`])
+    pub fn from_name(name: &str) -> Option<SymbolCategory> {
+        return SymbolCategory::ALL.iter().find(|category| category.name() == name).copied();
+    }
+
+    /// Returns true if 'mo' is in the category (only `mo`s are in a category)
+    pub fn contains(&self, mo: Element) -> bool {
+        if name(&mo) != "mo" {
+            return false;
+        }
+        return match self {
+            SymbolCategory::Relation => is_relational_op(mo),
+            SymbolCategory::Arrow => is_in_definition(mo, "Arrows"),
+            SymbolCategory::SetOperator => is_in_definition(mo, "SetOperators"),
+            SymbolCategory::LargeOperator => is_in_definition(mo, "LargeOperators"),
+            SymbolCategory::Fence => is_fence(mo),
+        };
+
+        fn is_in_definition(mo: Element, set_name: &str) -> bool {
+            return DEFINITIONS.with(|definitions| {
+                let definitions = definitions.borrow();
+                return definitions.get_hashset(set_name).is_some_and(|set| set.contains(as_text(mo)));
+            });
+        }
+    }
+}
+
+/// Returns all the categories that 'mo' is in (empty if it isn't an `mo`)
+pub fn symbol_categories(mo: Element) -> Vec<SymbolCategory> {
+    return SymbolCategory::ALL.iter()
+            .filter(|category| category.contains(mo))
+            .copied()
+            .collect();
+}
@@ -64,6 +64,11 @@
 //! * All systems -- pauses are given in milliseconds
 //!
 //! Note: Pauses on output are scaled based on the ratio of the current rate to the default rate (180 wpm)
+//!
+//! ## Earcons (experimental)
+//! * Non-speech sounds that mark structure boundaries (open/close fence, start of a root)
+//! * SSML: `<audio src='open-fence.mp4'>open paren</audio>` -- the words are only spoken if the audio can't be played
+//! * Other engines: not supported in the speech, but the cues can be retrieved via the API (`get_earcons`)
 #![allow(clippy::needless_return)]
 
 use crate::{errors::*, prefs::PreferenceManager, speech::ReplacementArray};
@@ -75,11 +80,11 @@ use crate::speech::{SpeechRulesWithContext, MyXPath, TreeOrString};
 use std::string::ToString;
 use std::str::FromStr;
 use strum_macros::{Display, EnumString};
-use regex::Regex;
+use regex::{Regex, Captures};
 use sxd_xpath::Value;
 
 const MIN_PAUSE:f64 = 50.0;         // ms -- avoids clutter of putting out pauses that probably can't be heard
-const PAUSE_SHORT:f64 = 150.0;  // ms
+pub const PAUSE_SHORT:f64 = 150.0;  // ms
 const PAUSE_MEDIUM:f64 = 300.0; // ms
 const PAUSE_LONG:f64 = 600.0;   // ms
 const PAUSE_AUTO:f64 = 987654321.5;   // ms -- hopefully unique
@@ -606,10 +611,15 @@ impl TTS {
             return "".to_string(); 
         }
         let pause = std::cmp::min(3000, ((2 * before_len + after_len)/48) * 128);
+        return self.pause(prefs, pause as f64);
+    }
+
+    /// Returns the string for a pause of 'amount' (in ms) for this TTS engine.
+    pub fn pause(&self, prefs: &PreferenceManager, amount: f64) -> String {
         // create a TTSCommandRule so we reuse code
         let command = TTSCommandRule::new(
             TTSCommand::Pause,
-            TTSCommandValue::Number(pause as f64),
+            TTSCommandValue::Number(amount),
             ReplacementArray::build_empty(),
         );
         return match self {
@@ -617,7 +627,6 @@ impl TTS {
             TTS::SSML  => self.get_string_ssml(&command, prefs, true),
             TTS::SAPI5 => self.get_string_sapi5(&command, prefs, true),
         };
-
     }
 
     /// Take the longest of the pauses
@@ -642,11 +651,8 @@ impl TTS {
             static ref MULTIPLE_PAUSES: Regex = Regex::new(r"[,;][,;]+").unwrap();   // two or more pauses
         }
         // we reduce all sequences of two or more pauses to a single medium pause
-        let mut merges_string = str.to_string();
-        for cap in MULTIPLE_PAUSES.captures_iter(str) {
-            merges_string = merges_string.replace(&cap[0], ";");
-        }
-        return merges_string;
+        // note: replacing each match in turn can leave pauses behind when one match is a prefix of another (";;" and ";;,")
+        return MULTIPLE_PAUSES.replace_all(str, ";").to_string();
     }
 
     fn merge_pauses_xml<F>(str: &str, full_attr_re: &Regex, sub_attr_re: &Regex, replace_with: F) -> String 
@@ -682,4 +688,146 @@ impl TTS {
         let replacement = |amount: usize| format!("<break time='{}ms'/>", amount);
         return TTS::merge_pauses_xml(str, &CONSECUTIVE_BREAKS, &PAUSE_AMOUNT, replacement);
     }
-}
\ No newline at end of file
+
+    /// Join the speech for sentences (see `SentenceLength`) so that they are spoken as separate sentences.
+    ///
+    /// With no TTS markup, each sentence (other than the last) ends with a '.';
+    /// otherwise, a long pause is placed between the sentences.
+    pub fn join_sentences(&self, sentences: &[String], prefs: &PreferenceManager) -> String {
+        if *self == TTS::None {
+            let last = sentences.len() - 1;
+            return sentences.iter().enumerate()
+                    .map(|(i, sentence)| if i == last {
+                            sentence.to_string()
+                        } else {
+                            sentence.trim_end_matches([',', ';']).to_string() + "."
+                        })
+                    .collect::<Vec<String>>()
+                    .join(" ");
+        }
+        let command = TTSCommandRule::new(
+            TTSCommand::Pause,
+            TTSCommandValue::Number(PAUSE_LONG),
+            ReplacementArray::build_empty(),
+        );
+        let pause = match self {
+            TTS::SSML  => self.get_string_ssml(&command, prefs, true),
+            _          => self.get_string_sapi5(&command, prefs, true),
+        };
+        return self.merge_pauses(&sentences.join(&format!(" {} ", pause)));
+    }
+
+    /// Estimate how long (in ms) it takes to speak `speech`, the string generated for this TTS engine.
+    ///
+    /// The estimate is based on the number of words spoken at `Rate` (words/minute) adjusted by `MathRate`
+    /// plus the pauses in the markup (or the ',' and ';' used for pauses when there is no markup).
+    /// Spelled out text is counted a character at a time.
+    pub fn estimate_duration(&self, speech: &str, prefs: &PreferenceManager) -> usize {
+        lazy_static! {
+            static ref XML_TAG: Regex = Regex::new(r"<.+?>").unwrap();
+            static ref SSML_PAUSE: Regex = Regex::new(r"<break time='(\d+)ms'/>").unwrap();
+            static ref SAPI5_PAUSE: Regex = Regex::new(r"<silence msec=='(\d+)ms'/>").unwrap();
+            static ref SPELLED: Regex = Regex::new(r"<(?:say-as interpret-as='characters'|spell)>(.*?)</(?:say-as|spell)>").unwrap();
+            static ref PAUSE_PUNCTUATION: Regex = Regex::new(r"([,;])(\s|$)").unwrap();     // not "1,000"
+        }
+        let math_rate = prefs.get_user_prefs().to_string("MathRate").parse::<f64>().unwrap_or(100.0)/100.0;
+        let ms_per_word = 60000.0 / (prefs.get_rate() * math_rate);
+        let (text, pause_amount) = match self {
+            TTS::None => {
+                // same as the punctuation generated in get_string_none() (there the rate is accounted for by the TTS engine)
+                let pause_amount: f64 = PAUSE_PUNCTUATION.captures_iter(speech)
+                        .map(|cap| if &cap[1] == "," {PAUSE_SHORT} else {PAUSE_MEDIUM})
+                        .sum();
+                (PAUSE_PUNCTUATION.replace_all(speech, " ").to_string(), pause_amount * 180.0/prefs.get_rate())
+            },
+            TTS::SSML | TTS::SAPI5 => {
+                // the pause amounts in the markup already include the rate adjustment
+                let pause = if *self == TTS::SSML {&*SSML_PAUSE} else {&*SAPI5_PAUSE};
+                let pause_amount: f64 = pause.captures_iter(speech)
+                        .map(|cap| cap[1].parse::<f64>().unwrap_or(0.0))
+                        .sum();
+                let text = SPELLED.replace_all(speech, |cap: &Captures| {
+                    cap[1].chars().map(|ch| ch.to_string()).collect::<Vec<String>>().join(" ")
+                });
+                (XML_TAG.replace_all(&text, " ").to_string(), pause_amount)
+            },
+        };
+        let n_words = text.split_whitespace().count();
+        return (n_words as f64 * ms_per_word + pause_amount).round() as usize;
+    }
+
+    /// The start and end tags used to speak text (`mtext`) differently than the math for the `TextVoice` preference.
+    /// `text_voice` is "Emphasis" or the name of a voice. None is returned if the preference is "None" or the TTS engine
+    ///   doesn't support markup -- in that case the text is spoken the same as the math.
+    pub fn text_voice_tags(&self, text_voice: &str) -> Option<(String, String)> {
+        if text_voice.is_empty() || text_voice.eq_ignore_ascii_case("none") {
+            return None;
+        }
+        let emphasis = text_voice.eq_ignore_ascii_case("emphasis");
+        let voice_name = text_voice.replace(['\'', '"', '<', '>', '&'], "");
+        return match self {
+            TTS::None => None,
+            TTS::SSML => Some( if emphasis {
+                    ("<emphasis level='moderate'>".to_string(), "</emphasis>".to_string())
+                } else {
+                    (format!("<voice name='{}'>", voice_name), "</voice>".to_string())
+                } ),
+            TTS::SAPI5 => Some( if emphasis {
+                    ("<emph>".to_string(), "</emph>".to_string())
+                } else {
+                    (format!("<voice required=\"Name={}\">", voice_name), "</voice>".to_string())
+                } ),
+        };
+    }
+}
+
+/// Returns the name of the earcon that marks the structure boundary 'mathml' (if any).
+/// The names are "open-fence" and "close-fence" for fences that begin/end an `mrow`, and "root-start" for roots.
+/// 'mathml' can be either MathML or an intent tree.
+pub fn earcon_name(mathml: Element) -> Option<&'static str> {
+    use crate::canonicalize::{name, is_fence};
+    match name(&mathml) {
+        "msqrt" | "mroot" | "square-root" | "root" => return Some("root-start"),    // MathML and intent names
+        "mo" => {
+            let parent = mathml.parent()?.element()?;
+            if name(&parent) != "mrow" || !is_fence(mathml) {
+                return None;
+            }
+            if mathml.preceding_siblings().is_empty() {
+                return Some("open-fence");
+            } else if mathml.following_siblings().is_empty() {
+                return Some("close-fence");
+            }
+            return None;
+        },
+        _ => return None,
+    }
+}
+
+/// Wraps/prefixes the SSML 'speech' for an element with the audio for 'earcon'.
+/// Fences are replaced by the earcon (the words are fallback text); roots get the earcon before the speech.
+pub fn add_earcon(speech: String, earcon: &str) -> String {
+    if earcon == "root-start" {
+        return format!("<audio src='{}.mp4'/> {}", earcon, speech);
+    } else {
+        return format!("<audio src='{}.mp4'>{}</audio>", earcon, speech);
+    }
+}
+
+/// Returns (id, earcon name) pairs for all the structure boundaries in 'mathml' in document order.
+pub fn earcon_cues(mathml: Element) -> Vec<(String, String)> {
+    let mut cues = vec![];
+    add_cues(mathml, &mut cues);
+    return cues;
+
+    fn add_cues(mathml: Element, cues: &mut Vec<(String, String)>) {
+        if let Some(earcon) = earcon_name(mathml) {
+            cues.push( (mathml.attribute_value("id").unwrap_or_default().to_string(), earcon.to_string()) );
+        }
+        for child in mathml.children() {
+            if let Some(child) = child.element() {
+                add_cues(child, cues);
+            }
+        }
+    }
+}
@@ -9,7 +9,18 @@
 //!   * `fractional` -- true if this is a fractional ordinal (e.g, "half")
 //!   * `plural` -- true if answer should be plural
 //! * `ToCommonFraction(mfrac)` -- converts the fraction to an ordinal version (e.g, 2 thirds)
+//! * `FractionPart(denominator)` -- the words for one part of a fraction when the denominator is read first (e.g., "third" or "kolmasosa")
+//! * `RootDegree(index)` -- the words for a root with the (integer or single letter) index (e.g, "cube root", "n-th root")
+//! * `ToleranceBound(center, tolerance, 'lower'/'upper')` -- the lower/upper bound of `center` ± `tolerance` (two `mn`s) written
+//!   with the same decimal separator and number of decimal places as the args (e.g., "4.9" for 5 ± 0.1) or "" if it can't be computed
 //! * `IsLargeOp(node)` -- returns true if the node is a large operator (e.g, integral or sum)
+//! * `IsInCategory(node, category)` -- returns true if the node is an operator in the category (e.g., "Relation", "Arrow", "SetOperator").
+//!   The categories are shared with navigation and braille (see `symbol_category.rs`)
+//! * `IsUnit(node)` -- returns true if the node is a unit (e.g, an upright "m", "km", "m²", or "kg⋅m")
+//! * `UnitWords(node)` -- the words for a (compound) unit (e.g, "meters per second squared") or "" if it isn't a unit
+//! * `IdentifierWords(node)` -- the words for an identifier in the lexicon of the subject area (e.g, "big O") or "" if it isn't in it
+//! * `DigitsInBase(number, base)` -- the digits of a number written in another base (e.g, "F F" for FF₁₆) or "" if it isn't one
+//! * `RepeatingDecimalPart(node, part)` -- the "integer", "decimals" (non-repeating), or "repeating" digits of a repeating decimal
 //! * `IsBracketed(node, left, right, requires_comma)` -- returns true if the first/last element in the mrow match `left`/`right`.
 //!    If the optional `requires_comma` argument is given and is `true`, then there also must be a "," in the mrow (e.g., "f(x,y)")
 //! * `DEBUG(xpath)` -- _Very_ useful function for debugging speech rules.
@@ -26,7 +37,8 @@ use std::cell::Ref;
 use phf::phf_set;
 
 
-use crate::canonicalize::{as_element, name};
+use crate::canonicalize::{as_element, as_text, name};
+use crate::symbol_category::SymbolCategory;
 
 // useful utility functions
 // note: child of an element is a ChildOfElement, so sometimes it is useful to have parallel functions,
@@ -343,6 +355,14 @@ pub fn is_leaf(element: Element) -> bool {
     return MATHML_LEAF_NODES.contains(name(&element));
 }
 
+/// Same as `is_leaf`, but an identifier with an accent (e.g., "x̂") and a labeled arrow (e.g., "-f->") are also leaves
+///   -- used by `IsNode(..., 'leaf')` and for navigation
+fn is_leaf_or_embellished_identifier(element: Element) -> bool {
+    return is_leaf(element) ||
+           element.attribute(crate::canonicalize::EMBELLISHED_IDENTIFIER_ATTR).is_some() ||
+           element.attribute(crate::canonicalize::LABELED_ARROW_ATTR).is_some();
+}
+
 impl Function for IsNode {
     // eval function for IsNode
     // errors happen for wrong number/kind of arg
@@ -373,7 +393,7 @@ impl Function for IsNode {
                         if let Node::Element(e) = node {
                             match kind.as_str() {
                                 "simple" => IsNode::is_simple(&e),
-                                "leaf"   => MATHML_LEAF_NODES.contains(name(&e)),
+                                "leaf"   => is_leaf_or_embellished_identifier(e),
                                 "2D" => MATHML_2D_NODES.contains(name(&e)),
                                 "trig_name" => IsNode::is_trig_name(&e),
                                 "common_fraction" => IsNode::is_common_fraction(&e, usize::MAX, usize::MAX), 
@@ -479,12 +499,16 @@ impl ToOrdinal {
                     definitions.get_vec("NumbersTens").unwrap(),
                     definitions.get_vec("NumbersOnes").unwrap(),
                 ];
+                let prefix_words = [
+                    definitions.get_vec("NumbersHundreds").unwrap(),
+                    definitions.get_vec("NumbersTens").unwrap(),
+                ];
                 answer = digits[0..digits.len()-3]
                             .chunks(3)
                             .enumerate()
                             .map(|(i, chunk)| {
                                 if chunk[0] != 0 || chunk[1] != 0 || chunk[2] != 0 {
-                                    ToOrdinal::hundreds_to_words(chunk, &words) + " " + 
+                                    ToOrdinal::hundreds_to_words(chunk, &words, &prefix_words) + " " + 
                                         &large_words[num_thousands_at_end + digits.len()/3 - 1 - i] + " "
                                 } else {
                                     "".to_string()
@@ -521,7 +545,19 @@ impl ToOrdinal {
                     definitions.get_vec("NumbersOrdinalOnes").unwrap(),
                 ],
             };
-            answer += &ToOrdinal::hundreds_to_words(&digits[digits.len()-3..], &words);
+            // Some languages (e.g., Finnish) inflect every part of an ordinal ("kahdeskymmenes ensimmäinen" for "21st"),
+            //   not just the last part -- they define 'NumbersOrdinalCompoundHundreds/Tens' for the words that precede the last part
+            let prefix_words = match (num_thousands_at_end > 0, plural) {
+                (false, false) => [
+                    definitions.get_vec("NumbersOrdinalCompoundHundreds").unwrap_or_else(|| definitions.get_vec("NumbersHundreds").unwrap()),
+                    definitions.get_vec("NumbersOrdinalCompoundTens").unwrap_or_else(|| definitions.get_vec("NumbersTens").unwrap()),
+                ],
+                _ => [
+                    definitions.get_vec("NumbersHundreds").unwrap(),
+                    definitions.get_vec("NumbersTens").unwrap(),
+                ],
+            };
+            answer += &ToOrdinal::hundreds_to_words(&digits[digits.len()-3..], &words, &prefix_words);
             if num_thousands_at_end > 0 {
                 let large_words = if plural {
                     definitions.get_vec("NumbersOrdinalPluralLarge").unwrap()
@@ -534,30 +570,29 @@ impl ToOrdinal {
         })
     }
 
-    fn hundreds_to_words(number: &[usize], words: &[Ref<Vec<String>>; 3]) -> String {
+    /// 'words' are the hundreds/tens/ones words used for the last part of the number
+    /// 'prefix_words' are the hundreds/tens words used when something follows them (e.g., the "twenty" in "twenty first")
+    fn hundreds_to_words(number: &[usize], words: &[Ref<Vec<String>>; 3], prefix_words: &[Ref<Vec<String>>; 2]) -> String {
         assert!( number.len() == 3 );
-        return DEFINITIONS.with(|definitions| {
-            let definitions = definitions.borrow();
-            if number[0] != 0 && number[1] == 0 && number[2] == 0 {
-                return words[0][number[0]].clone();
-            }
+        if number[0] != 0 && number[1] == 0 && number[2] == 0 {
+            return words[0][number[0]].clone();
+        }
 
-            let mut hundreds = definitions.get_vec("NumbersHundreds").unwrap()[number[0]].clone();
-            if !hundreds.is_empty() {
-                hundreds += " ";
-            }
+        let mut hundreds = prefix_words[0][number[0]].clone();
+        if !hundreds.is_empty() {
+            hundreds += " ";
+        }
 
-            if number[1] != 0 && number[2] == 0 {
-                return hundreds + &words[1][number[1]];
-            }
+        if number[1] != 0 && number[2] == 0 {
+            return hundreds + &words[1][number[1]];
+        }
 
-            if 10*number[1] < words[2].len() {
-                // usurp regular ordering to handle something like '14'
-                return hundreds + &words[2][10*number[1] + number[2]];
-            } else {
-                return hundreds + &definitions.get_vec("NumbersTens").unwrap()[number[1]] + " " + &words[2][number[2]];
-            }
-        });
+        if 10*number[1] < words[2].len() {
+            // usurp regular ordering to handle something like '14'
+            return hundreds + &words[2][10*number[1] + number[2]];
+        } else {
+            return hundreds + &prefix_words[1][number[1]] + " " + &words[2][number[2]];
+        }
     }
 }
 
@@ -580,6 +615,108 @@ impl Function for ToOrdinal {
 }
 
 
+struct RootDegree;
+impl RootDegree {
+    /// The (language specific) words for a root of degree 'number' (e.g., "cube root").
+    /// These come from 'NumbersRootIrregular' and 'NumbersRootRegular' in definitions.yaml.
+    /// A single lowercase letter index (e.g., "n") uses 'NumbersRootSymbolic' (e.g., "n-th root").
+    /// Otherwise, if 'number' is not an integer, it is returned unchanged.
+    fn convert(number: &str) -> String {
+        let mut chars = number.chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            if ch.is_ascii_lowercase() {
+                return DEFINITIONS.with(|definitions| {
+                    return match definitions.borrow().get_vec("NumbersRootSymbolic") {
+                        Some(symbolic) if !symbolic.is_empty() => symbolic[0].replace("{index}", number),
+                        _ => number.to_string(),
+                    };
+                });
+            }
+        }
+        if number.is_empty() || !number.chars().all(|ch| ch.is_ascii_digit()) {
+            return number.to_string();
+        }
+        let ordinal = ToOrdinal::convert(number, false, false);
+        return DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            if let Some(irregular) = definitions.get_vec("NumbersRootIrregular") {
+                if let Ok(n) = number.parse::<usize>() {
+                    if n < irregular.len() && !irregular[n].is_empty() {
+                        return irregular[n].clone();
+                    }
+                }
+            }
+            return match definitions.get_vec("NumbersRootRegular") {
+                Some(regular) if !regular.is_empty() => regular[0].replace("{ordinal}", &ordinal).replace("{number}", number),
+                _ => ordinal,       // language doesn't define root words
+            };
+        });
+    }
+}
+
+impl Function for RootDegree {
+    // convert the index of a root to words for the root (e.g, "cube root")
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(1)?;
+        let node = validate_one_node(args.pop_nodeset()?, "RootDegree")?;
+        return match node {
+            Node::Text(t) =>  Ok( Value::String( RootDegree::convert(t.text()) ) ),
+            Node::Element(e) => Ok( Value::String( RootDegree::convert(&get_text_from_element(&e)) ) ),
+            _   =>  Err( Error::ArgumentNotANodeset{actual: ArgumentType::String} ),
+        }
+    }
+}
+
+struct FractionPart;
+impl FractionPart {
+    /// The (language specific) words for one part of a whole divided into 'number' parts (e.g., "half" or "third").
+    /// These are used when the denominator is read first and come from 'NumbersFractionPartIrregular' and 'NumbersFractionPartRegular'
+    /// in definitions.yaml. If 'number' is not an integer, it is returned unchanged.
+    fn convert(number: &str) -> String {
+        if number.is_empty() || !number.chars().all(|ch| ch.is_ascii_digit()) {
+            return number.to_string();
+        }
+        let ordinal = ToOrdinal::convert(number, false, false);
+        return DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            if let Some(irregular) = definitions.get_vec("NumbersFractionPartIrregular") {
+                if let Ok(n) = number.parse::<usize>() {
+                    if n < irregular.len() && !irregular[n].is_empty() {
+                        return irregular[n].clone();
+                    }
+                }
+            }
+            return match definitions.get_vec("NumbersFractionPartRegular") {
+                Some(regular) if !regular.is_empty() => regular[0].replace("{ordinal}", &ordinal).replace("{number}", number),
+                _ => ordinal,       // language doesn't define fraction parts
+            };
+        });
+    }
+}
+
+impl Function for FractionPart {
+    // convert the denominator of a fraction to the words for one part (e.g, "third")
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(1)?;
+        let node = validate_one_node(args.pop_nodeset()?, "FractionPart")?;
+        return match node {
+            Node::Text(t) =>  Ok( Value::String( FractionPart::convert(t.text()) ) ),
+            Node::Element(e) => Ok( Value::String( FractionPart::convert(&get_text_from_element(&e)) ) ),
+            _   =>  Err( Error::ArgumentNotANodeset{actual: ArgumentType::String} ),
+        }
+    }
+}
+
 struct ToCommonFraction;
 
 impl Function for ToCommonFraction {
@@ -653,6 +790,58 @@ impl Function for Max {
 }
 
 
+struct ToleranceBound;
+/**
+ * Returns the lower or upper bound of a tolerance (e.g., "4.9" or "5.1" for "5 ± 0.1")
+ * @param(center) -- an mn
+ * @param(tolerance) -- an mn
+ * @param(bound) -- 'lower' or 'upper'
+ */
+impl ToleranceBound {
+    fn compute(center: &str, tolerance: &str, is_upper: bool) -> Option<String> {
+        lazy_static! {
+            static ref DECIMAL_NUMBER: Regex = Regex::new(r"^[0-9]+(?:([.,])([0-9]+))?$").unwrap();
+        }
+        let center = DECIMAL_NUMBER.captures(center.trim())?;
+        let tolerance = DECIMAL_NUMBER.captures(tolerance.trim())?;
+        let separators: Vec<&str> = [&center, &tolerance].iter().filter_map(|number| number.get(1).map(|sep| sep.as_str())).collect();
+        if separators.windows(2).any(|pair| pair[0] != pair[1]) {
+            return None;
+        }
+        let separator = separators.first().copied().unwrap_or(".");
+        if separator == "," && [&center, &tolerance].iter().any(|number| number.get(2).is_some_and(|decimals| decimals.len() == 3)) {
+            return None;    // could be a "1,000" style digit block separator
+        }
+        let n_decimals = [&center, &tolerance].iter()
+                .map(|number| number.get(2).map_or(0, |decimals| decimals.len()))
+                .max().unwrap_or(0);
+        let to_f64 = |number: &regex::Captures| number[0].replace(',', ".").parse::<f64>().ok();
+        let (center, tolerance) = (to_f64(&center)?, to_f64(&tolerance)?);
+        let bound = if is_upper {center + tolerance} else {center - tolerance};
+        return Some( format!("{:.*}", n_decimals, bound).replace('.', separator) );
+    }
+}
+
+impl Function for ToleranceBound {
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(3)?;
+        let is_upper = args.pop_string()? == "upper";
+        let tolerance = validate_one_node(args.pop_nodeset()?, "ToleranceBound")?;
+        let center = validate_one_node(args.pop_nodeset()?, "ToleranceBound")?;
+        let (Node::Element(center), Node::Element(tolerance)) = (center, tolerance) else {
+            return Err( Error::Other("ToleranceBound -- arguments are not elements".to_string()) );
+        };
+        let bound = ToleranceBound::compute(&get_text_from_element(&center), &get_text_from_element(&tolerance), is_upper);
+        return Ok( Value::String( bound.unwrap_or_default() ) );
+    }
+}
+
+
 struct IsLargeOp;
 /**
  * Returns true if the node is a large op
@@ -669,14 +858,138 @@ struct IsLargeOp;
         args.exactly(1)?;
         let node = validate_one_node(args.pop_nodeset()?, "IsLargeOp")?;
         if let Node::Element(e) = node {
-            if !is_tag(&e, "mo") {
-                return Ok( Value::Boolean(false) );
+            return Ok( Value::Boolean(SymbolCategory::LargeOperator.contains(e)) );
+        } else {
+            // xpath is something besides an element, so no match
+            return Ok( Value::Boolean(false) );
+        }
+    }
+}
+
+
+struct IsInCategory;
+/**
+ * Returns true if the node is an operator in the category (e.g., "Relation" or "Arrow")
+ * @param(node)     -- node(s) to test -- should be an <mo>
+ * @param(category) -- the name of a category (see crate::symbol_category::SymbolCategory)
+ */
+ impl Function for IsInCategory {
+
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(2)?;
+        let category_name = args.pop_string()?;
+        let category = match SymbolCategory::from_name(&category_name) {
+            Some(category) => category,
+            None => return Err( Error::Other( format!("\n  IsInCategory: '{}' is not a known category", category_name) ) ),
+        };
+        let node = validate_one_node(args.pop_nodeset()?, "IsInCategory")?;
+        if let Node::Element(e) = node {
+            return Ok( Value::Boolean(category.contains(e)) );
+        }
+        // xpath is something besides an element, so no match
+        return Ok( Value::Boolean(false) );
+    }
+}
+
+
+/// Unit symbols that can have an SI prefix
+static PREFIXABLE_UNITS: phf::Set<&str> = phf_set! {
+    "m", "g", "s", "A", "K", "mol", "cd", "Hz", "N", "Pa", "J", "W", "C", "V", "F", "Ω", "S", "Wb", "T", "H",
+    "lm", "lx", "Bq", "Gy", "Sv", "kat", "L", "l", "eV", "bar", "rad", "sr", "B",
+};
+
+/// Unit symbols that don't take a prefix
+static OTHER_UNITS: phf::Set<&str> = phf_set! {
+    "h", "min", "d", "°C", "°F", "ha", "au", "Da", "mph", "ft", "lb", "oz", "yd", "gal",
+};
+
+static SI_PREFIXES: &[&str] = &["da", "Y", "Z", "E", "P", "T", "G", "M", "k", "h", "d", "c", "m", "µ", "μ", "n", "p", "f", "a", "z", "y"];
+
+pub struct IsUnit;
+/**
+ * Returns true if the node is a unit
+ * @param(node)     -- node(s) to test
+ */
+impl IsUnit {
+    /// Returns true if 'text' is a unit symbol, possibly with an SI prefix (e.g., "km")
+    fn is_unit_symbol(text: &str) -> bool {
+        if PREFIXABLE_UNITS.contains(text) || OTHER_UNITS.contains(text) {
+            return true;
+        }
+        return SI_PREFIXES.iter()
+                .any(|prefix| text.strip_prefix(prefix).is_some_and(|unit| PREFIXABLE_UNITS.contains(unit)));
+    }
+
+    /// Returns true if 'element' is a unit symbol (single letter symbols must be upright),
+    /// a unit raised to a (possibly negative) number (e.g., "m²"), or a product of units (e.g., "kg⋅m")
+    pub fn is_unit(element: Element) -> bool {
+        return match name(&element) {
+            "mi" | "mtext" => {
+                let text = as_text(element);
+                IsUnit::is_unit_symbol(text) &&
+                    (text.chars().count() > 1 || name(&element) == "mtext" || element.attribute_value("mathvariant") == Some("normal"))
+            },
+            "msup" | "power" => {
+                let children = element.children();
+                let exponent = as_element(children[1]);
+                IsUnit::is_unit(as_element(children[0])) &&
+                    (name(&exponent) == "mn" || is_negative_number(exponent))
+            },
+            "per" => {
+                let children = element.children();
+                children.len() == 2 && IsUnit::is_unit(as_element(children[1])) &&
+                    (IsUnit::is_unit(as_element(children[0])) || is_one(as_element(children[0])))
+            },
+            "mrow" => {
+                let children = element.children();
+                children.len() % 2 == 1 &&
+                    children.iter().enumerate().all(|(i, &child)| {
+                        let child = as_element(child);
+                        if i % 2 == 0 {
+                            IsUnit::is_unit(child)
+                        } else {
+                            name(&child) == "mo" && matches!(as_text(child), "\u{2062}" | "·" | "⋅")
+                        }
+                    })
+            },
+            _ => false,
+        };
+
+        fn is_negative_number(mrow: Element) -> bool {
+            let children = mrow.children();
+            if name(&mrow) == "negative" {
+                return children.len() == 1 && name(&as_element(children[0])) == "mn";
+            }
+            if name(&mrow) != "mrow" || children.len() != 2 {
+                return false;
             }
-            return DEFINITIONS.with(|definitions| {
-                let definitions = definitions.borrow();
-                let text = get_text_from_element(&e);
-                return Ok( Value::Boolean(definitions.get_hashset("LargeOperators").unwrap().get(&text).is_some()) );
-            });
+            let minus = as_element(children[0]);
+            return name(&minus) == "mo" && matches!(as_text(minus), "-" | "−") && name(&as_element(children[1])) == "mn";
+        }
+
+        fn is_one(mn: Element) -> bool {
+            return name(&mn) == "mn" && as_text(mn) == "1";
+        }
+    }
+}
+
+impl Function for IsUnit {
+
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(1)?;
+        let node = validate_one_node(args.pop_nodeset()?, "IsUnit")?;
+        if let Node::Element(e) = node {
+            return Ok( Value::Boolean(IsUnit::is_unit(e)) );
         } else {
             // xpath is something besides an element, so no match
             return Ok( Value::Boolean(false) );
@@ -685,6 +998,382 @@ struct IsLargeOp;
 }
 
 
+/// Returns true if 'element' is an 'mo' whose text is one of 'texts'
+fn is_mo(element: Element, texts: &[&str]) -> bool {
+    return name(&element) == "mo" && texts.contains(&as_text(element));
+}
+
+struct UnitWords;
+/**
+ * Returns the words for a (compound) unit such as "m s⁻²" or "kg·m/s²" (e.g., "meters per second squared")
+ * If the node is an mrow that starts with a number, the number is not included in the words, but it is used to
+ *   decide whether the unit should be plural.
+ * An empty string is returned if the node isn't recognized as a unit.
+ * To avoid speaking an upright letter such as "d" or "A" as a unit, a unit must follow a number, or contain a "per"
+ *   (a solidus or negative power), or contain a multi-letter unit symbol (e.g., "kg").
+ * The words come from 'UnitNames_vec', 'UnitPrefixes_vec', 'UnitPowers_vec', 'UnitPowerRegular_vec', and 'UnitPer_vec'
+ *   in definitions.yaml. Unknown symbols are spoken as written.
+ * @param(node)     -- node to convert to words
+ */
+impl UnitWords {
+    fn convert(element: Element) -> String {
+        let mut factors = vec![];    // (symbol, power)
+        let mut number = None;
+        let children = element.children();
+        if name(&element) == "mrow" && children.len() > 2 &&
+           name(&as_element(children[0])) == "mn" && is_mo(as_element(children[1]), &["\u{2062}"]) {
+            number = Some(as_text(as_element(children[0])));
+            let rest = &children[2..];
+            if !rest.iter().enumerate().all(|(i, &child)| UnitWords::collect_factor(as_element(child), i, 1, &mut factors)) {
+                return "".to_string();
+            }
+        } else if !UnitWords::collect(element, 1, &mut factors) {
+            return "".to_string();
+        }
+        if factors.is_empty() ||
+           !(number.is_some() || factors.iter().any(|(symbol, power)| *power < 0 || symbol.chars().count() > 1)) {
+            return "".to_string();
+        }
+
+        return DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            let per = match definitions.get_vec("UnitPer_vec") {
+                Some(per) if !per.is_empty() => per[0].clone(),
+                _ => "per".to_string(),
+            };
+            let plural = number.is_some_and(|number| number != "1");
+            let last_numerator = factors.iter().rposition(|(_, power)| *power > 0);
+            let mut words = vec![];
+            for (i, (symbol, power)) in factors.iter().enumerate().filter(|(_, (_, power))| *power > 0) {
+                words.push( UnitWords::factor_words(&definitions, symbol, *power, plural && Some(i) == last_numerator) );
+            }
+            for (symbol, power) in factors.iter().filter(|(_, power)| *power < 0) {
+                words.push(per.clone());
+                words.push( UnitWords::factor_words(&definitions, symbol, -power, false) );
+            }
+            return words.join(" ");
+        });
+    }
+
+    /// Adds the (symbol, power) pairs for 'element' to 'factors'; returns false if 'element' isn't a unit
+    /// 'sign' is -1 if the unit is in the denominator
+    fn collect(element: Element, sign: i32, factors: &mut Vec<(String, i32)>) -> bool {
+        let children = element.children();
+        return match name(&element) {
+            "mi" | "mtext" => {
+                if !IsUnit::is_unit(element) {
+                    return false;
+                }
+                factors.push( (as_text(element).to_string(), sign) );
+                true
+            },
+            "msup" | "power" => {
+                let base = as_element(children[0]);
+                let exponent = as_element(children[1]);
+                if !IsUnit::is_unit(element) || !matches!(name(&base), "mi" | "mtext") {
+                    return false;
+                }
+                let power = match name(&exponent) {
+                    "mn" => as_text(exponent).parse::<i32>().ok(),
+                    _ => as_text(as_element(*exponent.children().last().unwrap())).parse::<i32>().ok().map(|power| -power),
+                };
+                match power {
+                    Some(power) => {
+                        factors.push( (as_text(base).to_string(), sign * power) );
+                        true
+                    },
+                    None => false,
+                }
+            },
+            "per" => {
+                children.len() == 2 &&
+                    (is_one(as_element(children[0])) || UnitWords::collect(as_element(children[0]), sign, factors)) &&
+                    UnitWords::collect(as_element(children[1]), -sign, factors)
+            },
+            "mrow" => {
+                // a solidus is only a "per" if the 'Solidus' pref allows it, so it is left to the "per" intent
+                children.len() % 2 == 1 &&
+                    children.iter().enumerate().all(|(i, &child)| UnitWords::collect_factor(as_element(child), i, sign, factors))
+            },
+            _ => false,
+        };
+
+        fn is_one(mn: Element) -> bool {
+            return name(&mn) == "mn" && as_text(mn) == "1";
+        }
+    }
+
+    /// 'child' is the i-th child of a product: even children are units and odd children are multiplication operators
+    fn collect_factor(child: Element, i: usize, sign: i32, factors: &mut Vec<(String, i32)>) -> bool {
+        if i.is_multiple_of(2) {
+            return UnitWords::collect(child, sign, factors);
+        }
+        return is_mo(child, &["\u{2062}", "·", "⋅"]);
+    }
+
+    /// The words for the unit 'symbol' raised to 'power' (e.g., "seconds squared")
+    fn factor_words(definitions: &Ref<crate::definitions::Definitions>, symbol: &str, power: i32, plural: bool) -> String {
+        let unit = UnitWords::unit_name(definitions, symbol, plural);
+        if power == 1 {
+            return unit;
+        }
+        let power_string = power.to_string();
+        if let Some(irregular) = definitions.get_vec("UnitPowers_vec") {
+            if (power as usize) < irregular.len() && !irregular[power as usize].is_empty() {
+                return format!("{} {}", unit, irregular[power as usize]);
+            }
+        }
+        let power_words = match definitions.get_vec("UnitPowerRegular_vec") {
+            Some(regular) if !regular.is_empty() => regular[0]
+                    .replace("{ordinal}", &ToOrdinal::convert(&power_string, false, false))
+                    .replace("{number}", &power_string),
+            _ => power_string,
+        };
+        return format!("{} {}", unit, power_words);
+    }
+
+    /// The name of the unit 'symbol' (e.g, "km" -> "kilometers")
+    /// Entries in 'UnitNames_vec' are of the form "symbol|singular|plural" and those in 'UnitPrefixes_vec' "symbol|prefix"
+    fn unit_name(definitions: &Ref<crate::definitions::Definitions>, symbol: &str, plural: bool) -> String {
+        let names = match definitions.get_vec("UnitNames_vec") {
+            Some(names) => names,
+            None => return symbol.to_string(),
+        };
+        let lookup = |symbol: &str| -> Option<String> {
+            return names.iter()
+                .map(|entry| entry.split('|').collect::<Vec<&str>>())
+                .find(|parts| parts.len() == 3 && parts[0] == symbol)
+                .map(|parts| (if plural {parts[2]} else {parts[1]}).to_string());
+        };
+        if let Some(name) = lookup(symbol) {
+            return name;
+        }
+        if let Some(prefixes) = definitions.get_vec("UnitPrefixes_vec") {
+            for entry in prefixes.iter() {
+                if let Some((prefix_symbol, prefix)) = entry.split_once('|') {
+                    if let Some(name) = symbol.strip_prefix(prefix_symbol).filter(|unit| PREFIXABLE_UNITS.contains(unit)).and_then(lookup) {
+                        return prefix.to_string() + &name;
+                    }
+                }
+            }
+        }
+        return symbol.to_string();
+    }
+}
+
+impl Function for UnitWords {
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(1)?;
+        let node = validate_one_node(args.pop_nodeset()?, "UnitWords")?;
+        if let Node::Element(e) = node {
+            return Ok( Value::String(UnitWords::convert(e)) );
+        } else {
+            return Ok( Value::String("".to_string()) );
+        }
+    }
+}
+
+
+/// The subject area for 'element': the `subject` attribute of 'element' or its closest ancestor that has one,
+///   otherwise the `SubjectArea` preference (e.g., "LinearAlgebra")
+pub fn subject_area(element: Element) -> String {
+    let mut element = Some(element);
+    while let Some(e) = element {
+        if let Some(subject) = e.attribute_value("subject") {
+            return subject.to_string();
+        }
+        element = e.parent().and_then(|parent| parent.element());
+    }
+    return crate::prefs::PreferenceManager::get().borrow().get_user_prefs().to_string("SubjectArea");
+}
+
+struct IdentifierWords;
+/**
+ * Returns the words for an identifier in the lexicon of the subject area (e.g., "big O" for "O" in "ComputerScience")
+ * The words come from 'IdentifierWords_<SubjectArea>_vec' in definitions.yaml, whose entries are "identifier|words".
+ * An empty string is returned if the node isn't a leaf or the identifier isn't in the lexicon.
+ * @param(node)     -- node to convert to words
+ */
+impl IdentifierWords {
+    fn convert(element: Element) -> String {
+        if !is_leaf(element) {
+            return "".to_string();
+        }
+        let text = as_text(element).trim();
+        let lexicon_name = format!("IdentifierWords_{}_vec", subject_area(element));
+        return DEFINITIONS.with(|definitions| {
+            let definitions = definitions.borrow();
+            return match definitions.get_vec(&lexicon_name) {
+                None => "".to_string(),
+                Some(lexicon) => lexicon.iter()
+                        .filter_map(|entry| entry.split_once('|'))
+                        .find(|(identifier, _)| *identifier == text)
+                        .map(|(_, words)| words.to_string())
+                        .unwrap_or_default(),
+            };
+        });
+    }
+}
+
+impl Function for IdentifierWords {
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(1)?;
+        let node = validate_one_node(args.pop_nodeset()?, "IdentifierWords")?;
+        if let Node::Element(e) = node {
+            return Ok( Value::String(IdentifierWords::convert(e)) );
+        } else {
+            return Ok( Value::String("".to_string()) );
+        }
+    }
+}
+
+
+struct DigitsInBase;
+/**
+ * Returns the digits of a number written in a base other than ten separated by spaces (e.g., "1 0 1 1" for 1011₂ or "F F" for FF₁₆)
+ * An empty string is returned if 'base' isn't an integer from 2 to 36 or 'number' isn't written with the digits of that base.
+ * 'number' can be an `mn`, an `mi` (e.g., "FF"), or an `mrow` of them joined by invisible times (e.g., "7A").
+ * Letter digits are only accepted for bases above ten and a number that is only letters must have at least two of them (so "A₁₆" is a subscript).
+ * @param(number)   -- the number
+ * @param(base)     -- the base (an `mn`)
+ */
+impl DigitsInBase {
+    fn convert(number: Element, base: Element) -> String {
+        let base = match get_text_from_element(&base).trim().parse::<u32>() {
+            Ok(base) if (2..=36).contains(&base) => base,
+            _ => return "".to_string(),
+        };
+        let digits = match DigitsInBase::number_text(number) {
+            Some(digits) if !digits.is_empty() => digits,
+            _ => return "".to_string(),
+        };
+        if !digits.chars().all(|ch| ch.is_digit(base)) {
+            return "".to_string();
+        }
+        if !digits.chars().any(|ch| ch.is_ascii_digit()) && (base <= 10 || digits.chars().count() < 2) {
+            return "".to_string();
+        }
+        return digits.chars().map(|ch| ch.to_ascii_uppercase().to_string()).collect::<Vec<String>>().join(" ");
+    }
+
+    /// The text of 'number' if it is an `mn`, `mi`, or an `mrow` of them joined by invisible times
+    fn number_text(number: Element) -> Option<String> {
+        return match name(&number) {
+            "mn" | "mi" => Some( get_text_from_element(&number).trim().to_string() ),
+            "mrow" => {
+                let mut text = String::new();
+                for (i, child) in number.children().iter().enumerate() {
+                    let child = as_element(*child);
+                    if i % 2 == 1 {
+                        if name(&child) != "mo" || get_text_from_element(&child) != "\u{2062}" {
+                            return None;
+                        }
+                    } else if name(&child) == "mn" || name(&child) == "mi" {
+                        text.push_str(get_text_from_element(&child).trim());
+                    } else {
+                        return None;
+                    }
+                }
+                Some(text)
+            },
+            _ => None,
+        };
+    }
+}
+
+impl Function for DigitsInBase {
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(2)?;
+        let base = validate_one_node(args.pop_nodeset()?, "DigitsInBase")?;
+        let number = validate_one_node(args.pop_nodeset()?, "DigitsInBase")?;
+        if let (Node::Element(number), Node::Element(base)) = (number, base) {
+            return Ok( Value::String(DigitsInBase::convert(number, base)) );
+        } else {
+            return Ok( Value::String("".to_string()) );
+        }
+    }
+}
+
+
+struct RepeatingDecimalPart;
+/**
+ * Returns a part of a repeating decimal (the mrow that canonicalization marks as a "repeating-decimal")
+ * The parts are "integer" (e.g., "0" for 0.12̄3̄), "decimals" (the digits after the decimal separator that don't repeat, e.g., "1"),
+ *   and "repeating" (the digits that repeat, e.g., "23").
+ * For a number written with an ellipsis (e.g., "0.1666…"), the repeated copies of the repeating digits are not part of "decimals" (e.g., "1").
+ * @param(node)     -- the repeating decimal mrow
+ * @param(part)     -- "integer", "decimals", or "repeating"
+ */
+impl RepeatingDecimalPart {
+    fn convert(mrow: Element, part: &str) -> String {
+        let children = mrow.children();
+        if name(&mrow) != "mrow" || children.len() != 2 {
+            return "".to_string();
+        }
+        let number = as_text(as_element(children[0]));
+        // any separators before the (language's) decimal separator group the integer digits
+        let (integer, mut decimals) = crate::canonicalize::split_at_decimal_separator(number).unwrap_or((number, ""));
+        let repeating = match mrow.attribute_value(crate::canonicalize::REPEATING_DIGITS_ATTR) {
+            Some(repeating) => {
+                // written with an ellipsis -- remove the copies of the repeating digits from the end of the number
+                if !repeating.is_empty() {
+                    while let Some(non_repeating) = decimals.strip_suffix(repeating) {
+                        decimals = non_repeating;
+                    }
+                }
+                repeating
+            },
+            None => {
+                let repeat_marker = as_element(children[1]);
+                if repeat_marker.children().is_empty() {
+                    return "".to_string();
+                }
+                as_text(as_element(repeat_marker.children()[0]))
+            },
+        };
+        return match part {
+            "integer" => integer,
+            "decimals" => decimals,
+            "repeating" => repeating,
+            _ => "",
+        }.to_string();
+    }
+}
+
+impl Function for RepeatingDecimalPart {
+    fn evaluate<'c, 'd>(&self,
+                        _context: &context::Evaluation<'c, 'd>,
+                        args: Vec<Value<'d>>)
+                        -> Result<Value<'d>, Error>
+    {
+        let mut args = Args(args);
+        args.exactly(2)?;
+        let part = args.pop_string()?;
+        let node = validate_one_node(args.pop_nodeset()?, "RepeatingDecimalPart")?;
+        if let Node::Element(mrow) = node {
+            return Ok( Value::String(RepeatingDecimalPart::convert(mrow, &part)) );
+        } else {
+            return Ok( Value::String("".to_string()) );
+        }
+    }
+}
+
+
 struct BaseNode;
 /**
  * Returns true if the node is a large op
@@ -937,7 +1626,7 @@ impl DistanceFromLeaf {
         let mut element = element;
         let mut distance = 1;
         loop {
-            if is_leaf(element) {
+            if is_leaf_or_embellished_identifier(element) {
                 return distance;
             }
             if treat_2d_elements_as_tokens && MATHML_2D_NODES.contains(name(&element)) {
@@ -1063,8 +1752,17 @@ pub fn add_builtin_functions(context: &mut Context) {
     context.set_function("BrailleChars", crate::braille::BrailleChars);
     context.set_function("IsNode", IsNode);
     context.set_function("ToOrdinal", ToOrdinal);
+    context.set_function("RootDegree", RootDegree);
+    context.set_function("FractionPart", FractionPart);
     context.set_function("ToCommonFraction", ToCommonFraction);
+    context.set_function("ToleranceBound", ToleranceBound);
     context.set_function("IsLargeOp", IsLargeOp);
+    context.set_function("IsInCategory", IsInCategory);
+    context.set_function("IsUnit", IsUnit);
+    context.set_function("UnitWords", UnitWords);
+    context.set_function("IdentifierWords", IdentifierWords);
+    context.set_function("DigitsInBase", DigitsInBase);
+    context.set_function("RepeatingDecimalPart", RepeatingDecimalPart);
     context.set_function("IsBracketed", IsBracketed);
     context.set_function("IsInDefinition", IsInDefinition);
     context.set_function("BaseNode", BaseNode);
@@ -1186,6 +1884,39 @@ mod tests {
         assert_eq!("zeroth", ToOrdinal::convert("00000", false, false));
     }
 
+    #[test]
+    fn root_degree() {
+        init_word_list();
+        assert_eq!("square root", RootDegree::convert("2"));
+        assert_eq!("cube root", RootDegree::convert("3"));
+        assert_eq!("fourth root", RootDegree::convert("4"));
+        assert_eq!("twenty first root", RootDegree::convert("21"));
+        assert_eq!("2.5", RootDegree::convert("2.5"));
+        assert_eq!("n-th root", RootDegree::convert("n"));
+        assert_eq!("N", RootDegree::convert("N"));
+    }
+
+    #[test]
+    fn fraction_part() {
+        init_word_list();
+        assert_eq!("half", FractionPart::convert("2"));
+        assert_eq!("third", FractionPart::convert("3"));
+        assert_eq!("tenth", FractionPart::convert("10"));
+        assert_eq!("x", FractionPart::convert("x"));
+    }
+
+    #[test]
+    fn tolerance_bound() {
+        assert_eq!(ToleranceBound::compute("5", "0.1", false), Some("4.9".to_string()));
+        assert_eq!(ToleranceBound::compute("5", "0.1", true), Some("5.1".to_string()));
+        assert_eq!(ToleranceBound::compute("0.3", "0.25", true), Some("0.55".to_string()));
+        assert_eq!(ToleranceBound::compute("3", "2", false), Some("1".to_string()));
+        assert_eq!(ToleranceBound::compute("2,5", "0,2", false), Some("2,3".to_string()));
+        assert_eq!(ToleranceBound::compute("1,000", "5", false), None);
+        assert_eq!(ToleranceBound::compute("2.5", "0,2", false), None);
+        assert_eq!(ToleranceBound::compute("x", "2", false), None);
+    }
+
 
     fn test_is_simple(message: &'static str, mathml_str: &'static str) {
 		// this forces initialization
@@ -78,7 +78,7 @@ fn trig_fourth() {
 
 #[test]
 fn trig_power_other() {
-    let expr = "<math><msup><mi>sinh</mi><mrow>><mi>n</mi><mo>-</mo><mn>1</mn></mrow></msup><mi>x</mi></math>";
+    let expr = "<math><msup><mi>sinh</mi><mrow><mi>n</mi><mo>-</mo><mn>1</mn></mrow></msup><mi>x</mi></math>";
     test("en", "ClearSpeak", expr, "the n minus 1 power of, hyperbolic sine of x");
 }
 
@@ -257,3 +257,31 @@ fn binomial() {
                 </math>";
     test("en", "ClearSpeak", expr, "2 times 7 choose 3");
 }
+
+#[test]
+fn binomial_linethickness_units() {
+    let expr = "<math>
+                    <mo>(</mo>
+                    <mfrac linethickness='0pt'> <mi>n</mi> <mi>k</mi> </mfrac>
+                    <mo>)</mo>
+                </math>";
+    test("en", "ClearSpeak", expr, "n choose k");
+}
+
+#[test]
+fn bevelled() {
+    let expr = "<math><mfrac bevelled='true'> <mn>1</mn> <mi>x</mi> </mfrac></math>";
+    test("en", "ClearSpeak", expr, "slanted fraction 1 over x,");
+    let expr = "<math><mfrac bevelled='true'> <mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow> <mi>c</mi> </mfrac></math>";
+    test("en", "ClearSpeak", expr, "slanted fraction eigh plus b over c, end slanted fraction,");
+}
+
+#[test]
+fn denominator_first() {
+    let expr = "<math><mfrac><mn>1</mn><mn>3</mn></mfrac><mi>y</mi><mo>+</mo><mfrac><mi>x</mi><mn>2</mn></mfrac></math>";
+    test("en", "ClearSpeak", expr, "1 third y plus x over 2");
+    test_prefs("en", "ClearSpeak", vec![("FractionOrder", "DenominatorFirst")], expr, "one third y, plus one half of x");
+    // not unit fractions or part of a mixed number
+    let expr = "<math><mfrac><mn>2</mn><mn>3</mn></mfrac><mo>+</mo><mn>2</mn><mfrac><mn>1</mn><mn>3</mn></mfrac></math>";
+    test_prefs("en", "ClearSpeak", vec![("FractionOrder", "DenominatorFirst")], expr, "2 thirds plus 2 and 1 third");
+}
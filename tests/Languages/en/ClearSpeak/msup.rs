@@ -339,3 +339,9 @@ fn default_power() {
   </math>";
   test("en", "ClearSpeak", expr, "t raised to the fraction with numerator; b plus 1; and denominator 3; power");
 }
+
+#[test]
+fn superscript_chars_in_token() {
+    let expr = "<math><mn>10²</mn><mo>+</mo><mi>xⁿ</mi></math>";
+    test("en", "ClearSpeak", expr, "10 squared plus x to the n-th power");
+}
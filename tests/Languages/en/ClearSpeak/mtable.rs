@@ -211,6 +211,23 @@ fn matrix_2x3() {
     test("en", "ClearSpeak", expr, "the 2 by 3 matrix; row 1; 3, 1, 4; row 2; 0, 2, 6;");
 }
 
+#[test]
+fn matrix_augmented() {
+    let expr = "<math>
+      <mrow><mo>[</mo>
+        <mtable columnlines='none solid'>
+          <mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd><mtd><mn>5</mn></mtd></mtr>
+          <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd><mtd><mn>6</mn></mtd></mtr>
+        </mtable>
+      <mo>]</mo></mrow>
+    </math>";
+    test("en", "ClearSpeak", expr,
+        "the augmented matrix, 2 by 2, with constants column; row 1; 1, 2, constants; 5; row 2; 3, 4, constants; 6;");
+    test_prefs("en", "ClearSpeak", vec![("ClearSpeak_Matrix", "SpeakColNum")], expr,
+        "the augmented matrix, 2 by 2, with constants column; \
+         row 1; column 1; 1, column 2; 2, constants; 5; row 2; column 1; 3, column 2; 4, constants; 6;");
+}
+
 #[test]
 fn matrix_2x3_labeled() {
     let expr = "
@@ -538,6 +555,30 @@ fn simple_cardinality_value() {
   test_prefs("en", "ClearSpeak", vec![("Verbosity", "Medium"), ("ClearSpeak_AbsoluteValue", "Cardinality")], expr,
              "the cardinality of cap s,");
 }
+
+#[test]
+fn cardinality_of_set_like_operand() {
+  let expr = "<math>
+    <mrow><mo>|</mo> <mrow><mi>A</mi><mo>∪</mo><mi>B</mi></mrow> <mo>|</mo></mrow>
+  </math>";
+  test("en", "ClearSpeak", expr, "the cardinality of cap eigh union cap b,");
+  test_prefs("en", "ClearSpeak", vec![("ClearSpeak_AbsoluteValue", "AbsEnd")], expr,
+             "the absolute value of cap eigh union cap b, end absolute value,");
+  let expr = "<math><mrow><mo>|</mo> <mi mathvariant='double-struck'>N</mi> <mo>|</mo></mrow></math>";
+  test("en", "SimpleSpeak", expr, "the cardinality of the natural numbers, end cardinality,");
+}
+
+#[test]
+fn vertical_bars_by_subject_area() {
+  let expr = "<math>
+    <mrow><mo>|</mo> <mi>S</mi> <mo>|</mo></mrow>
+  </math>";
+  test("en", "SimpleSpeak", expr, "the absolute value of cap s,");
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "SetTheory")], expr, "the cardinality of cap s,");
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "LinearAlgebra")], expr, "determinant of cap s");
+  test_prefs("en", "ClearSpeak", vec![("ClearSpeak_AbsoluteValue", "Determinant")],
+             "<math><mrow><mo>|</mo> <mi>x</mi> <mo>|</mo></mrow></math>", "determinant of x");
+}
   
 // Test preferences
 #[test]
@@ -960,3 +1001,26 @@ fn matrix_binomial() {
     </math>";
   test_ClearSpeak("en", "ClearSpeak_Matrix", "Combinatorics", expr, "3 choose 2");
 }
+
+#[test]
+fn matrix_empty_cell() {
+  let expr = "<math><mrow><mo>(</mo><mtable>
+      <mtr><mtd><mn>1</mn></mtd><mtd></mtd></mtr>
+      <mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr>
+    </mtable><mo>)</mo></mrow></math>";
+  test("en", "ClearSpeak", expr,
+        "the 2 by 2 matrix; row 1; column 1; 1, column 2; blank; row 2; column 1; 3, column 2; 4;");
+  test_prefs("en", "ClearSpeak", vec![("EmptyCells", "Skip")], expr,
+        "the 2 by 2 matrix; row 1; column 1; 1, row 2; column 1; 3, column 2; 4;");
+}
+
+#[test]
+fn aligned_equations_empty_cell() {
+  let expr = "<math><mtable>
+      <mtr><mtd><mi>x</mi></mtd><mtd><mo>=</mo></mtd><mtd><mn>1</mn></mtd></mtr>
+      <mtr><mtd></mtd><mtd><mo>=</mo></mtd><mtd><mn>2</mn></mtd></mtr>
+    </mtable></math>";
+  test("en", "ClearSpeak", expr, "2 lines, line 1; x, is equal to, 1; line 2; is equal to, 2;");
+  test_prefs("en", "ClearSpeak", vec![("EmptyCells", "Announce")], expr,
+        "2 lines, line 1; x, is equal to, 1; line 2; blank, is equal to, 2;");
+}
@@ -31,9 +31,9 @@ fn case_1() {
         </math>
    ";
    test_ClearSpeak("en", "ClearSpeak_MultiLineLabel", "Auto", expr, "f of x is equal to; 3 cases, \
-                case 1; negative 1 if x; is less than 0; \
-                case 2; 0 if x, is equal to 0; \
-                case 3; 1 if x, is greater than 0;");
+                case 1; negative 1, if; x is less than 0; \
+                case 2; 0, if; x is equal to 0; \
+                case 3; 1, if; x is greater than 0;");
 }
 
 #[test]
@@ -64,7 +64,7 @@ fn trig_fourth() {
 
 #[test]
 fn trig_power_other() {
-    let expr = "<math><msup><mi>sinh</mi><mrow>><mi>n</mi><mo>-</mo><mn>1</mn></mrow></msup><mi>x</mi></math>";
+    let expr = "<math><msup><mi>sinh</mi><mrow><mi>n</mi><mo>-</mo><mn>1</mn></mrow></msup><mi>x</mi></math>";
     test("en", "SimpleSpeak", expr, "the n minus 1 power of, hyperbolic sine of x");
 }
 
@@ -143,6 +143,18 @@ fn explicit_times() {
     test("en", "SimpleSpeak", expr, "t x");
 }
 
+#[test]
+fn function_with_parameter() {
+    let expr = "<math><mi>p</mi><mo>(</mo><mi>x</mi><mo>;</mo><mi>θ</mi><mo>)</mo></math>";
+    test("en", "SimpleSpeak", expr, "p of x, with parameter theta");
+}
+
+#[test]
+fn function_with_parameters() {
+    let expr = "<math><mi>p</mi><mo>(</mo><mi>x</mi><mo>;</mo><mi>μ</mi><mo>,</mo><mi>σ</mi><mo>)</mo></math>";
+    test("en", "SimpleSpeak", expr, "p of x, with parameters mu comma sigma");
+}
+
 
 /*
     * Tests for times
@@ -25,3 +25,15 @@ fn ray_mtext() {
   let expr = "<math> <mover><mtext>XY</mtext><mo>→</mo></mover> </math>";
   test("en", "SimpleSpeak", expr, "ray cap x cap y");
 }
+
+#[test]
+fn bar_over_acronym() {
+  let expr = "<math> <mover><mi>GDP</mi><mo>&#xAF;</mo></mover> </math>";
+  test("en", "SimpleSpeak", expr, "GDP bar,");
+}
+
+#[test]
+fn bar_over_unconfirmed_points() {
+  let expr = "<math> <mover><mi>XYZ</mi><mo>&#xAF;</mo></mover> </math>";
+  test("en", "SimpleSpeak", expr, "XYZ with line above");
+}
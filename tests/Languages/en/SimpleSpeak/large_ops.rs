@@ -10,7 +10,7 @@ fn sum_both() {
         </munderover>
         <mi>n</mi>
     </math>";
-    test("en", "SimpleSpeak", expr, "the sum from n is equal to 1 to 10 of n");
+    test("en", "SimpleSpeak", expr, "the sum over n from 1 to 10 of n");
 }
 
 #[test]
@@ -34,7 +34,7 @@ fn sum_both_msubsup() {
         </msubsup>
         <mi>n</mi>
     </math>";
-    test("en", "SimpleSpeak", expr, "the sum from n is equal to 1 to 10 of n");
+    test("en", "SimpleSpeak", expr, "the sum over n from 1 to 10 of n");
 }
 
 #[test]
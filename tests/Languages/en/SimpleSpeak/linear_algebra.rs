@@ -6,6 +6,28 @@ fn transpose() {
   test("en", "SimpleSpeak", expr, "cap m transpose");
 }
 
+#[test]
+fn conjugate_transpose() {
+  let expr = "<math> <msup><mi>M</mi><mo>†</mo></msup> </math>";
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "LinearAlgebra")], expr, "cap m conjugate transpose");
+  let expr = "<math> <msup><mi>M</mi><mo>*</mo></msup><mo>=</mo>
+                <mrow><mo>(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr></mtable><mo>)</mo></mrow> </math>";
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "General")], expr,
+        "cap m conjugate transpose, is equal to, the 1 by 2 row matrix; column 1; 1; column 2; 2;");
+}
+
+#[test]
+fn inverse() {
+  let expr = "<math> <msup><mi>M</mi><mrow><mo>-</mo><mn>1</mn></mrow></msup> </math>";
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "LinearAlgebra")], expr, "cap m inverse");
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "General")], expr, "cap m to the negative 1");
+  // a matrix elsewhere in the expression (here, in a fraction) isn't a linear algebra context for 'M'
+  let expr = "<math> <msup><mi>M</mi><mrow><mo>-</mo><mn>1</mn></mrow></msup><mo>+</mo>
+                <mfrac><mrow><mo>(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr></mtable><mo>)</mo></mrow><mn>2</mn></mfrac> </math>";
+  test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "General")], expr,
+        "cap m to the negative 1, plus; fraction, the 1 by 2 row matrix; column 1; 1; column 2; 2; over 2, end fraction;");
+}
+
 #[test]
 fn trace() {
   let expr = "<math> <mi>Tr</mi><mi>M</mi> </math>";
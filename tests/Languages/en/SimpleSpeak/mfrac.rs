@@ -214,3 +214,34 @@ fn binomial() {
                 </math>";
     test("en", "SimpleSpeak", expr, "2 times 7 choose 3");
 }
+
+#[test]
+fn solidus_units() {
+    let expr = "<math><mn>5</mn><mi>km</mi><mo>/</mo><mi mathvariant='normal'>h</mi></math>";
+    test("en", "SimpleSpeak", expr, "5 kilometers per hour");
+    let expr = "<math><mn>9.8</mn><mi mathvariant='normal'>m</mi><mo>/</mo><msup><mi mathvariant='normal'>s</mi><mn>2</mn></msup></math>";
+    test("en", "SimpleSpeak", expr, "9.8 meters per second squared");
+}
+
+#[test]
+fn solidus_simple_and_complex() {
+    let expr = "<math><mn>1</mn><mo>/</mo><mi>x</mi></math>";
+    test("en", "SimpleSpeak", expr, "1 over x,");
+    let expr = "<math><mo>(</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>)</mo><mo>/</mo><mi>c</mi></math>";
+    test("en", "SimpleSpeak", expr, "open paren eigh plus b, close paren; divided by c");
+}
+
+#[test]
+fn solidus_prefs() {
+    let expr = "<math><mi mathvariant='normal'>m</mi><mo>/</mo><mi mathvariant='normal'>s</mi></math>";
+    test_prefs("en", "SimpleSpeak", vec![("Solidus", "DividedBy")], expr, "m divided by s");
+    test_prefs("en", "SimpleSpeak", vec![("Solidus", "Over")], expr, "m over s,");
+    test_prefs("en", "SimpleSpeak", vec![("Solidus", "Auto")], expr, "meter per second");
+}
+
+#[test]
+fn denominator_first() {
+    let expr = "<math><mfrac><mi>x</mi><mn>4</mn></mfrac></math>";
+    test_prefs("en", "SimpleSpeak", vec![("FractionOrder", "DenominatorFirst")], expr, "one fourth of x");
+    test_prefs("en", "SimpleSpeak", vec![("FractionOrder", "NumeratorFirst")], expr, "x over 4,");
+}
@@ -31,9 +31,9 @@ fn case_1() {
         </math>
    ";
     test("en", "SimpleSpeak", expr, "f of x is equal to; 3 cases, \
-                case 1; negative 1 if x; is less than 0; \
-                case 2; 0 if x, is equal to 0; \
-                case 3; 1 if x, is greater than 0;");
+                case 1; negative 1, if; x is less than 0; \
+                case 2; 0, if; x is equal to 0; \
+                case 3; 1, if; x is greater than 0;");
 }
 
 #[test]
@@ -75,3 +75,22 @@ fn equation_1() {
                 equation 1; x plus y, is equal to, 7; \
                 equation 2; 2 x plus 3 y, is equal to, 17;");
 }
+
+#[test]
+fn linebreaks() {
+    let expr = "<math>
+        <mi>x</mi><mo>+</mo><mi>y</mi><mo>=</mo><mn>7</mn>
+        <mspace linebreak='newline'/>
+        <mi>x</mi><mo>-</mo><mi>y</mi><mo>=</mo><mn>1</mn>
+    </math>";
+    test("en", "SimpleSpeak", expr, "2 equations, \
+                equation 1; x plus y, is equal to 7; \
+                equation 2; x minus y, is equal to 1;");
+    let expr = "<math>
+        <mi>f</mi><mo>(</mo><mi>x</mi><mo>)</mo>
+        <mo linebreak='newline'>=</mo><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn>
+    </math>";
+    test("en", "SimpleSpeak", expr, "2 lines, \
+                line 1; f of x; \
+                line 2; is equal to, x squared plus 1;");
+}
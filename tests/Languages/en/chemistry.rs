@@ -16,6 +16,13 @@ fn water() {
   test_prefs("en", "ClearSpeak", vec![("Verbosity", "Verbose")], expr, "cap h, subscript 2, cap o,");
 }
 
+#[test]
+fn water_as_math() {
+  // the author says it isn't chemistry
+  let expr = "<math><mrow data-interpretation='math'><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></mrow></math>";
+  test_prefs("en", "ClearSpeak", vec![("Verbosity", "Medium")], expr, "cap h sub 2 cap o");
+}
+
 #[test]
 fn carbon() {
   let expr = "<math><mi>C</mi></math>";     // not enough to trigger recognition
@@ -655,3 +662,18 @@ fn mhchem_roman_in_superscript() {
 }
 
 
+
+#[test]
+fn hydrate() {
+  let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>·</mo><mn>5</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+  test_prefs("en", "ClearSpeak", vec![("Verbosity", "Medium")], expr, "cap c u, cap s, cap o, sub 4, dot 5, cap h, sub 2 cap o,");
+  test_prefs("en", "ClearSpeak", vec![("Verbosity", "Verbose")], expr, "cap c u, cap s, cap o, subscript 4, with 5 water of hydration");
+  let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>⋅</mo><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+  test_prefs("en", "SimpleSpeak", vec![("Verbosity", "Verbose")], expr, "cap c u, cap s, cap o, subscript 4, with 1 water of hydration");
+}
+
+#[test]
+fn adduct() {
+  let expr = "<math><mi>B</mi><msub><mi>F</mi><mn>3</mn></msub><mo>·</mo><mi>N</mi><msub><mi>H</mi><mn>3</mn></msub></math>";
+  test_prefs("en", "ClearSpeak", vec![("Verbosity", "Medium")], expr, "cap b, cap f, sub 3, dot, cap n, cap h, sub 3");
+}
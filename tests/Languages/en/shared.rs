@@ -1,5 +1,6 @@
 /// Tests for rules shared between various speech styles:
 /// *  modified var
+/// *  repeating decimals
 use crate::common::*;
 
 #[test]
@@ -23,6 +24,18 @@ fn modified_vars() {
             x dot, y dot, z double dot, u triple dot, v quadruple dot; plus x hat, plus vector t");
 }
 
+#[test]
+fn repeating_decimal() {
+    let expr = "<math><mn>0.</mn><mover><mn>3</mn><mo>¯</mo></mover></math>";
+    test("en", "SimpleSpeak", expr, "0 point 3 repeating");
+    let expr = "<math><mn>0.1</mn><mover><mn>23</mn><mo>&#x2D9;</mo></mover></math>";
+    test("en", "ClearSpeak", expr, "0 point 1, 2 3 repeating");
+    let expr = "<math><mn>0.1666</mn><mo>…</mo></math>";
+    test("en", "ClearSpeak", expr, "0 point 1, 6 repeating");
+    let expr = "<math><mn>0.142857142857</mn><mo>…</mo></math>";
+    test("en", "SimpleSpeak", expr, "0 point 1 4 2 8 5 7 repeating");
+}
+
 #[test]
 fn limit() {
     let expr = "<math>
@@ -101,6 +114,33 @@ fn huge_num_mmultiscripts() {
             "cap r with 4 prescripts, pre-subscript cap i, pre-superscript cap j and alternating prescripts cap k none cap l none end prescripts and with 5 postscripts, subscript i superscript j subscript k subscript l and alternating scripts m none end scripts");
 }
 
+#[test]
+fn unresolved_script() {
+    // there is no base for the "_2" in "x = {}_2 = 3", so the script is spoken by itself
+    let expr = "<math><mi>x</mi><mo>=</mo><msub><mi/><mn>2</mn></msub><mo>=</mo><mn>3</mn></math>";
+    test("en", "SimpleSpeak", expr, "x is equal to subscript 2, is equal to 3");
+    let expr = "<math><msubsup><mrow/><mn>2</mn><mi>k</mi></msubsup></math>";
+    test("en", "ClearSpeak", expr, "subscript 2, superscript k,");
+}
+
+#[test]
+fn function_guess() {
+    let expr = "<math><mi>t</mi><mo>(</mo><mi>x</mi><mo>+</mo><mn>1</mn><mo>)</mo></math>";
+    test("en", "ClearSpeak", expr, "t, open paren x plus 1, close paren");
+    test_prefs("en", "ClearSpeak", vec![("FunctionGuess", "Function")], expr, "t of, open paren x plus 1, close paren");
+    test_prefs("en", "SimpleSpeak", vec![("FunctionGuess", "Times")], expr, "t times, open paren x plus 1, close paren");
+}
+
+#[test]
+fn enumerated_parts() {
+    let expr = "<math>
+            <mo>(</mo><mi>i</mi><mo>)</mo><mspace width='1em'/><mi>f</mi><mo>(</mo><mi>x</mi><mo>)</mo><mo>=</mo><mn>2</mn><mi>x</mi>
+            <mspace width='2em'/>
+            <mo>(</mo><mi>ii</mi><mo>)</mo><mspace width='1em'/><mi>g</mi><mo>(</mo><mi>x</mi><mo>)</mo><mo>=</mo><msup><mi>x</mi><mn>2</mn></msup>
+        </math>";
+    test("en", "ClearSpeak", expr, "part i; f of x is equal to 2 x; part ii; g of x is equal to x squared");
+}
+
 #[test]
 fn prime() {
     let expr = "<math> <msup><mi>x</mi><mo >&#x2032;</mo></msup> </math>";
@@ -194,7 +234,7 @@ fn ignore_period() {
       </annotation-xml>
     </semantics>  
   </math>";
-    test("en", "SimpleSpeak", expr, "cap p; open paren, cap eigh and cap b; close paren; is equal to; cap p, open paren, cap eigh intersection cap b; close paren; is equal to, cap p of cap eigh, cap p of cap b");
+    test("en", "SimpleSpeak", expr, "cap p, open paren, cap eigh, and, cap b; close paren; is equal to; cap p, open paren, cap eigh intersection cap b; close paren; is equal to, cap p of cap eigh, cap p of cap b");
 }
 
 #[test]
@@ -280,3 +320,325 @@ fn ignore_period_and_space() {
     test("en", "ClearSpeak", expr, "phi of x is equal to; c, e raised to the negative h squared x squared power");
 }
 
+#[test]
+fn sequence() {
+    let expr = "<math>
+        <msub><mi>a</mi><mn>1</mn></msub><mo>,</mo>
+        <msub><mi>a</mi><mn>2</mn></msub><mo>,</mo>
+        <mo>…</mo><mo>,</mo>
+        <msub><mi>a</mi><mi>n</mi></msub>
+    </math>";
+    test("en", "SimpleSpeak", expr, "eigh sub 1 comma eigh sub 2, and so on up to eigh sub n");
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Verbose")], expr,
+            "the sequence eigh sub 1 comma eigh sub 2, and so on up to eigh sub n");
+}
+
+#[test]
+fn not_sequence() {
+    // different bases
+    let expr = "<math>
+        <msub><mi>a</mi><mn>1</mn></msub><mo>,</mo>
+        <mo>…</mo><mo>,</mo>
+        <msub><mi>b</mi><mi>n</mi></msub>
+    </math>";
+    test("en", "SimpleSpeak", expr, "eigh sub 1 comma dot dot dot comma b sub n");
+}
+
+#[test]
+fn sentence_length() {
+    let expr = "<math>
+        <mi>a</mi><mo>=</mo><mi>b</mi><mo>+</mo><mi>c</mi><mo>+</mo><mi>d</mi>
+        <mo>=</mo><mi>e</mi><mo>+</mo><mi>f</mi><mo>+</mo><mi>g</mi><mo>+</mo><mi>h</mi>
+    </math>";
+    test_prefs("en", "SimpleSpeak", vec![("SentenceLength", "3")], expr,
+            "eigh is equal to. b plus c plus d is equal to e. plus f plus g plus h");
+    test_prefs("en", "SimpleSpeak", vec![("SentenceLength", "0")], expr,
+            "eigh is equal to, b plus c plus d, is equal to, e plus f plus g plus h");
+}
+
+#[test]
+fn sentence_length_function_application() {
+    let expr = "<math>
+        <mi>y</mi><mo>=</mo><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi><mo>+</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>+</mo><mi>c</mi>
+    </math>";
+    test_prefs("en", "SimpleSpeak", vec![("SentenceLength", "3")], expr,
+            "y is equal to sine of x plus eigh. plus b plus c");
+}
+
+#[test]
+fn constant_names() {
+    let expr = "<math>
+        <msup><mi>e</mi><mi>x</mi></msup><mo>+</mo><mn>2</mn><mi>i</mi><mo>+</mo><mi>ℏ</mi>
+    </math>";
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Named")], expr,
+            "Euler's number to the x-th, plus 2 imaginary unit, plus reduced planck constant");
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Literal")], expr,
+            "e to the x-th plus 2 i plus h bar");
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Auto")], expr,
+            "e to the x-th plus 2 i plus reduced planck constant");
+}
+
+#[test]
+fn named_subexpression() {
+    let expr = "<math>
+        <mrow intent='kinetic-energy'><mfrac><mn>1</mn><mn>2</mn></mfrac><mi>m</mi><msup><mi>v</mi><mn>2</mn></msup></mrow>
+    </math>";
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Auto")], expr, "kinetic energy");
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Literal")], expr, "1 half m v squared");
+    test_prefs("en", "SimpleSpeak", vec![("ConstantNames", "Auto")], expr, "kinetic energy");
+}
+
+#[test]
+fn modular_congruence() {
+    let expr = "<math>
+        <mi>a</mi><mo>≡</mo><mi>b</mi><mo>+</mo><mn>1</mn>
+        <mo stretchy='false'>(</mo><mi>mod</mi><mi>n</mi><mo stretchy='false'>)</mo>
+    </math>";
+    test("en", "SimpleSpeak", expr, "eigh is congruent to b plus 1, modulo n");
+    let expr = "<math><mn>17</mn><mo>≢</mo><mn>3</mn><mrow><mo>(</mo><mo>mod</mo><mn>5</mn><mo>)</mo></mrow></math>";
+    test("en", "ClearSpeak", expr, "17 is not congruent to 3, modulo 5");
+    // only a congruence that is the nearest relation takes the modulus
+    let expr = "<math><mi>a</mi><mo>≡</mo><mi>b</mi><mo>,</mo><mi>c</mi><mo>=</mo><mi>d</mi><mo>(</mo><mi>mod</mi><mi>n</mi><mo>)</mo></math>";
+    test("en", "SimpleSpeak", expr, "eigh is identical to b, comma; c is equal to, d, open paren mod n close paren");
+    let expr = "<math><mi>a</mi><mo>≡</mo><mi>b</mi><mo>,</mo><mi>c</mi><mo>≡</mo><mi>d</mi><mo>(</mo><mi>mod</mi><mi>n</mi><mo>)</mo></math>";
+    test("en", "SimpleSpeak", expr, "eigh is identical to b, comma c is congruent to d, modulo n");
+}
+
+#[test]
+fn intent_verbosity_override() {
+    let expr = "<math>
+        <mrow intent=':verbose'><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>+</mo><mrow><mo>|</mo><mi>y</mi><mo>|</mo></mrow>
+    </math>";
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Terse")], expr,
+            "the absolute value of x; plus absolute value of y,");
+    let expr = "<math>
+        <mrow intent=':terse'><mo>|</mo><mi>x</mi><mo>|</mo></mrow><mo>+</mo><mrow><mo>|</mo><mi>y</mi><mo>|</mo></mrow>
+    </math>";
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Verbose")], expr,
+            "absolute value of x, end absolute value; plus, the absolute value of y, end absolute value,");
+}
+
+#[test]
+fn intent_numbers_and_self() {
+    let expr = "<math><msup intent='power($b, 2.5)'><mi arg='b'>x</mi><mn>2</mn></msup></math>";
+    test("en", "ClearSpeak", expr, "x raised to the 2.5 power");
+    let expr = "<math><mi intent='-3'>x</mi></math>";
+    test("en", "ClearSpeak", expr, "negative 3");
+    let expr = "<math><mrow intent='_speed:verbose($self)'><mrow><mo>|</mo><mi>v</mi><mo>|</mo></mrow></mrow></math>";
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Terse")], expr, "speed of, the absolute value of v,");
+}
+
+#[test]
+fn structure_announcement() {
+    let expr = "<math>
+        <mfrac><mi>a</mi><mi>b</mi></mfrac><mo>+</mo><msqrt><mi>x</mi></msqrt><mo>+</mo>
+        <msub><mi>x</mi><mrow><mi>i</mi><mo>+</mo><mn>1</mn></mrow></msub><mo>+</mo><msup><mi>x</mi><mn>2</mn></msup>
+    </math>";
+    // simple scripts such as "x squared" are not announced
+    test_prefs("en", "SimpleSpeak", vec![("StructureAnnouncement", "Before")], expr,
+            "fraction, eigh over b; plus the square root of x; plus, subscript, x sub i plus 1 end sub; plus x squared");
+    test_prefs("en", "SimpleSpeak", vec![("StructureAnnouncement", "After")], expr,
+            "eigh over b; end fraction, plus the square root of x; end root, plus, x sub i plus 1 end sub; end subscript; plus x squared");
+    // already announced by the rules
+    let expr = "<math><mfrac><mrow><mi>a</mi><mo>+</mo><mn>1</mn></mrow><mi>b</mi></mfrac></math>";
+    test_prefs("en", "SimpleSpeak", vec![("StructureAnnouncement", "Before")], expr,
+            "fraction, eigh plus 1, over b, end fraction;");
+}
+
+#[test]
+fn accented_identifiers() {
+    let expr = "<math>
+        <mi>x&#x302;</mi><mo>+</mo><mover><mi>y</mi><mo>&#x303;</mo></mover><mo>+</mo><mi>&#x1E8B;</mi><mo>+</mo><mi>z&#x304;</mi>
+    </math>";
+    test("en", "SimpleSpeak", expr, "x hat, plus y tilde, plus x dot, plus z bar,");
+    // dotless i and j are used for unit vectors
+    let expr = "<math><mover><mi>ı</mi><mo>^</mo></mover><mo>+</mo><mover><mi>&#x1D6A5;</mi><mo>^</mo></mover></math>";
+    test("en", "SimpleSpeak", expr, "i hat, plus j hat,");
+}
+
+#[test]
+fn string_literals() {
+    let expr = "<math><ms>a</ms><mo>+</mo><ms>a b</ms><mo>+</mo><ms></ms></math>";
+    test("en", "SimpleSpeak", expr, "the string, eigh plus, the string, a b, end string; plus the empty string");
+    test_prefs("en", "SimpleSpeak", vec![("Verbosity", "Verbose")], "<math><ms>a b</ms></math>",
+            "the string, open quote a b close quote,");
+}
+
+#[test]
+fn compound_units() {
+    let expr = "<math><mn>9.8</mn><mi mathvariant='normal'>m</mi><msup><mi mathvariant='normal'>s</mi><mrow><mo>-</mo><mn>2</mn></mrow></msup></math>";
+    test("en", "SimpleSpeak", expr, "9.8 meters per second squared");
+    let expr = "<math><mi>kg</mi><mo>·</mo><mi mathvariant='normal'>m</mi><mo>/</mo><msup><mi mathvariant='normal'>s</mi><mn>2</mn></msup></math>";
+    test("en", "SimpleSpeak", expr, "kilogram meter per second squared");
+    let expr = "<math><mn>1</mn><msup><mi>cm</mi><mn>3</mn></msup></math>";
+    test("en", "ClearSpeak", expr, "1 centimeter cubed");
+    // a lone upright letter is not a unit
+    let expr = "<math><mi mathvariant='normal'>d</mi><mi>x</mi></math>";
+    test("en", "SimpleSpeak", expr, "d x");
+}
+
+#[test]
+fn relation_chain() {
+    let expr = "<math><mi>a</mi><mo>&lt;</mo><mi>b</mi><mo>&lt;</mo><mi>c</mi><mo>≤</mo><mi>d</mi></math>";
+    test("en", "SimpleSpeak", expr, "eigh is less than b is less than c is less than or equal to d");
+    test_prefs("en", "SimpleSpeak", vec![("RelationChains", "Summary")], expr,
+        "a chain of inequalities; eigh is less than b, which is less than c, which is less than or equal to d");
+    let expr = "<math><mi>x</mi><mo>=</mo><mi>y</mi><mo>=</mo><mn>2</mn></math>";
+    test_prefs("en", "ClearSpeak", vec![("RelationChains", "Summary")], expr,
+        "a chain of equations; x is equal to y, which is equal to 2");
+}
+
+#[test]
+fn connective_text() {
+    let expr = "<math><mi>x</mi><mo>=</mo><mn>1</mn><mtext>and</mtext><mi>y</mi><mo>=</mo><mn>2</mn></math>";
+    test("en", "ClearSpeak", expr, "x is equal to 1; and; y is equal to 2");
+    let expr = "<math><mi>x</mi><mo>&gt;</mo><mn>0</mn><mtext> for all </mtext><mi>x</mi><mo>∈</mo><mi>S</mi></math>";
+    test("en", "ClearSpeak", expr, "x is greater than 0; for all; x is a member of cap s");
+}
+
+#[test]
+fn descriptive_identifier() {
+    let expr = "<math><mn>2</mn><mtext>rate of change</mtext></math>";
+    test("en", "ClearSpeak", expr, "2, rate of change");
+    let expr = "<math><mi>d</mi><mo>=</mo><mi>velocity of light</mi><mi>t</mi></math>";
+    test("en", "ClearSpeak", expr, "d is equal to, velocity of light; t");
+}
+
+#[test]
+fn vectors() {
+    let expr = "<math><mover><mi>v</mi><mo>&#x20D7;</mo></mover><mo>+</mo><mi mathvariant='bold'>u</mi></math>";
+    test("en", "ClearSpeak", expr, "vector v plus bold u");
+    test_prefs("en", "ClearSpeak", vec![("Vectors", "Bold")], expr, "vector v plus vector u");
+    // bold capitals are usually matrices
+    let expr = "<math><mi mathvariant='bold'>A</mi><mi mathvariant='bold'>x</mi></math>";
+    test_prefs("en", "SimpleSpeak", vec![("Vectors", "Bold")], expr, "bold cap eigh vector x");
+}
+
+#[test]
+fn plain_text_in_mi() {
+    let expr = "<math><mi>x-1</mi></math>";
+    test("en", "ClearSpeak", expr, "x minus 1");
+}
+
+#[test]
+fn domain_lexicon() {
+    let expr = "<math><mi>O</mi><mo>(</mo><mi>n</mi><mi>log</mi><mi>n</mi><mo>)</mo></math>";
+    test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "ComputerScience")], expr, "big O of, open paren n log n, close paren");
+    test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "General")], expr, "cap o, open paren n log n, close paren");
+    let expr = "<math subject='ComputerScience'><mi>f</mi><mo>=</mo><mi mathvariant='normal'>Θ</mi><mo>(</mo><msup><mi>n</mi><mn>2</mn></msup><mo>)</mo></math>";
+    test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "General")], expr, "f is equal to, big theta of, open paren n squared close paren");
+    let expr = "<math><mi>mRNA</mi></math>";
+    test_prefs("en", "SimpleSpeak", vec![("SubjectArea", "Genetics")], expr, "m R N A");
+}
+
+#[test]
+fn lists() {
+    let expr = "<math><mo>(</mo><mn>1</mn><mo>,</mo><mn>2</mn><mo>,</mo><mn>3</mn><mo>,</mo><mn>4</mn><mo>)</mo></math>";
+    test_prefs("en", "ClearSpeak", vec![("Lists", "On")], expr, "the list 1, 2, 3, 4");
+    test_prefs("en", "ClearSpeak", vec![("Lists", "Off")], expr, "open paren, 1 comma 2 comma 3 comma 4; close paren");
+    test_prefs("en", "SimpleSpeak", vec![("Lists", "On"), ("ListSummaryLength", "3"), ("ListSummaryItems", "2")], expr,
+               "a list of 4 items, starting 1, 2, and so on");
+    test_prefs("en", "SimpleSpeak", vec![("Lists", "On"), ("ListSummaryLength", "0")], expr, "the list 1, 2, 3, 4");
+    // function args and points aren't lists
+    let expr = "<math><mi>f</mi><mo>(</mo><mi>x</mi><mo>,</mo><mi>y</mi><mo>,</mo><mi>z</mi><mo>)</mo></math>";
+    test_prefs("en", "ClearSpeak", vec![("Lists", "On")], expr, "f of, open paren, x comma y comma z, close paren");
+    let expr = "<math><mo>(</mo><mn>1</mn><mo>,</mo><mn>2</mn><mo>)</mo></math>";
+    test_prefs("en", "ClearSpeak", vec![("Lists", "On")], expr, "open paren 1 comma 2, close paren");
+}
+
+#[test]
+fn scientific_notation() {
+    let expr = "<math><mn>3.2</mn><mo>×</mo><msup><mn>10</mn><mn>5</mn></msup></math>";
+    test("en", "ClearSpeak", expr, "3.2 times 10 to the fifth");
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Terse")], expr, "3.2 e 5");
+    let expr = "<math><mn>6.02e-23</mn></math>";
+    test("en", "SimpleSpeak", expr, "6.02 times 10 to the negative 23");
+}
+
+#[test]
+fn tolerance() {
+    let expr = "<math><mn>5</mn><mo>±</mo><mn>0.1</mn></math>";
+    test("en", "ClearSpeak", expr, "5 plus or minus 0.1");
+    test_prefs("en", "ClearSpeak", vec![("Verbosity", "Verbose")], expr, "5 plus or minus 0.1, from 4.9 to 5.1");
+    let expr = "<math><mi>x</mi><mo>=</mo><mn>3</mn><mo>±</mo><mn>2</mn></math>";
+    test_prefs("en", "SimpleSpeak", vec![("Verbosity", "Verbose")], expr, "x is equal to, 3 plus or minus 2, from 1 to 5");
+    // not a tolerance
+    let expr = "<math><mi>x</mi><mo>=</mo><mi>a</mi><mo>±</mo><mi>b</mi></math>";
+    test_prefs("en", "SimpleSpeak", vec![("Verbosity", "Verbose")], expr, "x is equal to, eigh plus or minus b");
+}
+
+#[test]
+fn labeled_arrows() {
+    let expr = "<math><mi>A</mi><mover><mo>→</mo><mi>f</mi></mover><mi>B</mi></math>";
+    test("en", "ClearSpeak", expr, "cap eigh maps to cap b under f");
+    let expr = "<math><msub><mi>a</mi><mi>n</mi></msub><munder><mo>⟶</mo><mrow><mi>n</mi><mo>→</mo><mi>∞</mi></mrow></munder><mi>a</mi></math>";
+    test("en", "SimpleSpeak", expr, "eigh sub n approaches eigh, as n approaches infinity");
+    let expr = "<math><munder><mi>lim</mi><mrow><mi>x</mi><mo>→</mo><mi>a</mi></mrow></munder><mi>f</mi><mo>(</mo><mi>x</mi><mo>)</mo></math>";
+    test("en", "ClearSpeak", expr, "the limit as x approaches eigh, of f of x");
+}
+
+#[test]
+fn number_ranges() {
+    let expr = "<math><mn>5</mn><mo>–</mo><mn>10</mn><mi>km</mi></math>";
+    test("en", "SimpleSpeak", expr, "5 to 10 kilometers");
+    test_prefs("en", "SimpleSpeak", vec![("RangeDash", "Through")], expr, "5 through 10 kilometers");
+    let expr = "<math><mo>$</mo><mn>3</mn><mo>–</mo><mo>$</mo><mn>5</mn></math>";
+    test("en", "ClearSpeak", expr, "dollars 3 to dollars 5");
+    let expr = "<math><mtext>pages</mtext><mspace width='0.2em'/><mn>12</mn><mo>–</mo><mn>15</mn></math>";
+    test("en", "ClearSpeak", expr, "pages 12 to 15");
+    test_prefs("en", "ClearSpeak", vec![("RangeDash", "Minus")], expr, "pages 12 minus 15");
+    // an operator next to the numbers means the dash is probably a minus sign
+    let expr = "<math><mn>3</mn><mo>·</mo><mn>12</mn><mo>–</mo><mn>15</mn></math>";
+    test("en", "ClearSpeak", expr, "3 times 12, en dash 15");
+}
+
+#[test]
+fn author_alt_text() {
+    let expr = "<math alttext='\\frac{a}{b}=c'><mrow aria-label='the ratio of a to b'><mfrac><mi>a</mi><mi>b</mi></mfrac></mrow><mo>=</mo><mi>c</mi></math>";
+    test("en", "SimpleSpeak", expr, "eigh over b, is equal to c");
+    test_prefs("en", "SimpleSpeak", vec![("AltText", "AriaLabel")], expr, "the ratio of a to b, is equal to c");
+    let expr = "<math alttext='the area of a circle'><mi>π</mi><msup><mi>r</mi><mn>2</mn></msup></math>";
+    test_prefs("en", "SimpleSpeak", vec![("AltText", "AriaLabel")], expr, "pi r squared");
+    test_prefs("en", "SimpleSpeak", vec![("AltText", "All")], expr, "the area of a circle");
+}
+
+#[test]
+fn merror() {
+    let expr = "<math><mi>x</mi><mo>+</mo><merror><mtext>Undefined control sequence \\foo</mtext></merror></math>";
+    test("en", "SimpleSpeak", expr, "x plus, error in math; Undefined control sequence \\foo, end error,");
+    test_prefs("en", "SimpleSpeak", vec![("Verbosity", "Terse")], expr, "x plus, error in math; Undefined control sequence \\foo,");
+}
+
+#[test]
+fn function_signature() {
+    let expr = "<math><mi>f</mi><mo>:</mo><mi>A</mi><mo>→</mo><mi>B</mi></math>";
+    test("en", "SimpleSpeak", expr, "f is a function from cap eigh to cap b");
+    let expr = "<math><msub><mi>g</mi><mi>n</mi></msub><mo>:</mo><mi>X</mi><mo>⟶</mo><mi>Y</mi></math>";
+    test("en", "ClearSpeak", expr, "g sub n is a function from cap x to cap y");
+    // not a function signature
+    let expr = "<math><mn>3</mn><mo>:</mo><mn>4</mn></math>";
+    test("en", "ClearSpeak", expr, "3 colon 4");
+}
+
+#[test]
+fn number_bases() {
+    let expr = "<math><msub><mn>1011</mn><mn>2</mn></msub></math>";
+    test("en", "SimpleSpeak", expr, "1 0 1 1 base 2");
+    let expr = "<math><msub><mi>FF</mi><mn>16</mn></msub></math>";
+    test("en", "ClearSpeak", expr, "hexadecimal F F");
+    let expr = "<math><msub><mrow><mn>7</mn><mo>&#x2062;</mo><mi>A</mi></mrow><mn>16</mn></msub></math>";
+    test("en", "ClearSpeak", expr, "hexadecimal 7 A");
+    // digits that aren't valid in the base and single letters are ordinary subscripts
+    let expr = "<math><msub><mn>25</mn><mn>2</mn></msub></math>";
+    test("en", "ClearSpeak", expr, "25 sub 2");
+    let expr = "<math><msub><mi>A</mi><mn>16</mn></msub></math>";
+    test("en", "ClearSpeak", expr, "cap eigh sub 16");
+}
+
+#[test]
+fn number_list() {
+    // a row of numbers separated by wide spaces is a list of numbers, not a product
+    let expr = "<math><mn>299</mn><mspace width='thickmathspace'/><mn>792</mn><mspace width='thickmathspace'/><mn>458</mn>
+                    <mspace width='2em'/><mn>9</mn><mspace width='thickmathspace'/><mn>192</mn></math>";
+    test("en", "SimpleSpeak", expr, "299 792 458; 9 192");
+}
@@ -15,5 +15,8 @@ mod braille {
     mod Vietnam {
         // mod vi;
     }
+    mod Finnish {
+        mod fi;
+    }
 }
 
@@ -0,0 +1,68 @@
+// Finnish braille math -- the numbers, letters, and punctuation come from the shared Nordic components
+use crate::common::*;
+
+#[test]
+fn number() {
+    let expr = "<math><mn>12</mn></math>";
+    test_braille("Finnish", expr, "⠼⠁⠃");
+}
+
+#[test]
+fn decimal_comma() {
+    let expr = "<math><mn>3,14</mn></math>";
+    test_braille("Finnish", expr, "⠼⠉⠂⠁⠙");
+}
+
+#[test]
+fn decimal_point() {
+    let expr = "<math><mn>3.14</mn></math>";
+    test_braille("Finnish", expr, "⠼⠉⠂⠁⠙");
+}
+
+#[test]
+fn letter_after_number() {
+    let expr = "<math><mn>2</mn><mi>a</mi><mo>+</mo><mn>3</mn><mi>x</mi></math>";
+    test_braille("Finnish", expr, "⠼⠃⠰⠁⠖⠼⠉⠭");
+}
+
+#[test]
+fn capital_after_number() {
+    let expr = "<math><mn>2</mn><mi>K</mi></math>";
+    test_braille("Finnish", expr, "⠼⠃⠠⠅");
+}
+
+#[test]
+fn nordic_letters() {
+    let expr = "<math><mi>ä</mi><mo>+</mo><mi>Ö</mi></math>";
+    test_braille("Finnish", expr, "⠜⠖⠠⠪");
+}
+
+#[test]
+fn simple_fraction() {
+    let expr = "<math><mfrac><mn>3</mn><mn>4</mn></mfrac></math>";
+    test_braille("Finnish", expr, "⠼⠉⠌⠼⠙");
+}
+
+#[test]
+fn fraction_with_grouping() {
+    let expr = "<math><mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mn>2</mn></mfrac></math>";
+    test_braille("Finnish", expr, "⠦⠁⠖⠃⠴⠌⠼⠃");
+}
+
+#[test]
+fn quadratic() {
+    let expr = "<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>2</mn><mi>x</mi><mo>=</mo><mn>0</mn></math>";
+    test_braille("Finnish", expr, "⠭⠬⠼⠃⠖⠼⠃⠭⠶⠼⠚");
+}
+
+#[test]
+fn sqrt() {
+    let expr = "<math><msqrt><mi>x</mi><mo>+</mo><mn>1</mn></msqrt></math>";
+    test_braille("Finnish", expr, "⠩⠦⠭⠖⠼⠁⠴");
+}
+
+#[test]
+fn parens() {
+    let expr = "<math><mn>2</mn><mo>(</mo><mn>3,5</mn><mo>-</mo><mi>y</mi><mo>)</mo></math>";
+    test_braille("Finnish", expr, "⠼⠃⠦⠼⠉⠂⠑⠤⠽⠴");
+}
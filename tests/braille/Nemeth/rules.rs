@@ -92,6 +92,19 @@ fn boldface_32_b_3() {
     test_braille("Nemeth", expr, "⠸⠼⠒⠲⠢");
 }
 
+#[test]
+fn bold_italic_digit() {
+    // there are no bold italic digits in Unicode -- the italic part comes from mathvariant
+    let expr = "<math><mn mathvariant='bold-italic'>2</mn></math>";
+    test_braille("Nemeth", expr, "⠸⠨⠼⠆");
+}
+
+#[test]
+fn bold_mstyle() {
+    let expr = "<math><mstyle mathvariant='bold'><mi>x</mi><mo>+</mo><mn>2</mn></mstyle></math>";
+    test_braille("Nemeth", expr, "⠸⠰⠭⠬⠸⠼⠆");
+}
+
 #[test]
 fn boldface_32_b_6() {
     let expr = "<math><mn>𝟒35</mn></math>";
@@ -769,6 +782,13 @@ fn overbar_86_b_1() {
     test_braille("Nemeth", expr, "⠭⠱");
 }
 
+#[test]
+fn vector_combining_arrow() {
+    // the combining arrow is the same as a right arrow over the letter
+    let expr = "<math><mover><mi>v</mi><mo>&#x20D7;</mo></mover><mo>+</mo><mi mathvariant='bold'>v</mi></math>";
+    test_braille("Nemeth", expr, "⠐⠧⠣⠫⠕⠻⠬⠸⠰⠧");
+}
+
 #[test]
 fn menclose_86_b_1() {
     let expr = "<math><menclose notation='top'><mi>x</mi></menclose></math>";
@@ -1183,9 +1203,9 @@ fn ms_38_4_8() {
 #[test]
 fn ms() {
     let expr = "<math><ms>a string</ms><mo>,</mo><ms lquote='‘' rquote='’'>another string</ms></math>";
-    // Not 100% sure this is the right output -- I am a little skeptical of "⠄⠄" being the braille for '"'
+    // the default quotes ('"') are brailled as the opening/closing quotation marks
     // Note: no punct indicator after word (see 38_4_8)
-    test_braille("Nemeth", expr, "⠄⠄⠁⠀⠎⠞⠗⠊⠝⠛⠄⠄⠠⠀⠸⠠⠦⠁⠝⠕⠞⠓⠑⠗⠀⠎⠞⠗⠊⠝⠛⠴⠠");
+    test_braille("Nemeth", expr, "⠦⠁⠀⠎⠞⠗⠊⠝⠛⠴⠠⠀⠸⠠⠦⠁⠝⠕⠞⠓⠑⠗⠀⠎⠞⠗⠊⠝⠛⠴⠠");
 }
 
 #[test]
@@ -1273,3 +1293,33 @@ fn tensor_from_mathml_spec() {
     // Note: the braille answer was verified to be correct (see https://github.com/NSoiffer/MathCAT/issues/55) 
     test_braille("Nemeth", expr, "⠠⠗⠰⠊⠐⠘⠚⠐⠰⠅⠐⠰⠇");
 }
+
+#[test]
+fn scientific_notation() {
+    let expr = "<math><mn>3.2</mn><mo>×</mo><msup><mn>10</mn><mn>5</mn></msup></math>";
+    test_braille("Nemeth", expr, "⠼⠒⠨⠆⠈⠡⠂⠴⠘⠢");
+    let expr = "<math><mn>6.02</mn><mo>×</mo><msup><mn>10</mn><mrow><mo>−</mo><mn>23</mn></mrow></msup></math>";
+    test_braille("Nemeth", expr, "⠼⠖⠨⠴⠆⠈⠡⠂⠴⠘⠤⠆⠒");
+}
+
+#[test]
+fn chemistry_hydrate() {
+    let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>·</mo><mn>5</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    test_braille("Nemeth", expr, "⠠⠉⠥⠠⠎⠠⠕⠲⠡⠢⠠⠓⠆⠠⠕");
+    let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>⋅</mo><mn>5</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    test_braille("Nemeth", expr, "⠠⠉⠥⠠⠎⠠⠕⠲⠡⠢⠠⠓⠆⠠⠕");
+}
+
+#[test]
+fn number_list() {
+    let expr = "<math><mn>299</mn><mspace width='thickmathspace'/><mn>792</mn><mspace width='thickmathspace'/><mn>458</mn>
+                    <mspace width='2em'/><mn>9</mn><mspace width='thickmathspace'/><mn>192</mn></math>";
+    test_braille("Nemeth", expr, "⠼⠆⠔⠔⠀⠶⠔⠆⠀⠲⠢⠦⠀⠼⠔⠀⠂⠔⠆");
+}
+
+#[test]
+fn accented_letter() {
+    // a letter of an alphabet isn't split into an accented identifier
+    let expr = "<math><mi>ë</mi></math>";
+    test_braille("Nemeth", expr, "⠈⠑");
+}
@@ -232,6 +232,12 @@ fn bold_2_7_3() {
     test_braille("UEB", expr, "⠼⠋⠛⠘⠂⠼⠓⠙⠘⠄⠼⠑");
 }
 
+#[test]
+fn bold_mstyle() {
+    let expr = "<math><mstyle mathvariant='bold'><mi>x</mi><mo>+</mo><mn>2</mn></mstyle></math>";
+    test_braille("UEB", expr, "⠘⠆⠭⠐⠖⠘⠆⠼⠃");
+}
+
 #[test]
 fn signs_2_10_2() {
     let expr = "<math><mo>$</mo><mn>0.30</mn><mo>,</mo><mo>&#xA0;</mo>
@@ -1212,3 +1218,25 @@ fn contractions_5() {
     let expr = "<math><mi>Real</mi><mo>(</mo><mi>z</mi><mo>)</mo></math>";
     test_braille("UEB", expr, "⠠⠗⠂⠇⠐⠣⠵⠐⠜");
 }
+
+#[test]
+fn string_literal() {
+    let expr = "<math><ms>ab</ms><mo>+</mo><ms lquote='&#x2018;' rquote='&#x2019;'>c</ms></math>";
+    test_braille("UEB", expr, "⠦⠁⠃⠴⠐⠖⠠⠦⠉⠠⠴");
+}
+
+#[test]
+fn scientific_notation() {
+    let expr = "<math><mn>3.2</mn><mo>×</mo><msup><mn>10</mn><mn>5</mn></msup></math>";
+    test_braille("UEB", expr, "⠼⠉⠲⠃⠐⠦⠼⠁⠚⠔⠼⠑");
+    let expr = "<math><mn>1.5E-3</mn></math>";
+    test_braille("UEB", expr, "⠼⠁⠲⠑⠠⠑⠐⠤⠼⠉");
+}
+
+#[test]
+fn chemistry_hydrate() {
+    let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>·</mo><mn>5</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    test_braille("UEB", expr, "⠰⠰⠠⠉⠥⠠⠎⠠⠕⠢⠼⠙⠐⠲⠼⠑⠠⠓⠢⠼⠃⠠⠕");
+    let expr = "<math><mi>Cu</mi><mi>S</mi><msub><mi>O</mi><mn>4</mn></msub><mo>⋅</mo><mn>5</mn><msub><mi>H</mi><mn>2</mn></msub><mi>O</mi></math>";
+    test_braille("UEB", expr, "⠰⠰⠠⠉⠥⠠⠎⠠⠕⠢⠼⠙⠐⠲⠼⠑⠠⠓⠢⠼⠃⠠⠕");
+}